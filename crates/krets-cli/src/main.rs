@@ -1,12 +1,20 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use krets_gui::run_gui;
-use krets_parser::analyses::AnalysisSpec;
+use krets_parser::{analyses::AnalysisSpec, circuit::Circuit};
 use krets_result::{
-    write_ac_results_to_parquet, write_dc_results_to_parquet, write_op_results_to_parquet,
-    write_tran_results_to_parquet,
+    write_ac_results_to_csv, write_ac_results_to_parquet, write_dc_results_to_csv,
+    write_dc_results_to_parquet, write_op_results_to_csv, write_op_results_to_parquet,
+    write_results_to_json, write_tran_results_to_csv, write_tran_results_to_parquet,
 };
-use krets_solver::{AnalysisResult, config::SolverConfig, solver::Solver};
-use log::info;
+use krets_solver::{
+    AnalysisResult,
+    config::SolverConfig,
+    prelude::c64,
+    report::{ReportOptions, format_result},
+    solver::Solver,
+};
+use log::{info, warn};
+use std::{collections::HashMap, path::PathBuf};
 
 /// Krets is a SPICE-like circuit simulator written in Rust.
 #[derive(Parser, Debug)]
@@ -16,12 +24,48 @@ struct Args {
     #[arg()]
     krets_file: String,
 
-    /// Whether to launch the GUI.
-    #[arg(short, long, default_value_t = true)]
+    /// Launch the GUI after writing the results.
+    #[arg(short, long, overrides_with = "no_gui")]
     gui: bool,
 
+    /// Exit after writing the results without launching the GUI (the default).
+    #[arg(long = "no-gui", overrides_with = "gui")]
+    no_gui: bool,
+
+    /// Path of the written results, by default `result.<format>` next to the krets file.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Print the elements and MNA unknowns of the parsed circuit and exit without solving.
+    #[arg(long = "list-nodes")]
+    list_nodes: bool,
+
+    /// Print the results to the console as aligned tables.
+    #[arg(short, long)]
+    print: bool,
+
+    /// Signals to print, e.g. `V(out),I(V1)`, or all of them by default.
+    #[arg(long, value_delimiter = ',', requires = "print")]
+    signals: Vec<String>,
+
+    /// Largest number of points of a sweep to print.
+    #[arg(long = "max-rows", requires = "print")]
+    max_rows: Option<usize>,
+
     #[arg(short = 'l', long = "log-level", default_value = "info")]
     log_level: String,
+
+    /// File format of the written results.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Parquet)]
+    format: OutputFormat,
+}
+
+/// Supported result file formats.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Parquet,
+    Csv,
+    Json,
 }
 
 fn main() {
@@ -41,8 +85,12 @@ fn main() {
         .parent()
         .unwrap_or_else(|| std::path::Path::new("."));
 
-    // decide output file path: always write result.parquet next to the krets file
-    let output_path_buf = krets_parent.join("result.parquet");
+    // Write the result to the given path, or next to the krets file by default.
+    let output_path_buf = args.output.clone().unwrap_or_else(|| match args.format {
+        OutputFormat::Parquet => krets_parent.join("result.parquet"),
+        OutputFormat::Csv => krets_parent.join("result.csv"),
+        OutputFormat::Json => krets_parent.join("result.json"),
+    });
     let output_file_str = output_path_buf.to_string_lossy().into_owned();
 
     // First try the path interpreted relative to the krets file.
@@ -75,8 +123,21 @@ fn main() {
         }
     };
 
-    // 2. Create a default solver configuration.
-    let config = SolverConfig::default();
+    // Netlist mistakes are reported, but left to the solver to reject.
+    if let Err(findings) = circuit.validate() {
+        for finding in findings {
+            warn!("{finding}");
+        }
+    }
+
+    if args.list_nodes {
+        print_topology(&circuit);
+        return;
+    }
+
+    // 2. Create a default solver configuration, tuned by the `.options` cards of the netlist.
+    let mut config = SolverConfig::default();
+    config.apply_options(&circuit.options);
 
     // 3. Instantiate the solver.
     let mut solver = Solver::new(circuit, config);
@@ -95,44 +156,207 @@ fn main() {
         std::process::exit(1);
     });
 
-    // 5. Print results to console.
-    // print_results_to_console(&result);
+    // 5. Report the values of the `.measure` cards of the circuit.
+    if let Some(measurements) = result.measurements() {
+        let mut measurements: Vec<_> = measurements.iter().collect();
+        measurements.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in measurements {
+            info!("{name} = {value:e}");
+        }
+    }
+
+    // 6. Report the current through and power of every element at the operating point.
+    if let AnalysisResult::Op(op_solution) = &result {
+        let mut power: Vec<_> = solver.element_power(op_solution).into_iter().collect();
+        power.sort_by(|a, b| a.0.cmp(&b.0));
+        info!(
+            "{:<12} | {:>14} | {:>14}",
+            "Element", "Current (A)", "Power (W)"
+        );
+        for (identifier, (current, power)) in power {
+            info!("{identifier:<12} | {current:>14.6e} | {power:>14.6e}");
+        }
+    }
+
+    if args.print {
+        let options = ReportOptions {
+            signals: args.signals.clone(),
+            max_rows: args.max_rows,
+        };
+        print!("{}", format_result(&result, &options));
+    }
+
+    match args.format {
+        OutputFormat::Parquet => write_parquet(&result, &output_file_str),
+        OutputFormat::Csv => write_csv(&result, &output_file_str),
+        OutputFormat::Json => {
+            write_results_to_json(&result, &output_file_str).unwrap_or_else(|e| {
+                info!("Error writing results to JSON: {e}");
+                std::process::exit(1);
+            })
+        }
+    }
+
+    // 7. Optionally launch the GUI.
+    if args.gui {
+        let _ = run_gui(
+            circuit_path_resolved
+                .parent()
+                .unwrap_or_else(|| std::path::Path::new("."))
+                .to_path_buf(),
+            Some(output_path_buf.clone()),
+        );
+    }
+}
+
+/// Prints every element with its type and nodes, after subcircuit expansion and ground
+/// normalization, followed by the MNA unknowns in row order.
+fn print_topology(circuit: &Circuit) {
+    println!("Elements:");
+    for element in &circuit.elements {
+        println!(
+            "  {:<12} {:<20} {}",
+            element.identifier(),
+            element.kind(),
+            element.nodes().join(" ")
+        );
+    }
+
+    println!(
+        "MNA unknowns ({} nodes, {} branch currents):",
+        circuit.node_count(),
+        circuit.branch_count()
+    );
+    for (index, unknown) in circuit.unknowns().iter().enumerate() {
+        println!("  {index:>4} {unknown}");
+    }
+}
 
-    match &result {
+/// Lays out poles and zeros as rows, one per pole followed by one per zero, with their real
+/// and imaginary parts in `pole_re`/`pole_im` or `zero_re`/`zero_im`.
+fn pole_zero_rows(poles: &[c64], zeros: &[c64]) -> Vec<HashMap<String, f64>> {
+    let row = |kind: &str, value: &c64| {
+        HashMap::from([
+            (format!("{kind}_re"), value.re),
+            (format!("{kind}_im"), value.im),
+        ])
+    };
+    poles
+        .iter()
+        .map(|pole| row("pole", pole))
+        .chain(zeros.iter().map(|zero| row("zero", zero)))
+        .collect()
+}
+
+/// Writes the analysis result to a Parquet file.
+fn write_parquet(result: &AnalysisResult, output_file_str: &str) {
+    match result {
         AnalysisResult::Op(op_solution) => {
-            write_op_results_to_parquet(op_solution, &output_file_str).unwrap_or_else(|e| {
+            write_op_results_to_parquet(op_solution, output_file_str).unwrap_or_else(|e| {
                 info!("Error writing OP results to Parquet: {e}");
                 std::process::exit(1);
             });
         }
-        AnalysisResult::Dc(dc_solution) => {
-            write_dc_results_to_parquet(dc_solution, &output_file_str).unwrap_or_else(|e| {
+        AnalysisResult::Dc(dc_solution, _) => {
+            write_dc_results_to_parquet(dc_solution, output_file_str).unwrap_or_else(|e| {
                 info!("Error writing DC results to Parquet: {e}");
                 std::process::exit(1);
             });
         }
         AnalysisResult::Ac(ac_solution) => {
-            write_ac_results_to_parquet(ac_solution, &output_file_str).unwrap_or_else(|e| {
+            write_ac_results_to_parquet(ac_solution, output_file_str).unwrap_or_else(|e| {
                 info!("Error writing AC results to Parquet: {e}");
                 std::process::exit(1);
             });
         }
-        AnalysisResult::Transient(tran_solution) => {
-            write_tran_results_to_parquet(tran_solution, &output_file_str).unwrap_or_else(|e| {
+        AnalysisResult::Transient(tran_solution, _) => {
+            write_tran_results_to_parquet(tran_solution, output_file_str).unwrap_or_else(|e| {
                 info!("Error writing Transient results to Parquet: {e}");
                 std::process::exit(1);
             });
         }
+        AnalysisResult::Noise(noise_solution) => {
+            write_dc_results_to_parquet(noise_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing Noise results to Parquet: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Tf(tf_solution) => {
+            write_op_results_to_parquet(tf_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing TF results to Parquet: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Sensitivity(sensitivity_solution) => {
+            write_op_results_to_parquet(sensitivity_solution, output_file_str).unwrap_or_else(
+                |e| {
+                    info!("Error writing sensitivity results to Parquet: {e}");
+                    std::process::exit(1);
+                },
+            );
+        }
+        AnalysisResult::PoleZero { poles, zeros } => {
+            write_dc_results_to_parquet(&pole_zero_rows(poles, zeros), output_file_str)
+                .unwrap_or_else(|e| {
+                    info!("Error writing pole-zero results to Parquet: {e}");
+                    std::process::exit(1);
+                });
+        }
     }
+}
 
-    // 7. Optionally launch the GUI.
-    if args.gui {
-        let _ = run_gui(
-            circuit_path_resolved
-                .parent()
-                .unwrap_or_else(|| std::path::Path::new("."))
-                .to_path_buf(),
-            Some(output_path_buf.clone()),
-        );
+/// Writes the analysis result to a CSV file.
+fn write_csv(result: &AnalysisResult, output_file_str: &str) {
+    match result {
+        AnalysisResult::Op(op_solution) => {
+            write_op_results_to_csv(op_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing OP results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Dc(dc_solution, _) => {
+            write_dc_results_to_csv(dc_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing DC results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Ac(ac_solution) => {
+            write_ac_results_to_csv(ac_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing AC results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Transient(tran_solution, _) => {
+            write_tran_results_to_csv(tran_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing Transient results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Noise(noise_solution) => {
+            write_dc_results_to_csv(noise_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing Noise results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Tf(tf_solution) => {
+            write_op_results_to_csv(tf_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing TF results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::Sensitivity(sensitivity_solution) => {
+            write_op_results_to_csv(sensitivity_solution, output_file_str).unwrap_or_else(|e| {
+                info!("Error writing sensitivity results to CSV: {e}");
+                std::process::exit(1);
+            });
+        }
+        AnalysisResult::PoleZero { poles, zeros } => {
+            write_dc_results_to_csv(&pole_zero_rows(poles, zeros), output_file_str).unwrap_or_else(
+                |e| {
+                    info!("Error writing pole-zero results to CSV: {e}");
+                    std::process::exit(1);
+                },
+            );
+        }
     }
 }
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, process::Command};
+
+    // Writes `netlist` and a krets file running an operating point analysis of it into a
+    // fresh directory under the temp dir, and returns the path of the krets file.
+    fn write_spec(name: &str, netlist: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("krets_cli_{name}_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("circuit.cir"), netlist).unwrap();
+        let spec = dir.join("krets.toml");
+        fs::write(
+            &spec,
+            "circuit_path = \"circuit.cir\"\n\n[analysis]\nop = {}\n",
+        )
+        .unwrap();
+        spec
+    }
+
+    #[test]
+    fn test_headless_run_writes_output() {
+        let spec = write_spec("divider", "V1 in 0 1\nR1 in out 1k\nR2 out 0 1k\n");
+        let output = spec.with_file_name("divider.parquet");
+
+        let status = Command::new(env!("CARGO_BIN_EXE_krets"))
+            .arg(&spec)
+            .arg("--no-gui")
+            .arg("--output")
+            .arg(&output)
+            .status()
+            .unwrap();
+
+        assert!(status.success());
+        assert!(output.exists());
+        fs::remove_dir_all(spec.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_headless_run_reports_solver_error() {
+        // Two voltage sources forcing different voltages on the same node.
+        let spec = write_spec("conflict", "V1 a 0 1\nV2 a 0 2\nR1 a 0 1k\n");
+
+        let status = Command::new(env!("CARGO_BIN_EXE_krets"))
+            .arg(&spec)
+            .status()
+            .unwrap();
+
+        assert!(!status.success());
+        assert!(!spec.with_file_name("result.parquet").exists());
+        fs::remove_dir_all(spec.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_list_nodes_prints_expanded_topology() {
+        let netlist = "V1 in 0 1\nX1 in out gnd divider\n\n.subckt divider a b c\nR1 a b 1k\nR2 b c 1k\n.ends\n";
+        let spec = write_spec("list_nodes", netlist);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_krets"))
+            .arg(&spec)
+            .arg("--list-nodes")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(output.status.success());
+        // The subcircuit is flattened into its resistors, named after the instance, and
+        // `gnd` is renamed to ground.
+        assert!(stdout.contains("R1_1"), "{stdout}");
+        assert!(stdout.contains("R1_2"), "{stdout}");
+        assert!(stdout.contains("resistor"), "{stdout}");
+        assert!(!stdout.contains("gnd"), "{stdout}");
+        assert!(
+            stdout.contains("MNA unknowns (2 nodes, 1 branch currents):"),
+            "{stdout}"
+        );
+        assert!(
+            stdout.contains("I(V1)") && stdout.contains("V(out)"),
+            "{stdout}"
+        );
+        // Nothing is solved or written.
+        assert!(!spec.with_file_name("result.parquet").exists());
+        fs::remove_dir_all(spec.parent().unwrap()).unwrap();
+    }
+}
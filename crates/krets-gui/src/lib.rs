@@ -1,10 +1,19 @@
+use arrow::compute::concat_batches;
 use arrow::record_batch::RecordBatch;
 use eframe::egui;
 use egui_extras::{Column, TableBuilder};
-use egui_plot::{Legend, Line, Plot, PlotPoints};
+use egui_plot::{Legend, Line, Plot, PlotPoint, PlotPoints, Points, VLine};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rustfft::{FftPlanner, num_complex::Complex};
 use std::collections::HashSet;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// How often the loaded files are checked for changes while they are watched.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Represents an entry in the directory listing.
 #[derive(Clone)]
@@ -15,20 +24,88 @@ struct DirectoryEntry {
 
 /// Holds the data loaded from a Parquet file for display.
 struct TableData {
+    /// The file the data was loaded from.
+    path: PathBuf,
+    /// Overlay the curves of this file on the plot.
+    visible: bool,
+    /// The modification time of the file when it was read, or `None` if it is unknown or the
+    /// file has since been deleted.
+    modified: Option<SystemTime>,
     /// The column names.
     headers: Vec<String>,
+    /// The unit of every column, from the Arrow field metadata if the writer recorded one.
+    units: Vec<Option<String>>,
     /// The data itself, held as an Arrow `RecordBatch`.
     batch: RecordBatch,
 }
 
+impl TableData {
+    /// Reads the Parquet file at `path`.
+    fn read(path: PathBuf) -> Result<Self, String> {
+        let modified = modification_time(&path);
+        let batch = read_parquet_file(&path)?;
+        let headers = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect();
+        let units = column_units(&batch);
+
+        Ok(TableData {
+            path,
+            visible: true,
+            modified,
+            headers,
+            units,
+            batch,
+        })
+    }
+
+    /// Returns the file name without its extension, which prefixes the legend labels when
+    /// several files are loaded.
+    fn label(&self) -> String {
+        self.path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Returns the index of the column called `name`, if there is one.
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|header| header == name)
+    }
+}
+
 struct KretsApp {
     current_path: PathBuf,
     entries: Vec<DirectoryEntry>,
     error_message: Option<String>,
-    file_to_load: Option<PathBuf>, // Initial file to load
-    table_data: Option<TableData>,
+    /// A file clicked in the explorer, loaded after the frame is drawn.
+    file_to_load: Option<PathBuf>,
+    /// The loaded files, in the order they were loaded. The columns are selected in the
+    /// first one, and the other files contribute the columns with the same names.
+    datasets: Vec<TableData>,
+    /// The selected columns of the first loaded file.
     selection: HashSet<usize>,
-    current_loaded_file: Option<PathBuf>,
+    /// Reload the loaded files when they change on disk, e.g. when the CLI writes them again.
+    watch_files: bool,
+    /// Plot the X axis logarithmically. Kept across file loads.
+    log_x: bool,
+    /// Plot the Y values in decibels. Kept across file loads.
+    db_y: bool,
+    /// The plotted point nearest to the pointer in the last frame, in plot coordinates.
+    cursor: Option<[f64; 2]>,
+    /// Drop markers by clicking the plot and report the distance between them.
+    measure_mode: bool,
+    /// Up to two markers dropped in measure mode, in plot coordinates.
+    markers: Vec<[f64; 2]>,
+    /// Plot the amplitude spectrum of the selected columns against frequency instead of
+    /// the columns themselves against time.
+    spectrum: bool,
+    /// The fundamental and THD of every plotted spectrum in the last frame.
+    spectrum_info: Vec<String>,
 }
 
 impl KretsApp {
@@ -40,10 +117,17 @@ impl KretsApp {
                 .unwrap_or(initial_folder_path), // Canonicalize for cleaner display
             entries: Vec::new(),
             error_message: None,
-            file_to_load: initial_result_file.clone(), // Set initial file to load
-            table_data: None,
+            file_to_load: None,
+            datasets: Vec::new(),
             selection: HashSet::new(),
-            current_loaded_file: None,
+            watch_files: false,
+            log_x: false,
+            db_y: false,
+            cursor: None,
+            measure_mode: false,
+            markers: Vec::new(),
+            spectrum: false,
+            spectrum_info: Vec::new(),
         };
         app.refresh_entries();
 
@@ -61,6 +145,20 @@ impl eframe::App for KretsApp {
         // This will be set by the file explorer UI if navigation is requested.
         let mut path_to_navigate = None;
 
+        let reload_requested = ctx.input(|input| {
+            input.key_pressed(egui::Key::F5)
+                || (input.modifiers.command && input.key_pressed(egui::Key::R))
+        });
+        if reload_requested {
+            self.reload_all();
+        } else if self.watch_files {
+            self.reload_changed();
+        }
+        if self.watch_files {
+            // Without input egui does not redraw, so wake up to look at the files again.
+            ctx.request_repaint_after(WATCH_INTERVAL);
+        }
+
         egui::SidePanel::left("file_panel").show(ctx, |ui| {
             // Delegate file explorer UI and logic
             path_to_navigate = self.ui_file_explorer(ui);
@@ -80,16 +178,7 @@ impl eframe::App for KretsApp {
 
         // Handle file loading requested from the side panel *after* initial load
         if let Some(path) = self.file_to_load.take() {
-            // Only load if it's different from the currently loaded file
-            let already_loaded = self
-                .current_loaded_file
-                .as_ref()
-                .map(|p| p == &path)
-                .unwrap_or(false);
-
-            if !already_loaded {
-                self.load_parquet_file(&path);
-            }
+            self.load_parquet_file(&path);
         }
     }
 }
@@ -168,6 +257,7 @@ impl KretsApp {
     /// Renders the central panel, delegating to table and plot methods.
     fn ui_central_panel(&mut self, ui: &mut egui::Ui) {
         ui.heading("Data Viewer");
+        self.ui_loaded_files(ui);
         self.ui_stats_table(ui);
 
         ui.separator();
@@ -175,14 +265,48 @@ impl KretsApp {
         self.ui_plot_viewer(ui);
     }
 
-    /// Renders the column statistics table.
+    /// Renders the loaded files with a toggle to show or hide each one on the plot and a
+    /// button to unload it, below the reload actions.
+    fn ui_loaded_files(&mut self, ui: &mut egui::Ui) {
+        if self.datasets.is_empty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .button("⟳ Reload")
+                .on_hover_text("Read the loaded files again (F5 or Ctrl+R)")
+                .clicked()
+            {
+                self.reload_all();
+            }
+            ui.checkbox(&mut self.watch_files, "Watch files")
+                .on_hover_text("Reload a file when it changes on disk");
+        });
+
+        let mut to_remove = None;
+        for (index, data) in self.datasets.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let label = data.label();
+                ui.checkbox(&mut data.visible, label);
+                if ui.small_button("✖").clicked() {
+                    to_remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = to_remove {
+            self.remove_dataset(index);
+        }
+        ui.separator();
+    }
+
+    /// Renders the column statistics table of the first loaded file.
     fn ui_stats_table(&mut self, ui: &mut egui::Ui) {
-        if let Some(data) = &self.table_data {
+        if let Some(data) = self.datasets.first() {
             // Use a TableBuilder to display the column stats.
             let table = TableBuilder::new(ui)
                 .striped(true)
                 .resizable(true)
-                .columns(Column::auto(), 4)
+                .columns(Column::auto(), 5)
                 .sense(egui::Sense::click());
 
             table
@@ -190,6 +314,9 @@ impl KretsApp {
                     header.col(|ui| {
                         ui.strong("Name");
                     });
+                    header.col(|ui| {
+                        ui.strong("Unit");
+                    });
                     header.col(|ui| {
                         ui.strong("Min");
                     });
@@ -214,11 +341,14 @@ impl KretsApp {
                             row.col(|ui| {
                                 ui.label(column_name);
                             });
-                            // Second cell is the min value
+                            row.col(|ui| {
+                                ui.label(data.units[index].as_deref().unwrap_or(""));
+                            });
+                            // Then the min value
                             row.col(|ui| {
                                 ui.label(min_str);
                             });
-                            // Third cell is the max value
+                            // And the max value
                             row.col(|ui| {
                                 ui.label(max_str);
                             });
@@ -250,88 +380,201 @@ impl KretsApp {
     }
 
     /// Renders the plot viewer.
+    ///
+    /// The selected columns are plotted from every visible file that shares the X axis of the
+    /// first loaded file, see [`shared_x_index`]. Hovering the plot snaps a cursor to the
+    /// nearest plotted point and shows its value. In measure mode, two clicks drop markers
+    /// whose distance is reported as ΔX and ΔY.
     fn ui_plot_viewer(&mut self, ui: &mut egui::Ui) {
-        let my_plot = Plot::new("My Plot").legend(Legend::default());
-        my_plot.show(ui, |plot_ui| {
+        ui.horizontal(|ui| {
+            let log_x_changed = ui.checkbox(&mut self.log_x, "log X").changed();
+            let db_y_changed = ui.checkbox(&mut self.db_y, "dB Y (20*log10)").changed();
+            let measure_changed = ui.checkbox(&mut self.measure_mode, "Measure").changed();
+            let spectrum_changed = ui.checkbox(&mut self.spectrum, "Spectrum").changed();
+            // Markers are stored in plot coordinates, which change with the axis transforms.
+            if log_x_changed || db_y_changed || measure_changed || spectrum_changed {
+                self.markers.clear();
+            }
+
+            ui.separator();
+            let to_data = |point: [f64; 2]| plot_to_data(point, self.log_x);
+            if let Some([x, y]) = self.cursor.map(to_data) {
+                ui.label(format!("x = {x:.6e}, y = {y:.6e}"));
+            }
+            if let [first, second] = self.markers[..] {
+                let ([x0, y0], [x1, y1]) = (to_data(first), to_data(second));
+                ui.separator();
+                ui.label(format!("ΔX = {:.6e}, ΔY = {:.6e}", x1 - x0, y1 - y0));
+            }
+        });
+        if self.spectrum {
+            if self.spectrum_info.is_empty() {
+                ui.label("Select the time column and the columns to transform.");
+            }
+            for info in &self.spectrum_info {
+                ui.label(info);
+            }
+        }
+
+        let (log_x, db_y, spectrum) = (self.log_x, self.db_y, self.spectrum);
+        let mut my_plot = Plot::new("My Plot").legend(Legend::default());
+        if let Some(data) = self.datasets.first()
+            && let Some(idx_x) = x_axis_index(&data.headers, &self.selection)
+        {
+            let y_units = self
+                .selection
+                .iter()
+                .filter(|&&index| index != idx_x)
+                .map(|&index| data.units[index].as_deref());
+            // A spectrum is plotted against frequency but keeps the unit of its columns.
+            let x_label = if spectrum {
+                axis_label("frequency", Some("Hz"))
+            } else {
+                axis_label(&data.headers[idx_x], data.units[idx_x].as_deref())
+            };
+            my_plot = my_plot.x_axis_label(x_label);
+            if let Some(y_label) = y_axis_label(y_units, db_y) {
+                my_plot = my_plot.y_axis_label(y_label);
+            }
+        }
+        if log_x {
+            // The points are plotted at log10(x), so label the ticks with the original values.
+            my_plot =
+                my_plot.x_axis_formatter(|mark, _range| format!("{:.3e}", 10f64.powf(mark.value)));
+        }
+        let markers = self.markers.clone();
+        let response = my_plot.show(ui, |plot_ui| {
+            let mut lines: Vec<Vec<[f64; 2]>> = Vec::new();
+            let mut spectrum_info = Vec::new();
+
             // Only plot if we have data and *at least* one column is selected
-            if let Some(data) = &self.table_data
+            if let Some(reference) = self.datasets.first()
                 && !self.selection.is_empty()
+                // We must have an X axis to plot
+                && let Some(idx_x) = x_axis_index(&reference.headers, &self.selection)
             {
-                let selected_indices: HashSet<usize> = self.selection.iter().copied().collect();
-
-                // Helper to find a selected column by name
-                let find_selected_index = |name: &str| -> Option<usize> {
-                    data.headers
-                        .iter()
-                        .position(|h| h == name)
-                        .filter(|&index| selected_indices.contains(&index))
-                };
-
-                // 1. Prefer "time" if it's selected
-                let mut idx_x: Option<usize> = find_selected_index("time");
-
-                // 2. Otherwise, prefer "frequency" if it's selected
-                if idx_x.is_none() {
-                    idx_x = find_selected_index("frequency");
-                }
-
-                // 3. Otherwise, prefer "step" if it's selected
-                if idx_x.is_none() {
-                    idx_x = find_selected_index("step");
-                }
+                // Y-axes are all selected columns *except* the chosen X-axis. They are plotted
+                // by name, as the files may order their columns differently.
+                let mut y_indices: Vec<usize> = self
+                    .selection
+                    .iter()
+                    .copied()
+                    .filter(|&idx| idx != idx_x)
+                    .collect();
+                y_indices.sort_unstable();
+                let name_x = &reference.headers[idx_x];
+                let overlay = self.datasets.len() > 1;
 
-                // 4. Otherwise, use the smallest selected index
-                if idx_x.is_none() {
-                    idx_x = selected_indices.iter().min().copied();
-                }
+                for data in self.datasets.iter().filter(|data| data.visible) {
+                    // Try to get the X-axis data
+                    let Some(x_vals) = shared_x_index(reference, idx_x, data)
+                        .and_then(|index| get_column_as_f64(&data.batch.columns()[index]))
+                    else {
+                        continue;
+                    };
+                    // Tell the files apart in the legend.
+                    let prefix = if overlay {
+                        format!("{}: ", data.label())
+                    } else {
+                        String::new()
+                    };
 
-                // We must have an X axis to plot
-                if let Some(idx_x) = idx_x {
-                    // --- Find Y-axis indices ---
-                    // Y-axes are all selected indices *except* the chosen X-axis
-                    let y_indices: Vec<usize> = selected_indices
-                        .iter()
-                        .copied()
-                        .filter(|&idx| idx != idx_x)
-                        .collect();
-
-                    // We only plot if we have at least one Y-axis
-                    if y_indices.is_empty() {
-                        // This happens if only one column (the x-axis) is selected.
-                        // We can still plot this single line against its index if we want,
-                        // but for now, just don't plot.
-                        return;
-                    }
+                    // Now, iterate over all *other* selected columns and plot them as Y
+                    for &idx_y in &y_indices {
+                        let name_y = &reference.headers[idx_y];
 
-                    let name_x = &data.headers[idx_x];
-                    let col_x_arr = &data.batch.columns()[idx_x];
+                        // Try to get the Y-axis data
+                        let Some(y_vals) = data
+                            .column_index(name_y)
+                            .and_then(|index| get_column_as_f64(&data.batch.columns()[index]))
+                        else {
+                            continue;
+                        };
 
-                    // Try to get the X-axis data
-                    if let Some(x_vals) = get_column_as_f64(col_x_arr) {
-                        // Now, iterate over all *other* selected columns and plot them as Y
-                        for &idx_y in &y_indices {
-                            let name_y = &data.headers[idx_y];
-                            let col_y_arr = &data.batch.columns()[idx_y];
-
-                            // Try to get the Y-axis data
-                            if let Some(y_vals) = get_column_as_f64(col_y_arr) {
-                                let line_name = format!("{name_y} (Y) vs. {name_x} (X)");
-
-                                // Combine the X and Y vectors into PlotPoints
-                                // Ensure vectors are the same length before zipping
-                                let points: PlotPoints = x_vals
-                                    .iter()
-                                    .zip(y_vals.iter())
-                                    .map(|(&x, &y)| [x, y])
-                                    .collect();
-
-                                plot_ui.line(Line::new(line_name, points));
+                        if spectrum {
+                            // Spectra are only defined over time.
+                            if name_x != "time" {
+                                continue;
+                            }
+                            let Some(amplitudes) = amplitude_spectrum(&x_vals, &y_vals) else {
+                                continue;
+                            };
+                            if let Some(harmonics) = harmonic_distortion(&amplitudes) {
+                                spectrum_info
+                                    .push(harmonics.describe(&format!("{prefix}{name_y}")));
                             }
+
+                            let (frequencies, magnitudes): (Vec<f64>, Vec<f64>) =
+                                amplitudes.into_iter().map(|[f, m]| (f, m)).unzip();
+                            let line_name = if db_y {
+                                format!("{prefix}|FFT {name_y}| [dB] vs. frequency")
+                            } else {
+                                format!("{prefix}|FFT {name_y}| vs. frequency")
+                            };
+                            let points = transform_points(&frequencies, &magnitudes, log_x, db_y);
+                            plot_ui.line(Line::new(line_name, PlotPoints::from(points.clone())));
+                            lines.push(points);
+                        } else {
+                            let line_name = if db_y {
+                                format!("{prefix}{name_y} [dB] (Y) vs. {name_x} (X)")
+                            } else {
+                                format!("{prefix}{name_y} (Y) vs. {name_x} (X)")
+                            };
+
+                            let points = transform_points(&x_vals, &y_vals, log_x, db_y);
+                            plot_ui.line(Line::new(line_name, PlotPoints::from(points.clone())));
+                            lines.push(points);
                         }
                     }
                 }
             }
+
+            // Snap the cursor to the plotted point nearest to the pointer on screen.
+            let transform = plot_ui.transform();
+            let cursor = plot_ui.pointer_coordinate().and_then(|pointer| {
+                let pointer_pos = transform.position_from_point(&pointer);
+                lines
+                    .iter()
+                    .filter_map(|points| {
+                        let x_vals: Vec<f64> = points.iter().map(|&[x, _]| x).collect();
+                        nearest_index(&x_vals, pointer.x).map(|index| points[index])
+                    })
+                    .min_by(|a, b| {
+                        let distance = |&[x, y]: &[f64; 2]| {
+                            transform
+                                .position_from_point(&PlotPoint::new(x, y))
+                                .distance(pointer_pos)
+                        };
+                        distance(a).total_cmp(&distance(b))
+                    })
+            });
+
+            if let Some(point) = cursor {
+                // Unnamed items stay out of the legend.
+                plot_ui.points(Points::new("", vec![point]).radius(4.0));
+            }
+            for &marker in &markers {
+                plot_ui.vline(VLine::new("", marker[0]));
+                plot_ui.points(Points::new("", vec![marker]).radius(4.0).filled(false));
+            }
+
+            let clicked = plot_ui.response().clicked();
+            (cursor, clicked, spectrum_info)
         });
+
+        let (cursor, clicked, spectrum_info) = response.inner;
+        self.cursor = cursor;
+        self.spectrum_info = spectrum_info;
+        if self.measure_mode
+            && clicked
+            && let Some(point) = cursor
+        {
+            // A third click starts a new measurement.
+            if self.markers.len() == 2 {
+                self.markers.clear();
+            }
+            self.markers.push(point);
+        }
     }
 
     fn refresh_entries(&mut self) {
@@ -367,74 +610,187 @@ impl KretsApp {
         }
     }
 
-    /// Loads data from a specified Parquet file into the app's state.
-    fn load_parquet_file(&mut self, path: &PathBuf) {
-        self.table_data = None; // Clear previous data
+    /// Loads data from a specified Parquet file and adds it to the loaded files. A file that is
+    /// already loaded is not loaded again.
+    fn load_parquet_file(&mut self, path: &Path) {
         self.error_message = None;
-        self.selection.clear(); // Clear selection when loading new file
-
-        match fs::File::open(path) {
-            Ok(file) => {
-                match ParquetRecordBatchReaderBuilder::try_new(file) {
-                    Ok(builder) => match builder.build() {
-                        Ok(reader) => {
-                            // Load all batches into a single Vec for simplicity
-                            let batches: Vec<Result<RecordBatch, _>> = reader.collect();
-                            let ok_batches: Vec<RecordBatch> =
-                                batches.into_iter().filter_map(Result::ok).collect();
-
-                            if ok_batches.is_empty() {
-                                self.error_message = Some(
-                                    "Parquet file is empty or has no valid batches.".to_string(),
-                                );
-                                return;
-                            }
+        // Canonicalize for consistency if possible
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.datasets.iter().any(|data| data.path == path) {
+            return;
+        }
 
-                            // For simplicity, we'll just display the first batch.
-                            // Concatenating batches could be done here if needed.
-                            let first_batch = ok_batches[0].clone();
+        match TableData::read(path) {
+            Ok(data) => self.datasets.push(data),
+            Err(e) => self.error_message = Some(e),
+        }
+    }
 
-                            let headers = first_batch
-                                .schema()
-                                .fields()
-                                .iter()
-                                .map(|field| field.name().clone())
-                                .collect();
+    /// Reads every loaded file again.
+    fn reload_all(&mut self) {
+        self.error_message = None;
+        for index in 0..self.datasets.len() {
+            self.reload_dataset(index);
+        }
+    }
 
-                            self.table_data = Some(TableData {
-                                headers,
-                                batch: first_batch,
-                            });
+    /// Reads the loaded files again whose modification time has changed since they were read.
+    /// A deleted file keeps its data and is reported once.
+    fn reload_changed(&mut self) {
+        for index in 0..self.datasets.len() {
+            let data = &mut self.datasets[index];
+            let modified = modification_time(&data.path);
+            if modified == data.modified {
+                continue;
+            }
+            if modified.is_none() {
+                data.modified = None;
+                self.error_message =
+                    Some(format!("File '{}' no longer exists", data.path.display()));
+                continue;
+            }
+            self.reload_dataset(index);
+        }
+    }
 
-                            // Update file_to_load to reflect the currently loaded file path
-                            // Canonicalize for consistency if possible
-                            let canonical = path.canonicalize().ok().or_else(|| Some(path.clone()));
-                            self.file_to_load = canonical.clone();
-                            // Record the successfully loaded file so future clicks on the same file do nothing
-                            self.current_loaded_file = canonical;
-                        }
-                        Err(e) => {
-                            self.error_message = Some(format!("Failed to read Parquet batch: {e}"));
-                        }
-                    },
-                    Err(e) => {
-                        self.error_message = Some(format!("Failed to build Parquet reader: {e}"));
-                    }
+    /// Reads the file at `index` of the loaded files again, keeping whether it is shown. The
+    /// columns selected in the first file stay selected by name. If the file cannot be read,
+    /// e.g. because it was deleted, the previous data is kept and the error is reported.
+    fn reload_dataset(&mut self, index: usize) {
+        let old = &self.datasets[index];
+        match TableData::read(old.path.clone()) {
+            Ok(mut data) => {
+                data.visible = old.visible;
+                if index == 0 {
+                    self.selection = remap_selection(&old.headers, &self.selection, &data.headers);
                 }
+                self.datasets[index] = data;
             }
             Err(e) => {
-                self.error_message =
-                    Some(format!("Failed to open file '{}': {}", path.display(), e))
+                self.datasets[index].modified = None;
+                self.error_message = Some(e);
             }
         }
     }
+
+    /// Unloads the file at `index` of the loaded files.
+    fn remove_dataset(&mut self, index: usize) {
+        self.datasets.remove(index);
+        // The columns are selected in the first file, so a new first file starts afresh.
+        if index == 0 {
+            self.selection.clear();
+            self.cursor = None;
+            self.markers.clear();
+        }
+    }
+}
+
+/// Returns the modification time of the file at `path`, or `None` if it cannot be read, e.g.
+/// because the file does not exist.
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Maps the `selection` of columns of `old_headers` to the columns of `new_headers` with the
+/// same names. Columns that no longer exist are dropped from the selection.
+fn remap_selection(
+    old_headers: &[String],
+    selection: &HashSet<usize>,
+    new_headers: &[String],
+) -> HashSet<usize> {
+    selection
+        .iter()
+        .filter_map(|&index| old_headers.get(index))
+        .filter_map(|name| new_headers.iter().position(|header| header == name))
+        .collect()
+}
+
+/// Reads all record batches of a Parquet file and concatenates them into a single batch.
+fn read_parquet_file(path: &PathBuf) -> Result<RecordBatch, String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open file '{}': {}", path.display(), e))?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(|e| format!("Failed to build Parquet reader: {e}"))?
+        .build()
+        .map_err(|e| format!("Failed to read Parquet batch: {e}"))?;
+
+    let batches = reader
+        .collect::<Result<Vec<RecordBatch>, _>>()
+        .map_err(|e| format!("Failed to read Parquet batch: {e}"))?;
+
+    let Some(first_batch) = batches.first() else {
+        return Err("Parquet file is empty or has no valid batches.".to_string());
+    };
+
+    // Fails if the batches do not share the schema of the first one.
+    concat_batches(&first_batch.schema(), &batches)
+        .map_err(|e| format!("Failed to merge Parquet batches: {e}"))
+}
+
+/// Returns the unit of every column of `batch`, read from the `unit` key of the Arrow field
+/// metadata. Columns without one, e.g. from files written before units were recorded, get
+/// `None`.
+fn column_units(batch: &RecordBatch) -> Vec<Option<String>> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.metadata().get("unit").cloned())
+        .collect()
+}
+
+/// Picks the selected column to plot on the X axis: "time", "frequency" or "step" in that
+/// order of preference, otherwise the leftmost selected column.
+fn x_axis_index(headers: &[String], selection: &HashSet<usize>) -> Option<usize> {
+    ["time", "frequency", "step"]
+        .iter()
+        .find_map(|name| {
+            headers
+                .iter()
+                .position(|h| h == name)
+                .filter(|index| selection.contains(index))
+        })
+        .or_else(|| selection.iter().min().copied())
+}
+
+/// Returns the index of the X column of `reference`, at `idx_x`, in `other`.
+///
+/// Files share an X axis when they have a column with the same name and unit, e.g. the `time`
+/// of two transients, so their curves line up on a common axis. Returns `None` for a file
+/// without such a column, which is left out of the plot.
+fn shared_x_index(reference: &TableData, idx_x: usize, other: &TableData) -> Option<usize> {
+    let index = other.column_index(&reference.headers[idx_x])?;
+    (other.units[index] == reference.units[idx_x]).then_some(index)
+}
+
+/// Formats an axis label as `name [unit]`, or just `name` without a unit.
+fn axis_label(name: &str, unit: Option<&str>) -> String {
+    match unit {
+        Some(unit) => format!("{name} [{unit}]"),
+        None => name.to_string(),
+    }
+}
+
+/// Labels the Y axis with the unit shared by all plotted columns, or "dB" when plotting in
+/// decibels. Returns `None` if the columns have no unit or different ones.
+fn y_axis_label<'a>(
+    mut units: impl Iterator<Item = Option<&'a str>>,
+    db_y: bool,
+) -> Option<String> {
+    if db_y {
+        return Some("dB".to_string());
+    }
+    let first = units.next()??;
+    units
+        .all(|unit| unit == Some(first))
+        .then(|| format!("[{first}]"))
 }
 
 /// Helper to get min/max stats for an Arrow array as strings.
 fn get_col_stats(array: &arrow::array::ArrayRef) -> (String, String) {
-    use arrow::array::{
-        Array, Float32Array, Float64Array, Int8Array, Int16Array, Int32Array, Int64Array,
-    };
+    use arrow::array::{Array, Int8Array, Int16Array, Int32Array, Int64Array};
     use arrow::compute::kernels::aggregate::{max, min};
 
     fn format_opt<T: ToString>(opt: Option<T>) -> String {
@@ -458,17 +814,17 @@ fn get_col_stats(array: &arrow::array::ArrayRef) -> (String, String) {
             let arr = array.as_any().downcast_ref::<Int64Array>().unwrap();
             (format_opt(min(arr)), format_opt(max(arr)))
         }
-        arrow::datatypes::DataType::Float32 => {
-            let arr = array.as_any().downcast_ref::<Float32Array>().unwrap();
-            let min_str = min(arr).map_or_else(|| "NULL".to_string(), |v| format!("{v:.4}"));
-            let max_str = max(arr).map_or_else(|| "NULL".to_string(), |v| format!("{v:.4}"));
-            (min_str, max_str)
-        }
-        arrow::datatypes::DataType::Float64 => {
-            let arr = array.as_any().downcast_ref::<Float64Array>().unwrap();
-            let min_str = min(arr).map_or_else(|| "NULL".to_string(), |v| format!("{v:.4}"));
-            let max_str = max(arr).map_or_else(|| "NULL".to_string(), |v| format!("{v:.4}"));
-            (min_str, max_str)
+        arrow::datatypes::DataType::Float32 | arrow::datatypes::DataType::Float64 => {
+            // Nulls read as NaN, so skipping NaN skips nulls and stored NaN values alike. The
+            // Arrow kernels would order NaN above every number instead.
+            let values = get_column_as_f64(array).unwrap_or_default();
+            let numbers = || values.iter().copied().filter(|v| !v.is_nan());
+            let format =
+                |v: Option<f64>| v.map_or_else(|| "NULL".to_string(), |v| format!("{v:.4}"));
+            (
+                format(numbers().reduce(f64::min)),
+                format(numbers().reduce(f64::max)),
+            )
         }
         _ => ("N/A".to_string(), "N/A".to_string()),
     }
@@ -509,6 +865,154 @@ fn get_column_as_f64(array: &arrow::array::ArrayRef) -> Option<Vec<f64>> {
     }
 }
 
+/// Converts a magnitude to decibels, `20 * log10(magnitude)`.
+/// Returns `None` for non-positive magnitudes, which have no finite dB value.
+fn magnitude_to_db(magnitude: f64) -> Option<f64> {
+    (magnitude > 0.0).then(|| 20.0 * magnitude.log10())
+}
+
+/// Combines the X and Y values into plot points, applying the selected axis transforms.
+/// Points with a NaN value, such as the nulls of a signal missing from some sweep steps, and
+/// points with non-positive values on a logarithmic axis are dropped.
+fn transform_points(x_vals: &[f64], y_vals: &[f64], log_x: bool, db_y: bool) -> Vec<[f64; 2]> {
+    x_vals
+        .iter()
+        .zip(y_vals)
+        .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+        .filter_map(|(&x, &y)| {
+            let x = if log_x {
+                (x > 0.0).then(|| x.log10())?
+            } else {
+                x
+            };
+            let y = if db_y { magnitude_to_db(y)? } else { y };
+            Some([x, y])
+        })
+        .collect()
+}
+
+/// The fundamental of a spectrum and its total harmonic distortion.
+#[derive(Debug)]
+struct Harmonics {
+    /// Frequency of the largest non-DC component.
+    fundamental: f64,
+    /// Amplitude of the fundamental.
+    amplitude: f64,
+    /// Total harmonic distortion as a fraction of the fundamental, if at least one harmonic
+    /// lies below the Nyquist frequency.
+    thd: Option<f64>,
+}
+
+impl Harmonics {
+    /// Formats the fundamental and THD of the column `name` for display.
+    fn describe(&self, name: &str) -> String {
+        let thd = self
+            .thd
+            .map_or_else(|| "n/a".to_string(), |thd| format!("{:.4}%", 100.0 * thd));
+        format!(
+            "{name}: fundamental {:.6e} Hz, amplitude {:.6e}, THD {thd}",
+            self.fundamental, self.amplitude
+        )
+    }
+}
+
+/// Computes the single-sided amplitude spectrum `[frequency, amplitude]` of `values` sampled
+/// at `time`, which must be increasing but may be spaced non-uniformly.
+///
+/// The signal is linearly interpolated onto a uniform grid of `2^n` samples, at least as many
+/// as there are input samples, before the FFT. Returns `None` if fewer than two finite
+/// samples span a positive time.
+fn amplitude_spectrum(time: &[f64], values: &[f64]) -> Option<Vec<[f64; 2]>> {
+    let samples: Vec<(f64, f64)> = time
+        .iter()
+        .zip(values)
+        .filter(|(t, v)| t.is_finite() && v.is_finite())
+        .map(|(&t, &v)| (t, v))
+        .collect();
+    let (&(start, _), &(stop, _)) = samples.first().zip(samples.last())?;
+    if samples.len() < 2 || stop <= start {
+        return None;
+    }
+
+    let n = samples.len().next_power_of_two();
+    let dt = (stop - start) / n as f64;
+
+    // Linearly interpolate onto the uniform grid, walking both grids in step.
+    let mut buffer = Vec::with_capacity(n);
+    let mut segment = 0;
+    for i in 0..n {
+        let t = start + i as f64 * dt;
+        while segment + 2 < samples.len() && samples[segment + 1].0 < t {
+            segment += 1;
+        }
+        let ((t0, v0), (t1, v1)) = (samples[segment], samples[segment + 1]);
+        let value = if t1 > t0 {
+            v0 + (v1 - v0) * (t - t0) / (t1 - t0)
+        } else {
+            v0
+        };
+        buffer.push(Complex::new(value, 0.0));
+    }
+
+    FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
+
+    // Fold the negative frequencies onto the positive ones, except for DC and Nyquist.
+    let spectrum = buffer[..=n / 2]
+        .iter()
+        .enumerate()
+        .map(|(k, x)| {
+            let scale = if k == 0 || k == n / 2 { 1.0 } else { 2.0 };
+            [k as f64 / (n as f64 * dt), scale * x.norm() / n as f64]
+        })
+        .collect();
+    Some(spectrum)
+}
+
+/// Finds the fundamental of an amplitude spectrum and its total harmonic distortion, the RMS
+/// sum of the harmonics below the Nyquist frequency relative to the fundamental.
+fn harmonic_distortion(spectrum: &[[f64; 2]]) -> Option<Harmonics> {
+    let (index, &[fundamental, amplitude]) = spectrum
+        .iter()
+        .enumerate()
+        .skip(1)
+        .max_by(|(_, a), (_, b)| a[1].total_cmp(&b[1]))?;
+    if amplitude <= 0.0 {
+        return None;
+    }
+
+    let harmonics: Vec<f64> = (2..)
+        .map(|order| order * index)
+        .take_while(|&bin| bin < spectrum.len())
+        .map(|bin| spectrum[bin][1])
+        .collect();
+    let thd = (!harmonics.is_empty())
+        .then(|| harmonics.iter().map(|h| h * h).sum::<f64>().sqrt() / amplitude);
+
+    Some(Harmonics {
+        fundamental,
+        amplitude,
+        thd,
+    })
+}
+
+/// Converts a point in plot coordinates back to data values, undoing the logarithmic X axis.
+/// Y values in decibels are kept in decibels.
+fn plot_to_data([x, y]: [f64; 2], log_x: bool) -> [f64; 2] {
+    let x = if log_x { 10f64.powf(x) } else { x };
+    [x, y]
+}
+
+/// Returns the index of the value in `x_vals` closest to `target`, ignoring NaN values.
+/// Returns `None` if there are no such values.
+fn nearest_index(x_vals: &[f64], target: f64) -> Option<usize> {
+    x_vals
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| !x.is_nan())
+        .min_by(|(_, a), (_, b)| (*a - target).abs().total_cmp(&(*b - target).abs()))
+        .map(|(index, _)| index)
+}
+
 /// This function launches the native eframe GUI application with specific starting paths.
 pub fn run_gui(
     initial_folder_path: PathBuf,
@@ -531,3 +1035,333 @@ pub fn run_gui(
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_parquet_file_concatenates_batches() {
+        use arrow::array::Float64Array;
+        use arrow::datatypes::{DataType, Field, Schema};
+        use parquet::arrow::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "time",
+            DataType::Float64,
+            false,
+        )]));
+        let batch = |start: usize, len: usize| {
+            let values: Float64Array = (start..start + len).map(|i| i as f64).collect();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(values)]).unwrap()
+        };
+
+        let path = std::env::temp_dir().join("krets_gui_two_batches.parquet");
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(1500)
+            .build();
+        let file = fs::File::create(&path).unwrap();
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties)).unwrap();
+        writer.write(&batch(0, 1500)).unwrap();
+        writer.write(&batch(1500, 1000)).unwrap();
+        writer.close().unwrap();
+
+        let loaded = read_parquet_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.num_rows(), 2500);
+        let time = get_column_as_f64(loaded.column(0)).unwrap();
+        assert_eq!(time.last(), Some(&2499.0));
+    }
+
+    /// Writes a Parquet file with the `columns` in that order, each holding `0, 1, 2`. A
+    /// `time` column is in seconds.
+    fn write_columns(name: &str, columns: &[&str]) -> PathBuf {
+        use arrow::array::{ArrayRef, Float64Array};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use parquet::arrow::ArrowWriter;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        let fields: Vec<Field> = columns
+            .iter()
+            .map(|&column| {
+                let field = Field::new(column, DataType::Float64, false);
+                if column == "time" {
+                    field.with_metadata(HashMap::from([("unit".to_string(), "s".to_string())]))
+                } else {
+                    field
+                }
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let values = || Arc::new(Float64Array::from(vec![0.0, 1.0, 2.0])) as ArrayRef;
+        let batch =
+            RecordBatch::try_new(schema.clone(), columns.iter().map(|_| values()).collect())
+                .unwrap();
+
+        let path = std::env::temp_dir().join(name);
+        let mut writer =
+            ArrowWriter::try_new(fs::File::create(&path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_loading_files_adds_datasets() {
+        let first = write_columns("krets_gui_variant_a.parquet", &["V(out)", "time"]);
+        let second = write_columns("krets_gui_variant_b.parquet", &["V(out)", "time"]);
+
+        let mut app = KretsApp::new(std::env::temp_dir(), Some(first.clone()));
+        app.selection.insert(0);
+        app.load_parquet_file(&second);
+        // Loading a file again does not add it twice.
+        app.load_parquet_file(&first);
+        fs::remove_file(&first).unwrap();
+        fs::remove_file(&second).unwrap();
+
+        let labels: Vec<String> = app.datasets.iter().map(TableData::label).collect();
+        assert_eq!(labels, ["krets_gui_variant_a", "krets_gui_variant_b"]);
+        assert!(app.datasets.iter().all(|data| data.visible));
+        assert_eq!(app.selection, HashSet::from([0]));
+
+        // Removing the first file drops the selection made in its columns.
+        app.remove_dataset(0);
+        assert_eq!(app.datasets.len(), 1);
+        assert!(app.selection.is_empty());
+    }
+
+    #[test]
+    fn test_reload_keeps_selection_by_name() {
+        let path = write_columns("krets_gui_reload.parquet", &["V(in)", "time", "V(out)"]);
+        let mut app = KretsApp::new(std::env::temp_dir(), Some(path.clone()));
+        app.selection = HashSet::from([1, 2]);
+
+        // The file is written again with its columns in another order and without `V(in)`.
+        write_columns("krets_gui_reload.parquet", &["time", "V(out)", "V(mid)"]);
+        app.reload_all();
+        assert_eq!(app.datasets[0].headers, ["time", "V(out)", "V(mid)"]);
+        assert_eq!(app.selection, HashSet::from([0, 1]));
+        assert!(app.error_message.is_none());
+
+        // A deleted file keeps its data and reports the error.
+        fs::remove_file(&path).unwrap();
+        app.reload_all();
+        assert_eq!(app.datasets.len(), 1);
+        assert_eq!(app.selection, HashSet::from([0, 1]));
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn test_remap_selection() {
+        let headers = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let old = headers(&["time", "V(a)", "V(b)"]);
+        let new = headers(&["V(b)", "V(c)", "time"]);
+
+        assert_eq!(
+            remap_selection(&old, &HashSet::from([0, 2]), &new),
+            HashSet::from([2, 0])
+        );
+        assert_eq!(
+            remap_selection(&old, &HashSet::from([1]), &new),
+            HashSet::new()
+        );
+        assert_eq!(
+            remap_selection(&old, &HashSet::from([7]), &new),
+            HashSet::new()
+        );
+    }
+
+    #[test]
+    fn test_shared_x_index_matches_name_and_unit() {
+        use arrow::array::{ArrayRef, Float64Array};
+        use std::sync::Arc;
+
+        let table = |headers: &[&str], units: &[Option<&str>]| {
+            let column = Arc::new(Float64Array::from(vec![1.0])) as ArrayRef;
+            TableData {
+                path: PathBuf::from("result.parquet"),
+                visible: true,
+                modified: None,
+                headers: headers.iter().map(|h| h.to_string()).collect(),
+                units: units.iter().map(|u| u.map(String::from)).collect(),
+                batch: RecordBatch::try_from_iter(
+                    headers.iter().map(|h| (h.to_string(), column.clone())),
+                )
+                .unwrap(),
+            }
+        };
+        let reference = table(&["time", "V(out)"], &[Some("s"), Some("V")]);
+
+        let reordered = table(&["V(out)", "time"], &[Some("V"), Some("s")]);
+        assert_eq!(shared_x_index(&reference, 0, &reordered), Some(1));
+        let sweep = table(&["step", "V(out)"], &[None, Some("V")]);
+        assert_eq!(shared_x_index(&reference, 0, &sweep), None);
+        let other_unit = table(&["time", "V(out)"], &[Some("ms"), Some("V")]);
+        assert_eq!(shared_x_index(&reference, 0, &other_unit), None);
+    }
+
+    #[test]
+    fn test_column_units_fall_back_without_metadata() {
+        use arrow::array::Float64Array;
+        use arrow::datatypes::{DataType, Field, Schema};
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        let frequency = Field::new("frequency", DataType::Float64, false)
+            .with_metadata(HashMap::from([("unit".to_string(), "Hz".to_string())]));
+        let gain = Field::new("gain", DataType::Float64, false);
+        let schema = Arc::new(Schema::new(vec![frequency, gain]));
+        let column = || Arc::new(Float64Array::from(vec![1.0])) as arrow::array::ArrayRef;
+        let batch = RecordBatch::try_new(schema, vec![column(), column()]).unwrap();
+
+        assert_eq!(column_units(&batch), vec![Some("Hz".to_string()), None]);
+    }
+
+    #[test]
+    fn test_axis_labels() {
+        assert_eq!(axis_label("frequency", Some("Hz")), "frequency [Hz]");
+        assert_eq!(axis_label("step", None), "step");
+
+        let units = [Some("V"), Some("V")];
+        assert_eq!(
+            y_axis_label(units.into_iter(), false),
+            Some("[V]".to_string())
+        );
+        assert_eq!(
+            y_axis_label(units.into_iter(), true),
+            Some("dB".to_string())
+        );
+        assert_eq!(
+            y_axis_label([Some("V"), Some("deg")].into_iter(), false),
+            None
+        );
+        assert_eq!(y_axis_label([Some("V"), None].into_iter(), false), None);
+        assert_eq!(y_axis_label(std::iter::empty(), false), None);
+    }
+
+    #[test]
+    fn test_x_axis_index_prefers_sweep_columns() {
+        let headers: Vec<String> = ["V(out)_mag", "frequency", "V(out)_phase_deg"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(x_axis_index(&headers, &HashSet::from([0, 1])), Some(1));
+        assert_eq!(x_axis_index(&headers, &HashSet::from([2, 0])), Some(0));
+        assert_eq!(x_axis_index(&headers, &HashSet::new()), None);
+    }
+
+    #[test]
+    fn test_magnitude_to_db() {
+        assert_eq!(magnitude_to_db(1.0), Some(0.0));
+        assert!((magnitude_to_db(0.1).unwrap() + 20.0).abs() < 1e-12);
+        assert!((magnitude_to_db(1.0 / 2f64.sqrt()).unwrap() + 3.0103).abs() < 1e-4);
+        assert_eq!(magnitude_to_db(0.0), None);
+        assert_eq!(magnitude_to_db(-1.0), None);
+    }
+
+    #[test]
+    fn test_transform_points_drops_non_positive_values() {
+        let x_vals = [0.0, 10.0, 100.0, 1000.0];
+        let y_vals = [1.0, 1.0, 0.0, 0.1];
+
+        let points = transform_points(&x_vals, &y_vals, true, true);
+        assert_eq!(points, vec![[1.0, 0.0], [3.0, -20.0]]);
+
+        let points = transform_points(&x_vals, &y_vals, false, false);
+        assert_eq!(points.len(), 4);
+    }
+
+    #[test]
+    fn test_null_values_are_not_plotted() {
+        use arrow::array::{ArrayRef, Float64Array};
+        use std::sync::Arc;
+
+        let x: ArrayRef = Arc::new(Float64Array::from(vec![0.0, 1.0, 2.0, 3.0, 4.0]));
+        let y: ArrayRef = Arc::new(Float64Array::from(vec![
+            Some(5.0),
+            None,
+            Some(-1.0),
+            None,
+            Some(2.0),
+        ]));
+        let x_vals = get_column_as_f64(&x).unwrap();
+        let y_vals = get_column_as_f64(&y).unwrap();
+
+        let points = transform_points(&x_vals, &y_vals, false, false);
+        assert_eq!(points, vec![[0.0, 5.0], [2.0, -1.0], [4.0, 2.0]]);
+        assert_eq!(transform_points(&y_vals, &x_vals, false, false).len(), 3);
+
+        assert_eq!(
+            get_col_stats(&y),
+            ("-1.0000".to_string(), "5.0000".to_string())
+        );
+        let nan: ArrayRef = Arc::new(Float64Array::from(vec![Some(f64::NAN), None, Some(1.0)]));
+        assert_eq!(
+            get_col_stats(&nan),
+            ("1.0000".to_string(), "1.0000".to_string())
+        );
+        let empty: ArrayRef = Arc::new(Float64Array::from(vec![None, None]));
+        assert_eq!(
+            get_col_stats(&empty),
+            ("NULL".to_string(), "NULL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_nearest_index() {
+        let x_vals = [0.0, 1.0, 2.5, f64::NAN, 4.0];
+
+        assert_eq!(nearest_index(&x_vals, -3.0), Some(0));
+        assert_eq!(nearest_index(&x_vals, 1.2), Some(1));
+        assert_eq!(nearest_index(&x_vals, 2.0), Some(2));
+        assert_eq!(nearest_index(&x_vals, 3.5), Some(4));
+        assert_eq!(nearest_index(&x_vals, 100.0), Some(4));
+        assert_eq!(nearest_index(&[], 1.0), None);
+        assert_eq!(nearest_index(&[f64::NAN], 1.0), None);
+    }
+
+    #[test]
+    fn test_amplitude_spectrum_of_sine() {
+        // Ten periods of a 1 kHz sine with amplitude 2, sampled non-uniformly.
+        let time: Vec<f64> = (0..=1000)
+            .map(|i| {
+                let jitter = if i % 2 == 1 { 2e-6 } else { 0.0 };
+                i as f64 * 10e-6 + jitter
+            })
+            .collect();
+        let values: Vec<f64> = time
+            .iter()
+            .map(|t| 2.0 * (2.0 * std::f64::consts::PI * 1e3 * t).sin())
+            .collect();
+
+        // 1001 samples resample to 1024 over 10 ms, so the bins are 100 Hz apart.
+        let spectrum = amplitude_spectrum(&time, &values).unwrap();
+        assert_eq!(spectrum.len(), 513);
+        assert!((spectrum[1][0] - 100.0).abs() < 1e-9);
+
+        let harmonics = harmonic_distortion(&spectrum).unwrap();
+        assert!((harmonics.fundamental - 1e3).abs() < 1e-9);
+        assert!((harmonics.amplitude - 2.0).abs() < 1e-2);
+        assert!(harmonics.thd.unwrap() < 1e-3);
+    }
+
+    #[test]
+    fn test_harmonic_distortion_with_third_harmonic() {
+        let time: Vec<f64> = (0..1024).map(|i| i as f64 / 1024.0).collect();
+        let values: Vec<f64> = time
+            .iter()
+            .map(|t| {
+                let x = (2.0 * std::f64::consts::PI * 8.0 * t).sin();
+                x + 0.1 * (2.0 * std::f64::consts::PI * 24.0 * t).sin()
+            })
+            .collect();
+
+        let spectrum = amplitude_spectrum(&time, &values).unwrap();
+        let harmonics = harmonic_distortion(&spectrum).unwrap();
+        assert!((harmonics.thd.unwrap() - 0.1).abs() < 1e-2);
+        assert!(amplitude_spectrum(&[0.0], &[1.0]).is_none());
+    }
+}
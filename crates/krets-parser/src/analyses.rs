@@ -38,10 +38,33 @@ pub enum Analysis {
 
     /// Transient Analysis.
     Transient(TransientAnalysis),
+
+    /// AC Noise Analysis.
+    Noise(NoiseAnalysis),
+
+    /// DC Transfer Function Analysis.
+    Tf(TfAnalysis),
+
+    /// DC Sensitivity Analysis of an output voltage to the element values.
+    Sensitivity(SensitivityAnalysis),
+
+    /// Pole-Zero Analysis of the linearized circuit.
+    #[serde(rename = "polezero")]
+    PoleZero(PoleZeroAnalysis),
+
+    /// Parameter Sweep, repeating an inner analysis for each value of an element or the temperature.
+    Sweep(ParamSweep),
+
+    /// Monte Carlo Analysis, repeating an inner analysis with element values drawn from their tolerances.
+    #[serde(rename = "montecarlo")]
+    MonteCarlo(MonteCarlo),
 }
 
 /// Contains the parameters for a DC Sweep analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// An optional secondary source is swept in an outer loop around the primary sweep, so the
+/// result spans the full grid of both sources, e.g. a family of MOSFET output curves.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DcAnalysis {
     /// The identifier of the element to sweep (e.g., "V1").
     pub element: String,
@@ -49,8 +72,98 @@ pub struct DcAnalysis {
     pub start: f64,
     /// The ending value for the sweep.
     pub stop: f64,
-    /// The increment for each step of the sweep.
-    pub step_size: f64,
+    /// How the values between `start` and `stop` are spaced.
+    #[serde(flatten)]
+    pub sweep: DcSweepType,
+    /// The identifier of the secondary element to sweep, if any.
+    #[serde(default)]
+    pub element2: Option<String>,
+    /// The starting value for the secondary sweep.
+    #[serde(default)]
+    pub start2: f64,
+    /// The ending value for the secondary sweep.
+    #[serde(default)]
+    pub stop2: f64,
+    /// The increment for each step of the secondary sweep.
+    #[serde(default)]
+    pub step2: f64,
+}
+
+/// The spacing of the values of a DC sweep.
+///
+/// The variant is picked by its field, e.g. `step_size = 0.1` for a linear sweep or
+/// `points_per_decade = 10` for a logarithmic one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DcSweepType {
+    /// Linear variation: Steps from `start` to `stop` by `step_size`.
+    Linear { step_size: f64 },
+    /// Decade variation: Specifies the number of points per decade from `start` to `stop`,
+    /// which must be non-zero and of the same sign.
+    Decade { points_per_decade: u32 },
+    /// An explicit list of values, swept in order. `start` and `stop` are ignored.
+    List { values: Vec<f64> },
+}
+
+impl Default for DcSweepType {
+    fn default() -> Self {
+        Self::Linear { step_size: 1.0 }
+    }
+}
+
+impl DcAnalysis {
+    /// Generates the primary swept values from `start` to `stop`, both included.
+    pub fn values(&self) -> Vec<f64> {
+        match &self.sweep {
+            DcSweepType::Linear { step_size } => sweep_values(self.start, self.stop, *step_size),
+            DcSweepType::Decade { points_per_decade } => {
+                decade_values(self.start, self.stop, *points_per_decade)
+            }
+            DcSweepType::List { values } => values.clone(),
+        }
+    }
+
+    /// Generates the secondary swept values from `start2` to `stop2`, both included, or
+    /// `None` without a secondary sweep.
+    pub fn secondary_values(&self) -> Option<Vec<f64>> {
+        self.element2
+            .as_ref()
+            .map(|_| sweep_values(self.start2, self.stop2, self.step2))
+    }
+}
+
+/// Generates the values of a linear sweep from `start` to `stop`, both included.
+fn sweep_values(start: f64, stop: f64, step_size: f64) -> Vec<f64> {
+    // Use an integer-based count to avoid floating-point precision issues.
+    let num_steps = ((stop - start) / step_size).abs() as usize + 1;
+    (0..num_steps)
+        .map(|i| start + i as f64 * step_size)
+        .collect()
+}
+
+/// Generates the values of a decade sweep from `start` to `stop`, both included.
+///
+/// Sweeps of negative values run over the magnitudes and keep the sign, and a sweep with
+/// `start` above `stop` in magnitude runs downwards.
+fn decade_values(start: f64, stop: f64, points_per_decade: u32) -> Vec<f64> {
+    if start == 0.0 || stop == 0.0 || start.signum() != stop.signum() {
+        info!(
+            "Warning: Invalid decade sweep range start={start}, stop={stop}. Returning empty sweep."
+        );
+        return Vec::new();
+    }
+
+    let sign = start.signum();
+    let (low, high) = (start.abs(), stop.abs());
+    let mut values = if low <= high {
+        logarithmic_values(low, high, 10.0, points_per_decade)
+    } else {
+        let mut values = logarithmic_values(high, low, 10.0, points_per_decade);
+        values.reverse();
+        values
+    };
+    values.iter_mut().for_each(|value| *value *= sign);
+    values
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,15 +189,33 @@ pub struct AcAnalysis {
 }
 
 impl AcAnalysis {
-    /// Generates a vector of frequencies based on the AC analysis sweep parameters.
+    /// Returns why the frequency range cannot be swept, or `None` if it can.
+    ///
+    /// A linear sweep may start at 0Hz, a DC point where capacitors are open and inductors
+    /// are shorts. A decade or octave sweep cannot, as 0Hz lies infinitely many decades below
+    /// any other frequency.
+    pub fn invalid_range(&self) -> Option<&'static str> {
+        if self.fstart < 0.0 || self.fstop < 0.0 {
+            Some("frequencies must not be negative")
+        } else if self.fstart > self.fstop {
+            Some("fstart lies above fstop")
+        } else if self.fstart == 0.0 && !matches!(self.sweep, AcSweep::Linear { .. }) {
+            Some("a logarithmic sweep cannot start at 0Hz, use a linear sweep to include DC")
+        } else {
+            None
+        }
+    }
+
+    /// Generates a vector of frequencies based on the AC analysis sweep parameters. The list is
+    /// empty if the range is invalid, see [`AcAnalysis::invalid_range`].
     pub fn generate_frequencies(self) -> Vec<f64> {
         let mut freqs = Vec::new();
         let fstart = self.fstart;
         let fstop = self.fstop;
 
-        if fstart <= 0.0 || fstop <= 0.0 || fstart > fstop {
+        if let Some(reason) = self.invalid_range() {
             info!(
-                "Warning: Invalid frequency range fstart={fstart}, fstop={fstop}. Returning empty frequency list."
+                "Warning: Invalid frequency range fstart={fstart}, fstop={fstop}: {reason}. Returning empty frequency list."
             );
             return freqs; // Return empty vector for invalid range
         }
@@ -101,67 +232,160 @@ impl AcAnalysis {
                 } // If total_points is 0, freqs remains empty
             }
             AcSweep::Decade { points_per_decade } => {
-                if points_per_decade == 0 {
-                    return freqs;
-                } // Avoid infinite loop/division by zero
-                let num_decades = (fstop / fstart).log10();
-                let total_points = (num_decades * points_per_decade as f64).round() as u32 + 1;
-                let factor = 10.0f64.powf(1.0 / points_per_decade as f64);
-                let mut current_freq = fstart;
-                for _ in 0..total_points {
-                    if current_freq > fstop * (1.0 + 1e-9) {
-                        break;
-                    } // Add tolerance for float comparison
-                    freqs.push(current_freq);
-                    current_freq *= factor;
-
-                    // Ensure fstop is included if the loop finishes slightly before it
-                    if current_freq > fstop && freqs.last().is_none_or(|&f| f < fstop) {
-                        freqs.push(fstop);
-                        break;
-                    }
-                }
-                // Ensure fstop is included if factor logic steps over it
-                if freqs.last().is_none_or(|&f| f < fstop * (1.0 - 1e-9)) {
-                    freqs.push(fstop);
-                }
+                freqs = logarithmic_values(fstart, fstop, 10.0, points_per_decade);
             }
             AcSweep::Octave { points_per_octave } => {
-                if points_per_octave == 0 {
-                    return freqs;
-                }
-                let num_octaves = (fstop / fstart).log2();
-                let total_points = (num_octaves * points_per_octave as f64).round() as u32 + 1;
-                let factor = 2.0f64.powf(1.0 / points_per_octave as f64);
-                let mut current_freq = fstart;
-                for _ in 0..total_points {
-                    if current_freq > fstop * (1.0 + 1e-9) {
-                        break;
-                    }
-                    freqs.push(current_freq);
-                    current_freq *= factor;
-
-                    if current_freq > fstop && freqs.last().is_none_or(|&f| f < fstop) {
-                        freqs.push(fstop);
-                        break;
-                    }
-                }
-                if freqs.last().is_none_or(|&f| f < fstop * (1.0 - 1e-9)) {
-                    freqs.push(fstop);
-                }
+                freqs = logarithmic_values(fstart, fstop, 2.0, points_per_octave);
             }
         }
-        // Ensure uniqueness and sort, although generation methods should ideally produce sorted unique values.
-        freqs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-        freqs.dedup();
         freqs
     }
 }
 
+/// Generates `fstart * base^(i / points_per_base)` up to `fstop`, with `fstop` always included
+/// exactly once as the last point.
+fn logarithmic_values(fstart: f64, fstop: f64, base: f64, points_per_base: u32) -> Vec<f64> {
+    if points_per_base == 0 {
+        return Vec::new();
+    }
+
+    // Tolerance so that an `fstop` lying on the grid (e.g. 1kHz at 1 point per decade from
+    // 1Hz) is neither skipped nor duplicated because of rounding in the logarithm.
+    const TOLERANCE: f64 = 1e-9;
+    let steps = (fstop / fstart).log(base) * points_per_base as f64;
+    let whole_steps = (steps + TOLERANCE).floor() as u32;
+
+    let mut freqs: Vec<f64> = (0..=whole_steps)
+        .map(|i| fstart * base.powf(i as f64 / points_per_base as f64))
+        .collect();
+    if steps - whole_steps as f64 > TOLERANCE {
+        freqs.push(fstop);
+    } else if let Some(last) = freqs.last_mut() {
+        *last = fstop;
+    }
+    freqs
+}
+
+/// Holds the parameters for an AC Noise Analysis (`.noise`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoiseAnalysis {
+    /// The node at which the output noise is measured (e.g., "out").
+    pub output_node: String,
+    /// The identifier of the independent source the input noise is referred to (e.g., "V1").
+    pub input_source: String,
+    /// The type of sweep and its corresponding point specification.
+    pub sweep: AcSweep,
+    /// The starting frequency (`fstart`) in Hertz.
+    pub fstart: f64,
+    /// The final frequency (`fstop`) in Hertz.
+    pub fstop: f64,
+}
+
+impl NoiseAnalysis {
+    /// Returns the equivalent AC sweep.
+    fn ac_sweep(&self) -> AcAnalysis {
+        AcAnalysis {
+            sweep: self.sweep.clone(),
+            fstart: self.fstart,
+            fstop: self.fstop,
+        }
+    }
+
+    /// Returns why the frequency range cannot be swept, exactly like the equivalent AC sweep.
+    pub fn invalid_range(&self) -> Option<&'static str> {
+        self.ac_sweep().invalid_range()
+    }
+
+    /// Generates a vector of frequencies, exactly like the equivalent AC sweep.
+    pub fn generate_frequencies(self) -> Vec<f64> {
+        self.ac_sweep().generate_frequencies()
+    }
+}
+
+/// Holds the parameters for a DC Transfer Function analysis (`.tf`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TfAnalysis {
+    /// The node whose voltage is the output (e.g., "out").
+    pub output: String,
+    /// The identifier of the independent voltage source driving the input (e.g., "V1").
+    pub input_source: String,
+}
+
+/// Holds the parameters for a DC Sensitivity analysis (`.sens`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitivityAnalysis {
+    /// The node whose voltage is the output (e.g., "out").
+    pub output: String,
+}
+
+/// Holds the parameters for a Pole-Zero analysis (`.pz`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoleZeroAnalysis {
+    /// The node whose voltage is the output of the transfer function (e.g., "out").
+    pub output: String,
+    /// The identifier of the independent voltage or current source driving the input (e.g., "V1").
+    pub input: String,
+}
+
+/// Contains the parameters for a Parameter Sweep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParamSweep {
+    /// The identifier of the element whose value is swept (e.g., "R2"), or `temp` to sweep
+    /// the circuit temperature in degrees Celsius.
+    pub element_or_param: String,
+    /// The starting value for the sweep.
+    pub start: f64,
+    /// The ending value for the sweep.
+    pub stop: f64,
+    /// The increment for each step of the sweep.
+    pub step_size: f64,
+    /// The analysis to run at each swept value.
+    pub inner: Box<Analysis>,
+}
+
+impl ParamSweep {
+    /// Generates the swept values from `start` to `stop`, both included.
+    pub fn values(&self) -> Vec<f64> {
+        sweep_values(self.start, self.stop, self.step_size)
+    }
+}
+
+/// The distribution element values are drawn from in a Monte Carlo analysis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Distribution {
+    /// Normal distribution with the tolerance as its 3-sigma deviation.
+    #[default]
+    Gaussian,
+    /// Uniform distribution over the full tolerance band.
+    Uniform,
+}
+
+/// Contains the parameters for a Monte Carlo analysis.
+///
+/// Every run draws the value of each resistor, capacitor and inductor with a tolerance
+/// (`tol=5%`) around its nominal value and repeats the inner analysis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarlo {
+    /// The number of runs.
+    pub runs: usize,
+    /// The seed of the random number generator, so that runs are reproducible.
+    #[serde(default)]
+    pub seed: u64,
+    /// The distribution of the element values within their tolerances.
+    #[serde(default)]
+    pub distribution: Distribution,
+    /// The analysis to run for every set of element values.
+    pub inner: Box<Analysis>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransientAnalysis {
     pub time_step: f64,
     pub stop_time: f64,
+    /// Start from the `.ic` node voltages instead of the DC operating point (UIC).
+    #[serde(default)]
+    pub use_initial_conditions: bool,
 }
 
 // Add a small test that parses a transient TOML block.
@@ -212,6 +436,78 @@ stop_time = 1e-3
         }
     }
 
+    #[test]
+    fn parse_monte_carlo_toml() {
+        let toml_str = r#"
+[montecarlo]
+runs = 100
+seed = 42
+distribution = "uniform"
+inner = "op"
+"#;
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::MonteCarlo(mc) => {
+                assert_eq!(mc.runs, 100);
+                assert_eq!(mc.seed, 42);
+                assert_eq!(mc.distribution, Distribution::Uniform);
+                assert!(matches!(*mc.inner, Analysis::Op));
+            }
+            other => panic!("expected MonteCarlo analysis, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_tf_toml() {
+        let toml_str = r#"
+[tf]
+output = "out"
+input_source = "V1"
+"#;
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::Tf(tf) => {
+                assert_eq!(tf.output, "out");
+                assert_eq!(tf.input_source, "V1");
+            }
+            other => panic!("expected Tf analysis, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_sensitivity_toml() {
+        let toml_str = r#"
+[sensitivity]
+output = "out"
+"#;
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::Sensitivity(sensitivity) => assert_eq!(sensitivity.output, "out"),
+            other => panic!("expected Sensitivity analysis, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_pole_zero_toml() {
+        let toml_str = r#"
+[polezero]
+output = "out"
+input = "V1"
+"#;
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::PoleZero(pz) => {
+                assert_eq!(pz.output, "out");
+                assert_eq!(pz.input, "V1");
+            }
+            other => panic!("expected PoleZero analysis, got {:?}", other),
+        }
+    }
+
     #[test]
     fn parse_ac_toml() {
         let toml_str = r#"
@@ -266,4 +562,239 @@ fstop = 1000.0
             other => panic!("expected Ac analysis in AnalysisSpec, got {:?}", other),
         }
     }
+
+    #[test]
+    fn generate_frequencies_table() {
+        let cases = [
+            (
+                AcSweep::Decade {
+                    points_per_decade: 1,
+                },
+                1.0,
+                1e3,
+                vec![1.0, 10.0, 100.0, 1e3],
+            ),
+            (
+                AcSweep::Decade {
+                    points_per_decade: 1,
+                },
+                1.0,
+                500.0,
+                vec![1.0, 10.0, 100.0, 500.0],
+            ),
+            (
+                AcSweep::Octave {
+                    points_per_octave: 1,
+                },
+                1.0,
+                8.0,
+                vec![1.0, 2.0, 4.0, 8.0],
+            ),
+            (
+                AcSweep::Octave {
+                    points_per_octave: 1,
+                },
+                1.0,
+                10.0,
+                vec![1.0, 2.0, 4.0, 8.0, 10.0],
+            ),
+            (
+                AcSweep::Linear { total_points: 3 },
+                1.0,
+                3.0,
+                vec![1.0, 2.0, 3.0],
+            ),
+            (AcSweep::Linear { total_points: 1 }, 5.0, 10.0, vec![5.0]),
+            (AcSweep::Linear { total_points: 0 }, 1.0, 10.0, vec![]),
+            (
+                AcSweep::Decade {
+                    points_per_decade: 0,
+                },
+                1.0,
+                10.0,
+                vec![],
+            ),
+        ];
+
+        for (sweep, fstart, fstop, expected) in cases {
+            let freqs = AcAnalysis {
+                sweep: sweep.clone(),
+                fstart,
+                fstop,
+            }
+            .generate_frequencies();
+            assert_eq!(
+                freqs.len(),
+                expected.len(),
+                "{sweep:?} from {fstart} to {fstop}"
+            );
+            for (f, e) in freqs.iter().zip(&expected) {
+                assert!((f - e).abs() <= 1e-9 * e, "{sweep:?}: got {freqs:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn generate_frequencies_from_zero() {
+        let linear = AcAnalysis {
+            sweep: AcSweep::Linear { total_points: 3 },
+            fstart: 0.0,
+            fstop: 2.0,
+        };
+        assert_eq!(linear.invalid_range(), None);
+        assert_eq!(linear.generate_frequencies(), vec![0.0, 1.0, 2.0]);
+
+        let decade = AcAnalysis {
+            sweep: AcSweep::Decade {
+                points_per_decade: 10,
+            },
+            fstart: 0.0,
+            fstop: 1e3,
+        };
+        assert!(decade.invalid_range().is_some());
+        assert!(decade.generate_frequencies().is_empty());
+
+        for (fstart, fstop) in [(-1.0, 1.0), (0.0, -1.0), (2.0, 1.0)] {
+            let ac = AcAnalysis {
+                sweep: AcSweep::Linear { total_points: 3 },
+                fstart,
+                fstop,
+            };
+            assert!(ac.invalid_range().is_some(), "{fstart} to {fstop}");
+        }
+    }
+
+    #[test]
+    fn generate_frequencies_decade_has_single_fstop() {
+        for (fstart, fstop) in [(1.0, 1e3), (1.0, 1e6), (3.0, 7e4), (10.0, 1e9)] {
+            let freqs = AcAnalysis {
+                sweep: AcSweep::Decade {
+                    points_per_decade: 10,
+                },
+                fstart,
+                fstop,
+            }
+            .generate_frequencies();
+
+            assert_eq!(freqs.first(), Some(&fstart));
+            assert_eq!(freqs.last(), Some(&fstop));
+            assert!(freqs.windows(2).all(|pair| pair[0] < pair[1]));
+            // Consecutive points never collapse onto fstop.
+            assert!(freqs[freqs.len() - 2] < fstop * (1.0 - 1e-6));
+        }
+
+        let freqs = AcAnalysis {
+            sweep: AcSweep::Decade {
+                points_per_decade: 10,
+            },
+            fstart: 1.0,
+            fstop: 1e3,
+        }
+        .generate_frequencies();
+        assert_eq!(freqs.len(), 31);
+    }
+
+    #[test]
+    fn parse_nested_dc_toml() {
+        let toml_str = r#"
+[dc]
+element = "VDS"
+start = 0.0
+stop = 5.0
+step_size = 0.1
+element2 = "VGS"
+start2 = 1.0
+stop2 = 3.0
+step2 = 1.0
+"#;
+
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::Dc(dc) => {
+                assert_eq!(dc.values().len(), 51);
+                assert_eq!(dc.element2.as_deref(), Some("VGS"));
+                assert_eq!(dc.secondary_values(), Some(vec![1.0, 2.0, 3.0]));
+            }
+            other => panic!("expected Dc analysis, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_dc_sweep_types_toml() {
+        let parse_dc = |sweep: &str| {
+            let toml_str = format!("[dc]\nelement = \"I1\"\nstart = 1e-9\nstop = 1e-6\n{sweep}\n");
+            match toml::from_str(&toml_str).expect("failed to parse TOML into Analysis") {
+                Analysis::Dc(dc) => dc,
+                other => panic!("expected Dc analysis, got {:?}", other),
+            }
+        };
+
+        let decade = parse_dc("points_per_decade = 10");
+        assert_eq!(
+            decade.sweep,
+            DcSweepType::Decade {
+                points_per_decade: 10
+            }
+        );
+        assert_eq!(decade.values().len(), 31);
+
+        let list = parse_dc("values = [1.0, 0.5, 2.0]");
+        assert_eq!(list.values(), vec![1.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn test_decade_values() {
+        assert_eq!(decade_values(1.0, 100.0, 1), vec![1.0, 10.0, 100.0]);
+        assert_eq!(decade_values(100.0, 1.0, 1), vec![100.0, 10.0, 1.0]);
+        assert_eq!(decade_values(-1.0, -100.0, 1), vec![-1.0, -10.0, -100.0]);
+        assert!(decade_values(0.0, 1.0, 10).is_empty());
+        assert!(decade_values(-1.0, 1.0, 10).is_empty());
+    }
+
+    #[test]
+    fn parse_noise_toml() {
+        let toml_str = r#"
+[noise]
+output_node = "out"
+input_source = "V1"
+sweep = { variation = "Linear", total_points = 3 }
+fstart = 1.0
+fstop = 3.0
+"#;
+
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::Noise(n) => {
+                assert_eq!(n.output_node, "out");
+                assert_eq!(n.input_source, "V1");
+                assert_eq!(n.generate_frequencies(), vec![1.0, 2.0, 3.0]);
+            }
+            other => panic!("expected Noise analysis, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_param_sweep_toml() {
+        let toml_str = r#"
+[sweep]
+element_or_param = "R2"
+start = 1e3
+stop = 3e3
+step_size = 1e3
+inner = "op"
+"#;
+
+        let parsed: Analysis =
+            toml::from_str(toml_str).expect("failed to parse TOML into Analysis");
+        match parsed {
+            Analysis::Sweep(sweep) => {
+                assert_eq!(sweep.element_or_param, "R2");
+                assert!(matches!(*sweep.inner, Analysis::Op));
+                assert_eq!(sweep.values(), vec![1e3, 2e3, 3e3]);
+            }
+            other => panic!("expected Sweep analysis, got {:?}", other),
+        }
+    }
 }
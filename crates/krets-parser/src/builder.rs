@@ -0,0 +1,248 @@
+use crate::prelude::*;
+
+use crate::{
+    circuit::Circuit,
+    constants::{GROUND_ALIASES, TEMPERATURE},
+    elements::{
+        capacitor::Capacitor, current_source::CurrentSource, diode::Diode, inductor::Inductor,
+        resistor::Resistor, vccs::VCCS, vcvs::VCVS, voltage_source::VoltageSource,
+    },
+    models::{Model, diode::DiodeModel},
+};
+
+/// Builds a `Circuit` programmatically instead of parsing a netlist.
+///
+/// Elements are named by their identifier, e.g. `R1`; the leading type letter may be left
+/// out. Nodes named after one of the default ground aliases, e.g. `gnd`, are ground.
+/// [`CircuitBuilder::build`] assigns the MNA rows and applies the models exactly like
+/// `parse_circuit_description` does for the same elements in the same order, so both
+/// circuits give identical solver results.
+///
+/// ```
+/// use krets_parser::builder::CircuitBuilder;
+///
+/// let circuit = CircuitBuilder::new()
+///     .voltage_source("V1", "in", "0", 1.0)
+///     .resistor("R1", "in", "out", 1e3)
+///     .resistor("R2", "out", "0", 2e3)
+///     .build()
+///     .unwrap();
+/// assert_eq!(circuit.index_map["I(V1)"], 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CircuitBuilder {
+    elements: Vec<Element>,
+    models: HashMap<String, Model>,
+}
+
+impl CircuitBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a resistor of `value` Ohms between `plus` and `minus`. A 0Ω resistor is an ideal
+    /// wire with the branch current `I(R...)`.
+    pub fn resistor(self, name: &str, plus: &str, minus: &str, value: f64) -> Self {
+        self.element(Element::Resistor(Resistor {
+            name: element_name(name, 'R'),
+            value,
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            g2: value == 0.0,
+            tc1: 0.0,
+            tc2: 0.0,
+            temperature: TEMPERATURE,
+            tolerance: None,
+        }))
+    }
+
+    /// Adds a capacitor of `value` Farads between `plus` and `minus`.
+    pub fn capacitor(self, name: &str, plus: &str, minus: &str, value: f64) -> Self {
+        self.element(Element::Capacitor(Capacitor {
+            name: element_name(name, 'C'),
+            value,
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            g2: false,
+            initial_condition: None,
+            tolerance: None,
+        }))
+    }
+
+    /// Adds an inductor of `value` Henries between `plus` and `minus`.
+    pub fn inductor(self, name: &str, plus: &str, minus: &str, value: f64) -> Self {
+        self.element(Element::Inductor(Inductor {
+            name: element_name(name, 'L'),
+            value,
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            initial_condition: None,
+            tolerance: None,
+            esr: 0.0,
+        }))
+    }
+
+    /// Adds an independent DC voltage source of `dc_value` Volts from `plus` to `minus`.
+    pub fn voltage_source(self, name: &str, plus: &str, minus: &str, dc_value: f64) -> Self {
+        self.element(Element::VoltageSource(VoltageSource {
+            name: element_name(name, 'V'),
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            dc_value,
+            ac_amplitude: 0.0,
+            ac_phase: 0.0,
+            pulse: None,
+            sinusoidal: None,
+            exponential: None,
+            pwl: None,
+        }))
+    }
+
+    /// Adds an independent current source driving `value` Amperes from `plus` through
+    /// itself into `minus`.
+    pub fn current_source(self, name: &str, plus: &str, minus: &str, value: f64) -> Self {
+        self.element(Element::CurrentSource(CurrentSource {
+            name: element_name(name, 'I'),
+            value,
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            ac_amplitude: 0.0,
+            ac_phase: 0.0,
+            pwl: None,
+        }))
+    }
+
+    /// Adds a diode from `plus` (anode) to `minus` (cathode) using the model `model_name`,
+    /// which must be added with [`CircuitBuilder::model`].
+    pub fn diode(self, name: &str, plus: &str, minus: &str, model_name: &str) -> Self {
+        self.element(Element::Diode(Diode {
+            name: element_name(name, 'D'),
+            model_name: model_name.to_string(),
+            model: DiodeModel::default(),
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            temperature: TEMPERATURE,
+        }))
+    }
+
+    /// Adds a voltage-controlled voltage source with voltage `gain` between `plus` and
+    /// `minus`, controlled by `V(control_plus) - V(control_minus)`.
+    pub fn vcvs(
+        self,
+        name: &str,
+        plus: &str,
+        minus: &str,
+        control_plus: &str,
+        control_minus: &str,
+        gain: f64,
+    ) -> Self {
+        self.element(Element::VCVS(VCVS {
+            name: element_name(name, 'E'),
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            control_plus: control_plus.to_string(),
+            control_minus: control_minus.to_string(),
+            gain,
+        }))
+    }
+
+    /// Adds a voltage-controlled current source with `transconductance` between `plus` and
+    /// `minus`, controlled by `V(control_plus) - V(control_minus)`.
+    pub fn vccs(
+        self,
+        name: &str,
+        plus: &str,
+        minus: &str,
+        control_plus: &str,
+        control_minus: &str,
+        transconductance: f64,
+    ) -> Self {
+        self.element(Element::VCCS(VCCS {
+            name: element_name(name, 'G'),
+            plus: plus.to_string(),
+            minus: minus.to_string(),
+            control_plus: control_plus.to_string(),
+            control_minus: control_minus.to_string(),
+            transconductance,
+        }))
+    }
+
+    /// Adds any element, for element types without a dedicated method.
+    pub fn element(mut self, element: Element) -> Self {
+        self.elements.push(element);
+        self
+    }
+
+    /// Adds a model, replacing any model of the same name.
+    pub fn model(mut self, model: Model) -> Self {
+        self.models.insert(model.name().to_string(), model);
+        self
+    }
+
+    /// Finalizes the circuit, assigning the MNA rows and applying the models.
+    ///
+    /// Fails like `parse_circuit_description` for an empty circuit or an element referencing
+    /// an undefined model.
+    pub fn build(self) -> Result<Circuit> {
+        let mut circuit = Circuit::new(self.elements, HashMap::new(), Vec::new(), self.models);
+        if circuit.is_empty() {
+            return Err(Error::EmptyNetlist);
+        }
+        circuit.normalize_ground(&GROUND_ALIASES);
+        circuit.assign_indices();
+        circuit.resolve_models()?;
+        Ok(circuit)
+    }
+}
+
+/// Returns the name stored in an element, i.e. its identifier without the type letter.
+fn element_name(identifier: &str, prefix: char) -> String {
+    match identifier.chars().next() {
+        Some(first) if first.eq_ignore_ascii_case(&prefix) && identifier.len() > 1 => {
+            identifier[first.len_utf8()..].to_string()
+        }
+        _ => identifier.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_circuit_description;
+
+    #[test]
+    fn test_builder_matches_parsed_index_map() {
+        let parsed = parse_circuit_description(
+            "V1 in 0 1\nR1 in out 1k\nC1 out 0 1u\nL1 out load 1m\nR2 load 0 2k\n",
+        )
+        .unwrap();
+        let built = CircuitBuilder::new()
+            .voltage_source("V1", "in", "0", 1.0)
+            .resistor("R1", "in", "out", 1e3)
+            .capacitor("C1", "out", "0", 1e-6)
+            .inductor("L1", "out", "load", 1e-3)
+            .resistor("2", "load", "0", 2e3)
+            .build()
+            .unwrap();
+
+        assert_eq!(built.index_map, parsed.index_map);
+        let mut nodes = built.nodes.clone();
+        let mut parsed_nodes = parsed.nodes.clone();
+        nodes.sort();
+        parsed_nodes.sort();
+        assert_eq!(nodes, parsed_nodes);
+        assert_eq!(built.to_netlist_string(), parsed.to_netlist_string());
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        assert!(matches!(
+            CircuitBuilder::new().build(),
+            Err(Error::EmptyNetlist)
+        ));
+        assert!(matches!(
+            CircuitBuilder::new().diode("D1", "a", "0", "DMOD").build(),
+            Err(Error::UndefinedModel(name)) if name == "DMOD"
+        ));
+    }
+}
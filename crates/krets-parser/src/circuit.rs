@@ -1,6 +1,16 @@
 use crate::prelude::*;
 
-use crate::models::Model;
+use crate::{
+    constants::ZERO_CELSIUS,
+    elements::{
+        bjt::BjtType, current_source::CurrentSource, resistor::Resistor,
+        subcircuit::SubcircuitDefinition, switch::SwitchControl, voltage_source::VoltageSource,
+    },
+    measure::Measurement,
+    models::Model,
+    options::SimulatorOptions,
+};
+use std::collections::HashSet;
 
 #[derive(Debug, Clone)]
 /// A structured representation of a circuit element.
@@ -16,6 +26,33 @@ pub struct Circuit {
 
     /// A list of models in the circuit.
     pub models: HashMap<String, Model>,
+
+    /// Initial node voltages from `.ic` cards, keyed like the index map (e.g. `V(out)`).
+    pub initial_conditions: HashMap<String, f64>,
+
+    /// Starting guesses for the DC operating point from `.nodeset` cards, keyed like the
+    /// index map (e.g. `V(out)`). Unlike `.ic`, they only seed the first Newton iteration.
+    pub nodesets: HashMap<String, f64>,
+
+    /// Values of the `.param` definitions, keyed by their lowercased name.
+    pub parameters: HashMap<String, f64>,
+
+    /// Circuit temperature in degrees Celsius from a `.temp` card, if any.
+    pub temperature: Option<f64>,
+
+    /// Solver tolerances and limits from `.options` cards.
+    pub options: SimulatorOptions,
+
+    /// Post-processing measurements from `.measure` cards.
+    pub measurements: Vec<Measurement>,
+
+    /// Signals from `.save` (or `.probe`) cards, keyed like the index map (e.g. `V(out)`).
+    /// When non-empty, only these node voltages and branch currents are reported.
+    pub saves: Vec<String>,
+
+    /// The `.subckt` definitions of the netlist, keyed by name. Their instances have already
+    /// been flattened into `elements`.
+    pub subcircuits: HashMap<String, SubcircuitDefinition>,
 }
 
 impl Circuit {
@@ -31,6 +68,14 @@ impl Circuit {
             index_map,
             nodes,
             models,
+            initial_conditions: HashMap::new(),
+            nodesets: HashMap::new(),
+            parameters: HashMap::new(),
+            temperature: None,
+            options: SimulatorOptions::default(),
+            measurements: Vec::new(),
+            saves: Vec::new(),
+            subcircuits: HashMap::new(),
         }
     }
 
@@ -40,10 +85,584 @@ impl Circuit {
             index_map: HashMap::new(),
             nodes: Vec::new(),
             models: HashMap::new(),
+            initial_conditions: HashMap::new(),
+            nodesets: HashMap::new(),
+            parameters: HashMap::new(),
+            temperature: None,
+            options: SimulatorOptions::default(),
+            measurements: Vec::new(),
+            saves: Vec::new(),
+            subcircuits: HashMap::new(),
         }
     }
 
     pub fn is_empty(&self) -> bool {
         self.elements.is_empty()
     }
+
+    /// Returns the number of nodes other than ground, each of which has an MNA row `V(node)`.
+    pub fn node_count(&self) -> usize {
+        self.index_map
+            .keys()
+            .filter(|key| key.starts_with("V("))
+            .count()
+    }
+
+    /// Returns the number of branch currents `I(element)` of the G2 elements, which get an MNA
+    /// row of their own.
+    pub fn branch_count(&self) -> usize {
+        self.index_map
+            .keys()
+            .filter(|key| key.starts_with("I("))
+            .count()
+    }
+
+    /// Returns the dimension of the MNA system, the number of nodes plus branch currents.
+    pub fn mna_size(&self) -> usize {
+        self.index_map.len()
+    }
+
+    /// Returns the names of the MNA unknowns, such as `V(out)` or `I(V1)`, in row order.
+    pub fn unknowns(&self) -> Vec<String> {
+        let mut unknowns = vec![String::new(); self.index_map.len()];
+        for (name, &index) in &self.index_map {
+            unknowns[index] = name.clone();
+        }
+        unknowns
+    }
+
+    /// Returns the independent voltage sources of the circuit.
+    pub fn voltage_sources(&self) -> impl Iterator<Item = &VoltageSource> {
+        self.elements.iter().filter_map(|element| match element {
+            Element::VoltageSource(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Returns the independent current sources of the circuit.
+    pub fn current_sources(&self) -> impl Iterator<Item = &CurrentSource> {
+        self.elements.iter().filter_map(|element| match element {
+            Element::CurrentSource(i) => Some(i),
+            _ => None,
+        })
+    }
+
+    /// Returns the resistors of the circuit, in group 1 and group 2 alike.
+    pub fn resistors(&self) -> impl Iterator<Item = &Resistor> {
+        self.elements.iter().filter_map(|element| match element {
+            Element::Resistor(r) => Some(r),
+            _ => None,
+        })
+    }
+
+    /// Returns the group 2 elements, which have a branch current `I(element)` of their own in
+    /// the MNA system, see [`Element::is_g2`].
+    pub fn g2_elements(&self) -> impl Iterator<Item = &Element> {
+        self.elements.iter().filter(|element| element.is_g2())
+    }
+
+    /// Returns the elements whose stamps depend on the solution, which are relinearized every
+    /// Newton-Raphson iteration, see [`Element::is_nonlinear`].
+    pub fn nonlinear_elements(&self) -> impl Iterator<Item = &Element> {
+        self.elements
+            .iter()
+            .filter(|element| element.is_nonlinear())
+    }
+
+    /// Serializes the circuit back into a netlist.
+    ///
+    /// The netlist holds one line per element, followed by the `.model` cards sorted by
+    /// name, the `.ic` conditions, the `.nodeset` guesses, the `.save` card, the `.temp` card
+    /// and the `.options` card, and parses back through `parse_circuit_description` into an
+    /// equivalent circuit. Subcircuits have already been flattened into their elements, so no
+    /// `.subckt` definitions are written.
+    pub fn to_netlist_string(&self) -> String {
+        let mut lines: Vec<String> = self.elements.iter().map(Element::to_netlist_line).collect();
+
+        let mut models: Vec<&Model> = self.models.values().collect();
+        models.sort_by_key(|model| model.name());
+        lines.extend(models.iter().map(|model| model.to_string()));
+
+        let mut initial_conditions: Vec<(&String, &f64)> = self.initial_conditions.iter().collect();
+        initial_conditions.sort_by_key(|&(node, _)| node);
+        for (node, value) in initial_conditions {
+            lines.push(format!(".ic {node}={value}"));
+        }
+
+        let mut nodesets: Vec<(&String, &f64)> = self.nodesets.iter().collect();
+        nodesets.sort_by_key(|&(node, _)| node);
+        for (node, value) in nodesets {
+            lines.push(format!(".nodeset {node}={value}"));
+        }
+
+        if !self.saves.is_empty() {
+            lines.push(format!(".save {}", self.saves.join(" ")));
+        }
+
+        if let Some(temperature) = self.temperature {
+            lines.push(format!(".temp {temperature}"));
+        }
+
+        lines.extend(self.options.to_card());
+
+        lines.push(".end".to_string());
+        lines.join("\n") + "\n"
+    }
+
+    /// Checks the netlist for common mistakes and returns all findings.
+    ///
+    /// Reports elements sharing an identifier ([`Error::DuplicateElement`]), nodes other than
+    /// ground connected to a single element ([`Error::DanglingNode`]), ideal voltage sources
+    /// (including controlled ones and 0Ω resistors) forming a loop such as two sources in
+    /// parallel ([`Error::VoltageSourceLoop`]) and subcircuit pins without any element connected
+    /// to them ([`Error::UnconnectedPin`]). The findings are in netlist order.
+    pub fn validate(&self) -> std::result::Result<(), Vec<Error>> {
+        let mut findings = Vec::new();
+
+        let mut identifiers = HashSet::new();
+        for element in &self.elements {
+            let identifier = element.identifier();
+            if !identifiers.insert(identifier.clone()) {
+                findings.push(Error::DuplicateElement(identifier));
+            }
+        }
+
+        // An element counts once per node, even if several of its terminals share it.
+        let mut nodes: Vec<&str> = Vec::new();
+        let mut connections: HashMap<&str, usize> = HashMap::new();
+        for element in &self.elements {
+            let mut terminals = HashSet::new();
+            for node in element.nodes() {
+                if !terminals.insert(node) {
+                    continue;
+                }
+                let count = connections.entry(node).or_insert(0);
+                if *count == 0 {
+                    nodes.push(node);
+                }
+                *count += 1;
+            }
+        }
+        findings.extend(
+            nodes
+                .into_iter()
+                .filter(|&node| node != "0" && connections[node] == 1)
+                .map(|node| Error::DanglingNode(node.to_string())),
+        );
+
+        // Nodes joined by voltage sources share a group; a source within one group closes a loop.
+        let mut groups: HashMap<&str, usize> = HashMap::new();
+        for element in &self.elements {
+            let (plus, minus) = match element {
+                Element::VoltageSource(v) => (v.plus.as_str(), v.minus.as_str()),
+                Element::VCVS(e) => (e.plus.as_str(), e.minus.as_str()),
+                Element::CCVS(h) => (h.plus.as_str(), h.minus.as_str()),
+                Element::Resistor(r) if r.g2 && r.value == 0.0 => {
+                    (r.plus.as_str(), r.minus.as_str())
+                }
+                _ => continue,
+            };
+            let next = groups.len();
+            let a = *groups.entry(plus).or_insert(next);
+            let next = groups.len();
+            let b = *groups.entry(minus).or_insert(next);
+            if a == b {
+                findings.push(Error::VoltageSourceLoop(element.identifier()));
+            } else {
+                groups
+                    .values_mut()
+                    .filter(|g| **g == b)
+                    .for_each(|g| *g = a);
+            }
+        }
+
+        let mut subcircuits: Vec<&SubcircuitDefinition> = self.subcircuits.values().collect();
+        subcircuits.sort_by_key(|definition| &definition.name);
+        for definition in subcircuits {
+            let mut scope = self.parameters.clone();
+            scope.extend(definition.parameters.clone());
+            // The lines were checked when the netlist was parsed.
+            let elements: Vec<Element> = definition
+                .lines
+                .iter()
+                .filter_map(|line| definition.parse_line(line, &scope).ok())
+                .collect();
+            let used: HashSet<&str> = elements.iter().flat_map(Element::nodes).collect();
+            findings.extend(
+                definition
+                    .pins
+                    .iter()
+                    .filter(|pin| !used.contains(pin.as_str()))
+                    .map(|pin| Error::UnconnectedPin {
+                        subcircuit: definition.name.clone(),
+                        pin: pin.clone(),
+                    }),
+            );
+        }
+
+        if findings.is_empty() {
+            Ok(())
+        } else {
+            Err(findings)
+        }
+    }
+
+    /// Renames every node listed in `ground_aliases` to the ground node `0`.
+    pub(crate) fn normalize_ground(&mut self, ground_aliases: &[&str]) {
+        for element in self.elements.iter_mut() {
+            for node in element.nodes_mut() {
+                if ground_aliases.contains(&node.as_str()) {
+                    *node = "0".to_string();
+                }
+            }
+        }
+    }
+
+    /// Assigns the rows of the MNA system and collects the node names.
+    ///
+    /// Elements are visited in order: a G2 element first gets the row of its branch current
+    /// `I(element)`, then each of its nodes not seen before gets a row `V(node)`. Ground (`0`)
+    /// is listed as a node but gets no row. The nodes are listed in the order they are first
+    /// seen, so parsing the same netlist always gives the same rows and nodes.
+    pub(crate) fn assign_indices(&mut self) {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut nodes = Vec::new();
+        let mut index_counter = 0;
+        self.index_map.clear();
+
+        for element in self.elements.iter() {
+            if element.is_g2() {
+                self.index_map
+                    .insert(format!("I({element})"), index_counter);
+                index_counter += 1;
+            }
+
+            for node in &element.nodes() {
+                if seen.insert(node.to_string()) {
+                    nodes.push(node.to_string());
+                    // Skip adding the ground node to the index map
+                    if *node == "0" {
+                        continue;
+                    }
+                    self.index_map.insert(format!("V({node})"), index_counter);
+                    index_counter += 1;
+                }
+            }
+        }
+
+        self.nodes = nodes;
+    }
+
+    /// Copies the `.model` parameters into the elements referencing them and resolves the
+    /// mutual inductances against the inductors they couple.
+    pub(crate) fn resolve_models(&mut self) -> Result<()> {
+        let undefined_or_mismatched = |model_name: &str, identifier: String, kind: &str| {
+            if self.models.contains_key(model_name) {
+                Error::InvalidModelType(format!(
+                    "Model '{model_name}' of '{identifier}' must be of type {kind}"
+                ))
+            } else {
+                Error::UndefinedModel(model_name.to_string())
+            }
+        };
+
+        for element in self.elements.iter_mut() {
+            match element {
+                Element::Diode(diode) => match self.models.get(&diode.model_name) {
+                    Some(Model::Diode(model)) => diode.model = model.clone(),
+                    _ => {
+                        return Err(undefined_or_mismatched(
+                            &diode.model_name,
+                            diode.identifier(),
+                            "D",
+                        ));
+                    }
+                },
+                Element::NMOSFET(mosfet) => match self.models.get(&mosfet.model_name) {
+                    Some(Model::NMosfet(model)) => mosfet.model = model.clone(),
+                    _ => {
+                        return Err(undefined_or_mismatched(
+                            &mosfet.model_name,
+                            mosfet.identifier(),
+                            "NMOS",
+                        ));
+                    }
+                },
+                Element::PMOSFET(mosfet) => match self.models.get(&mosfet.model_name) {
+                    Some(Model::PMosfet(model)) => mosfet.model = model.clone(),
+                    _ => {
+                        return Err(undefined_or_mismatched(
+                            &mosfet.model_name,
+                            mosfet.identifier(),
+                            "PMOS",
+                        ));
+                    }
+                },
+                Element::Switch(switch) => {
+                    match (&switch.control, self.models.get(&switch.model_name)) {
+                        (SwitchControl::Voltage { .. }, Some(Model::Switch(model)))
+                        | (SwitchControl::Current { .. }, Some(Model::CurrentSwitch(model))) => {
+                            switch.model = model.clone();
+                        }
+                        (control, _) => {
+                            let kind = match control {
+                                SwitchControl::Voltage { .. } => "SW",
+                                SwitchControl::Current { .. } => "CSW",
+                            };
+                            return Err(undefined_or_mismatched(
+                                &switch.model_name,
+                                switch.identifier(),
+                                kind,
+                            ));
+                        }
+                    }
+                }
+                Element::BJT(bjt) => {
+                    // A BJT without a model name keeps the default model.
+                    let Some(model_name) = &bjt.model_name else {
+                        continue;
+                    };
+                    match (&bjt.bjt_type, self.models.get(model_name)) {
+                        (BjtType::NPN, Some(Model::Npn(model)))
+                        | (BjtType::PNP, Some(Model::Pnp(model))) => bjt.model = model.clone(),
+                        (bjt_type, _) => {
+                            let kind = match bjt_type {
+                                BjtType::NPN => "NPN",
+                                BjtType::PNP => "PNP",
+                            };
+                            return Err(undefined_or_mismatched(
+                                model_name,
+                                bjt.identifier(),
+                                kind,
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // --- Resolve mutual inductances against the inductors they couple ---
+        let inductances: HashMap<String, f64> = self
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                Element::Inductor(inductor) => Some((inductor.identifier(), inductor.value)),
+                _ => None,
+            })
+            .collect();
+
+        for element in self.elements.iter_mut() {
+            if let Element::MutualInductance(coupling) = element {
+                coupling.validate()?;
+                let lookup = |identifier: &str| {
+                    inductances.get(identifier).copied().ok_or_else(|| {
+                        Error::InvalidFormat(format!(
+                            "{} couples unknown inductor '{}'",
+                            coupling.identifier(),
+                            identifier
+                        ))
+                    })
+                };
+                let inductance_a = lookup(&coupling.inductor_a)?;
+                let inductance_b = lookup(&coupling.inductor_b)?;
+                coupling.resolve(inductance_a, inductance_b);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether all temperature dependent elements operate at `celsius` degrees Celsius.
+    pub fn is_at_temperature(&self, celsius: f64) -> bool {
+        let kelvin = celsius + ZERO_CELSIUS;
+        self.elements.iter().all(|element| match element {
+            Element::Resistor(resistor) => resistor.temperature == kelvin,
+            Element::Diode(diode) => diode.temperature == kelvin,
+            _ => true,
+        })
+    }
+
+    /// Sets the operating temperature, in degrees Celsius, of all temperature dependent elements.
+    pub fn set_temperature(&mut self, celsius: f64) {
+        let kelvin = celsius + ZERO_CELSIUS;
+        for element in &mut self.elements {
+            match element {
+                Element::Resistor(resistor) => resistor.temperature = kelvin,
+                Element::Diode(diode) => diode.temperature = kelvin,
+                _ => {}
+            }
+        }
+    }
+
+    /// Collapses resistors in series and in parallel into single equivalent resistors, which
+    /// shrinks the MNA system of large passive networks.
+    ///
+    /// Resistors between the same pair of nodes are replaced by their parallel combination,
+    /// and two resistors meeting at a node that no other element touches are replaced by their
+    /// sum, removing that node. This is repeated until nothing more can be merged, and the MNA
+    /// rows are reassigned. The voltages of the remaining nodes are unchanged.
+    ///
+    /// Ground and the nodes referenced by `.ic`, `.nodeset`, `.measure` or `.save` cards are
+    /// kept. G2 resistors, resistors with a tolerance and measured or saved resistors are left
+    /// alone, and only resistors with the same temperature coefficients are merged. A merged
+    /// resistor keeps the name of one of the resistors it replaces.
+    ///
+    /// Returns the identifier of every merged resistor mapped to the identifiers of the
+    /// original resistors it replaces.
+    pub fn reduce_passives(&mut self) -> HashMap<String, Vec<String>> {
+        let protected: HashSet<String> = self
+            .initial_conditions
+            .keys()
+            .chain(self.nodesets.keys())
+            .chain(
+                self.measurements
+                    .iter()
+                    .map(|measurement| &measurement.target),
+            )
+            .chain(&self.saves)
+            .cloned()
+            .collect();
+
+        let mut merged: HashMap<String, Vec<String>> = HashMap::new();
+        while let Some(merge) = self
+            .find_parallel_resistors(&protected)
+            .or_else(|| self.find_series_resistors(&protected))
+        {
+            let removed = self.elements.remove(merge.remove);
+            let keep = if merge.remove < merge.keep {
+                merge.keep - 1
+            } else {
+                merge.keep
+            };
+            let Element::Resistor(resistor) = &mut self.elements[keep] else {
+                unreachable!("only resistors are merged");
+            };
+            resistor.value = merge.value;
+            resistor.plus = merge.plus;
+            resistor.minus = merge.minus;
+
+            let removed_originals = merged
+                .remove(&removed.identifier())
+                .unwrap_or_else(|| vec![removed.identifier()]);
+            merged
+                .entry(resistor.identifier())
+                .or_insert_with(|| vec![resistor.identifier()])
+                .extend(removed_originals);
+        }
+
+        self.assign_indices();
+        merged
+    }
+
+    /// Returns the element at `index` if it is a resistor that [`Circuit::reduce_passives`]
+    /// may merge.
+    fn reducible_resistor(&self, index: usize, protected: &HashSet<String>) -> Option<&Resistor> {
+        match &self.elements[index] {
+            Element::Resistor(r)
+                if !r.g2
+                    && r.tolerance.is_none()
+                    && r.plus != r.minus
+                    && !protected.contains(&format!("I({})", r.identifier())) =>
+            {
+                Some(r)
+            }
+            _ => None,
+        }
+    }
+
+    /// Finds two reducible resistors between the same pair of nodes.
+    fn find_parallel_resistors(&self, protected: &HashSet<String>) -> Option<ResistorMerge> {
+        let mut by_pair: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+        for index in 0..self.elements.len() {
+            let Some(r) = self.reducible_resistor(index, protected) else {
+                continue;
+            };
+            let pair = if r.plus < r.minus {
+                (r.plus.as_str(), r.minus.as_str())
+            } else {
+                (r.minus.as_str(), r.plus.as_str())
+            };
+            let candidates = by_pair.entry(pair).or_default();
+            for &other in candidates.iter() {
+                let Some(keep) = self.reducible_resistor(other, protected) else {
+                    continue;
+                };
+                if same_temperature_dependence(keep, r) {
+                    return Some(ResistorMerge {
+                        keep: other,
+                        remove: index,
+                        value: 1.0 / (1.0 / keep.value + 1.0 / r.value),
+                        plus: keep.plus.clone(),
+                        minus: keep.minus.clone(),
+                    });
+                }
+            }
+            candidates.push(index);
+        }
+        None
+    }
+
+    /// Finds two reducible resistors meeting at a node that no other element touches.
+    fn find_series_resistors(&self, protected: &HashSet<String>) -> Option<ResistorMerge> {
+        // An element counts once per node, even if several of its terminals share it.
+        let mut nodes: Vec<&str> = Vec::new();
+        let mut touching: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, element) in self.elements.iter().enumerate() {
+            let mut terminals = HashSet::new();
+            for node in element.nodes() {
+                if terminals.insert(node) {
+                    let elements = touching.entry(node).or_default();
+                    if elements.is_empty() {
+                        nodes.push(node);
+                    }
+                    elements.push(index);
+                }
+            }
+        }
+
+        nodes.into_iter().find_map(|node| {
+            if node == "0" || protected.contains(&format!("V({node})")) {
+                return None;
+            }
+            let &[first, second] = touching[node].as_slice() else {
+                return None;
+            };
+            let a = self.reducible_resistor(first, protected)?;
+            let b = self.reducible_resistor(second, protected)?;
+            if !same_temperature_dependence(a, b) {
+                return None;
+            }
+            let far_end = |r: &Resistor| {
+                if r.plus == node {
+                    r.minus.clone()
+                } else {
+                    r.plus.clone()
+                }
+            };
+            Some(ResistorMerge {
+                keep: first,
+                remove: second,
+                value: a.value + b.value,
+                plus: far_end(a),
+                minus: far_end(b),
+            })
+        })
+    }
+}
+
+/// Two resistors found by [`Circuit::reduce_passives`] to be merged into the resistor at
+/// `keep`, with the given resistance and nodes.
+struct ResistorMerge {
+    keep: usize,
+    remove: usize,
+    value: f64,
+    plus: String,
+    minus: String,
+}
+
+/// Returns whether two resistors scale alike with temperature, so that their combination
+/// stays exact at any temperature.
+fn same_temperature_dependence(a: &Resistor, b: &Resistor) -> bool {
+    a.tc1 == b.tc1 && a.tc2 == b.tc2 && a.temperature == b.temperature
 }
@@ -2,3 +2,7 @@ pub const KB: f64 = 1.380649e-23; // Boltzmann constant in J/K
 pub const Q: f64 = 1.602176634e-19; // Elementary charge in C
 pub const TEMPERATURE: f64 = 300.0; // Standard temperature in Kelvin
 pub const THERMAL_VOLTAGE: f64 = KB * TEMPERATURE / Q; // Thermal voltage at 300K in V
+pub const ZERO_CELSIUS: f64 = 273.15; // 0 degrees Celsius in Kelvin
+
+/// Node names treated as ground by default; they are all renamed to `0`.
+pub const GROUND_ALIASES: [&str; 3] = ["0", "gnd", "GND"];
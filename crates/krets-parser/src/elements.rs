@@ -2,12 +2,20 @@ use crate::prelude::*;
 use nom::{Parser, branch::alt};
 pub mod bjt;
 pub mod capacitor;
+pub mod cccs;
+pub mod ccvs;
 pub mod current_source;
 pub mod diode;
 pub mod inductor;
+pub mod mutual_inductance;
 pub mod nmosfet;
+pub mod pmosfet;
 pub mod resistor;
 pub mod subcircuit;
+pub mod switch;
+pub mod transmission_line;
+pub mod vccs;
+pub mod vcvs;
 pub mod voltage_source;
 /// Represents any component that can be included in a circuit simulation.
 #[derive(Debug, Clone)]
@@ -17,9 +25,17 @@ pub enum Element {
     Resistor(resistor::Resistor),
     Capacitor(capacitor::Capacitor),
     Inductor(inductor::Inductor),
+    MutualInductance(mutual_inductance::MutualInductance),
     Diode(diode::Diode),
     BJT(bjt::BJT),
     NMOSFET(nmosfet::NMOSFET),
+    PMOSFET(pmosfet::PMOSFET),
+    VCVS(vcvs::VCVS),
+    VCCS(vccs::VCCS),
+    CCVS(ccvs::CCVS),
+    CCCS(cccs::CCCS),
+    Switch(switch::Switch),
+    TransmissionLine(transmission_line::TransmissionLine),
     SubcktInstance(subcircuit::SubcircuitInstance),
 }
 
@@ -33,37 +49,124 @@ macro_rules! dispatch {
             Element::Resistor(e) => e.$method($($args),*),
             Element::Capacitor(e) => e.$method($($args),*),
             Element::Inductor(e) => e.$method($($args),*),
+            Element::MutualInductance(e) => e.$method($($args),*),
             Element::Diode(e) => e.$method($($args),*),
             Element::BJT(e) => e.$method($($args),*),
             Element::NMOSFET(e) => e.$method($($args),*),
+            Element::PMOSFET(e) => e.$method($($args),*),
+            Element::VCVS(e) => e.$method($($args),*),
+            Element::VCCS(e) => e.$method($($args),*),
+            Element::CCVS(e) => e.$method($($args),*),
+            Element::CCCS(e) => e.$method($($args),*),
+            Element::Switch(e) => e.$method($($args),*),
+            Element::TransmissionLine(e) => e.$method($($args),*),
             Element::SubcktInstance(e) => e.$method($($args),*),
         }
     };
 }
 
-pub fn parse_element(input: &str) -> Result<Element> {
-    let (_, element) = alt((
-        map(parse_resistor, Element::Resistor),
-        map(parse_capacitor, Element::Capacitor),
-        map(parse_inductor, Element::Inductor),
-        map(parse_voltage_source, Element::VoltageSource),
-        map(parse_current_source, Element::CurrentSource),
-        map(parse_diode, Element::Diode),
-        map(parse_bjt, Element::BJT),
-        map(parse_nmosfet, Element::NMOSFET),
-        map(parse_subckt_instance, Element::SubcktInstance),
-    ))
-    .parse(input)
-    .map_err(|e| {
-        Error::Unexpected(format!(
-            "Failed to parse element from input '{}': parser error: {:?}",
-            input, e
+/// Parses an element card with the parser of its type letter, so that a malformed card
+/// reports the position at which that parser failed. Input after the element is left over.
+pub fn parse_element_variant(input: &str) -> IResult<&str, Element> {
+    match input.chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some('R') => map(parse_resistor, Element::Resistor).parse(input),
+        Some('C') => map(parse_capacitor, Element::Capacitor).parse(input),
+        Some('L') => map(parse_inductor, Element::Inductor).parse(input),
+        Some('K') => map(parse_mutual_inductance, Element::MutualInductance).parse(input),
+        Some('V') => map(parse_voltage_source, Element::VoltageSource).parse(input),
+        Some('I') => map(parse_current_source, Element::CurrentSource).parse(input),
+        Some('D') => map(parse_diode, Element::Diode).parse(input),
+        Some('Q') => map(parse_bjt, Element::BJT).parse(input),
+        Some('M') => alt((
+            map(parse_nmosfet, Element::NMOSFET),
+            map(parse_pmosfet, Element::PMOSFET),
         ))
+        .parse(input),
+        Some('E') => map(parse_vcvs, Element::VCVS).parse(input),
+        Some('G') => map(parse_vccs, Element::VCCS).parse(input),
+        Some('H') => map(parse_ccvs, Element::CCVS).parse(input),
+        Some('F') => map(parse_cccs, Element::CCCS).parse(input),
+        Some('S' | 'W') => map(parse_switch, Element::Switch).parse(input),
+        Some('T') => map(parse_transmission_line, Element::TransmissionLine).parse(input),
+        Some('X') => map(parse_subckt_instance, Element::SubcktInstance).parse(input),
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        ))),
+    }
+}
+
+pub fn parse_element(input: &str) -> Result<Element> {
+    let (_, element) = parse_element_variant(input).map_err(|e| {
+        let message = describe_element_error(input, &format!("parser error: {e:?}"));
+        if element_type(input).is_some() {
+            Error::InvalidElementFormat(message)
+        } else {
+            Error::UnknownElementType(message)
+        }
     })?;
 
     Ok(element)
 }
 
+/// The device letters that start the names of the elements, with the type of element each one
+/// selects. The most common elements come first, so they lead the letters listed in errors.
+const ELEMENT_PREFIXES: [(char, &str); 17] = [
+    ('R', "resistor"),
+    ('C', "capacitor"),
+    ('L', "inductor"),
+    ('V', "voltage source"),
+    ('I', "current source"),
+    ('D', "diode"),
+    ('Q', "bjt"),
+    ('M', "mosfet"),
+    ('X', "subcircuit instance"),
+    ('K', "mutual inductance"),
+    ('E', "vcvs"),
+    ('G', "vccs"),
+    ('H', "ccvs"),
+    ('F', "cccs"),
+    ('S', "switch"),
+    ('W', "switch"),
+    ('T', "transmission line"),
+];
+
+/// Returns the type of element selected by the device letter that `input` starts with, or
+/// `None` if there is no element with that letter.
+fn element_type(input: &str) -> Option<&'static str> {
+    let letter = input.chars().next()?.to_ascii_uppercase();
+    ELEMENT_PREFIXES
+        .iter()
+        .find(|(prefix, _)| *prefix == letter)
+        .map(|(_, kind)| *kind)
+}
+
+/// Explains why the element card `input` failed to parse.
+///
+/// A card with a known device letter names the type of element that was attempted, followed
+/// by the `detail` of the parser error. For an unknown device letter, the detail is left out
+/// and the valid device letters are listed instead.
+pub(crate) fn describe_element_error(input: &str, detail: &str) -> String {
+    let name = input.split_whitespace().next().unwrap_or_default();
+    if let Some(kind) = element_type(input) {
+        return format!("invalid {kind} '{name}': {detail}");
+    }
+    let letters = ELEMENT_PREFIXES
+        .iter()
+        .map(|(letter, _)| letter.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    match name.chars().next().filter(char::is_ascii_alphabetic) {
+        Some(letter) => format!(
+            "unknown element type '{}' of '{name}', expected one of {letters}",
+            letter.to_ascii_uppercase()
+        ),
+        None => format!(
+            "'{name}' does not start with a known element letter, expected one of {letters}"
+        ),
+    }
+}
+
 impl Element {
     /// Retrieves the nodes associated with the element.
     pub fn nodes(&self) -> Vec<&str> {
@@ -73,9 +176,23 @@ impl Element {
             Element::Resistor(r) => vec![&r.plus, &r.minus],
             Element::Capacitor(c) => vec![&c.plus, &c.minus],
             Element::Inductor(l) => vec![&l.plus, &l.minus],
+            // A coupling only references inductors; it has no terminals of its own.
+            Element::MutualInductance(_) => vec![],
             Element::Diode(d) => vec![&d.plus, &d.minus],
             Element::BJT(b) => vec![&b.collector, &b.emitter, &b.base],
             Element::NMOSFET(m) => vec![&m.drain, &m.gate, &m.source],
+            Element::PMOSFET(m) => vec![&m.drain, &m.gate, &m.source],
+            Element::VCVS(e) => vec![&e.plus, &e.minus, &e.control_plus, &e.control_minus],
+            Element::VCCS(g) => vec![&g.plus, &g.minus, &g.control_plus, &g.control_minus],
+            Element::CCVS(h) => vec![&h.plus, &h.minus],
+            Element::CCCS(f) => vec![&f.plus, &f.minus],
+            Element::Switch(s) => match &s.control {
+                switch::SwitchControl::Voltage { plus, minus } => {
+                    vec![&s.plus, &s.minus, plus, minus]
+                }
+                switch::SwitchControl::Current { .. } => vec![&s.plus, &s.minus],
+            },
+            Element::TransmissionLine(t) => vec![&t.plus1, &t.minus1, &t.plus2, &t.minus2],
             Element::SubcktInstance(s) => s.nodes.iter().map(String::as_str).collect(),
         }
     }
@@ -87,9 +204,34 @@ impl Element {
             Element::Resistor(r) => vec![&mut r.plus, &mut r.minus],
             Element::Capacitor(c) => vec![&mut c.plus, &mut c.minus],
             Element::Inductor(l) => vec![&mut l.plus, &mut l.minus],
+            Element::MutualInductance(_) => vec![],
             Element::Diode(d) => vec![&mut d.plus, &mut d.minus],
             Element::BJT(b) => vec![&mut b.collector, &mut b.emitter, &mut b.base],
             Element::NMOSFET(m) => vec![&mut m.drain, &mut m.gate, &mut m.source],
+            Element::PMOSFET(m) => vec![&mut m.drain, &mut m.gate, &mut m.source],
+            Element::VCVS(e) => vec![
+                &mut e.plus,
+                &mut e.minus,
+                &mut e.control_plus,
+                &mut e.control_minus,
+            ],
+            Element::VCCS(g) => vec![
+                &mut g.plus,
+                &mut g.minus,
+                &mut g.control_plus,
+                &mut g.control_minus,
+            ],
+            Element::CCVS(h) => vec![&mut h.plus, &mut h.minus],
+            Element::CCCS(f) => vec![&mut f.plus, &mut f.minus],
+            Element::Switch(s) => match &mut s.control {
+                switch::SwitchControl::Voltage { plus, minus } => {
+                    vec![&mut s.plus, &mut s.minus, plus, minus]
+                }
+                switch::SwitchControl::Current { .. } => vec![&mut s.plus, &mut s.minus],
+            },
+            Element::TransmissionLine(t) => {
+                vec![&mut t.plus1, &mut t.minus1, &mut t.plus2, &mut t.minus2]
+            }
             Element::SubcktInstance(s) => s.nodes.iter_mut().collect(),
         }
     }
@@ -101,9 +243,17 @@ impl Element {
             Element::Resistor(r) => &r.name,
             Element::Capacitor(c) => &c.name,
             Element::Inductor(l) => &l.name,
+            Element::MutualInductance(k) => &k.name,
             Element::Diode(d) => &d.name,
             Element::BJT(b) => &b.name,
             Element::NMOSFET(m) => &m.name,
+            Element::PMOSFET(m) => &m.name,
+            Element::VCVS(e) => &e.name,
+            Element::VCCS(g) => &g.name,
+            Element::CCVS(h) => &h.name,
+            Element::CCCS(f) => &f.name,
+            Element::Switch(s) => &s.name,
+            Element::TransmissionLine(t) => &t.name,
             Element::SubcktInstance(s) => &s.instance_name,
         }
     }
@@ -114,13 +264,70 @@ impl Element {
             Element::Resistor(r) => r.name = new_name.to_string(),
             Element::Capacitor(c) => c.name = new_name.to_string(),
             Element::Inductor(l) => l.name = new_name.to_string(),
+            Element::MutualInductance(k) => k.name = new_name.to_string(),
             Element::Diode(d) => d.name = new_name.to_string(),
             Element::BJT(b) => b.name = new_name.to_string(),
             Element::NMOSFET(m) => m.name = new_name.to_string(),
+            Element::PMOSFET(m) => m.name = new_name.to_string(),
+            Element::VCVS(e) => e.name = new_name.to_string(),
+            Element::VCCS(g) => g.name = new_name.to_string(),
+            Element::CCVS(h) => h.name = new_name.to_string(),
+            Element::CCCS(f) => f.name = new_name.to_string(),
+            Element::Switch(s) => s.name = new_name.to_string(),
+            Element::TransmissionLine(t) => t.name = new_name.to_string(),
             Element::SubcktInstance(s) => s.instance_name = new_name.to_string(),
         }
     }
 
+    /// Returns the name of the `.model` card the element refers to, if it takes one.
+    pub fn model_name_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Element::Diode(d) => Some(&mut d.model_name),
+            Element::NMOSFET(m) => Some(&mut m.model_name),
+            Element::PMOSFET(m) => Some(&mut m.model_name),
+            Element::Switch(s) => Some(&mut s.model_name),
+            Element::BJT(b) => b.model_name.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Returns the main value of a resistor, capacitor or inductor, or the DC value of an
+    /// independent source, e.g. for sweeping it. Other elements have no such value.
+    pub fn primary_value(&self) -> Option<f64> {
+        match self {
+            Element::Resistor(r) => Some(r.value),
+            Element::Capacitor(c) => Some(c.value),
+            Element::Inductor(l) => Some(l.value),
+            Element::VoltageSource(vs) => Some(vs.dc_value),
+            Element::CurrentSource(is) => Some(is.value),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the element can take `value` as the value returned by
+    /// [`Element::primary_value`]. An ordinary resistor is stamped as the conductance `1/R`, so
+    /// only a group 2 resistor, which has a branch current of its own, can be set to 0Ω.
+    pub fn accepts_primary_value(&self, value: f64) -> bool {
+        match self {
+            Element::Resistor(r) => r.g2 || value != 0.0,
+            _ => self.primary_value().is_some(),
+        }
+    }
+
+    /// Sets the value returned by [`Element::primary_value`]. Elements without such a value
+    /// are left unchanged. The value must be accepted by [`Element::accepts_primary_value`].
+    pub fn set_primary_value(&mut self, value: f64) {
+        debug_assert!(self.accepts_primary_value(value));
+        match self {
+            Element::Resistor(r) => r.value = value,
+            Element::Capacitor(c) => c.value = value,
+            Element::Inductor(l) => l.value = value,
+            Element::VoltageSource(vs) => vs.dc_value = value,
+            Element::CurrentSource(is) => is.value = value,
+            _ => {}
+        }
+    }
+
     /// Determines if the element requires a dedicated branch current (Group 2) in MNA.
     pub fn is_g2(&self) -> bool {
         match self {
@@ -131,10 +338,20 @@ impl Element {
             Element::Resistor(e) => e.g2,
             Element::Capacitor(e) => e.g2,
             Element::CurrentSource(_) => true,
+            // Controlled voltage sources need a branch current for their constraint equation.
+            Element::VCVS(_) | Element::CCVS(_) => true,
+            // The branch current of a transmission line is the current into its first port.
+            Element::TransmissionLine(_) => true,
+            // Controlled current sources only inject into existing KCL rows.
+            Element::VCCS(_) | Element::CCCS(_) => false,
+            // Couplings only add terms to the branch equations of their inductors.
+            Element::MutualInductance(_) => false,
             // Non-linear elements are linearized into Group 1 companion models.
             Element::Diode(_)
             | Element::BJT(_)
             | Element::NMOSFET(_)
+            | Element::PMOSFET(_)
+            | Element::Switch(_)
             | Element::SubcktInstance(_) => false,
         }
     }
@@ -143,13 +360,45 @@ impl Element {
     pub fn is_nonlinear(&self) -> bool {
         matches!(
             self,
-            Element::Diode(_) | Element::BJT(_) | Element::NMOSFET(_)
+            Element::Diode(_)
+                | Element::BJT(_)
+                | Element::NMOSFET(_)
+                | Element::PMOSFET(_)
+                | Element::Switch(_)
         )
     }
 
     pub fn identifier(&self) -> String {
         dispatch!(self, identifier())
     }
+
+    /// Returns a human-readable name of the element type, e.g. `resistor`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Element::VoltageSource(_) => "voltage source",
+            Element::CurrentSource(_) => "current source",
+            Element::Resistor(_) => "resistor",
+            Element::Capacitor(_) => "capacitor",
+            Element::Inductor(_) => "inductor",
+            Element::MutualInductance(_) => "mutual inductance",
+            Element::Diode(_) => "diode",
+            Element::BJT(_) => "bjt",
+            Element::NMOSFET(_) => "nmos",
+            Element::PMOSFET(_) => "pmos",
+            Element::VCVS(_) => "vcvs",
+            Element::VCCS(_) => "vccs",
+            Element::CCVS(_) => "ccvs",
+            Element::CCCS(_) => "cccs",
+            Element::Switch(_) => "switch",
+            Element::TransmissionLine(_) => "transmission line",
+            Element::SubcktInstance(_) => "subcircuit instance",
+        }
+    }
+
+    /// Returns the netlist line of the element, which parses back into an equivalent element.
+    pub fn to_netlist_line(&self) -> String {
+        dispatch!(self, to_string())
+    }
 }
 
 impl std::fmt::Display for Element {
@@ -1,4 +1,6 @@
-use crate::prelude::*;
+use crate::{constants::THERMAL_VOLTAGE, models::bjt::BjtModel, prelude::*};
+use nom::{combinator::not, sequence::terminated};
+use std::fmt;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Represents the type of a BJT (Bipolar Junction Transistor).
@@ -20,14 +22,41 @@ pub struct BJT {
     pub base: String,
     /// Emitter node of the BJT.
     pub emitter: String,
-    /// Value or model name associated with the BJT (optional).
-    /// NOTE: SPICE often uses a model name here instead of a simple value.
-    ///       The parser now accepts an alphanumeric string, but the `value` field
-    ///       remains Option<f64>. This might need adjustment based on how models are handled.
-    ///       For now, we attempt to parse it as a value if present.
-    pub value: Option<f64>, // Kept as Option<f64> for now
+    /// Legacy numeric value after the nodes (optional). It is parsed for compatibility with
+    /// older netlists but not used by the model.
+    pub value: Option<f64>,
+    /// Name of the `.model` card of the BJT (optional). Without one the default model is used.
+    pub model_name: Option<String>,
     /// Type of the BJT.
     pub bjt_type: BjtType,
+    /// Model parameters for the BJT, copied from its `.model` card when the circuit is parsed.
+    pub model: BjtModel,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The linearized Ebers-Moll model of a BJT at a given bias point.
+///
+/// Voltages and currents are normalized to NPN polarity; for a PNP device they must
+/// be multiplied by `BJT::polarity()` to obtain the actual terminal quantities.
+/// The conductances form a hybrid-pi companion model:
+/// `i_c = g_m * v_be + g_o * v_ce - g_mu * v_bc` and `i_b = g_pi * v_be + g_mu * v_bc`.
+pub struct BjtOperatingPoint {
+    /// Base-emitter junction voltage.
+    pub v_be: f64,
+    /// Base-collector junction voltage.
+    pub v_bc: f64,
+    /// Current flowing into the collector.
+    pub i_c: f64,
+    /// Current flowing into the base.
+    pub i_b: f64,
+    /// Base-emitter input conductance.
+    pub g_pi: f64,
+    /// Base-collector feedback conductance.
+    pub g_mu: f64,
+    /// Transconductance from `v_be` to the collector current.
+    pub g_m: f64,
+    /// Collector-emitter output conductance.
+    pub g_o: f64,
 }
 
 impl BJT {
@@ -35,9 +64,120 @@ impl BJT {
     pub fn identifier(&self) -> String {
         format!("Q{}", self.name)
     }
+
+    /// Returns `1.0` for NPN devices and `-1.0` for PNP devices.
+    pub fn polarity(&self) -> f64 {
+        match self.bjt_type {
+            BjtType::NPN => 1.0,
+            BjtType::PNP => -1.0,
+        }
+    }
+
+    fn node_voltage(&self, solution_map: &HashMap<String, f64>, node: &str) -> f64 {
+        *solution_map.get(&format!("V({})", node)).unwrap_or(&0.0)
+    }
+
+    /// Returns the polarity-normalized junction voltages `(v_be, v_bc)`.
+    ///
+    /// Without a previous solution the device is assumed to be forward active,
+    /// which helps Newton-Raphson converge from a cold start.
+    pub fn junction_voltages(&self, solution_map: &HashMap<String, f64>) -> (f64, f64) {
+        if solution_map.is_empty() {
+            return (0.6, 0.0);
+        }
+        let v_b = self.node_voltage(solution_map, &self.base);
+        let v_c = self.node_voltage(solution_map, &self.collector);
+        let v_e = self.node_voltage(solution_map, &self.emitter);
+        let p = self.polarity();
+
+        (
+            self.limit_junction_voltage(p * (v_b - v_e)),
+            self.limit_junction_voltage(p * (v_b - v_c)),
+        )
+    }
+
+    // Voltage limiting function to prevent floating-point overflows
+    // in the exponential function, mirroring the diode implementation.
+    pub fn limit_junction_voltage(&self, v: f64) -> f64 {
+        let is = self.model.saturation_current;
+        let v_critical = THERMAL_VOLTAGE * f64::ln(f64::MAX * THERMAL_VOLTAGE / is);
+        v.clamp(-v_critical, v_critical)
+    }
+
+    /// Evaluates the Ebers-Moll transport model at the bias point stored in `solution_map`.
+    pub fn operating_point(&self, solution_map: &HashMap<String, f64>) -> BjtOperatingPoint {
+        let (v_be, v_bc) = self.junction_voltages(solution_map);
+        let is = self.model.saturation_current;
+        let bf = self.model.forward_beta;
+        let br = self.model.reverse_beta;
+        let inv_vaf = self.model.inverse_early_voltage();
+
+        let exp_be = f64::exp(v_be / THERMAL_VOLTAGE);
+        let exp_bc = f64::exp(v_bc / THERMAL_VOLTAGE);
+
+        // Forward and reverse transport currents and their derivatives.
+        let i_f = is * (exp_be - 1.0);
+        let i_r = is * (exp_bc - 1.0);
+        let g_f = is / THERMAL_VOLTAGE * exp_be;
+        let g_r = is / THERMAL_VOLTAGE * exp_bc;
+
+        // The Early effect modulates the transport current with the base-collector voltage.
+        let early = 1.0 - v_bc * inv_vaf;
+        let i_t = i_f - i_r;
+
+        let i_c = i_t * early - i_r / br;
+        let i_b = i_f / bf + i_r / br;
+
+        let g_pi = g_f / bf;
+        let g_mu = g_r / br;
+        let g_o = g_r * early + i_t * inv_vaf;
+        let g_m = (g_f - g_r) * early - i_t * inv_vaf;
+
+        BjtOperatingPoint {
+            v_be,
+            v_bc,
+            i_c,
+            i_b,
+            g_pi,
+            g_mu,
+            g_m,
+            g_o,
+        }
+    }
+
+    /// Returns the small-signal capacitances `(c_pi, c_mu)` at the operating point `op`.
+    ///
+    /// `c_pi` is the base-emitter junction capacitance plus the diffusion capacitance
+    /// `TF * g_m`; `c_mu` is the base-collector junction capacitance. The junction
+    /// capacitances are taken as constant, independent of the junction voltages.
+    pub fn capacitances(&self, op: &BjtOperatingPoint) -> (f64, f64) {
+        let c_pi =
+            self.model.base_emitter_capacitance + self.model.forward_transit_time * op.g_m.max(0.0);
+        (c_pi, self.model.base_collector_capacitance)
+    }
 }
 
 // Nom parser for BJT
+impl fmt::Display for BJT {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_char = match self.bjt_type {
+            BjtType::NPN => "N",
+            BjtType::PNP => "P",
+        };
+        write!(
+            f,
+            "Q{}{} {} {} {}",
+            type_char, self.name, self.collector, self.base, self.emitter,
+        )?;
+        if let Some(model_name) = &self.model_name {
+            write!(f, " {model_name}")?;
+        } else if let Some(value) = self.value {
+            write!(f, " {value}")?;
+        }
+        Ok(())
+    }
+}
+
 pub fn parse_bjt(input: &str) -> IResult<&str, BJT> {
     // Parse the initial 'Q' (case-insensitive)
     let (input, _) = tag_no_case("Q").parse(input)?;
@@ -53,19 +193,29 @@ pub fn parse_bjt(input: &str) -> IResult<&str, BJT> {
     // Parse the numeric name part
     let (input, name) = alphanumeric1(input)?; // Allows QN123 etc.
 
-    dbg!(name);
-
     // Parse nodes: collector, base, emitter
     let (input, collector) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, base) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, emitter) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
 
-    dbg!(&collector, &base, &emitter);
-
-    // Optionally parse the value/model
-    let (input, value) = opt(preceded(space1, value_parser)).parse(input)?; // Changed to alphanumeric for model names
-
-    dbg!(&value);
+    // Optionally parse a model name, or a bare value as older netlists have. A value must
+    // end the token, so names starting with a digit such as `2N2222` are model names.
+    let (input, value_or_model) = opt(preceded(
+        space1,
+        alt((
+            map(
+                terminated(value_parser, not(alphanumeric_or_underscore1)),
+                Ok,
+            ),
+            map(alphanumeric_or_underscore1, Err),
+        )),
+    ))
+    .parse(input)?;
+    let (value, model_name) = match value_or_model {
+        Some(Ok(value)) => (Some(value), None),
+        Some(Err(model_name)) => (None, Some(model_name.to_string())),
+        None => (None, None),
+    };
 
     let bjt = BJT {
         name: name.to_string(),
@@ -73,7 +223,9 @@ pub fn parse_bjt(input: &str) -> IResult<&str, BJT> {
         base: base.to_string(),
         emitter: emitter.to_string(),
         value,
+        model_name,
         bjt_type,
+        model: BjtModel::default(),
     };
 
     Ok((input, bjt))
@@ -84,7 +236,7 @@ impl FromStr for BJT {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split(['%', '*']).next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
         if s_without_comment.is_empty() {
             return Err(Error::InvalidFormat(
                 "Empty line after comment removal".to_string(),
@@ -122,6 +274,7 @@ mod tests {
         assert_eq!(bjt.base, "2");
         assert_eq!(bjt.emitter, "0");
         assert_eq!(bjt.value, Some(0.7));
+        assert_eq!(bjt.model_name, None);
         assert_eq!(bjt.bjt_type, BjtType::NPN);
         assert_eq!(bjt.identifier(), "Q1");
     }
@@ -154,15 +307,73 @@ mod tests {
         assert_eq!(bjt.identifier(), "Q2");
     }
 
+    #[test]
+    fn test_parse_bjt_with_model_name() {
+        let bjt = "QN1 c b e 2N2222".parse::<BJT>().unwrap();
+        assert_eq!(bjt.value, None);
+        assert_eq!(bjt.model_name.as_deref(), Some("2N2222"));
+        assert_eq!(bjt.to_string(), "QN1 c b e 2N2222");
+
+        let bjt = "QP2 c b e QMOD".parse::<BJT>().unwrap();
+        assert_eq!(bjt.model_name.as_deref(), Some("QMOD"));
+    }
+
     #[test]
     fn test_parse_with_comment() {
-        let s = "Qp10 coll base emit * My PNP";
+        let s = "Qp10 coll base emit ; My PNP";
         let bjt = s.parse::<BJT>().unwrap();
         assert_eq!(bjt.name, "10");
         assert_eq!(bjt.bjt_type, BjtType::PNP);
         assert_eq!(bjt.value, None);
     }
 
+    #[test]
+    fn test_operating_point_forward_active() {
+        let bjt = "QN1 c b e".parse::<BJT>().unwrap();
+        let solution_map = HashMap::from([
+            ("V(c)".to_string(), 5.0),
+            ("V(b)".to_string(), 0.7),
+            ("V(e)".to_string(), 0.0),
+        ]);
+        let op = bjt.operating_point(&solution_map);
+
+        assert!((op.i_c / op.i_b - bjt.model.forward_beta).abs() < 1e-6);
+        assert!((op.g_m - op.i_c / THERMAL_VOLTAGE).abs() / op.g_m < 1e-6);
+        assert!((op.g_pi - op.g_m / bjt.model.forward_beta).abs() / op.g_pi < 1e-6);
+    }
+
+    #[test]
+    fn test_capacitances_include_diffusion_capacitance() {
+        let mut bjt = "QN1 c b e".parse::<BJT>().unwrap();
+        bjt.model.forward_transit_time = 1e-9;
+        bjt.model.base_emitter_capacitance = 2e-12;
+        bjt.model.base_collector_capacitance = 1e-12;
+        let solution_map = HashMap::from([
+            ("V(c)".to_string(), 5.0),
+            ("V(b)".to_string(), 0.7),
+            ("V(e)".to_string(), 0.0),
+        ]);
+        let op = bjt.operating_point(&solution_map);
+
+        let (c_pi, c_mu) = bjt.capacitances(&op);
+        assert!((c_pi - (2e-12 + 1e-9 * op.g_m)).abs() < 1e-18);
+        assert_eq!(c_mu, 1e-12);
+    }
+
+    #[test]
+    fn test_operating_point_pnp_mirrors_npn() {
+        let npn = "QN1 c b e".parse::<BJT>().unwrap();
+        let pnp = "QP1 c b e".parse::<BJT>().unwrap();
+        let npn_map = HashMap::from([
+            ("V(c)".to_string(), 5.0),
+            ("V(b)".to_string(), 0.7),
+            ("V(e)".to_string(), 0.0),
+        ]);
+        let pnp_map: HashMap<String, f64> = npn_map.iter().map(|(k, v)| (k.clone(), -v)).collect();
+
+        assert_eq!(npn.operating_point(&npn_map), pnp.operating_point(&pnp_map));
+    }
+
     #[test]
     fn test_invalid_bjt_format_parts() {
         let bjt_str = "QN1 1 2"; // Missing emitter node
@@ -1,4 +1,6 @@
 use crate::prelude::*;
+use nom::multi::many0;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 /// Represents a capacitor in a circuit.
@@ -13,12 +15,42 @@ pub struct Capacitor {
     pub minus: String,
     /// If the capacitor is G2.
     pub g2: bool,
+    /// Initial voltage `V(plus) - V(minus)` used when the transient skips the operating point.
+    pub initial_condition: Option<f64>,
+    /// Relative tolerance of the value (`tol`), used by Monte Carlo analyses.
+    pub tolerance: Option<f64>,
 }
 
 impl Capacitor {
     pub fn identifier(&self) -> String {
         format!("C{}", self.name)
     }
+
+    /// Returns the capacitance used by the DC, AC and transient stamps. There are no temperature
+    /// coefficients, and Monte Carlo analyses apply the tolerance by drawing `value` itself.
+    pub fn capacitance(&self) -> f64 {
+        self.value
+    }
+}
+
+impl fmt::Display for Capacitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "C{} {} {} {}",
+            self.name, self.plus, self.minus, self.value
+        )?;
+        if self.g2 {
+            write!(f, " G2")?;
+        }
+        if let Some(voltage) = self.initial_condition {
+            write!(f, " ic={voltage}")?;
+        }
+        if let Some(tolerance) = self.tolerance {
+            write!(f, " tol={tolerance}")?;
+        }
+        Ok(())
+    }
 }
 
 pub fn parse_capacitor(input: &str) -> IResult<&str, Capacitor> {
@@ -27,16 +59,44 @@ pub fn parse_capacitor(input: &str) -> IResult<&str, Capacitor> {
     let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, value) = preceded(space1, value_parser).parse(input)?;
-    let (input, g2_opt) = opt(preceded(space1, tag_no_case("G2"))).parse(input)?;
+    // The `G2` flag, `ic=` and `tol=` may follow the value in any order.
+    let (input, options) = many0(preceded(
+        space1,
+        alt((
+            map(parse_element_parameter, Some),
+            map(tag_no_case("G2"), |_| None),
+        )),
+    ))
+    .parse(input)?;
 
-    let capacitor = Capacitor {
+    let mut capacitor = Capacitor {
         name: name.to_string(),
         plus: plus.to_string(),
         minus: minus.to_string(),
         value,
-        g2: g2_opt.is_some(),
+        g2: false,
+        initial_condition: None,
+        tolerance: None,
     };
 
+    for option in options {
+        match option {
+            None => capacitor.g2 = true,
+            Some((key, value)) if key.eq_ignore_ascii_case("ic") => {
+                capacitor.initial_condition = Some(value);
+            }
+            Some((key, value)) if key.eq_ignore_ascii_case("tol") => {
+                capacitor.tolerance = Some(value);
+            }
+            Some((key, _)) => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    key,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+    }
+
     Ok((input, capacitor))
 }
 
@@ -44,7 +104,7 @@ impl FromStr for Capacitor {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split('%').next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
 
         let (_, capacitor) = all_consuming(parse_capacitor)
             .parse(s_without_comment)
@@ -114,6 +174,28 @@ mod tests {
         assert!(capacitor.g2);
     }
 
+    #[test]
+    fn test_parse_capacitor_with_initial_condition() {
+        let capacitor = "C1 1 0 1u ic=3".parse::<Capacitor>().unwrap();
+        assert_eq!(capacitor.value, 1e-6);
+        assert_eq!(capacitor.initial_condition, Some(3.0));
+        assert!(!capacitor.g2);
+
+        let capacitor = "C1 1 0 1u IC=3 G2 % comment".parse::<Capacitor>().unwrap();
+        assert_eq!(capacitor.initial_condition, Some(3.0));
+        assert!(capacitor.g2);
+
+        let capacitor = "C1 1 0 1u g2 ic=-0.5".parse::<Capacitor>().unwrap();
+        assert_eq!(capacitor.initial_condition, Some(-0.5));
+        assert!(capacitor.g2);
+
+        let capacitor = "C1 1 0 1u tol=10% G2".parse::<Capacitor>().unwrap();
+        assert_eq!(capacitor.tolerance, Some(0.1));
+        assert!(capacitor.g2);
+
+        assert!("C1 1 0 1u tc=3".parse::<Capacitor>().is_err());
+    }
+
     #[test]
     fn test_parse_lowercase() {
         let capacitor_str = "c1 1 0 1e-6 g2";
@@ -123,6 +205,30 @@ mod tests {
         assert!(capacitor.g2);
     }
 
+    #[test]
+    fn test_parse_capacitor_with_suffix() {
+        let epsilon = 1e-18;
+        let capacitor = "C1 1 0 1u".parse::<Capacitor>().unwrap();
+        assert!((capacitor.value - 1e-6).abs() < epsilon);
+
+        let capacitor = "C2 1 0 4.7meg".parse::<Capacitor>().unwrap();
+        assert!((capacitor.value - 4.7e6).abs() < 1e-6);
+
+        let capacitor = "C3 1 0 100n".parse::<Capacitor>().unwrap();
+        assert!((capacitor.value - 100e-9).abs() < epsilon);
+    }
+
+    #[test]
+    fn test_parse_capacitor_with_suffix_g2_and_comment() {
+        let capacitor = "C1 1 0 100nF G2 % comment".parse::<Capacitor>().unwrap();
+        assert!((capacitor.value - 100e-9).abs() < 1e-18);
+        assert!(capacitor.g2);
+
+        let capacitor = "C1 1 0 10p%comment".parse::<Capacitor>().unwrap();
+        assert!((capacitor.value - 10e-12).abs() < 1e-18);
+        assert!(!capacitor.g2);
+    }
+
     #[test]
     fn test_invalid_capacitor_format() {
         let capacitor_str = "C1 1 0";
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents a current-controlled current source (CCCS) in a circuit.
+/// SPICE format: F<name> <n+> <n-> <vname> <gain>
+///
+/// The controlling current is the branch current of the element `vname`,
+/// which must have a dedicated branch current (Group 2) in MNA.
+pub struct CCCS {
+    /// Name of the CCCS.
+    pub name: String,
+    /// Positive output node.
+    pub plus: String,
+    /// Negative output node.
+    pub minus: String,
+    /// Identifier of the element whose branch current controls the source (e.g. `V1`).
+    pub control_source: String,
+    /// Current gain (A/A).
+    pub gain: f64,
+}
+
+impl CCCS {
+    /// Returns the identifier of the CCCS in the format `F{name}`.
+    pub fn identifier(&self) -> String {
+        format!("F{}", self.name)
+    }
+}
+
+impl fmt::Display for CCCS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "F{} {} {} {} {}",
+            self.name, self.plus, self.minus, self.control_source, self.gain,
+        )
+    }
+}
+
+pub fn parse_cccs(input: &str) -> IResult<&str, CCCS> {
+    let (input, _) = tag_no_case("F").parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, control_source) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, gain) = preceded(space1, value_parser).parse(input)?;
+
+    let cccs = CCCS {
+        name: name.to_string(),
+        plus: plus.to_string(),
+        minus: minus.to_string(),
+        control_source: normalize_identifier(control_source),
+        gain,
+    };
+
+    Ok((input, cccs))
+}
+
+impl FromStr for CCCS {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, cccs) = all_consuming(parse_cccs)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        Ok(cccs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cccs() {
+        let cccs = "F1 out 0 Vsense 50".parse::<CCCS>().unwrap();
+
+        assert_eq!(cccs.name, "1");
+        assert_eq!(cccs.plus, "out");
+        assert_eq!(cccs.minus, "0");
+        assert_eq!(cccs.control_source, "Vsense");
+        assert_eq!(cccs.gain, 50.0);
+        assert_eq!(cccs.identifier(), "F1");
+    }
+
+    #[test]
+    fn test_parse_lowercase_control_source() {
+        let cccs = "f2 a b vsense 10 % comment".parse::<CCCS>().unwrap();
+        assert_eq!(cccs.name, "2");
+        // The element prefix is normalized so it matches `Element::identifier()`.
+        assert_eq!(cccs.control_source, "Vsense");
+    }
+
+    #[test]
+    fn test_invalid_cccs_missing_value() {
+        assert!("F1 out 0 Vsense".parse::<CCCS>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!("H1 out 0 Vsense 10".parse::<CCCS>().is_err());
+    }
+}
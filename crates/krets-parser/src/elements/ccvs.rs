@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents a current-controlled voltage source (CCVS) in a circuit.
+/// SPICE format: H<name> <n+> <n-> <vname> <transresistance>
+///
+/// The controlling current is the branch current of the element `vname`,
+/// which must have a dedicated branch current (Group 2) in MNA.
+pub struct CCVS {
+    /// Name of the CCVS.
+    pub name: String,
+    /// Positive output node.
+    pub plus: String,
+    /// Negative output node.
+    pub minus: String,
+    /// Identifier of the element whose branch current controls the source (e.g. `V1`).
+    pub control_source: String,
+    /// Transresistance (V/A).
+    pub transresistance: f64,
+}
+
+impl CCVS {
+    /// Returns the identifier of the CCVS in the format `H{name}`.
+    pub fn identifier(&self) -> String {
+        format!("H{}", self.name)
+    }
+}
+
+impl fmt::Display for CCVS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "H{} {} {} {} {}",
+            self.name, self.plus, self.minus, self.control_source, self.transresistance,
+        )
+    }
+}
+
+pub fn parse_ccvs(input: &str) -> IResult<&str, CCVS> {
+    let (input, _) = tag_no_case("H").parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, control_source) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, transresistance) = preceded(space1, value_parser).parse(input)?;
+
+    let ccvs = CCVS {
+        name: name.to_string(),
+        plus: plus.to_string(),
+        minus: minus.to_string(),
+        control_source: normalize_identifier(control_source),
+        transresistance,
+    };
+
+    Ok((input, ccvs))
+}
+
+impl FromStr for CCVS {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, ccvs) = all_consuming(parse_ccvs)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        Ok(ccvs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ccvs() {
+        let ccvs = "H1 out 0 Vsense 1k".parse::<CCVS>().unwrap();
+
+        assert_eq!(ccvs.name, "1");
+        assert_eq!(ccvs.plus, "out");
+        assert_eq!(ccvs.minus, "0");
+        assert_eq!(ccvs.control_source, "Vsense");
+        assert_eq!(ccvs.transresistance, 1000.0);
+        assert_eq!(ccvs.identifier(), "H1");
+    }
+
+    #[test]
+    fn test_parse_lowercase_control_source() {
+        let ccvs = "h2 a b vsense 10 % comment".parse::<CCVS>().unwrap();
+        assert_eq!(ccvs.name, "2");
+        // The element prefix is normalized so it matches `Element::identifier()`.
+        assert_eq!(ccvs.control_source, "Vsense");
+    }
+
+    #[test]
+    fn test_invalid_ccvs_missing_value() {
+        assert!("H1 out 0 Vsense".parse::<CCVS>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!("F1 out 0 Vsense 10".parse::<CCVS>().is_err());
+    }
+}
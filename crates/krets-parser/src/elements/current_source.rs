@@ -1,4 +1,7 @@
+use crate::elements::voltage_source::{Pwl, parse_ac, parse_pwl};
 use crate::prelude::*;
+use nom::multi::many0;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 /// Represents a current source in a circuit.
@@ -11,12 +14,53 @@ pub struct CurrentSource {
     pub plus: String,
     /// The negative node of the current source.
     pub minus: String,
+    /// The magnitude of the AC excitation in Amperes.
+    pub ac_amplitude: f64,
+    /// The phase of the AC excitation in degrees.
+    pub ac_phase: f64,
+    /// An optional piecewise-linear waveform used during transient analysis.
+    pub pwl: Option<Pwl>,
 }
 
 impl CurrentSource {
     pub fn identifier(&self) -> String {
         format!("I{}", self.name)
     }
+
+    /// Calculates the source's value at a specific time for transient analysis.
+    pub fn transient_value_at(&self, time: f64) -> f64 {
+        match &self.pwl {
+            Some(pwl) => pwl.value_at(time),
+            None => self.value,
+        }
+    }
+}
+
+impl fmt::Display for CurrentSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "I{} {} {} {}",
+            self.name, self.plus, self.minus, self.value,
+        )?;
+        if self.ac_amplitude != 0.0 {
+            write!(f, " AC {}", self.ac_amplitude)?;
+            if self.ac_phase != 0.0 {
+                write!(f, " {}", self.ac_phase)?;
+            }
+        }
+        if let Some(pwl) = &self.pwl {
+            write!(f, " {pwl}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The optional parameters of a current source, in any order.
+enum Param {
+    Dc(f64),
+    Ac(f64, f64),
+    Pwl(Pwl),
 }
 
 pub fn parse_current_source(input: &str) -> IResult<&str, CurrentSource> {
@@ -24,14 +68,46 @@ pub fn parse_current_source(input: &str) -> IResult<&str, CurrentSource> {
     let (input, name) = alphanumeric_or_underscore1.parse(input)?;
     let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
-    let (input, value) = preceded(space1, value_parser).parse(input)?;
+    let (input, implicit_dc) = opt(preceded(space1, value_parser)).parse(input)?;
+    let (input, params) = many0(preceded(
+        space1,
+        alt((
+            map(
+                preceded((tag_no_case("dc"), space1), value_parser),
+                Param::Dc,
+            ),
+            map(parse_ac, |(magnitude, phase)| Param::Ac(magnitude, phase)),
+            map(parse_pwl, Param::Pwl),
+        )),
+    ))
+    .parse(input)?;
+    // The source needs a DC value, an AC specification or a waveform.
+    if implicit_dc.is_none() && params.is_empty() {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
 
-    let current_source = CurrentSource {
+    let mut current_source = CurrentSource {
         name: name.to_string(),
         plus: plus.to_string(),
         minus: minus.to_string(),
-        value,
+        value: implicit_dc.unwrap_or(0.0),
+        ac_amplitude: 0.0,
+        ac_phase: 0.0,
+        pwl: None,
     };
+    for param in params {
+        match param {
+            Param::Dc(value) => current_source.value = value,
+            Param::Ac(magnitude, phase) => {
+                current_source.ac_amplitude = magnitude;
+                current_source.ac_phase = phase;
+            }
+            Param::Pwl(pwl) => current_source.pwl = Some(pwl),
+        }
+    }
 
     Ok((input, current_source))
 }
@@ -40,7 +116,7 @@ impl FromStr for CurrentSource {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split('%').next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
         let (_, current_source) = all_consuming(parse_current_source)
             .parse(s_without_comment)
             .map_err(|e| Error::InvalidFormat(e.to_string()))?;
@@ -114,4 +190,39 @@ mod tests {
         let result = current_source_str.parse::<CurrentSource>();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_pwl() {
+        let source = "I1 0 a PWL(0 0 1m 2m)".parse::<CurrentSource>().unwrap();
+        assert_eq!(source.value, 0.0);
+        let pwl = source.pwl.as_ref().expect("PWL should be parsed");
+        assert_eq!(pwl.points, vec![(0.0, 0.0), (1e-3, 2e-3)]);
+        assert!((source.transient_value_at(0.5e-3) - 1e-3).abs() < 1e-12);
+        assert!((source.transient_value_at(5e-3) - 2e-3).abs() < 1e-12);
+
+        let source = "I1 0 a 1m PWL(0 1m 1m 2m)"
+            .parse::<CurrentSource>()
+            .unwrap();
+        assert_eq!(source.value, 1e-3);
+        assert!(source.pwl.is_some());
+    }
+
+    #[test]
+    fn test_parse_ac() {
+        let source = "I1 1 0 ac 1 90".parse::<CurrentSource>().unwrap();
+        assert_eq!(source.value, 0.0);
+        assert_eq!(source.ac_amplitude, 1.0);
+        assert_eq!(source.ac_phase, 90.0);
+        assert_eq!(source.to_string(), "I1 1 0 0 AC 1 90");
+
+        let source = "I2 1 0 DC 2m AC 0.5".parse::<CurrentSource>().unwrap();
+        assert_eq!(source.value, 2e-3);
+        assert_eq!(source.ac_amplitude, 0.5);
+        assert_eq!(source.ac_phase, 0.0);
+    }
+
+    #[test]
+    fn test_parse_pwl_rejects_non_increasing_times() {
+        assert!("I1 0 a PWL(1m 0 0 1)".parse::<CurrentSource>().is_err());
+    }
 }
@@ -1,4 +1,9 @@
-use crate::{constants::THERMAL_VOLTAGE, models::diode::DiodeModel, prelude::*};
+use crate::{
+    constants::{KB, Q, TEMPERATURE},
+    models::diode::DiodeModel,
+    prelude::*,
+};
+use std::fmt;
 
 #[derive(Debug, Clone)]
 /// Represents a diode in a circuit.
@@ -13,6 +18,8 @@ pub struct Diode {
     pub plus: String,
     /// Negative node of the diode.
     pub minus: String,
+    /// Operating temperature in Kelvin.
+    pub temperature: f64,
 }
 
 impl Diode {
@@ -39,20 +46,38 @@ impl Diode {
         self.v_plus(solution_map) - self.v_minus(solution_map)
     }
 
+    /// Returns the thermal voltage `kT/q` at the operating temperature.
+    pub fn thermal_voltage(&self) -> f64 {
+        KB * self.temperature / Q
+    }
+
+    /// Returns the saturation current at the operating temperature,
+    /// `IS(T) = IS * (T/Tnom)^(XTI/N) * exp((T/Tnom - 1) * EG / (N * Vt(T)))`.
+    pub fn saturation_current(&self) -> f64 {
+        let n = self.model.emission_coefficient;
+        let ratio = self.temperature / TEMPERATURE;
+        let xti = self.model.saturation_current_temperature_exponent;
+        let eg = self.model.energy_gap;
+
+        self.model.saturation_current
+            * ratio.powf(xti / n)
+            * f64::exp((ratio - 1.0) * eg / (n * self.thermal_voltage()))
+    }
+
     pub fn conductance(&self, solution_map: &HashMap<String, f64>) -> f64 {
         let diode_voltage = self.limit_diode_voltage(self.v_d(solution_map));
-        let n = self.model.emission_coefficient;
-        let is = self.model.saturation_current;
+        let n_vt = self.model.emission_coefficient * self.thermal_voltage();
+        let is = self.saturation_current();
 
-        (is / (n * THERMAL_VOLTAGE)) * f64::exp(diode_voltage / (n * THERMAL_VOLTAGE))
+        (is / n_vt) * f64::exp(diode_voltage / n_vt)
     }
 
     pub fn current(&self, solution_map: &HashMap<String, f64>) -> f64 {
         let diode_voltage = self.limit_diode_voltage(self.v_d(solution_map));
-        let n = self.model.emission_coefficient;
-        let is = self.model.saturation_current;
+        let n_vt = self.model.emission_coefficient * self.thermal_voltage();
+        let is = self.saturation_current();
 
-        is * (f64::exp(diode_voltage / (n * THERMAL_VOLTAGE)) - 1.0)
+        is * (f64::exp(diode_voltage / n_vt) - 1.0)
     }
 
     pub fn equivalent_current(&self, solution_map: &HashMap<String, f64>) -> f64 {
@@ -60,16 +85,39 @@ impl Diode {
         self.current(solution_map) - self.conductance(solution_map) * diode_voltage
     }
 
+    /// Returns the charge stored in the diode, `CJO * Vd + TT * Id`.
+    ///
+    /// The junction capacitance is taken as constant, independent of the junction voltage.
+    pub fn charge(&self, solution_map: &HashMap<String, f64>) -> f64 {
+        self.model.junction_capacitance * self.v_d(solution_map)
+            + self.model.transit_time * self.current(solution_map)
+    }
+
+    /// Returns the small-signal capacitance `dQ/dVd = CJO + TT * gd` at the current iteration.
+    pub fn capacitance(&self, solution_map: &HashMap<String, f64>) -> f64 {
+        self.model.junction_capacitance + self.model.transit_time * self.conductance(solution_map)
+    }
+
     // Voltage limiting function to prevent floating-point overflows
     // in the exponential function, which is a common issue in circuit simulators.
     pub fn limit_diode_voltage(&self, vd: f64) -> f64 {
-        let n = self.model.emission_coefficient;
-        let is = self.model.saturation_current;
-        let v_critical = n * THERMAL_VOLTAGE * f64::ln(f64::MAX * n * THERMAL_VOLTAGE / is);
+        let n_vt = self.model.emission_coefficient * self.thermal_voltage();
+        let is = self.saturation_current();
+        let v_critical = n_vt * f64::ln(f64::MAX * n_vt / is);
         vd.clamp(-v_critical, v_critical)
     }
 }
 
+impl fmt::Display for Diode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "D{} {} {} {}",
+            self.name, self.plus, self.minus, self.model_name,
+        )
+    }
+}
+
 // Updated nom parser function
 pub fn parse_diode(input: &str) -> IResult<&str, Diode> {
     let (input, _) = tag_no_case("D").parse(input)?;
@@ -85,6 +133,7 @@ pub fn parse_diode(input: &str) -> IResult<&str, Diode> {
         minus: minus.to_string(),
         model_name: model_name.unwrap_or("default").to_string(),
         model: DiodeModel::default(),
+        temperature: TEMPERATURE,
     };
 
     Ok((input, diode))
@@ -96,7 +145,7 @@ impl FromStr for Diode {
 
     fn from_str(s: &str) -> Result<Self> {
         // Remove comments first
-        let s_without_comment = s.split(['%', '*']).next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
 
         // Use the nom parser with all_consuming to ensure the whole line is parsed
         match all_consuming(parse_diode).parse(s_without_comment) {
@@ -173,13 +222,19 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_diode_with_star_comment() {
-        let diode_str = "D2 out 0 Special * With star comment";
+    fn test_parse_diode_with_semicolon_comment() {
+        let diode_str = "D2 out 0 Special ; a *fast* diode";
         let diode = diode_str.parse::<Diode>().unwrap();
         assert_eq!(diode.name, "2");
         assert_eq!(diode.model_name, "Special");
     }
 
+    #[test]
+    fn test_star_only_starts_a_comment_at_line_start() {
+        // A `*` after the model name is not a comment, so the line has an extra token.
+        assert!("D2 out 0 Special * note".parse::<Diode>().is_err());
+    }
+
     #[test]
     fn test_invalid_diode_format_missing_node() {
         let diode_str = "D1 1";
@@ -208,4 +263,15 @@ mod tests {
         let result = s.parse::<Diode>();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_saturation_current_temperature_scaling() {
+        let mut diode = "D1 1 0".parse::<Diode>().unwrap();
+        assert_eq!(diode.saturation_current(), diode.model.saturation_current);
+
+        // The saturation current of silicon roughly doubles every 5 Kelvin.
+        diode.temperature = TEMPERATURE + 10.0;
+        let ratio = diode.saturation_current() / diode.model.saturation_current;
+        assert!(ratio > 3.0 && ratio < 6.0);
+    }
 }
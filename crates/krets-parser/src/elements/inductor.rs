@@ -1,4 +1,6 @@
 use crate::prelude::*;
+use nom::multi::many0;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 /// Represents an inductor in a circuit.
@@ -11,12 +13,44 @@ pub struct Inductor {
     pub plus: String,
     /// Negative node of the inductor.
     pub minus: String,
+    /// Initial current from `plus` to `minus` used when the transient skips the operating point.
+    pub initial_condition: Option<f64>,
+    /// Relative tolerance of the value (`tol`), used by Monte Carlo analyses.
+    pub tolerance: Option<f64>,
+    /// Series resistance of the winding in Ohms (`esr`), 0 for an ideal inductor.
+    pub esr: f64,
 }
 
 impl Inductor {
     pub fn identifier(&self) -> String {
         format!("L{}", self.name)
     }
+
+    /// Returns the inductance used by the DC, AC and transient stamps. There are no temperature
+    /// coefficients, and Monte Carlo analyses apply the tolerance by drawing `value` itself.
+    pub fn inductance(&self) -> f64 {
+        self.value
+    }
+}
+
+impl fmt::Display for Inductor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "L{} {} {} {}",
+            self.name, self.plus, self.minus, self.value
+        )?;
+        if let Some(current) = self.initial_condition {
+            write!(f, " ic={current}")?;
+        }
+        if let Some(tolerance) = self.tolerance {
+            write!(f, " tol={tolerance}")?;
+        }
+        if self.esr != 0.0 {
+            write!(f, " esr={}", self.esr)?;
+        }
+        Ok(())
+    }
 }
 
 pub fn parse_inductor(input: &str) -> IResult<&str, Inductor> {
@@ -25,14 +59,32 @@ pub fn parse_inductor(input: &str) -> IResult<&str, Inductor> {
     let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, value) = preceded(space1, value_parser).parse(input)?;
+    let (input, options) = many0(preceded(space1, parse_element_parameter)).parse(input)?;
 
-    let inductor = Inductor {
+    let mut inductor = Inductor {
         name: name.to_string(),
         plus: plus.to_string(),
         minus: minus.to_string(),
         value,
+        initial_condition: None,
+        tolerance: None,
+        esr: 0.0,
     };
 
+    for (key, value) in options {
+        match key.to_lowercase().as_str() {
+            "ic" => inductor.initial_condition = Some(value),
+            "tol" => inductor.tolerance = Some(value),
+            "esr" => inductor.esr = value,
+            _ => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    key,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+    }
+
     Ok((input, inductor))
 }
 
@@ -40,7 +92,7 @@ impl FromStr for Inductor {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split('%').next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
         let (_, inductor) = all_consuming(parse_inductor)
             .parse(s_without_comment)
             .map_err(|e| Error::InvalidFormat(e.to_string()))?;
@@ -73,6 +125,25 @@ mod tests {
         assert_eq!(inductor.value, 0.001);
     }
 
+    #[test]
+    fn test_parse_inductor_with_initial_condition() {
+        let inductor = "L1 1 0 1m ic=2m % comment".parse::<Inductor>().unwrap();
+        assert_eq!(inductor.value, 1e-3);
+        assert_eq!(inductor.initial_condition, Some(2e-3));
+
+        let inductor = "L1 1 0 1m tol=20% ic=1m".parse::<Inductor>().unwrap();
+        assert_eq!(inductor.tolerance, Some(0.2));
+        assert_eq!(inductor.initial_condition, Some(1e-3));
+
+        let inductor = "L1 1 0 1m esr=0.5".parse::<Inductor>().unwrap();
+        assert_eq!(inductor.esr, 0.5);
+        assert_eq!(inductor.to_string(), "L1 1 0 0.001 esr=0.5");
+        assert_eq!("L1 1 0 1m".parse::<Inductor>().unwrap().esr, 0.0);
+
+        assert!("L1 1 0 1m G2".parse::<Inductor>().is_err());
+        assert!("L1 1 0 1m tc=1".parse::<Inductor>().is_err());
+    }
+
     #[test]
     fn test_parse_lowercase_and_scientific() {
         let s = "l2 vcc out 1e-6";
@@ -0,0 +1,131 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents the magnetic coupling between two inductors.
+/// SPICE format: K<name> <L1> <L2> <coupling>
+///
+/// The mutual inductance `M = k * sqrt(La * Lb)` is resolved by the parser once
+/// both referenced inductors are known.
+pub struct MutualInductance {
+    /// Name of the coupling.
+    pub name: String,
+    /// Identifier of the first coupled inductor (e.g. `L1`).
+    pub inductor_a: String,
+    /// Identifier of the second coupled inductor (e.g. `L2`).
+    pub inductor_b: String,
+    /// Coupling coefficient `k`, in the range `[0, 1]`.
+    pub coupling: f64,
+    /// Mutual inductance in Henries, filled in during circuit parsing.
+    pub inductance: f64,
+}
+
+impl MutualInductance {
+    /// Returns the identifier of the coupling in the format `K{name}`.
+    pub fn identifier(&self) -> String {
+        format!("K{}", self.name)
+    }
+
+    /// Checks that the coupling coefficient is physically meaningful.
+    pub fn validate(&self) -> Result<()> {
+        if (0.0..=1.0).contains(&self.coupling) {
+            Ok(())
+        } else {
+            Err(Error::InvalidFormat(format!(
+                "Coupling coefficient of {} must be within [0, 1], got {}",
+                self.identifier(),
+                self.coupling
+            )))
+        }
+    }
+
+    /// Computes the mutual inductance from the self inductances of the coupled inductors.
+    pub fn resolve(&mut self, inductance_a: f64, inductance_b: f64) {
+        self.inductance = self.coupling * (inductance_a * inductance_b).sqrt();
+    }
+}
+
+impl fmt::Display for MutualInductance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "K{} {} {} {}",
+            self.name, self.inductor_a, self.inductor_b, self.coupling,
+        )
+    }
+}
+
+pub fn parse_mutual_inductance(input: &str) -> IResult<&str, MutualInductance> {
+    let (input, _) = tag_no_case("K").parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, inductor_a) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, inductor_b) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, coupling) = preceded(space1, value_parser).parse(input)?;
+
+    let mutual_inductance = MutualInductance {
+        name: name.to_string(),
+        inductor_a: normalize_identifier(inductor_a),
+        inductor_b: normalize_identifier(inductor_b),
+        coupling,
+        inductance: 0.0,
+    };
+
+    Ok((input, mutual_inductance))
+}
+
+impl FromStr for MutualInductance {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, mutual_inductance) = all_consuming(parse_mutual_inductance)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        mutual_inductance.validate()?;
+        Ok(mutual_inductance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mutual_inductance() {
+        let k = "K1 L1 L2 0.98".parse::<MutualInductance>().unwrap();
+
+        assert_eq!(k.name, "1");
+        assert_eq!(k.inductor_a, "L1");
+        assert_eq!(k.inductor_b, "L2");
+        assert_eq!(k.coupling, 0.98);
+        assert_eq!(k.identifier(), "K1");
+    }
+
+    #[test]
+    fn test_parse_lowercase_inductor_names() {
+        let k = "k2 lpri lsec 1 % comment"
+            .parse::<MutualInductance>()
+            .unwrap();
+        assert_eq!(k.inductor_a, "Lpri");
+        assert_eq!(k.inductor_b, "Lsec");
+    }
+
+    #[test]
+    fn test_resolve_mutual_inductance() {
+        let mut k = "K1 L1 L2 0.5".parse::<MutualInductance>().unwrap();
+        k.resolve(1e-3, 4e-3);
+        assert!((k.inductance - 1e-3).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_invalid_coupling_out_of_range() {
+        assert!("K1 L1 L2 1.5".parse::<MutualInductance>().is_err());
+        assert!("K1 L1 L2 -0.1".parse::<MutualInductance>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_missing_coupling() {
+        assert!("K1 L1 L2".parse::<MutualInductance>().is_err());
+    }
+}
@@ -8,6 +8,7 @@ use nom::{
     multi,
     sequence::preceded,
 };
+use std::fmt;
 
 #[derive(Debug, Clone)]
 /// Represents a MOSFET (Metal-Oxide-Semiconductor Field-Effect Transistor) in a circuit.
@@ -40,8 +41,13 @@ impl NMOSFET {
         self.model.voltage_threshold
     }
 
+    /// Returns `beta = kp * (W / L) * m`, taking `W` and `L` from the instance when given
+    /// and from the model otherwise.
     pub fn beta(&self) -> f64 {
-        self.model.beta()
+        let width = self.width.unwrap_or(self.model.width);
+        let length = self.length.unwrap_or(self.model.length);
+        let multiplicity = self.multiplicity.unwrap_or(1) as f64;
+        self.model.process_transconductance * (width / length) * multiplicity
     }
 
     pub fn lambda(&self) -> f64 {
@@ -49,6 +55,10 @@ impl NMOSFET {
     }
 
     pub fn g_m(&self, v_gs: f64, v_ds: f64) -> f64 {
+        if v_ds < 0.0 {
+            // Reverse mode: drain and source swap roles.
+            return -self.g_m(v_gs - v_ds, -v_ds);
+        }
         let v_th = self.threshold_voltage();
         let beta = self.beta();
         let lambda = self.lambda();
@@ -66,6 +76,10 @@ impl NMOSFET {
     }
 
     pub fn g_ds(&self, v_gs: f64, v_ds: f64) -> f64 {
+        if v_ds < 0.0 {
+            // Reverse mode: drain and source swap roles.
+            return self.g_m(v_gs - v_ds, -v_ds) + self.g_ds(v_gs - v_ds, -v_ds);
+        }
         let v_th = self.threshold_voltage();
         let beta = self.beta();
         let lambda = self.lambda();
@@ -84,6 +98,10 @@ impl NMOSFET {
     }
 
     pub fn i_d(&self, v_gs: f64, v_ds: f64) -> f64 {
+        if v_ds < 0.0 {
+            // Reverse mode: drain and source swap roles.
+            return -self.i_d(v_gs - v_ds, -v_ds);
+        }
         let v_th = self.threshold_voltage();
         let beta = self.beta();
         let lambda = self.lambda();
@@ -100,6 +118,32 @@ impl NMOSFET {
             0.0
         }
     }
+
+    /// Returns the Meyer gate capacitances `(c_gs, c_gd)` at the given bias point.
+    ///
+    /// The total gate capacitance `W * L * C_ox` is only known when the instance
+    /// specifies both `W` and `L`; otherwise the device is treated as purely resistive.
+    pub fn gate_capacitances(&self, v_gs: f64, v_ds: f64) -> Option<(f64, f64)> {
+        if v_ds < 0.0 {
+            // Reverse mode: drain and source swap roles.
+            let (c_gd, c_gs) = self.gate_capacitances(v_gs - v_ds, -v_ds)?;
+            return Some((c_gs, c_gd));
+        }
+
+        let c_gate = self.width? * self.length? * self.model.oxide_capacitance();
+        let v_ov = v_gs - self.threshold_voltage();
+
+        if v_ov <= 0.0 {
+            // Cut-off: no channel couples the gate to source or drain.
+            Some((0.0, 0.0))
+        } else if v_ds <= v_ov {
+            // Linear region: the channel charge is shared evenly.
+            Some((c_gate / 2.0, c_gate / 2.0))
+        } else {
+            // Saturation region: the channel is pinched off at the drain.
+            Some((2.0 / 3.0 * c_gate, 0.0))
+        }
+    }
 }
 
 impl NMOSFET {
@@ -109,6 +153,26 @@ impl NMOSFET {
     }
 }
 
+impl fmt::Display for NMOSFET {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MN{} {} {} {} {} {}",
+            self.name, self.drain, self.gate, self.source, self.bulk, self.model_name,
+        )?;
+        if let Some(width) = self.width {
+            write!(f, " w={width}")?;
+        }
+        if let Some(length) = self.length {
+            write!(f, " l={length}")?;
+        }
+        if let Some(multiplicity) = self.multiplicity {
+            write!(f, " m={multiplicity}")?;
+        }
+        Ok(())
+    }
+}
+
 // Nom parser for NMOSFET
 pub fn parse_nmosfet(input: &str) -> IResult<&str, NMOSFET> {
     // Parse the initial 'MN' (case-insensitive)
@@ -144,6 +208,12 @@ pub fn parse_nmosfet(input: &str) -> IResult<&str, NMOSFET> {
             width = Some(v);
         }
         if k.eq_ignore_ascii_case("l") {
+            if v <= 0.0 {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    k,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
             length = Some(v);
         }
     }
@@ -168,7 +238,7 @@ impl FromStr for NMOSFET {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split(['%', '*']).next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
         if s_without_comment.is_empty() {
             return Err(Error::InvalidFormat(
                 "Empty line after comment removal".to_string(),
@@ -257,4 +327,51 @@ mod tests {
         let mosfet = mosfet_str.parse::<NMOSFET>().unwrap();
         assert_eq!(mosfet.multiplicity, Some(3))
     }
+
+    #[test]
+    fn test_drain_current_scales_with_geometry() {
+        let narrow = "MN1 D G S B NMOD w=2u l=1u".parse::<NMOSFET>().unwrap();
+        let wide = "MN2 D G S B NMOD w=10u l=1u".parse::<NMOSFET>().unwrap();
+        let long = "MN3 D G S B NMOD w=10u l=2u m=2"
+            .parse::<NMOSFET>()
+            .unwrap();
+
+        // Saturation and linear region currents both scale with W/L and m.
+        for (v_gs, v_ds) in [(2.0, 3.0), (2.0, 0.5)] {
+            let i_narrow = narrow.i_d(v_gs, v_ds);
+            assert!(i_narrow > 0.0);
+            assert!((wide.i_d(v_gs, v_ds) / i_narrow - 5.0).abs() < 1e-12);
+            assert!((long.i_d(v_gs, v_ds) / i_narrow - 5.0).abs() < 1e-12);
+        }
+
+        // Without instance geometry the model W and L are used.
+        let default = "MN4 D G S B NMOD".parse::<NMOSFET>().unwrap();
+        assert_eq!(default.beta(), default.model.beta());
+    }
+
+    #[test]
+    fn test_invalid_mosfet_length() {
+        assert!("MN1 D G S B NMOD w=1u l=0".parse::<NMOSFET>().is_err());
+        assert!("MN1 D G S B NMOD l=-1u".parse::<NMOSFET>().is_err());
+    }
+
+    #[test]
+    fn test_gate_capacitances() {
+        let mosfet = "MN1 D G S B NMOD w=10u l=1u".parse::<NMOSFET>().unwrap();
+        let c_gate = 10e-6 * 1e-6 * mosfet.model.oxide_capacitance();
+
+        // Saturation: all channel charge is attributed to the source.
+        let (c_gs, c_gd) = mosfet.gate_capacitances(2.0, 5.0).unwrap();
+        assert!((c_gs - 2.0 / 3.0 * c_gate).abs() < 1e-20);
+        assert_eq!(c_gd, 0.0);
+
+        // Linear region: split evenly between source and drain.
+        let (c_gs, c_gd) = mosfet.gate_capacitances(2.0, 0.1).unwrap();
+        assert!((c_gs - c_gate / 2.0).abs() < 1e-20);
+        assert!((c_gd - c_gate / 2.0).abs() < 1e-20);
+
+        // Without geometry the capacitances are unknown.
+        let mosfet = "MN1 D G S B NMOD".parse::<NMOSFET>().unwrap();
+        assert!(mosfet.gate_capacitances(2.0, 5.0).is_none());
+    }
 }
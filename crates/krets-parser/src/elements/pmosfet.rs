@@ -0,0 +1,355 @@
+use crate::{models::pmosfet::PMosfetModel, prelude::*};
+
+use nom::{
+    IResult, Parser,
+    bytes::complete::tag_no_case,
+    character::complete::{space0, space1},
+    combinator::all_consuming,
+    multi,
+    sequence::preceded,
+};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents a MOSFET (Metal-Oxide-Semiconductor Field-Effect Transistor) in a circuit.
+/// SPICE format: M<name> <drain> <gate> <source> <bulk/substrate> <model> [parameters...]
+pub struct PMOSFET {
+    /// Name of the MOSFET.
+    pub name: String,
+    /// Drain node of the MOSFET.
+    pub drain: String,
+    /// Gate node of the MOSFET.
+    pub gate: String,
+    /// Source node of the MOSFET.
+    pub source: String,
+    /// Bulk (or Substrate) node of the MOSFET.
+    pub bulk: String,
+    /// Model name associated with the MOSFET (required).
+    pub model_name: String,
+    /// The model associated with the MOSFET.
+    pub model: PMosfetModel,
+    /// Multiplicity factor. Simulates "m" parallel devices
+    pub multiplicity: Option<usize>,
+    /// Width of the MOSFET.
+    pub width: Option<f64>,
+    /// Length of the MOSFET.
+    pub length: Option<f64>,
+}
+
+impl PMOSFET {
+    pub fn threshold_voltage(&self) -> f64 {
+        self.model.voltage_threshold
+    }
+
+    /// Returns `beta = kp * (W / L) * m`, taking `W` and `L` from the instance when given
+    /// and from the model otherwise.
+    pub fn beta(&self) -> f64 {
+        let width = self.width.unwrap_or(self.model.width);
+        let length = self.length.unwrap_or(self.model.length);
+        let multiplicity = self.multiplicity.unwrap_or(1) as f64;
+        self.model.process_transconductance * (width / length) * multiplicity
+    }
+
+    pub fn lambda(&self) -> f64 {
+        self.model.channel_length_modulation
+    }
+
+    // The P-channel equations mirror the N-channel ones with every voltage negated.
+    // `i_d` is the current flowing from drain to source, which is negative while the
+    // device conducts, so both small-signal conductances remain positive.
+
+    pub fn g_m(&self, v_gs: f64, v_ds: f64) -> f64 {
+        if v_ds > 0.0 {
+            // Reverse mode: drain and source swap roles.
+            return -self.g_m(v_gs - v_ds, -v_ds);
+        }
+        let v_th = self.threshold_voltage();
+        let beta = self.beta();
+        let lambda = self.lambda();
+        if v_gs >= v_th {
+            0.0
+        } else if v_ds <= 0.0 && v_ds >= (v_gs - v_th) {
+            // Linear region
+            -beta * v_ds
+        } else if v_ds <= (v_gs - v_th) && v_ds <= 0.0 {
+            // Saturation region
+            -beta * (v_gs - v_th) * (1.0 - lambda * v_ds)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn g_ds(&self, v_gs: f64, v_ds: f64) -> f64 {
+        if v_ds > 0.0 {
+            // Reverse mode: drain and source swap roles.
+            return self.g_m(v_gs - v_ds, -v_ds) + self.g_ds(v_gs - v_ds, -v_ds);
+        }
+        let v_th = self.threshold_voltage();
+        let beta = self.beta();
+        let lambda = self.lambda();
+
+        if v_gs >= v_th {
+            0.0
+        } else if v_ds <= 0.0 && v_ds >= (v_gs - v_th) {
+            // Linear region
+            -beta * (v_gs - v_th - v_ds)
+        } else if v_ds <= (v_gs - v_th) && v_ds <= 0.0 {
+            // Saturation region
+            (beta / 2.0) * lambda * (v_gs - v_th).powi(2)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn i_d(&self, v_gs: f64, v_ds: f64) -> f64 {
+        if v_ds > 0.0 {
+            // Reverse mode: drain and source swap roles.
+            return -self.i_d(v_gs - v_ds, -v_ds);
+        }
+        let v_th = self.threshold_voltage();
+        let beta = self.beta();
+        let lambda = self.lambda();
+
+        if v_gs >= v_th {
+            0.0
+        } else if v_ds <= 0.0 && v_ds >= (v_gs - v_th) {
+            // Linear region
+            -beta * ((v_gs - v_th) * v_ds - (v_ds.powi(2) / 2.0))
+        } else if v_ds <= (v_gs - v_th) && v_ds <= 0.0 {
+            // Saturation region
+            -(beta / 2.0) * (v_gs - v_th).powi(2) * (1.0 - lambda * v_ds)
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the Meyer gate capacitances `(c_gs, c_gd)` at the given bias point.
+    ///
+    /// The total gate capacitance `W * L * C_ox` is only known when the instance
+    /// specifies both `W` and `L`; otherwise the device is treated as purely resistive.
+    pub fn gate_capacitances(&self, v_gs: f64, v_ds: f64) -> Option<(f64, f64)> {
+        if v_ds > 0.0 {
+            // Reverse mode: drain and source swap roles.
+            let (c_gd, c_gs) = self.gate_capacitances(v_gs - v_ds, -v_ds)?;
+            return Some((c_gs, c_gd));
+        }
+
+        let c_gate = self.width? * self.length? * self.model.oxide_capacitance();
+        let v_ov = self.threshold_voltage() - v_gs;
+
+        if v_ov <= 0.0 {
+            // Cut-off: no channel couples the gate to source or drain.
+            Some((0.0, 0.0))
+        } else if -v_ds <= v_ov {
+            // Linear region: the channel charge is shared evenly.
+            Some((c_gate / 2.0, c_gate / 2.0))
+        } else {
+            // Saturation region: the channel is pinched off at the drain.
+            Some((2.0 / 3.0 * c_gate, 0.0))
+        }
+    }
+}
+
+impl PMOSFET {
+    /// Returns the identifier of the MOSFET in the format `M{name}`.
+    pub fn identifier(&self) -> String {
+        format!("M{}", self.name)
+    }
+}
+
+impl fmt::Display for PMOSFET {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MP{} {} {} {} {} {}",
+            self.name, self.drain, self.gate, self.source, self.bulk, self.model_name,
+        )?;
+        if let Some(width) = self.width {
+            write!(f, " w={width}")?;
+        }
+        if let Some(length) = self.length {
+            write!(f, " l={length}")?;
+        }
+        if let Some(multiplicity) = self.multiplicity {
+            write!(f, " m={multiplicity}")?;
+        }
+        Ok(())
+    }
+}
+
+// Nom parser for PMOSFET
+pub fn parse_pmosfet(input: &str) -> IResult<&str, PMOSFET> {
+    // Parse the initial 'MP' (case-insensitive)
+    let (input, _) = tag_no_case("MP").parse(input)?;
+
+    // Parse the numeric name part
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+
+    // Parse nodes: drain, gate, source, bulk
+    let (input, drain) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, gate) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, source) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, bulk) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+
+    // Parse the required model name
+    let (input, model_name) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+
+    // Each parameter is expected to be separated by at least one space from the previous token.
+    let (input, params) = multi::many0(preceded(space1, parse_key_value)).parse(input)?;
+
+    // consume any trailing whitespace
+    let (input, _) = space0.parse(input)?;
+
+    let mut multiplicity: Option<usize> = None;
+    let mut width: Option<f64> = None;
+    let mut length: Option<f64> = None;
+    for (k, v) in params {
+        if k.eq_ignore_ascii_case("m") {
+            multiplicity = Some(v as usize);
+        }
+
+        if k.eq_ignore_ascii_case("w") {
+            width = Some(v);
+        }
+        if k.eq_ignore_ascii_case("l") {
+            if v <= 0.0 {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    k,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+            length = Some(v);
+        }
+    }
+
+    let mosfet = PMOSFET {
+        name: name.to_string(),
+        drain: drain.to_string(),
+        gate: gate.to_string(),
+        source: source.to_string(),
+        bulk: bulk.to_string(),
+        model_name: model_name.to_string(),
+        model: PMosfetModel::default(),
+        multiplicity,
+        width,
+        length,
+    };
+
+    Ok((input, mosfet))
+}
+
+impl FromStr for PMOSFET {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        if s_without_comment.is_empty() {
+            return Err(Error::InvalidFormat(
+                "Empty line after comment removal".to_string(),
+            ));
+        }
+
+        // Expected format: M<name> <drain> <gate> <source> <bulk> <model>
+        match all_consuming(parse_pmosfet).parse(s_without_comment) {
+            Ok((_, mosfet)) => Ok(mosfet),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(Error::InvalidFormat(format!(
+                "Failed to parse MOSFET line '{}': {:?}. Expected format: M<name> D G S B <model>", // Updated error message
+                s_without_comment, e.code
+            ))),
+            Err(nom::Err::Incomplete(_)) => Err(Error::InvalidFormat(format!(
+                "Incomplete parse for MOSFET line: '{}'. Expected format: M<name> D G S B <model>", // Updated error message
+                s_without_comment
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pchannel_mosfet() {
+        // Standard SPICE format: M<name> <drain> <gate> <source> <bulk> <model>
+        let mosfet_str = "MP1 D G S B MyPmosModel % bla";
+        let mosfet = mosfet_str.parse::<PMOSFET>().unwrap();
+
+        assert_eq!(mosfet.name, "1");
+        assert_eq!(mosfet.drain, "D");
+        assert_eq!(mosfet.gate, "G");
+        assert_eq!(mosfet.source, "S");
+        assert_eq!(mosfet.bulk, "B"); // Check bulk node
+        assert_eq!(mosfet.model_name, "MyPmosModel");
+    }
+
+    #[test]
+    fn test_invalid_mosfet_format_missing_bulk() {
+        let mosfet_str = "MP1 1 2 3 MyModel"; // Missing bulk node
+        let result = mosfet_str.parse::<PMOSFET>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_mosfet_format_missing_model() {
+        let mosfet_str = "MP1 1 2 3 0"; // Missing model name
+        let result = mosfet_str.parse::<PMOSFET>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_mosfet_format_too_few_nodes() {
+        let mosfet_str = "MP1 1 2 MyModel";
+        let result = mosfet_str.parse::<PMOSFET>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_type_char() {
+        let s = "MX1 1 2 3 0 MyModel";
+        let result = s.parse::<PMOSFET>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        let s = "R1 1 2 3 0 MyModel";
+        let result = s.parse::<PMOSFET>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_mosfet_with_optional_value_removed() {
+        // This format is no longer supported by the parser
+        let mosfet_str = "MP2 7 8 9 0 P_Model 1.5";
+        let result = mosfet_str.parse::<PMOSFET>();
+        assert!(result.is_err()); // Should fail because "1.5" is an extra part
+    }
+
+    #[test]
+    fn test_drain_current_regions() {
+        let mut mosfet = "MP1 D G S B PMOD".parse::<PMOSFET>().unwrap();
+        mosfet.model.voltage_threshold = -1.0;
+        mosfet.model.channel_length_modulation = 0.0;
+        let beta = mosfet.beta();
+
+        // Cut-off: the gate is not pulled far enough below the source.
+        assert_eq!(mosfet.i_d(-0.5, -2.0), 0.0);
+
+        // Saturation: current flows from source to drain.
+        let i_sat = mosfet.i_d(-3.0, -4.0);
+        assert!((i_sat + beta / 2.0 * 4.0).abs() < 1e-15);
+        assert!(mosfet.g_m(-3.0, -4.0) > 0.0);
+
+        // Linear region.
+        let i_lin = mosfet.i_d(-3.0, -1.0);
+        assert!((i_lin + beta * (2.0 - 0.5)).abs() < 1e-15);
+        assert!(mosfet.g_ds(-3.0, -1.0) > 0.0);
+    }
+
+    #[test]
+    fn test_parse_mosfet_with_multiplicity() {
+        let mosfet_str = "MP2 7 8 9 0 P_Model         m=3    ";
+        let mosfet = mosfet_str.parse::<PMOSFET>().unwrap();
+        assert_eq!(mosfet.multiplicity, Some(3))
+    }
+}
@@ -1,4 +1,8 @@
-use crate::prelude::*;
+use crate::{
+    constants::{KB, TEMPERATURE},
+    prelude::*,
+};
+use nom::multi::many0;
 use std::fmt;
 
 #[derive(Debug, Clone)]
@@ -12,8 +16,17 @@ pub struct Resistor {
     pub plus: String,
     /// Negative node of the resistor.
     pub minus: String,
-    /// g2
+    /// If the resistor is G2, with its branch current as an unknown of the MNA system. A 0Ω
+    /// resistor is always G2, an ideal wire whose current is measured like that of a 0V source.
     pub g2: bool,
+    /// First order temperature coefficient (`tc1`), in 1/K.
+    pub tc1: f64,
+    /// Second order temperature coefficient (`tc2`), in 1/K².
+    pub tc2: f64,
+    /// Operating temperature in Kelvin.
+    pub temperature: f64,
+    /// Relative tolerance of the value (`tol`), used by Monte Carlo analyses.
+    pub tolerance: Option<f64>,
 }
 
 impl Resistor {
@@ -21,6 +34,38 @@ impl Resistor {
     pub fn identifier(&self) -> String {
         format!("R{}", self.name)
     }
+
+    /// Returns the resistance at `temperature` in Kelvin,
+    /// `R0 * (1 + tc1 * dT + tc2 * dT^2)` with `dT` relative to the nominal temperature.
+    ///
+    /// Monte Carlo analyses apply the tolerance by drawing `value` itself, so `R0` is the
+    /// value of the current run.
+    pub fn resistance_at(&self, temperature: f64) -> f64 {
+        let dt = temperature - TEMPERATURE;
+        self.value * (1.0 + self.tc1 * dt + self.tc2 * dt * dt)
+    }
+
+    /// Returns the resistance at the operating temperature, see [`Resistor::resistance_at`].
+    pub fn resistance(&self) -> f64 {
+        self.resistance_at(self.temperature)
+    }
+
+    /// Returns the conductance at `temperature` in Kelvin, the inverse of
+    /// [`Resistor::resistance_at`]. The DC, AC and transient stamps all use it at the
+    /// operating temperature.
+    pub fn conductance(&self, temperature: f64) -> f64 {
+        1.0 / self.resistance_at(temperature)
+    }
+
+    /// Returns the power spectral density of the thermal noise current of the resistor at
+    /// `temperature` (in Kelvin), `4kT/R` in A²/Hz with `R` taken at the same temperature. A 0Ω
+    /// resistor is noiseless.
+    pub fn thermal_noise_psd(&self, temperature: f64) -> f64 {
+        if self.value == 0.0 {
+            return 0.0;
+        }
+        4.0 * KB * temperature / self.resistance_at(temperature)
+    }
 }
 
 impl fmt::Display for Resistor {
@@ -29,7 +74,20 @@ impl fmt::Display for Resistor {
             f,
             "R{} {} {} {}",
             self.name, self.plus, self.minus, self.value,
-        )
+        )?;
+        if self.g2 {
+            write!(f, " G2")?;
+        }
+        if self.tc1 != 0.0 {
+            write!(f, " tc1={}", self.tc1)?;
+        }
+        if self.tc2 != 0.0 {
+            write!(f, " tc2={}", self.tc2)?;
+        }
+        if let Some(tolerance) = self.tolerance {
+            write!(f, " tol={tolerance}")?;
+        }
+        Ok(())
     }
 }
 pub fn parse_resistor(input: &str) -> IResult<&str, Resistor> {
@@ -38,15 +96,51 @@ pub fn parse_resistor(input: &str) -> IResult<&str, Resistor> {
     let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, value) = preceded(space1, value_parser).parse(input)?;
+    // The `G2` flag, `tc1=`, `tc2=` and `tol=` may follow the value in any order.
+    let (input, options) = many0(preceded(
+        space1,
+        alt((
+            map(parse_element_parameter, Some),
+            map(tag_no_case("G2"), |_| None),
+        )),
+    ))
+    .parse(input)?;
 
-    let resistor = Resistor {
+    let mut resistor = Resistor {
         name: name.to_string(),
         plus: plus.to_string(),
         minus: minus.to_string(),
         value,
         g2: false,
+        tc1: 0.0,
+        tc2: 0.0,
+        temperature: TEMPERATURE,
+        tolerance: None,
     };
 
+    for option in options {
+        let Some((key, value)) = option else {
+            resistor.g2 = true;
+            continue;
+        };
+        match key.to_lowercase().as_str() {
+            "tc" | "tc1" => resistor.tc1 = value,
+            "tc2" => resistor.tc2 = value,
+            "tol" => resistor.tolerance = Some(value),
+            _ => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    key,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+    }
+
+    // A 0Ω resistor has no conductance to stamp, so it becomes a 0V branch instead.
+    if resistor.value == 0.0 {
+        resistor.g2 = true;
+    }
+
     Ok((input, resistor))
 }
 
@@ -54,14 +148,14 @@ impl FromStr for Resistor {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split('%').next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
         let (_, resistor) = all_consuming(parse_resistor)
             .parse(s_without_comment)
             .map_err(|e| Error::InvalidFormat(e.to_string()))?;
 
-        if resistor.value <= 0.0 {
+        if resistor.value < 0.0 {
             return Err(Error::InvalidFloatValue(format!(
-                "Resistor value must be positive: '{s}'"
+                "Resistor value must not be negative: '{s}'"
             )));
         }
 
@@ -73,6 +167,22 @@ impl FromStr for Resistor {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_conductance_at_nominal_temperature() {
+        let mut resistor = "R1 a b 2k tc1=1e-3 tc2=1e-5".parse::<Resistor>().unwrap();
+        assert_eq!(resistor.conductance(TEMPERATURE), 1.0 / 2000.0);
+        assert_eq!(resistor.conductance(resistor.temperature), 1.0 / 2000.0);
+
+        // 10 K above nominal: R = 2k * (1 + 1e-2 + 1e-3).
+        resistor.temperature = TEMPERATURE + 10.0;
+        let expected = 1.0 / (2000.0 * 1.011);
+        assert!((resistor.conductance(resistor.temperature) - expected).abs() < 1e-15);
+        assert_eq!(
+            resistor.resistance(),
+            resistor.resistance_at(TEMPERATURE + 10.0)
+        );
+    }
+
     #[test]
     fn test_parse_resistor() {
         let resistor_str = "R1 1 0 1000";
@@ -91,13 +201,37 @@ mod tests {
         assert_eq!(resistor.value, 1000.0);
     }
 
+    #[test]
+    fn test_parse_resistor_with_semicolon_comment() {
+        let resistor = "R1 1 0 1k ; load".parse::<Resistor>().unwrap();
+        assert_eq!(resistor.value, 1000.0);
+
+        let resistor = "R1 1 0 1k tol=5%; load".parse::<Resistor>().unwrap();
+        assert_eq!(resistor.tolerance, Some(0.05));
+    }
+
+    #[test]
+    fn test_parse_g2_resistor() {
+        let resistor = "R1 1 0 50 G2 % note".parse::<Resistor>().unwrap();
+        assert!(resistor.g2);
+        assert_eq!(resistor.value, 50.0);
+
+        let resistor = "R1 1 0 1k g2 tc1=1m".parse::<Resistor>().unwrap();
+        assert!(resistor.g2);
+        assert_eq!(resistor.tc1, 1e-3);
+
+        assert!(!"R1 1 0 50".parse::<Resistor>().unwrap().g2);
+        assert!(Element::Resistor("R1 1 0 50 G2".parse().unwrap()).is_g2());
+    }
+
     #[test]
     fn test_parse_lowercase() {
-        let s = "r5 2 3 1.5k"; // Note: SPICE suffixes like 'k' are not yet supported
-        let _ = s.parse::<Resistor>();
-        // This should fail on '1.5k' but pass the 'r' check. Let's test for a valid value.
-        let s_valid = "r5 2 3 1500";
-        assert!(s_valid.parse::<Resistor>().is_ok());
+        let s = "r5 2 3 1.5k";
+        let resistor = s.parse::<Resistor>().unwrap();
+        assert_eq!(resistor.value, 1500.0);
+
+        let s_kohm = "r5 2 3 1kOhm";
+        assert_eq!(s_kohm.parse::<Resistor>().unwrap().value, 1000.0);
     }
 
     #[test]
@@ -121,8 +255,16 @@ mod tests {
     }
 
     #[test]
-    fn test_error_on_zero_value() {
-        let s = "R1 1 0 0";
+    fn test_zero_value_is_g2() {
+        let resistor = "R1 1 0 0".parse::<Resistor>().unwrap();
+        assert!(resistor.g2);
+        assert_eq!(resistor.thermal_noise_psd(300.0), 0.0);
+        assert_eq!(resistor.to_string(), "R1 1 0 0 G2");
+    }
+
+    #[test]
+    fn test_error_on_negative_value() {
+        let s = "R1 1 0 -1k";
         assert!(s.parse::<Resistor>().is_err());
     }
 
@@ -139,4 +281,56 @@ mod tests {
         let resistor = resistor_str.parse::<Resistor>().unwrap();
         assert_eq!(resistor.name, "in");
     }
+
+    #[test]
+    fn test_parse_temperature_coefficients() {
+        let mut resistor = "R1 1 0 1k tc1=1m TC2 = 1e-5".parse::<Resistor>().unwrap();
+        assert_eq!(resistor.tc1, 1e-3);
+        assert_eq!(resistor.tc2, 1e-5);
+        assert_eq!(resistor.resistance(), 1000.0);
+
+        resistor.temperature = TEMPERATURE + 100.0;
+        assert!((resistor.resistance() - 1000.0 * (1.0 + 0.1 + 0.1)).abs() < 1e-9);
+
+        let resistor = "R1 1 0 1k tc=2m".parse::<Resistor>().unwrap();
+        assert_eq!(resistor.tc1, 2e-3);
+    }
+
+    #[test]
+    fn test_thermal_noise_psd() {
+        let resistor = "R1 1 0 1k".parse::<Resistor>().unwrap();
+        let expected = 4.0 * 1.380649e-23 * 300.0 / 1e3;
+        assert!((resistor.thermal_noise_psd(300.0) - expected).abs() < 1e-12 * expected);
+        assert!((resistor.thermal_noise_psd(600.0) - 2.0 * expected).abs() < 1e-12 * expected);
+    }
+
+    #[test]
+    fn test_thermal_noise_psd_follows_the_tempco() {
+        let resistor = "R1 1 0 1k tc1=1m".parse::<Resistor>().unwrap();
+        for temperature in [TEMPERATURE, TEMPERATURE + 100.0] {
+            let resistance = resistor.resistance_at(temperature);
+            let expected = 4.0 * 1.380649e-23 * temperature / resistance;
+            let psd = resistor.thermal_noise_psd(temperature);
+            assert!((psd - expected).abs() < 1e-12 * expected);
+        }
+        assert!((resistor.resistance_at(TEMPERATURE + 100.0) - 1.1e3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_tolerance() {
+        let resistor = "R1 1 0 1k tol=5% % five percent"
+            .parse::<Resistor>()
+            .unwrap();
+        assert_eq!(resistor.tolerance, Some(0.05));
+        assert_eq!(resistor.to_string(), "R1 1 0 1000 tol=0.05");
+
+        let resistor = "R1 1 0 1k tol=0.01".parse::<Resistor>().unwrap();
+        assert_eq!(resistor.tolerance, Some(0.01));
+        assert_eq!("R1 1 0 1k".parse::<Resistor>().unwrap().tolerance, None);
+    }
+
+    #[test]
+    fn test_invalid_resistor_parameter() {
+        assert!("R1 1 0 1k tc3=1".parse::<Resistor>().is_err());
+    }
 }
@@ -1,13 +1,30 @@
-use crate::prelude::*;
+use crate::{
+    elements::switch::SwitchControl,
+    expression::substitute_expressions,
+    models::{Model, parse_model},
+    prelude::*,
+};
 use nom::{
-    IResult, Parser, bytes::complete::tag_no_case, character::complete::space1, multi::many0,
-    sequence::preceded,
+    IResult, Parser,
+    bytes::complete::tag_no_case,
+    character::complete::{space0, space1},
+    combinator::{not, peek},
+    multi::many0,
+    sequence::{preceded, terminated},
 };
+use std::collections::HashSet;
 #[derive(Debug, Clone)]
 pub struct SubcircuitDefinition {
     pub name: String,
     pub pins: Vec<String>,
-    pub elements: Vec<Element>,
+    /// Parameters declared in the header, e.g. `R=1k`, keyed by lowercased name.
+    pub parameters: HashMap<String, f64>,
+    /// The element lines of the subcircuit. They are parsed on every instantiation, since
+    /// their `{...}` expressions depend on the parameters of the instance.
+    pub lines: Vec<String>,
+    /// The `.model` cards declared inside the subcircuit, keyed by name. They take precedence
+    /// over the top-level models of the same name within the subcircuit.
+    pub models: HashMap<String, Model>,
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +32,8 @@ pub struct SubcircuitInstance {
     pub instance_name: String,
     pub definition_name: String,
     pub nodes: Vec<String>,
+    /// Parameter overrides of the instance, e.g. `R=2k`, keyed by lowercased name.
+    pub parameters: HashMap<String, f64>,
 }
 
 impl SubcircuitInstance {
@@ -27,12 +46,29 @@ impl SubcircuitInstance {
             instance_name: instance_name.into(),
             definition_name: definition_name.into(),
             nodes: nodes.into_iter().map(Into::into).collect(),
+            parameters: HashMap::new(),
         }
     }
 
+    /// Expands the instance into primitive elements, recursing into nested instances.
+    ///
+    /// The `{...}` expressions of the subcircuit lines are evaluated against the parameter
+    /// overrides of the instance, then the defaults of the definition and finally the global
+    /// `.param` values in `parameters`. Overriding a parameter the definition does not declare
+    /// is an error.
+    ///
+    /// The `global_nodes`, i.e. ground and the `.global` nodes, keep their names inside the
+    /// subcircuit instead of becoming internal nodes of the instance.
+    ///
+    /// Elements referring to a `.model` card of the definition get a copy of that model
+    /// prefixed with the instance name, like their own names, which is added to `models`.
+    /// Any other model name is left to resolve against the top-level models.
     pub fn instantiate(
         &self,
         definitions: &HashMap<String, SubcircuitDefinition>,
+        parameters: &HashMap<String, f64>,
+        global_nodes: &HashSet<String>,
+        models: &mut HashMap<String, Model>,
     ) -> Result<Vec<Element>> {
         let mut final_elements: Vec<Element> = Vec::new();
 
@@ -56,16 +92,46 @@ impl SubcircuitInstance {
         let port_to_node: HashMap<&String, &String> =
             definition.pins.iter().zip(self.nodes.iter()).collect();
 
+        if let Some(unknown) = self
+            .parameters
+            .keys()
+            .find(|name| !definition.parameters.contains_key(*name))
+        {
+            return Err(Error::InvalidFormat(format!(
+                "Unknown parameter '{unknown}' for instance {} of subcircuit {}",
+                self.instance_name, definition.name
+            )));
+        }
+        let mut scope = parameters.clone();
+        scope.extend(definition.parameters.clone());
+        scope.extend(self.parameters.clone());
+
         // 3. Iterate over all elements inside the definition
-        for sub_element in &definition.elements {
+        for line in &definition.lines {
+            let sub_element = definition.parse_line(line, &scope)?;
+
             // 4. Instantiate the nodes and name of this sub-element
-            let mapped_element = map_sub_element(sub_element, &port_to_node, &self.instance_name)?;
+            let mut mapped_element = map_sub_element(
+                &sub_element,
+                &port_to_node,
+                global_nodes,
+                &self.instance_name,
+            )?;
+            if let Some(model_name) = mapped_element.model_name_mut()
+                && let Some(model) = definition.models.get(model_name.as_str())
+            {
+                let mut local_model = model.clone();
+                local_model.set_name(&format!("{}_{}", self.instance_name, model_name));
+                *model_name = local_model.name().to_string();
+                models.insert(model_name.clone(), local_model);
+            }
 
             // 5. Check if the mapped element is *another* subcircuit or a primitive
             match mapped_element {
                 Element::SubcktInstance(next_instance) => {
                     // It's another subcircuit, recurse by calling the method on the nested instance
-                    let mut expanded_elements = next_instance.instantiate(definitions)?;
+                    let mut expanded_elements =
+                        next_instance.instantiate(definitions, parameters, global_nodes, models)?;
                     final_elements.append(&mut expanded_elements);
                 }
                 _ => {
@@ -84,6 +150,7 @@ impl SubcircuitInstance {
 pub fn map_sub_element(
     subckt_element: &Element,
     port_to_node: &HashMap<&String, &String>,
+    global_nodes: &HashSet<String>,
     parent_instance_name: &str,
 ) -> Result<Element> {
     // Clone the subcircuit element to modify
@@ -93,8 +160,9 @@ pub fn map_sub_element(
     for node in instantiated_element.nodes_mut() {
         if let Some(actual_node) = port_to_node.get(node) {
             *node = (*actual_node).clone();
-        } else {
-            // Internal node: prefix with parent instance name for uniqueness
+        } else if !global_nodes.contains(node) {
+            // Internal node: prefix with parent instance name for uniqueness. Ground and
+            // `.global` nodes are shared by the whole circuit and keep their names.
             *node = format!("{}_{}", parent_instance_name, node);
         }
     }
@@ -106,36 +174,123 @@ pub fn map_sub_element(
         instantiated_element.name()
     ));
 
+    // Current-controlled sources and couplings reference other elements of the same
+    // subcircuit, which have just been renamed in the same way.
+    if let Element::CCVS(h) = &mut instantiated_element {
+        h.control_source = prefix_identifier(&h.control_source, parent_instance_name);
+    }
+    if let Element::CCCS(f) = &mut instantiated_element {
+        f.control_source = prefix_identifier(&f.control_source, parent_instance_name);
+    }
+    if let Element::Switch(s) = &mut instantiated_element
+        && let SwitchControl::Current { source } = &mut s.control
+    {
+        *source = prefix_identifier(source, parent_instance_name);
+    }
+    if let Element::MutualInductance(k) = &mut instantiated_element {
+        k.inductor_a = prefix_identifier(&k.inductor_a, parent_instance_name);
+        k.inductor_b = prefix_identifier(&k.inductor_b, parent_instance_name);
+    }
+
     Ok(instantiated_element)
 }
 
+/// Inserts the instance prefix after the element type letter, e.g. `Vsense` -> `Vx1_sense`.
+fn prefix_identifier(identifier: &str, parent_instance_name: &str) -> String {
+    let (kind, name) = identifier.split_at(1.min(identifier.len()));
+    format!("{}{}_{}", kind, parent_instance_name, name)
+}
+
 impl SubcircuitInstance {
     pub fn identifier(&self) -> String {
         format!("X{}", self.instance_name)
     }
 }
 
+impl std::fmt::Display for SubcircuitInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "X{}", self.instance_name)?;
+        for node in &self.nodes {
+            write!(f, " {node}")?;
+        }
+        write!(f, " {}", self.definition_name)?;
+        let mut parameters: Vec<_> = self.parameters.iter().collect();
+        parameters.sort_by_key(|&(name, _)| name);
+        for (name, value) in parameters {
+            write!(f, " {name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
 impl SubcircuitDefinition {
     pub fn new(name: impl Into<String>, pins: Vec<&str>) -> Self {
         Self {
             name: name.into(),
             pins: pins.into_iter().map(Into::into).collect(),
-            elements: Vec::new(),
+            parameters: HashMap::new(),
+            lines: Vec::new(),
+            models: HashMap::new(),
         }
     }
+
+    /// Parses one of the element lines of the subcircuit, evaluating its `{...}` expressions
+    /// against `parameters`.
+    pub(crate) fn parse_line(
+        &self,
+        line: &str,
+        parameters: &HashMap<String, f64>,
+    ) -> Result<Element> {
+        let line = substitute_expressions(line, parameters)?;
+        parse_element(&line).map_err(|e| {
+            Error::InvalidFormat(format!(
+                "Failed to parse subcircuit element in '{}': {}",
+                self.name, e
+            ))
+        })
+    }
 }
 
+/// Parses a node name that is not followed by `=`, i.e. that does not start a parameter.
+fn node_name(input: &str) -> IResult<&str, &str> {
+    terminated(
+        alphanumeric_or_underscore1,
+        not(peek(preceded(space0, tag("=")))),
+    )
+    .parse(input)
+}
+
+/// Parses trailing `name=value` parameters into a map keyed by lowercased name.
+fn parameter_list(input: &str) -> IResult<&str, HashMap<String, f64>> {
+    map(many0(preceded(space1, parse_key_value)), |parameters| {
+        parameters
+            .into_iter()
+            .map(|(name, value)| (name.to_lowercase(), value))
+            .collect()
+    })
+    .parse(input)
+}
+
+/// Parses a subcircuit header such as `.subckt rc in out R=1k C=1u`, where the trailing
+/// `name=value` pairs declare the parameters of the subcircuit and their defaults.
 pub fn parse_subckt_header(input: &str) -> IResult<&str, SubcircuitDefinition> {
     let (input, _) = tag_no_case(".subckt").parse(input)?;
     let (input, name) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
-    let (input, pins) = many0(preceded(space1, alphanumeric_or_underscore1)).parse(input)?;
-    Ok((input, SubcircuitDefinition::new(name, pins)))
+    let (input, pins) = many0(preceded(space1, node_name)).parse(input)?;
+    let (input, parameters) = parameter_list(input)?;
+
+    let mut definition = SubcircuitDefinition::new(name, pins);
+    definition.parameters = parameters;
+    Ok((input, definition))
 }
 
+/// Parses a subcircuit instance such as `X1 a b rc R=2k`, where the trailing `name=value`
+/// pairs override the parameters of the subcircuit.
 pub fn parse_subckt_instance(input: &str) -> IResult<&str, SubcircuitInstance> {
     let (input, _) = tag_no_case("x").parse(input)?;
     let (input, instance_name) = alphanumeric_or_underscore1(input)?;
-    let (input, nodes) = many0(preceded(space1, alphanumeric_or_underscore1)).parse(input)?;
+    let (input, nodes) = many0(preceded(space1, node_name)).parse(input)?;
+    let (input, parameters) = parameter_list(input)?;
 
     if nodes.is_empty() {
         return Err(nom::Err::Error(nom::error::Error::new(
@@ -146,17 +301,25 @@ pub fn parse_subckt_instance(input: &str) -> IResult<&str, SubcircuitInstance> {
 
     let definition_name = nodes.last().unwrap();
     let nodes = &nodes[..nodes.len() - 1];
-    Ok((
-        input,
-        SubcircuitInstance::new(
-            instance_name.to_string(),
-            definition_name.to_string(),
-            nodes.to_vec(),
-        ),
-    ))
+    let mut instance = SubcircuitInstance::new(
+        instance_name.to_string(),
+        definition_name.to_string(),
+        nodes.to_vec(),
+    );
+    instance.parameters = parameters;
+    Ok((input, instance))
 }
 
-pub fn parse_subcircuits(input: &str) -> Result<HashMap<String, SubcircuitDefinition>> {
+/// Collects the `.subckt` definitions of the netlist.
+///
+/// Every element line is parsed once with the default parameters of its subcircuit and the
+/// global `.param` values in `parameters`, so syntax errors are reported even for unused
+/// subcircuits. The `.model` cards inside a subcircuit are evaluated with the same
+/// parameters and kept with its definition.
+pub fn parse_subcircuits(
+    input: &str,
+    parameters: &HashMap<String, f64>,
+) -> Result<HashMap<String, SubcircuitDefinition>> {
     let mut subcircuit_definitions: HashMap<String, SubcircuitDefinition> = HashMap::new();
     let mut inside_subckt_block = false;
     let mut current_subckt_name = String::new();
@@ -188,17 +351,17 @@ pub fn parse_subcircuits(input: &str) -> Result<HashMap<String, SubcircuitDefini
         }
 
         if inside_subckt_block {
-            // We now use parse_element, which can handle primitives (r) AND
-            // nested subcircuit instances (x)
-            let subckt_element = parse_element(line).map_err(|e| {
-                Error::InvalidFormat(format!(
-                    "Failed to parse subcircuit element in '{}': {}",
-                    current_subckt_name, e
-                ))
-            })?;
-
+            // Lines can be primitives (r) AND nested subcircuit instances (x)
             if let Some(subckt_def) = subcircuit_definitions.get_mut(&current_subckt_name) {
-                subckt_def.elements.push(subckt_element);
+                let mut scope = parameters.clone();
+                scope.extend(subckt_def.parameters.clone());
+                if line.to_lowercase().starts_with(".model") {
+                    let model = parse_model(&substitute_expressions(line, &scope)?)?;
+                    subckt_def.models.insert(model.name().to_string(), model);
+                    continue;
+                }
+                subckt_def.parse_line(line, &scope)?;
+                subckt_def.lines.push(line.to_string());
             }
             continue;
         }
@@ -217,5 +380,23 @@ mod tests {
         let (_, subckt) = parse_subckt_header(subckt_str).unwrap();
         assert_eq!(subckt.name, "my_subckt");
         assert_eq!(subckt.pins, vec!["in", "out", "vdd", "gnd"]);
+        assert!(subckt.parameters.is_empty());
+    }
+
+    #[test]
+    fn test_parse_header_with_parameters() {
+        let (_, subckt) = parse_subckt_header(".subckt rc in out R=1k C = 1u").unwrap();
+        assert_eq!(subckt.pins, vec!["in", "out"]);
+        assert_eq!(subckt.parameters["r"], 1e3);
+        assert_eq!(subckt.parameters["c"], 1e-6);
+    }
+
+    #[test]
+    fn test_parse_instance_with_parameters() {
+        let (_, instance) = parse_subckt_instance("X1 a b rc R=2k").unwrap();
+        assert_eq!(instance.nodes, vec!["a", "b"]);
+        assert_eq!(instance.definition_name, "rc");
+        assert_eq!(instance.parameters["r"], 2e3);
+        assert_eq!(instance.to_string(), "X1 a b rc r=2000");
     }
 }
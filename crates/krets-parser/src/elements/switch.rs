@@ -0,0 +1,229 @@
+use crate::{models::switch::SwitchModel, prelude::*};
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The quantity that opens and closes a switch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwitchControl {
+    /// The voltage between two controlling nodes (an `S` element).
+    Voltage { plus: String, minus: String },
+    /// The branch current of another element, e.g. `V1` (a `W` element).
+    Current { source: String },
+}
+
+#[derive(Debug)]
+/// Represents a voltage- or current-controlled switch in a circuit.
+/// SPICE format: S<name> <n+> <n-> <nc+> <nc-> <model>
+///           or: W<name> <n+> <n-> <vname> <model>
+///
+/// The switch is a resistor of either `RON` or `ROFF`. It closes once the controlling
+/// quantity rises above `VT + VH` and opens once it falls below `VT - VH`; in between it
+/// keeps its previous state. That state is carried across Newton-Raphson iterations, sweep
+/// points and time steps.
+pub struct Switch {
+    /// Name of the switch.
+    pub name: String,
+    /// Positive node of the switch.
+    pub plus: String,
+    /// Negative node of the switch.
+    pub minus: String,
+    /// The controlling voltage or current.
+    pub control: SwitchControl,
+    /// The name of the switch model to use.
+    pub model_name: String,
+    /// Model parameters for the switch.
+    pub model: SwitchModel,
+    /// Whether the switch is currently closed. It is atomic so that circuits can be shared
+    /// between threads.
+    pub closed: AtomicBool,
+}
+
+impl Clone for Switch {
+    fn clone(&self) -> Self {
+        Switch {
+            name: self.name.clone(),
+            plus: self.plus.clone(),
+            minus: self.minus.clone(),
+            control: self.control.clone(),
+            model_name: self.model_name.clone(),
+            model: self.model.clone(),
+            closed: AtomicBool::new(self.is_closed()),
+        }
+    }
+}
+
+impl Switch {
+    /// Returns the identifier of the switch in the format `S{name}` or `W{name}`.
+    pub fn identifier(&self) -> String {
+        match self.control {
+            SwitchControl::Voltage { .. } => format!("S{}", self.name),
+            SwitchControl::Current { .. } => format!("W{}", self.name),
+        }
+    }
+
+    /// Returns the controlling voltage or current in `solution_map`.
+    pub fn control_value(&self, solution_map: &HashMap<String, f64>) -> f64 {
+        let value = |key: String| solution_map.get(&key).copied().unwrap_or(0.0);
+        match &self.control {
+            SwitchControl::Voltage { plus, minus } => {
+                value(format!("V({plus})")) - value(format!("V({minus})"))
+            }
+            SwitchControl::Current { source } => value(format!("I({source})")),
+        }
+    }
+
+    /// Returns whether the switch is closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Updates the state of the switch from the controlling quantity in `solution_map`,
+    /// returning whether the switch is closed.
+    pub fn update_state(&self, solution_map: &HashMap<String, f64>) -> bool {
+        let control = self.control_value(solution_map);
+        if control > self.model.threshold + self.model.hysteresis {
+            self.closed.store(true, Ordering::Relaxed);
+        } else if control < self.model.threshold - self.model.hysteresis {
+            self.closed.store(false, Ordering::Relaxed);
+        }
+        self.is_closed()
+    }
+
+    /// Returns the conductance of the switch in its current state.
+    pub fn conductance(&self) -> f64 {
+        if self.is_closed() {
+            1.0 / self.model.on_resistance
+        } else {
+            1.0 / self.model.off_resistance
+        }
+    }
+}
+
+impl fmt::Display for Switch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.control {
+            SwitchControl::Voltage { plus, minus } => write!(
+                f,
+                "S{} {} {} {} {} {}",
+                self.name, self.plus, self.minus, plus, minus, self.model_name,
+            ),
+            SwitchControl::Current { source } => write!(
+                f,
+                "W{} {} {} {} {}",
+                self.name, self.plus, self.minus, source, self.model_name,
+            ),
+        }
+    }
+}
+
+pub fn parse_switch(input: &str) -> IResult<&str, Switch> {
+    let (input, kind) = alt((tag_no_case("S"), tag_no_case("W"))).parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+
+    let (input, control) = if kind.eq_ignore_ascii_case("S") {
+        let (input, control_plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+        let (input, control_minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+        let control = SwitchControl::Voltage {
+            plus: control_plus.to_string(),
+            minus: control_minus.to_string(),
+        };
+        (input, control)
+    } else {
+        let (input, source) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+        let control = SwitchControl::Current {
+            source: normalize_identifier(source),
+        };
+        (input, control)
+    };
+
+    let (input, model_name) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+
+    let switch = Switch {
+        name: name.to_string(),
+        plus: plus.to_string(),
+        minus: minus.to_string(),
+        control,
+        model_name: model_name.to_string(),
+        model: SwitchModel::default(),
+        closed: AtomicBool::new(false),
+    };
+
+    Ok((input, switch))
+}
+
+impl FromStr for Switch {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, switch) = all_consuming(parse_switch)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        Ok(switch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_voltage_controlled_switch() {
+        let switch = "S1 vdd out ctrl 0 SMOD".parse::<Switch>().unwrap();
+
+        assert_eq!(switch.name, "1");
+        assert_eq!(switch.plus, "vdd");
+        assert_eq!(switch.minus, "out");
+        assert_eq!(
+            switch.control,
+            SwitchControl::Voltage {
+                plus: "ctrl".to_string(),
+                minus: "0".to_string()
+            }
+        );
+        assert_eq!(switch.model_name, "SMOD");
+        assert_eq!(switch.identifier(), "S1");
+        assert!(!switch.is_closed());
+    }
+
+    #[test]
+    fn test_parse_current_controlled_switch() {
+        let switch = "w2 a b vsense WMOD % comment".parse::<Switch>().unwrap();
+
+        assert_eq!(switch.name, "2");
+        assert_eq!(
+            switch.control,
+            SwitchControl::Current {
+                source: "Vsense".to_string()
+            }
+        );
+        assert_eq!(switch.identifier(), "W2");
+    }
+
+    #[test]
+    fn test_invalid_switch_missing_model() {
+        assert!("S1 vdd out ctrl 0".parse::<Switch>().is_err());
+        assert!("W1 vdd out".parse::<Switch>().is_err());
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut switch = "S1 a b c 0 SMOD".parse::<Switch>().unwrap();
+        switch.model.threshold = 2.5;
+        switch.model.hysteresis = 0.5;
+        let control = |v: f64| HashMap::from([("V(c)".to_string(), v)]);
+
+        // Rising: the switch only closes above VT + VH.
+        assert!(!switch.update_state(&control(2.9)));
+        assert!(switch.update_state(&control(3.1)));
+        // Falling: the switch stays closed until below VT - VH.
+        assert!(switch.update_state(&control(2.1)));
+        assert!(!switch.update_state(&control(1.9)));
+        assert_eq!(switch.conductance(), 1.0 / switch.model.off_resistance);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::prelude::*;
+use nom::multi::many1;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents a lossless transmission line between two ports.
+/// SPICE format: T<name> <p1+> <p1-> <p2+> <p2-> Z0=<impedance> TD=<delay>
+pub struct TransmissionLine {
+    /// Name of the transmission line.
+    pub name: String,
+    /// Positive node of port 1.
+    pub plus1: String,
+    /// Negative (reference) node of port 1.
+    pub minus1: String,
+    /// Positive node of port 2.
+    pub plus2: String,
+    /// Negative (reference) node of port 2.
+    pub minus2: String,
+    /// Characteristic impedance in Ohms.
+    pub z0: f64,
+    /// Propagation delay from one port to the other in seconds.
+    pub td: f64,
+}
+
+impl TransmissionLine {
+    /// Returns the identifier of the transmission line in the format `T{name}`.
+    pub fn identifier(&self) -> String {
+        format!("T{}", self.name)
+    }
+}
+
+impl fmt::Display for TransmissionLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "T{} {} {} {} {} Z0={} TD={}",
+            self.name, self.plus1, self.minus1, self.plus2, self.minus2, self.z0, self.td,
+        )
+    }
+}
+
+pub fn parse_transmission_line(input: &str) -> IResult<&str, TransmissionLine> {
+    let (input, _) = tag_no_case("T").parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, plus1) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus1) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, plus2) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus2) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, options) = many1(preceded(space1, parse_key_value)).parse(input)?;
+
+    let mut z0 = None;
+    let mut td = None;
+    for (key, value) in options {
+        match key.to_lowercase().as_str() {
+            "z0" => z0 = Some(value),
+            "td" => td = Some(value),
+            _ => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    key,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+    }
+
+    // Both the impedance and the delay are required and must be positive.
+    let (Some(z0), Some(td)) = (z0, td) else {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    };
+    if z0 <= 0.0 || td <= 0.0 {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+
+    let transmission_line = TransmissionLine {
+        name: name.to_string(),
+        plus1: plus1.to_string(),
+        minus1: minus1.to_string(),
+        plus2: plus2.to_string(),
+        minus2: minus2.to_string(),
+        z0,
+        td,
+    };
+
+    Ok((input, transmission_line))
+}
+
+impl FromStr for TransmissionLine {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, transmission_line) = all_consuming(parse_transmission_line)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        Ok(transmission_line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transmission_line() {
+        let line = "T1 in 0 out 0 Z0=50 TD=10n"
+            .parse::<TransmissionLine>()
+            .unwrap();
+
+        assert_eq!(line.name, "1");
+        assert_eq!(line.plus1, "in");
+        assert_eq!(line.minus1, "0");
+        assert_eq!(line.plus2, "out");
+        assert_eq!(line.minus2, "0");
+        assert_eq!(line.z0, 50.0);
+        assert_eq!(line.td, 10e-9);
+        assert_eq!(line.identifier(), "T1");
+        assert_eq!(line.to_string(), "T1 in 0 out 0 Z0=50 TD=0.00000001");
+    }
+
+    #[test]
+    fn test_parse_lowercase_with_comment() {
+        let line = "tcable a b c d td=1u z0=75 ; coax"
+            .parse::<TransmissionLine>()
+            .unwrap();
+        assert_eq!(line.name, "cable");
+        assert_eq!(line.z0, 75.0);
+        assert_eq!(line.td, 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_transmission_line() {
+        assert!("T1 in 0 out 0 Z0=50".parse::<TransmissionLine>().is_err());
+        assert!(
+            "T1 in 0 out 0 Z0=50 TD=0"
+                .parse::<TransmissionLine>()
+                .is_err()
+        );
+        assert!(
+            "T1 in 0 out 0 Z0=50 TD=1n LEN=2"
+                .parse::<TransmissionLine>()
+                .is_err()
+        );
+        assert!(
+            "T1 in 0 out Z0=50 TD=1n"
+                .parse::<TransmissionLine>()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents a voltage-controlled current source (VCCS) in a circuit.
+/// SPICE format: G<name> <n+> <n-> <nc+> <nc-> <transconductance>
+pub struct VCCS {
+    /// Name of the VCCS.
+    pub name: String,
+    /// Positive output node.
+    pub plus: String,
+    /// Negative output node.
+    pub minus: String,
+    /// Positive controlling node.
+    pub control_plus: String,
+    /// Negative controlling node.
+    pub control_minus: String,
+    /// Transconductance (A/V).
+    pub transconductance: f64,
+}
+
+impl VCCS {
+    /// Returns the identifier of the VCCS in the format `G{name}`.
+    pub fn identifier(&self) -> String {
+        format!("G{}", self.name)
+    }
+}
+
+impl fmt::Display for VCCS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "G{} {} {} {} {} {}",
+            self.name,
+            self.plus,
+            self.minus,
+            self.control_plus,
+            self.control_minus,
+            self.transconductance,
+        )
+    }
+}
+
+pub fn parse_vccs(input: &str) -> IResult<&str, VCCS> {
+    let (input, _) = tag_no_case("G").parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, control_plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, control_minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, transconductance) = preceded(space1, value_parser).parse(input)?;
+
+    let vccs = VCCS {
+        name: name.to_string(),
+        plus: plus.to_string(),
+        minus: minus.to_string(),
+        control_plus: control_plus.to_string(),
+        control_minus: control_minus.to_string(),
+        transconductance,
+    };
+
+    Ok((input, vccs))
+}
+
+impl FromStr for VCCS {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, vccs) = all_consuming(parse_vccs)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        Ok(vccs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vccs() {
+        let vccs = "G1 out 0 in 0 1m".parse::<VCCS>().unwrap();
+
+        assert_eq!(vccs.name, "1");
+        assert_eq!(vccs.plus, "out");
+        assert_eq!(vccs.minus, "0");
+        assert_eq!(vccs.control_plus, "in");
+        assert_eq!(vccs.control_minus, "0");
+        assert_eq!(vccs.transconductance, 1e-3);
+        assert_eq!(vccs.identifier(), "G1");
+    }
+
+    #[test]
+    fn test_parse_lowercase_with_comment() {
+        let vccs = "gamp 3 4 1 2 2.5k % gm".parse::<VCCS>().unwrap();
+        assert_eq!(vccs.name, "amp");
+        assert_eq!(vccs.transconductance, 2500.0);
+    }
+
+    #[test]
+    fn test_invalid_vccs_missing_gain() {
+        assert!("G1 out 0 in 0".parse::<VCCS>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!("E1 out 0 in 0 100".parse::<VCCS>().is_err());
+    }
+}
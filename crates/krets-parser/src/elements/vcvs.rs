@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+/// Represents a voltage-controlled voltage source (VCVS) in a circuit.
+/// SPICE format: E<name> <n+> <n-> <nc+> <nc-> <gain>
+pub struct VCVS {
+    /// Name of the VCVS.
+    pub name: String,
+    /// Positive output node.
+    pub plus: String,
+    /// Negative output node.
+    pub minus: String,
+    /// Positive controlling node.
+    pub control_plus: String,
+    /// Negative controlling node.
+    pub control_minus: String,
+    /// Voltage gain (V/V).
+    pub gain: f64,
+}
+
+impl VCVS {
+    /// Returns the identifier of the VCVS in the format `E{name}`.
+    pub fn identifier(&self) -> String {
+        format!("E{}", self.name)
+    }
+}
+
+impl fmt::Display for VCVS {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "E{} {} {} {} {} {}",
+            self.name, self.plus, self.minus, self.control_plus, self.control_minus, self.gain,
+        )
+    }
+}
+
+pub fn parse_vcvs(input: &str) -> IResult<&str, VCVS> {
+    let (input, _) = tag_no_case("E").parse(input)?;
+    let (input, name) = alphanumeric_or_underscore1(input)?;
+    let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, control_plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, control_minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, gain) = preceded(space1, value_parser).parse(input)?;
+
+    let vcvs = VCVS {
+        name: name.to_string(),
+        plus: plus.to_string(),
+        minus: minus.to_string(),
+        control_plus: control_plus.to_string(),
+        control_minus: control_minus.to_string(),
+        gain,
+    };
+
+    Ok((input, vcvs))
+}
+
+impl FromStr for VCVS {
+    type Err = crate::prelude::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s_without_comment = strip_comment(s);
+        let (_, vcvs) = all_consuming(parse_vcvs)
+            .parse(s_without_comment)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        Ok(vcvs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vcvs() {
+        let vcvs = "E1 out 0 in 0 100".parse::<VCVS>().unwrap();
+
+        assert_eq!(vcvs.name, "1");
+        assert_eq!(vcvs.plus, "out");
+        assert_eq!(vcvs.minus, "0");
+        assert_eq!(vcvs.control_plus, "in");
+        assert_eq!(vcvs.control_minus, "0");
+        assert_eq!(vcvs.gain, 100.0);
+        assert_eq!(vcvs.identifier(), "E1");
+    }
+
+    #[test]
+    fn test_parse_lowercase_with_comment() {
+        let vcvs = "eamp 3 4 1 2 2.5k % gain".parse::<VCVS>().unwrap();
+        assert_eq!(vcvs.name, "amp");
+        assert_eq!(vcvs.gain, 2500.0);
+    }
+
+    #[test]
+    fn test_invalid_vcvs_missing_gain() {
+        assert!("E1 out 0 in 0".parse::<VCVS>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        assert!("G1 out 0 in 0 100".parse::<VCVS>().is_err());
+    }
+}
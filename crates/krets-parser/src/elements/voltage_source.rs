@@ -4,9 +4,8 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case},
     character::complete::{space0, space1},
-    combinator::{all_consuming, map, opt},
-    multi::many0,
-    number::complete::double,
+    combinator::{all_consuming, map, map_res, opt},
+    multi::{many0, separated_list1},
     sequence::{delimited, preceded},
 };
 use std::fmt;
@@ -47,6 +46,85 @@ pub struct Sinusoidal {
     pub phase: f64,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+/// Defines the parameters for an exponential (EXP) voltage source.
+pub struct Exponential {
+    /// Initial value.
+    pub initial_value: f64,
+    /// Value approached after the rise.
+    pub pulsed_value: f64,
+    /// Time at which the rise begins.
+    pub rise_delay: f64,
+    /// Time constant of the rise.
+    pub rise_tau: f64,
+    /// Time at which the fall begins.
+    pub fall_delay: f64,
+    /// Time constant of the fall.
+    pub fall_tau: f64,
+}
+
+impl Exponential {
+    /// Calculates the value of the exponential waveform at a given time.
+    pub fn value_at(&self, time: f64) -> f64 {
+        if time < self.rise_delay {
+            return self.initial_value;
+        }
+
+        let step = self.pulsed_value - self.initial_value;
+        let mut value =
+            self.initial_value + step * (1.0 - (-(time - self.rise_delay) / self.rise_tau).exp());
+
+        if time >= self.fall_delay {
+            value -= step * (1.0 - (-(time - self.fall_delay) / self.fall_tau).exp());
+        }
+
+        value
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Defines the breakpoints of a piecewise-linear (PWL) source.
+pub struct Pwl {
+    /// `(time, value)` breakpoints with strictly increasing times.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Pwl {
+    /// Calculates the value of the waveform at a given time.
+    ///
+    /// The value is linearly interpolated between breakpoints, holds the first value
+    /// before the first breakpoint and the last value after the final one.
+    pub fn value_at(&self, time: f64) -> f64 {
+        let Some(&(first_time, first_value)) = self.points.first() else {
+            return 0.0;
+        };
+        if time <= first_time {
+            return first_value;
+        }
+
+        for window in self.points.windows(2) {
+            let (t0, v0) = window[0];
+            let (t1, v1) = window[1];
+            if time <= t1 {
+                return v0 + (v1 - v0) * (time - t0) / (t1 - t0);
+            }
+        }
+
+        self.points.last().map_or(first_value, |&(_, value)| value)
+    }
+}
+
+impl fmt::Display for Pwl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self
+            .points
+            .iter()
+            .map(|(time, value)| format!("{time} {value}"))
+            .collect();
+        write!(f, "PWL({})", values.join(" "))
+    }
+}
+
 impl Sinusoidal {
     /// Calculates the value of the sinusoidal at a given time.
     pub fn value_at(&self, time: f64) -> f64 {
@@ -97,19 +175,38 @@ impl Pulse {
 #[derive(Debug, PartialEq)]
 enum Param {
     Dc(f64),
-    Ac(f64),
+    Ac(f64, f64),
     Pulse(Pulse),
     Sinusoidal(Sinusoidal),
+    Exponential(Exponential),
+    Pwl(Pwl),
 }
 
 /// Parses a DC parameter block, e.g., "dc 5.0"
 fn parse_dc_param(input: &str) -> IResult<&str, Param> {
-    map(preceded((tag_no_case("dc"), space1), double), Param::Dc).parse(input)
+    map(
+        preceded((tag_no_case("dc"), space1), value_parser),
+        Param::Dc,
+    )
+    .parse(input)
+}
+
+/// Parses an AC specification, e.g. "ac 10 90", into its magnitude and phase in degrees.
+/// The phase defaults to zero.
+pub(crate) fn parse_ac(input: &str) -> IResult<&str, (f64, f64)> {
+    map(
+        preceded(
+            (tag_no_case("ac"), space1),
+            (value_parser, opt(preceded(space1, value_parser))),
+        ),
+        |(magnitude, phase)| (magnitude, phase.unwrap_or(0.0)),
+    )
+    .parse(input)
 }
 
 /// Parses an AC parameter block, e.g., "ac 10 90"
 fn parse_ac_param(input: &str) -> IResult<&str, Param> {
-    map(preceded((tag_no_case("ac"), space1), double), Param::Ac).parse(input)
+    map(parse_ac, |(magnitude, phase)| Param::Ac(magnitude, phase)).parse(input)
 }
 
 fn parse_pulse_param(input: &str) -> IResult<&str, Param> {
@@ -184,6 +281,75 @@ fn parse_sinusoidal_param(input: &str) -> IResult<&str, Param> {
     Ok((input, Param::Sinusoidal(sinusoidal)))
 }
 
+fn parse_exponential_param(input: &str) -> IResult<&str, Param> {
+    let values_parser = (
+        preceded(space0, value_parser),
+        preceded(space1, value_parser),
+        preceded(space1, value_parser),
+        preceded(space1, value_parser),
+        preceded(space1, value_parser),
+        preceded(space1, value_parser),
+    );
+
+    let (input, (initial_value, pulsed_value, rise_delay, rise_tau, fall_delay, fall_tau)) =
+        preceded(
+            tag_no_case("exp"),
+            delimited(
+                preceded(space0, tag("(")),
+                values_parser,
+                preceded(space0, tag(")")),
+            ),
+        )
+        .parse(input)?;
+
+    let exponential = Exponential {
+        initial_value,
+        pulsed_value,
+        rise_delay,
+        rise_tau,
+        fall_delay,
+        fall_tau,
+    };
+
+    Ok((input, Param::Exponential(exponential)))
+}
+
+/// Parses a PWL block, e.g., "PWL(0 0 1m 5 2m 0)".
+///
+/// The values come in `time value` pairs, and the times must be strictly increasing.
+pub(crate) fn parse_pwl(input: &str) -> IResult<&str, Pwl> {
+    let values_parser = preceded(space0, separated_list1(space1, value_parser));
+
+    map_res(
+        preceded(
+            tag_no_case("pwl"),
+            delimited(
+                preceded(space0, tag("(")),
+                values_parser,
+                preceded(space0, tag(")")),
+            ),
+        ),
+        |values: Vec<f64>| {
+            if !values.len().is_multiple_of(2) {
+                return Err("PWL values must come in time/value pairs");
+            }
+            let points: Vec<(f64, f64)> = values
+                .chunks_exact(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect();
+            if points.windows(2).any(|window| window[1].0 <= window[0].0) {
+                return Err("PWL times must be strictly increasing");
+            }
+            Ok(Pwl { points })
+        },
+    )
+    .parse(input)
+}
+
+fn parse_pwl_param(input: &str) -> IResult<&str, Param> {
+    map(parse_pwl, Param::Pwl).parse(input)
+}
+
 /// Main nom parser for the VoltageSource
 pub fn parse_voltage_source(input: &str) -> IResult<&str, VoltageSource> {
     let (input, _) = tag_no_case("V").parse(input)?;
@@ -191,7 +357,7 @@ pub fn parse_voltage_source(input: &str) -> IResult<&str, VoltageSource> {
     let (input, plus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
     let (input, minus) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
 
-    let (input, implicit_dc) = opt(preceded(space1, double)).parse(input)?;
+    let (input, implicit_dc) = opt(preceded(space1, value_parser)).parse(input)?;
 
     let parse_any_param = preceded(
         space1,
@@ -200,6 +366,8 @@ pub fn parse_voltage_source(input: &str) -> IResult<&str, VoltageSource> {
             parse_ac_param,
             parse_pulse_param,
             parse_sinusoidal_param,
+            parse_exponential_param,
+            parse_pwl_param,
         )),
     );
 
@@ -209,15 +377,20 @@ pub fn parse_voltage_source(input: &str) -> IResult<&str, VoltageSource> {
     // 4. Process the collected parameters to build the struct
     let mut dc_value = implicit_dc.unwrap_or(0.0);
     let mut ac_amplitude = 0.0;
+    let mut ac_phase = 0.0;
     let mut pulse: Option<Pulse> = None;
     let mut sinusoidal: Option<Sinusoidal> = None;
+    let mut exponential: Option<Exponential> = None;
+    let mut pwl: Option<Pwl> = None;
 
     for param in params {
         match param {
             Param::Dc(val) => dc_value = val,
-            Param::Ac(val) => ac_amplitude = val,
+            Param::Ac(magnitude, phase) => (ac_amplitude, ac_phase) = (magnitude, phase),
             Param::Pulse(val) => pulse = Some(val),
             Param::Sinusoidal(val) => sinusoidal = Some(val),
+            Param::Exponential(val) => exponential = Some(val),
+            Param::Pwl(val) => pwl = Some(val),
         }
     }
 
@@ -227,8 +400,11 @@ pub fn parse_voltage_source(input: &str) -> IResult<&str, VoltageSource> {
         minus: minus.to_string(),
         dc_value,
         ac_amplitude,
+        ac_phase,
         pulse,
         sinusoidal,
+        exponential,
+        pwl,
     };
 
     Ok((input, voltage_source))
@@ -246,6 +422,10 @@ impl VoltageSource {
             pulse.value_at(time)
         } else if let Some(sinusoidal) = &self.sinusoidal {
             sinusoidal.value_at(time)
+        } else if let Some(exponential) = &self.exponential {
+            exponential.value_at(time)
+        } else if let Some(pwl) = &self.pwl {
+            pwl.value_at(time)
         } else {
             self.dc_value
         }
@@ -260,8 +440,12 @@ pub struct VoltageSource {
     pub minus: String,
     pub dc_value: f64,
     pub ac_amplitude: f64,
+    /// Phase of the AC excitation in degrees.
+    pub ac_phase: f64,
     pub pulse: Option<Pulse>,
     pub sinusoidal: Option<Sinusoidal>,
+    pub exponential: Option<Exponential>,
+    pub pwl: Option<Pwl>,
 }
 
 impl VoltageSource {
@@ -276,7 +460,44 @@ impl fmt::Display for VoltageSource {
             f,
             "V{} {} {} {}",
             self.name, self.plus, self.minus, self.dc_value,
-        )
+        )?;
+        if self.ac_amplitude != 0.0 {
+            write!(f, " AC {}", self.ac_amplitude)?;
+            if self.ac_phase != 0.0 {
+                write!(f, " {}", self.ac_phase)?;
+            }
+        }
+        if let Some(p) = &self.pulse {
+            write!(
+                f,
+                " PULSE({} {} {} {} {} {} {})",
+                p.initial_value,
+                p.pulsed_value,
+                p.delay_time,
+                p.rise_time,
+                p.fall_time,
+                p.pulse_width,
+                p.period,
+            )?;
+        }
+        if let Some(s) = &self.sinusoidal {
+            write!(
+                f,
+                " SIN({} {} {} {} {} {})",
+                s.offset, s.amplitude, s.frequency, s.delay, s.damping, s.phase,
+            )?;
+        }
+        if let Some(e) = &self.exponential {
+            write!(
+                f,
+                " EXP({} {} {} {} {} {})",
+                e.initial_value, e.pulsed_value, e.rise_delay, e.rise_tau, e.fall_delay, e.fall_tau,
+            )?;
+        }
+        if let Some(pwl) = &self.pwl {
+            write!(f, " {pwl}")?;
+        }
+        Ok(())
     }
 }
 
@@ -284,7 +505,7 @@ impl FromStr for VoltageSource {
     type Err = crate::prelude::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let s_without_comment = s.split('%').next().unwrap_or("").trim();
+        let s_without_comment = strip_comment(s);
 
         let (_, voltage_source) = all_consuming(parse_voltage_source)
             .parse(s_without_comment)
@@ -326,6 +547,12 @@ mod tests {
         assert_eq!(vs.name, "2");
         assert_eq!(vs.dc_value, 0.0);
         assert_eq!(vs.ac_amplitude, 1.5);
+        assert_eq!(vs.ac_phase, 0.0);
+
+        let vs = "V3 3 0 AC 2 -45".parse::<VoltageSource>().unwrap();
+        assert_eq!(vs.ac_amplitude, 2.0);
+        assert_eq!(vs.ac_phase, -45.0);
+        assert_eq!(vs.to_string(), "V3 3 0 0 AC 2 -45");
     }
 
     #[test]
@@ -343,6 +570,18 @@ mod tests {
         assert_eq!(vs.dc_value, 5.0);
     }
 
+    #[test]
+    fn test_parse_with_suffixes() {
+        let s = "V1 in 0 DC 500m AC 10u";
+        let vs = s.parse::<VoltageSource>().unwrap();
+        assert!((vs.dc_value - 0.5).abs() < 1e-12);
+        assert!((vs.ac_amplitude - 10e-6).abs() < 1e-12);
+
+        let s = "V2 in 0 1.5kV";
+        let vs = s.parse::<VoltageSource>().unwrap();
+        assert!((vs.dc_value - 1500.0).abs() < 1e-12);
+    }
+
     #[test]
     fn test_invalid_format_too_many_parts() {
         assert!("V1 1 0 5 6".parse::<VoltageSource>().is_err());
@@ -494,4 +733,97 @@ mod tests {
             "Failed at delay with phase shift"
         );
     }
+
+    #[test]
+    fn test_parse_exponential() {
+        let s = "V1 in 0 EXP(0 5 1u 2u 10u 3u)";
+        let vs = s.parse::<VoltageSource>().unwrap();
+        let epsilon = 1e-12;
+
+        let exp = vs.exponential.expect("EXP should be parsed");
+        assert!((exp.initial_value - 0.0).abs() < epsilon);
+        assert!((exp.pulsed_value - 5.0).abs() < epsilon);
+        assert!((exp.rise_delay - 1e-6).abs() < epsilon);
+        assert!((exp.rise_tau - 2e-6).abs() < epsilon);
+        assert!((exp.fall_delay - 10e-6).abs() < epsilon);
+        assert!((exp.fall_tau - 3e-6).abs() < epsilon);
+    }
+
+    #[test]
+    fn test_exponential_value_at_time() {
+        let exp = Exponential {
+            initial_value: 1.0,
+            pulsed_value: 5.0,
+            rise_delay: 1e-6,
+            rise_tau: 2e-6,
+            fall_delay: 20e-6,
+            fall_tau: 3e-6,
+        };
+        let epsilon = 1e-9;
+
+        // 1. Before the rise delay
+        assert!((exp.value_at(0.5e-6) - 1.0).abs() < epsilon);
+
+        // 2. One time constant into the rise
+        let expected = 1.0 + (5.0 - 1.0) * (1.0 - (-1.0f64).exp());
+        assert!((exp.value_at(3e-6) - expected).abs() < epsilon);
+
+        // 3. One time constant into the fall, starting from the settled rise
+        let risen = 1.0 + 4.0 * (1.0 - (-22e-6_f64 / 2e-6).exp());
+        let expected = risen - 4.0 * (1.0 - (-1.0f64).exp());
+        assert!((exp.value_at(23e-6) - expected).abs() < epsilon);
+    }
+
+    #[test]
+    fn test_parse_pwl() {
+        let s = "V1 in 0 PWL(0 0 1m 5 2m 5 3m 0)";
+        let vs = s.parse::<VoltageSource>().unwrap();
+        let pwl = vs.pwl.expect("PWL should be parsed");
+        assert_eq!(pwl.points.len(), 4);
+        assert!((pwl.points[1].0 - 1e-3).abs() < 1e-12);
+        assert!((pwl.points[1].1 - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_pwl_rejects_non_increasing_times() {
+        assert!(
+            "V1 in 0 PWL(0 0 1m 5 1m 0)"
+                .parse::<VoltageSource>()
+                .is_err()
+        );
+        assert!(
+            "V1 in 0 PWL(0 0 2m 5 1m 0)"
+                .parse::<VoltageSource>()
+                .is_err()
+        );
+        assert!("V1 in 0 PWL(0 0 1m)".parse::<VoltageSource>().is_err());
+    }
+
+    #[test]
+    fn test_pwl_value_at_time() {
+        let pwl = Pwl {
+            points: vec![(1e-3, 1.0), (2e-3, 5.0), (4e-3, -3.0)],
+        };
+        let epsilon = 1e-9;
+
+        // 1. Before the first breakpoint the first value is held
+        assert!((pwl.value_at(0.0) - 1.0).abs() < epsilon);
+
+        // 2. Interpolated between breakpoints
+        assert!((pwl.value_at(1.5e-3) - 3.0).abs() < epsilon);
+        assert!((pwl.value_at(3e-3) - 1.0).abs() < epsilon);
+
+        // 3. Exactly on a breakpoint
+        assert!((pwl.value_at(2e-3) - 5.0).abs() < epsilon);
+
+        // 4. After the last breakpoint the last value is held
+        assert!((pwl.value_at(10e-3) - -3.0).abs() < epsilon);
+    }
+
+    #[test]
+    fn test_pwl_display_round_trip() {
+        let vs = "V1 in 0 PWL(0 0 1m 5)".parse::<VoltageSource>().unwrap();
+        let reparsed = vs.to_string().parse::<VoltageSource>().unwrap();
+        assert_eq!(reparsed.pwl, vs.pwl);
+    }
 }
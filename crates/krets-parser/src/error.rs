@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -35,8 +36,23 @@ pub enum Error {
     UnknownElementType(String),
 
     /// Error indicating a parsing failure on a specific line of the netlist.
-    #[error("Parse error on line {line}: {message}")]
-    ParseError { line: usize, message: String },
+    ///
+    /// It is displayed like a compiler diagnostic, quoting the `source_line` with a caret under
+    /// the `column` (a byte offset into `source_line`) at which parsing stopped, if known.
+    /// The `line` is counted within `file`, the netlist file it was read from, which is named in
+    /// the message if known.
+    #[error(
+        "Parse error{} on line {line}: {message}{}",
+        location(.file),
+        snippet(.line, .source_line, .column)
+    )]
+    ParseError {
+        file: Option<PathBuf>,
+        line: usize,
+        message: String,
+        source_line: String,
+        column: Option<usize>,
+    },
 
     #[error("IO error reading file: {0}")]
     Io(#[from] std::io::Error),
@@ -55,4 +71,44 @@ pub enum Error {
     /// Error for invalid model type
     #[error("Invalid model type: {0}")]
     InvalidModelType(String),
+
+    /// Two elements share the same identifier.
+    #[error("Duplicate element '{0}'")]
+    DuplicateElement(String),
+
+    /// A node is connected to a single element only.
+    #[error("Node '{0}' is only connected to a single element")]
+    DanglingNode(String),
+
+    /// A voltage source closes a loop of ideal voltage sources, e.g. two sources in parallel.
+    #[error("Voltage source '{0}' closes a loop of voltage sources")]
+    VoltageSourceLoop(String),
+
+    /// A pin of a subcircuit is not connected to any element inside it.
+    #[error("Pin '{pin}' of subcircuit '{subcircuit}' is not connected inside it")]
+    UnconnectedPin { subcircuit: String, pin: String },
+}
+
+/// Names the file of a parse error, if known.
+fn location(file: &Option<PathBuf>) -> String {
+    file.as_ref()
+        .map(|file| format!(" in {}", file.display()))
+        .unwrap_or_default()
+}
+
+/// Renders the source line of a parse error below its message, with a caret under the column.
+fn snippet(line: &usize, source_line: &str, column: &Option<usize>) -> String {
+    if source_line.is_empty() {
+        return String::new();
+    }
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    let mut snippet = format!("\n{gutter} |\n{number} | {source_line}");
+    if let Some(column) = column {
+        let offset = source_line
+            .get(..*column)
+            .map_or(0, |prefix| prefix.chars().count());
+        snippet.push_str(&format!("\n{gutter} | {}^", " ".repeat(offset)));
+    }
+    snippet
 }
@@ -0,0 +1,287 @@
+use crate::prelude::*;
+use nom::number::complete::recognize_float;
+use std::collections::HashSet;
+
+/// Evaluates an arithmetic expression such as `rload*2 + 1k`.
+///
+/// Supports `+ - * /`, parentheses, unary signs, numeric literals with SPICE suffixes
+/// and parameter references. `lookup` resolves a (lowercased) parameter name to its value.
+pub fn evaluate_expression(
+    input: &str,
+    lookup: &mut dyn FnMut(&str) -> Result<f64>,
+) -> Result<f64> {
+    let mut evaluator = Evaluator {
+        input,
+        position: 0,
+        lookup,
+    };
+    let value = evaluator.expression()?;
+    evaluator.skip_whitespace();
+    if evaluator.position != input.len() {
+        return Err(evaluator.error("unexpected trailing input"));
+    }
+    Ok(value)
+}
+
+/// Resolves `.param` definitions, given as name/expression pairs, into values.
+///
+/// Definitions may reference each other in any order; circular references and
+/// unknown identifiers are reported as `Error::InvalidFormat`.
+pub fn resolve_parameters(definitions: &HashMap<String, String>) -> Result<HashMap<String, f64>> {
+    let mut resolved = HashMap::new();
+    for name in definitions.keys() {
+        resolve_parameter(name, definitions, &mut resolved, &mut HashSet::new())?;
+    }
+    Ok(resolved)
+}
+
+fn resolve_parameter(
+    name: &str,
+    definitions: &HashMap<String, String>,
+    resolved: &mut HashMap<String, f64>,
+    visiting: &mut HashSet<String>,
+) -> Result<f64> {
+    if let Some(&value) = resolved.get(name) {
+        return Ok(value);
+    }
+    let definition = definitions
+        .get(name)
+        .ok_or_else(|| Error::InvalidFormat(format!("Unknown parameter '{name}'")))?;
+    if !visiting.insert(name.to_string()) {
+        return Err(Error::InvalidFormat(format!(
+            "Circular definition of parameter '{name}'"
+        )));
+    }
+
+    let value = evaluate_expression(definition, &mut |reference| {
+        resolve_parameter(reference, definitions, resolved, visiting)
+    })?;
+
+    visiting.remove(name);
+    resolved.insert(name.to_string(), value);
+    Ok(value)
+}
+
+/// Replaces every brace-delimited expression `{...}` in `input` by its value.
+pub fn substitute_expressions(input: &str, parameters: &HashMap<String, f64>) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| {
+            Error::InvalidFormat(format!("Unterminated expression in '{}'", &rest[start..]))
+        })? + start;
+
+        let value = evaluate_expression(&rest[start + 1..end], &mut |name| {
+            parameters
+                .get(name)
+                .copied()
+                .ok_or_else(|| Error::InvalidFormat(format!("Unknown parameter '{name}'")))
+        })?;
+
+        output.push_str(&rest[..start]);
+        output.push_str(&value.to_string());
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// A recursive descent evaluator over the grammar:
+///
+/// ```text
+/// expression := term (('+' | '-') term)*
+/// term       := factor (('*' | '/') factor)*
+/// factor     := ('+' | '-') factor | '(' expression ')' | number | identifier
+/// ```
+struct Evaluator<'a, 'b> {
+    input: &'a str,
+    position: usize,
+    lookup: &'b mut dyn FnMut(&str) -> Result<f64>,
+}
+
+impl Evaluator<'_, '_> {
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidFormat(format!(
+            "Invalid expression '{}': {message}",
+            self.input.trim()
+        ))
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn expression(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        while let Some(operator @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            let rhs = self.term()?;
+            if operator == '+' {
+                value += rhs;
+            } else {
+                value -= rhs;
+            }
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.factor()?;
+        while let Some(operator @ ('*' | '/')) = self.peek() {
+            self.position += 1;
+            let rhs = self.factor()?;
+            if operator == '*' {
+                value *= rhs;
+            } else {
+                value /= rhs;
+            }
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<f64> {
+        match self.peek() {
+            Some('+') => {
+                self.position += 1;
+                self.factor()
+            }
+            Some('-') => {
+                self.position += 1;
+                Ok(-self.factor()?)
+            }
+            Some('(') => {
+                self.position += 1;
+                let value = self.expression()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("missing ')'"));
+                }
+                self.position += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => self.identifier(),
+            Some(c) => Err(self.error(&format!("unexpected character '{c}'"))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let rest = self.rest();
+        let (after_float, _) = recognize_float::<&str, nom::error::Error<&str>>(rest)
+            .map_err(|_| self.error("invalid number"))?;
+        // Keep the metric suffix and unit attached to the number (e.g. `4.7k`, `1meg`).
+        let suffix_len = after_float
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after_float.len());
+        let token_len = rest.len() - after_float.len() + suffix_len;
+
+        let value = parse_value(&rest[..token_len])?;
+        self.position += token_len;
+        Ok(value)
+    }
+
+    fn identifier(&mut self) -> Result<f64> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = rest[..len].to_lowercase();
+        self.position += len;
+        (self.lookup)(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(input: &str, parameters: &[(&str, f64)]) -> Result<f64> {
+        let parameters: HashMap<String, f64> = parameters
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value))
+            .collect();
+        evaluate_expression(input, &mut |name| {
+            parameters
+                .get(name)
+                .copied()
+                .ok_or_else(|| Error::InvalidFormat(name.to_string()))
+        })
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3", &[]).unwrap(), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3", &[]).unwrap(), 9.0);
+        assert_eq!(evaluate("8 / 4 / 2", &[]).unwrap(), 1.0);
+        assert_eq!(evaluate("-(2 - 5) * -2", &[]).unwrap(), -6.0);
+    }
+
+    #[test]
+    fn test_nested_arithmetic_with_parameters() {
+        let value = evaluate(
+            "(Rload * (2 + x)) / 1k - 1e-3",
+            &[("rload", 1e3), ("x", 1.0)],
+        );
+        assert!((value.unwrap() - 2.999).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_suffixed_literals() {
+        assert_eq!(evaluate("2*4.7k", &[]).unwrap(), 9400.0);
+        assert_eq!(evaluate("1meg/10", &[]).unwrap(), 1e5);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert!(evaluate("1 +", &[]).is_err());
+        assert!(evaluate("(1 + 2", &[]).is_err());
+        assert!(evaluate("1 2", &[]).is_err());
+        assert!(matches!(
+            evaluate("unknown * 2", &[]),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_parameter_referencing_another_parameter() {
+        let definitions = HashMap::from([
+            ("double".to_string(), "base*2".to_string()),
+            ("base".to_string(), "1k".to_string()),
+        ]);
+        let parameters = resolve_parameters(&definitions).unwrap();
+        assert_eq!(parameters["double"], 2e3);
+    }
+
+    #[test]
+    fn test_circular_parameters() {
+        let definitions = HashMap::from([
+            ("a".to_string(), "b".to_string()),
+            ("b".to_string(), "a+1".to_string()),
+        ]);
+        assert!(matches!(
+            resolve_parameters(&definitions),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_substitute_expressions() {
+        let parameters = HashMap::from([("rload".to_string(), 1e3)]);
+        let line = substitute_expressions("R1 out 0 {rload*2}", &parameters).unwrap();
+        assert_eq!(line, "R1 out 0 2000");
+        assert!(substitute_expressions("R1 out 0 {rload*2", &parameters).is_err());
+        assert!(substitute_expressions("R1 out 0 {rl}", &parameters).is_err());
+    }
+}
@@ -1,9 +1,13 @@
 pub mod analyses;
+pub mod builder;
 pub mod circuit;
 pub mod constants;
 pub mod elements;
 pub mod error;
+pub mod expression;
+pub mod measure;
 pub mod models;
+pub mod options;
 pub mod parser;
 pub mod prelude;
 pub mod utils;
@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use nom::{character::complete::space0, multi::many0};
+
+/// The analysis whose results a measurement is evaluated on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasureAnalysis {
+    /// A DC sweep, windowed over the sweep step index.
+    Dc,
+    /// A transient analysis, windowed over time.
+    Tran,
+}
+
+/// The function a measurement computes over a signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeasureFunction {
+    /// The largest value of the signal.
+    Max,
+    /// The smallest value of the signal.
+    Min,
+    /// The average of the signal, weighted by the sweep variable.
+    Avg,
+    /// The peak-to-peak value, `max - min`.
+    Pp,
+    /// The sweep distance between the signal crossing `low` and `high`, given as fractions
+    /// of the transition from its first to its last value.
+    Rise { low: f64, high: f64 },
+}
+
+/// A `.measure` card, e.g. `.measure tran vmax MAX V(out) FROM=1m TO=5m`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// Name under which the measured value is reported.
+    pub name: String,
+    /// The analysis the measurement applies to.
+    pub analysis: MeasureAnalysis,
+    /// The function to compute.
+    pub function: MeasureFunction,
+    /// The measured signal, keyed like the MNA index map (e.g. `V(out)` or `I(V1)`).
+    pub target: String,
+    /// Start of the measurement window, if any.
+    pub from: Option<f64>,
+    /// End of the measurement window, if any.
+    pub to: Option<f64>,
+}
+
+/// Parses a `.measure` (or `.meas`) card.
+///
+/// The syntax is `.measure <tran|dc> <name> <function> <target> [FROM=<x>] [TO=<x>]`, where
+/// the function is one of `MAX`, `MIN`, `AVG`, `PP` or `RISE <low>% <high>%`.
+pub fn parse_measure(input: &str) -> IResult<&str, Measurement> {
+    let (input, _) = alt((tag_no_case(".measure"), tag_no_case(".meas"))).parse(input)?;
+    let (input, analysis) = preceded(
+        space1,
+        alt((
+            map(tag_no_case("tran"), |_| MeasureAnalysis::Tran),
+            map(tag_no_case("dc"), |_| MeasureAnalysis::Dc),
+        )),
+    )
+    .parse(input)?;
+    let (input, name) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, keyword) = preceded(space1, alphanumeric_or_underscore1).parse(input)?;
+    let (input, target) = preceded(space1, parse_signal).parse(input)?;
+
+    let (input, function) = match keyword.to_lowercase().as_str() {
+        "max" => (input, MeasureFunction::Max),
+        "min" => (input, MeasureFunction::Min),
+        "avg" => (input, MeasureFunction::Avg),
+        "pp" => (input, MeasureFunction::Pp),
+        "rise" => {
+            let (input, (low, high)) =
+                (preceded(space1, percentage), preceded(space1, percentage)).parse(input)?;
+            (input, MeasureFunction::Rise { low, high })
+        }
+        _ => {
+            return Err(nom::Err::Failure(nom::error::Error::new(
+                keyword,
+                nom::error::ErrorKind::Verify,
+            )));
+        }
+    };
+
+    let (input, window) = many0(preceded(space1, parse_key_value)).parse(input)?;
+    let (input, _) = space0(input)?;
+
+    let mut measurement = Measurement {
+        name: name.to_string(),
+        analysis,
+        function,
+        target,
+        from: None,
+        to: None,
+    };
+
+    for (key, value) in window {
+        match key.to_lowercase().as_str() {
+            "from" => measurement.from = Some(value),
+            "to" => measurement.to = Some(value),
+            _ => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    key,
+                    nom::error::ErrorKind::Verify,
+                )));
+            }
+        }
+    }
+
+    Ok((input, measurement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_windowed_average() {
+        let (_, measurement) =
+            parse_measure(".measure tran vavg AVG V(out) FROM=1m TO=5m").unwrap();
+        assert_eq!(
+            measurement,
+            Measurement {
+                name: "vavg".to_string(),
+                analysis: MeasureAnalysis::Tran,
+                function: MeasureFunction::Avg,
+                target: "V(out)".to_string(),
+                from: Some(1e-3),
+                to: Some(5e-3),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rise_time() {
+        let (_, measurement) = parse_measure(".meas tran trise rise v(out) 10% 90%").unwrap();
+        assert_eq!(
+            measurement.function,
+            MeasureFunction::Rise {
+                low: 0.1,
+                high: 0.9
+            }
+        );
+        assert_eq!(measurement.from, None);
+    }
+
+    #[test]
+    fn test_parse_branch_current_target() {
+        let (_, measurement) = parse_measure(".measure dc ipp PP i(v1)").unwrap();
+        assert_eq!(measurement.analysis, MeasureAnalysis::Dc);
+        assert_eq!(measurement.target, "I(V1)");
+    }
+
+    #[test]
+    fn test_parse_invalid_measure() {
+        assert!(parse_measure(".measure ac vmax MAX V(out)").is_err());
+        assert!(parse_measure(".measure tran vmax MAX V(out) AT=1m").is_err());
+        assert!(parse_measure(".measure tran trise RISE V(out) 10%").is_err());
+        assert!(parse_measure(".measure tran vrms RMS V(out)").is_err());
+    }
+}
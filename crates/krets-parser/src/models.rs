@@ -1,6 +1,8 @@
+pub mod bjt;
 pub mod diode;
 pub mod nmosfet;
 pub mod pmosfet;
+pub mod switch;
 
 use crate::{models::diode::DiodeModel, prelude::*};
 use nom::{
@@ -8,17 +10,21 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case},
     character::complete::{space0, space1},
-    combinator::{map, opt},
+    combinator::{eof, map, opt},
     multi::many0,
-    sequence::{delimited, preceded},
+    sequence::{delimited, preceded, terminated},
 };
 
 #[derive(Debug, PartialEq, Clone)]
 /// Enum representing the different types of devices supported by the .model card.
 pub enum Model {
-    Diode(diode::DiodeModel),       // D
-    NMosfet(nmosfet::NMosfetModel), // NMOSFET
-    PMosfet(pmosfet::PMosfetModel), // PMOSFET
+    Diode(diode::DiodeModel),           // D
+    NMosfet(nmosfet::NMosfetModel),     // NMOSFET
+    PMosfet(pmosfet::PMosfetModel),     // PMOSFET
+    Switch(switch::SwitchModel),        // SW
+    CurrentSwitch(switch::SwitchModel), // CSW
+    Npn(bjt::BjtModel),                 // NPN
+    Pnp(bjt::BjtModel),                 // PNP
 }
 
 impl Model {
@@ -28,6 +34,94 @@ impl Model {
             Model::Diode(model) => &model.name,
             Model::NMosfet(model) => &model.name,
             Model::PMosfet(model) => &model.name,
+            Model::Switch(model) | Model::CurrentSwitch(model) => &model.name,
+            Model::Npn(model) | Model::Pnp(model) => &model.name,
+        }
+    }
+
+    /// Renames the model.
+    pub fn set_name(&mut self, name: &str) {
+        match self {
+            Model::Diode(model) => model.name = name.to_string(),
+            Model::NMosfet(model) => model.name = name.to_string(),
+            Model::PMosfet(model) => model.name = name.to_string(),
+            Model::Switch(model) | Model::CurrentSwitch(model) => model.name = name.to_string(),
+            Model::Npn(model) | Model::Pnp(model) => model.name = name.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Model {
+    /// Writes the model as a `.model` card that parses back into an equivalent model.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Model::Diode(m) => write!(
+                f,
+                ".model {} D (is={} rs={} n={} xti={} eg={} cjo={} tt={})",
+                m.name,
+                m.saturation_current,
+                m.parasitic_resistance,
+                m.emission_coefficient,
+                m.saturation_current_temperature_exponent,
+                m.energy_gap,
+                m.junction_capacitance,
+                m.transit_time,
+            ),
+            Model::NMosfet(m) => write!(
+                f,
+                ".model {} NMOS (w={} l={} vto={} kp={} lambda={} tox={})",
+                m.name,
+                m.width,
+                m.length,
+                m.voltage_threshold,
+                m.process_transconductance,
+                m.channel_length_modulation,
+                m.oxide_thickness,
+            ),
+            Model::PMosfet(m) => write!(
+                f,
+                ".model {} PMOS (w={} l={} vto={} kp={} lambda={} tox={})",
+                m.name,
+                m.width,
+                m.length,
+                m.voltage_threshold,
+                m.process_transconductance,
+                m.channel_length_modulation,
+                m.oxide_thickness,
+            ),
+            Model::Switch(m) => write!(
+                f,
+                ".model {} SW (vt={} vh={} ron={} roff={})",
+                m.name, m.threshold, m.hysteresis, m.on_resistance, m.off_resistance,
+            ),
+            Model::CurrentSwitch(m) => write!(
+                f,
+                ".model {} CSW (it={} ih={} ron={} roff={})",
+                m.name, m.threshold, m.hysteresis, m.on_resistance, m.off_resistance,
+            ),
+            Model::Npn(m) | Model::Pnp(m) => {
+                let kind = if matches!(self, Model::Npn(_)) {
+                    "NPN"
+                } else {
+                    "PNP"
+                };
+                write!(
+                    f,
+                    ".model {} {kind} (is={} bf={} br={} tf={} cje={} cjc={}",
+                    m.name,
+                    m.saturation_current,
+                    m.forward_beta,
+                    m.reverse_beta,
+                    m.forward_transit_time,
+                    m.base_emitter_capacitance,
+                    m.base_collector_capacitance,
+                )?;
+                // An infinite Early voltage is the default and has no netlist value.
+                if m.forward_early_voltage.is_finite() {
+                    write!(f, " vaf={}", m.forward_early_voltage)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -50,6 +144,34 @@ fn parse_parameters(input: &str) -> IResult<&str, HashMap<String, f64>> {
     .parse(input)
 }
 
+/// Parses the parameters of a bipolar model, which must be in parentheses, e.g. `(BF=100)`.
+///
+/// Unlike [`parse_parameters`], the rest of the card must be empty, so a malformed parameter
+/// is an error instead of silently ending the list.
+fn parse_parenthesized_parameters(input: &str) -> IResult<&str, HashMap<String, f64>> {
+    terminated(
+        map(
+            opt(preceded(
+                space0,
+                delimited(
+                    tag("("),
+                    many0(preceded(space0, parse_key_value)),
+                    preceded(space0, tag(")")),
+                ),
+            )),
+            |parameters| {
+                parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect()
+            },
+        ),
+        (space0, eof),
+    )
+    .parse(input)
+}
+
 pub fn parse_model_variant(input: &str) -> IResult<&str, Model> {
     let (input, _) = preceded(tag_no_case(".model"), space1).parse(input)?;
     let (input, name) = alphanumeric_or_underscore1(input)?;
@@ -80,12 +202,45 @@ pub fn parse_model_variant(input: &str) -> IResult<&str, Model> {
             diode_model.apply_model_parameters(&parameters);
             Model::Diode(diode_model)
         }),
+        map((tag("SW"), parse_parameters), move |(_, parameters)| {
+            let mut switch_model = switch::SwitchModel {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            switch_model.apply_model_parameters(&parameters);
+            Model::Switch(switch_model)
+        }),
+        map((tag("CSW"), parse_parameters), move |(_, parameters)| {
+            let mut switch_model = switch::SwitchModel {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            switch_model.apply_model_parameters(&parameters);
+            Model::CurrentSwitch(switch_model)
+        }),
+        map(
+            (
+                alt((tag("NPN"), tag("PNP"))),
+                parse_parenthesized_parameters,
+            ),
+            move |(kind, parameters)| {
+                let mut bjt_model = bjt::BjtModel {
+                    name: name.to_string(),
+                    ..Default::default()
+                };
+                bjt_model.apply_model_parameters(&parameters);
+                match kind {
+                    "NPN" => Model::Npn(bjt_model),
+                    _ => Model::Pnp(bjt_model),
+                }
+            },
+        ),
     ))
     .parse(input)
 }
 
 pub fn parse_model(input: &str) -> Result<Model> {
-    let input_without_comment = input.split('%').next().unwrap_or("").trim();
+    let input_without_comment = strip_comment(input);
     let (_, model) = parse_model_variant
         .parse(input_without_comment)
         .map_err(|e| Error::InvalidFormat(e.to_string()))?;
@@ -99,7 +254,7 @@ mod tests {
 
     #[test]
     fn test_parse_diode() {
-        let input = ".model DMOD D (is=1e-12 rs=0.1 n=1.1)";
+        let input = ".model DMOD D (is=1e-12 rs=0.1 n=1.1 cjo=2p tt=5n)";
         let model = parse_model(input).unwrap();
         match model {
             Model::Diode(diode_model) => {
@@ -107,6 +262,8 @@ mod tests {
                 assert_eq!(diode_model.saturation_current, 1e-12);
                 assert_eq!(diode_model.parasitic_resistance, 0.1);
                 assert_eq!(diode_model.emission_coefficient, 1.1);
+                assert_eq!(diode_model.junction_capacitance, 2e-12);
+                assert_eq!(diode_model.transit_time, 5e-9);
             }
             _ => panic!("Expected Diode model"),
         }
@@ -144,6 +301,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_switch() {
+        let input = ".model SMOD SW (vt=2.5 vh=0.5 ron=1 roff=1meg)";
+        match parse_model(input).unwrap() {
+            Model::Switch(switch_model) => {
+                assert_eq!(switch_model.name, "SMOD");
+                assert_eq!(switch_model.threshold, 2.5);
+                assert_eq!(switch_model.hysteresis, 0.5);
+                assert_eq!(switch_model.on_resistance, 1.0);
+                assert_eq!(switch_model.off_resistance, 1e6);
+            }
+            _ => panic!("Expected Switch model"),
+        }
+
+        let input = ".model WMOD CSW (it=1m ih=0.1m)";
+        match parse_model(input).unwrap() {
+            Model::CurrentSwitch(switch_model) => {
+                assert_eq!(switch_model.threshold, 1e-3);
+                assert_eq!(switch_model.off_resistance, 1e12);
+            }
+            _ => panic!("Expected CurrentSwitch model"),
+        }
+    }
+
     #[test]
     fn test_parse_model_no_parameters() {
         // Technically valid SPICE, though unusual
@@ -157,6 +338,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_bjt() {
+        let input = ".model QMOD PNP (BF=80 is=2e-15 VAF=50 cje=1p)";
+        match parse_model(input).unwrap() {
+            Model::Pnp(bjt_model) => {
+                assert_eq!(bjt_model.name, "QMOD");
+                assert_eq!(bjt_model.forward_beta, 80.0);
+                assert_eq!(bjt_model.saturation_current, 2e-15);
+                assert_eq!(bjt_model.forward_early_voltage, 50.0);
+                assert_eq!(bjt_model.base_emitter_capacitance, 1e-12);
+                assert_eq!(bjt_model.reverse_beta, 1.0);
+            }
+            _ => panic!("Expected PNP model"),
+        }
+        assert!(matches!(
+            parse_model(".model Q2 NPN").unwrap(),
+            Model::Npn(_)
+        ));
+    }
+
     #[test]
     fn test_invalid_model_missing_type() {
         let input = ".model MOD1 (BF=50)";
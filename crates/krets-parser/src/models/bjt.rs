@@ -0,0 +1,65 @@
+use crate::{models::ModelTrait, prelude::*};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BjtModel {
+    pub name: String,
+    /// The transport saturation current (IS).
+    pub saturation_current: f64,
+    /// The ideal maximum forward beta (BF).
+    pub forward_beta: f64,
+    /// The ideal maximum reverse beta (BR).
+    pub reverse_beta: f64,
+    /// The forward Early voltage (VAF). Infinite disables the Early effect.
+    pub forward_early_voltage: f64,
+    /// The ideal forward transit time (TF), which sets the base-emitter diffusion capacitance.
+    pub forward_transit_time: f64,
+    /// The base-emitter junction capacitance (CJE).
+    pub base_emitter_capacitance: f64,
+    /// The base-collector junction capacitance (CJC).
+    pub base_collector_capacitance: f64,
+}
+
+impl Default for BjtModel {
+    fn default() -> Self {
+        BjtModel {
+            name: String::new(),
+            saturation_current: 1e-16,
+            forward_beta: 100.0,
+            reverse_beta: 1.0,
+            forward_early_voltage: f64::INFINITY,
+            forward_transit_time: 0.0,
+            base_emitter_capacitance: 0.0,
+            base_collector_capacitance: 0.0,
+        }
+    }
+}
+
+impl BjtModel {
+    /// Returns the inverse of the forward Early voltage, which is zero when VAF is infinite.
+    pub fn inverse_early_voltage(&self) -> f64 {
+        if self.forward_early_voltage.is_finite() && self.forward_early_voltage != 0.0 {
+            1.0 / self.forward_early_voltage
+        } else {
+            0.0
+        }
+    }
+}
+
+impl ModelTrait for BjtModel {
+    fn apply_model_parameters(&mut self, parameters: &HashMap<String, f64>) {
+        for (key, value) in parameters {
+            match key.to_lowercase().as_str() {
+                "is" => self.saturation_current = *value,
+                "bf" => self.forward_beta = *value,
+                "br" => self.reverse_beta = *value,
+                "vaf" | "va" => self.forward_early_voltage = *value,
+                "tf" => self.forward_transit_time = *value,
+                "cje" => self.base_emitter_capacitance = *value,
+                "cjc" => self.base_collector_capacitance = *value,
+                _ => {
+                    // Unknown parameter; could log a warning or ignore
+                }
+            }
+        }
+    }
+}
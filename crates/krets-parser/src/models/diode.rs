@@ -9,6 +9,14 @@ pub struct DiodeModel {
     pub parasitic_resistance: f64,
     /// The Emission coefficient (N).
     pub emission_coefficient: f64,
+    /// The Saturation current temperature exponent (XTI).
+    pub saturation_current_temperature_exponent: f64,
+    /// The Energy gap in eV (EG).
+    pub energy_gap: f64,
+    /// The Zero-bias junction capacitance (CJO).
+    pub junction_capacitance: f64,
+    /// The Transit time (TT), scaling the diffusion charge `TT * I`.
+    pub transit_time: f64,
 }
 
 impl Default for DiodeModel {
@@ -18,6 +26,10 @@ impl Default for DiodeModel {
             saturation_current: 1e-12,
             parasitic_resistance: 0.0,
             emission_coefficient: 1.0,
+            saturation_current_temperature_exponent: 3.0,
+            energy_gap: 1.11,
+            junction_capacitance: 0.0,
+            transit_time: 0.0,
         }
     }
 }
@@ -29,6 +41,10 @@ impl ModelTrait for DiodeModel {
                 "is" => self.saturation_current = *value,
                 "rs" => self.parasitic_resistance = *value,
                 "n" => self.emission_coefficient = *value,
+                "xti" => self.saturation_current_temperature_exponent = *value,
+                "eg" => self.energy_gap = *value,
+                "cjo" | "cj0" => self.junction_capacitance = *value,
+                "tt" => self.transit_time = *value,
                 _ => {
                     // Unknown parameter; could log a warning or ignore
                 }
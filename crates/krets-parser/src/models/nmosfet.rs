@@ -19,6 +19,9 @@ pub struct NMosfetModel {
     // Channel length modulation parameter in 1/V.
     // In netlist is specified with parameter "LAMBDA"
     pub channel_length_modulation: f64,
+    // Gate oxide thickness in meters.
+    // In netlist is specified with parameter "TOX"
+    pub oxide_thickness: f64,
 }
 
 impl Default for NMosfetModel {
@@ -30,6 +33,7 @@ impl Default for NMosfetModel {
             voltage_threshold: 0.0,          // Default threshold voltage of 0.0 V
             process_transconductance: 2e-5,  // Default process transconductance
             channel_length_modulation: 0.02, // Default channel length modulation
+            oxide_thickness: 1e-7,           // Default oxide thickness of 100 nanometers
         }
     }
 }
@@ -41,6 +45,12 @@ impl NMosfetModel {
     pub fn beta(&self) -> f64 {
         self.process_transconductance * (self.width / self.length)
     }
+
+    /// Calculates the gate oxide capacitance per unit area in F/m^2.
+    /// C_ox = ε_ox / t_ox, with the permittivity of silicon dioxide ε_ox = 3.9 * ε_0.
+    pub fn oxide_capacitance(&self) -> f64 {
+        3.9 * 8.854e-12 / self.oxide_thickness
+    }
 }
 
 impl ModelTrait for NMosfetModel {
@@ -52,6 +62,7 @@ impl ModelTrait for NMosfetModel {
                 "vto" => self.voltage_threshold = *value,
                 "kp" => self.process_transconductance = *value,
                 "lambda" => self.channel_length_modulation = *value,
+                "tox" => self.oxide_thickness = *value,
                 _ => {
                     // Unknown parameter; could log a warning or ignore
                 }
@@ -0,0 +1,43 @@
+use crate::{models::ModelTrait, prelude::*};
+
+#[derive(Debug, PartialEq, Clone)]
+/// Model of a voltage (`SW`) or current (`CSW`) controlled switch.
+pub struct SwitchModel {
+    pub name: String,
+    /// The threshold of the controlling voltage (VT) or current (IT).
+    pub threshold: f64,
+    /// The hysteresis of the controlling voltage (VH) or current (IH).
+    pub hysteresis: f64,
+    /// The resistance of the closed switch (RON).
+    pub on_resistance: f64,
+    /// The resistance of the open switch (ROFF).
+    pub off_resistance: f64,
+}
+
+impl Default for SwitchModel {
+    fn default() -> Self {
+        SwitchModel {
+            name: String::new(),
+            threshold: 0.0,
+            hysteresis: 0.0,
+            on_resistance: 1.0,
+            off_resistance: 1e12,
+        }
+    }
+}
+
+impl ModelTrait for SwitchModel {
+    fn apply_model_parameters(&mut self, parameters: &HashMap<String, f64>) {
+        for (key, value) in parameters {
+            match key.to_lowercase().as_str() {
+                "vt" | "it" => self.threshold = *value,
+                "vh" | "ih" => self.hysteresis = *value,
+                "ron" => self.on_resistance = *value,
+                "roff" => self.off_resistance = *value,
+                _ => {
+                    // Unknown parameter; could log a warning or ignore
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::prelude::*;
+use log::warn;
+use nom::{character::complete::space0, multi::many1};
+
+/// Simulator options from `.options` cards, e.g. `.options reltol=1e-4 itl1=200`.
+///
+/// Options the netlist does not set are `None`, so the solver keeps its own defaults for them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulatorOptions {
+    /// Relative tolerance of the Newton-Raphson convergence check (`RELTOL`).
+    pub reltol: Option<f64>,
+    /// Absolute tolerance on branch currents in amperes (`ABSTOL`).
+    pub abstol: Option<f64>,
+    /// Absolute tolerance on node voltages in volts (`VNTOL`).
+    pub vntol: Option<f64>,
+    /// Maximum number of Newton-Raphson iterations of the operating point (`ITL1`).
+    pub itl1: Option<usize>,
+    /// Smallest conductance in siemens the solver works with (`GMIN`).
+    pub gmin: Option<f64>,
+}
+
+impl SimulatorOptions {
+    /// Sets the option `name` (case-insensitive) to `value`.
+    ///
+    /// Other simulators know many more options, so unknown ones, flags without a value and
+    /// values out of range are skipped with a warning instead of failing the netlist.
+    pub fn set(&mut self, name: &str, value: Option<f64>) {
+        let name = name.to_lowercase();
+        let Some(value) = value else {
+            warn!("Ignoring option '{name}' without a value.");
+            return;
+        };
+
+        match name.as_str() {
+            "reltol" => self.reltol = Some(value),
+            "abstol" => self.abstol = Some(value),
+            "vntol" => self.vntol = Some(value),
+            "gmin" => self.gmin = Some(value),
+            "itl1" if value >= 1.0 && value.fract() == 0.0 => self.itl1 = Some(value as usize),
+            "itl1" => warn!("Ignoring option 'itl1={value}', which is not a positive integer."),
+            _ => warn!("Ignoring unknown option '{name}'."),
+        }
+    }
+
+    /// Returns whether no option is set.
+    pub fn is_empty(&self) -> bool {
+        *self == SimulatorOptions::default()
+    }
+
+    /// Returns the options as an `.options` card, or `None` if no option is set.
+    pub fn to_card(&self) -> Option<String> {
+        let options: Vec<String> = [
+            ("reltol", self.reltol),
+            ("abstol", self.abstol),
+            ("vntol", self.vntol),
+            ("itl1", self.itl1.map(|itl1| itl1 as f64)),
+            ("gmin", self.gmin),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some(format!("{name}={}", value?)))
+        .collect();
+
+        (!options.is_empty()).then(|| format!(".options {}", options.join(" ")))
+    }
+}
+
+/// Parses an `.options` (or `.option`) card into its options, each a name with an optional
+/// `=value`, e.g. `.options reltol=1e-4 itl1=200 noacct`.
+pub fn parse_options(input: &str) -> IResult<&str, Vec<(String, Option<f64>)>> {
+    let (input, _) = alt((tag_no_case(".options"), tag_no_case(".option"))).parse(input)?;
+    let (input, options) = many1(preceded(
+        space1,
+        (
+            alphanumeric_or_underscore1,
+            opt(preceded((space0, tag("="), space0), value_parser)),
+        ),
+    ))
+    .parse(input)?;
+    let (input, _) = space0(input)?;
+
+    let options = options
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+    Ok((input, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        let (_, options) = parse_options(".OPTIONS reltol=1e-4 ITL1 = 200 noacct").unwrap();
+        assert_eq!(
+            options,
+            vec![
+                ("reltol".to_string(), Some(1e-4)),
+                ("ITL1".to_string(), Some(200.0)),
+                ("noacct".to_string(), None),
+            ]
+        );
+        assert!(parse_options(".option gmin=1p").is_ok());
+        assert!(parse_options(".options").is_err());
+    }
+
+    #[test]
+    fn test_set_skips_unknown_and_invalid_options() {
+        let mut options = SimulatorOptions::default();
+        options.set("trtol", Some(7.0));
+        options.set("post", None);
+        options.set("itl1", Some(2.5));
+        assert!(options.is_empty());
+
+        options.set("VNTOL", Some(1e-3));
+        options.set("itl1", Some(50.0));
+        assert_eq!(options.vntol, Some(1e-3));
+        assert_eq!(options.itl1, Some(50));
+        assert_eq!(options.to_card().unwrap(), ".options vntol=0.001 itl1=50");
+    }
+}
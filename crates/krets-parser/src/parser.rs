@@ -1,11 +1,18 @@
-use crate::{circuit::Circuit, models::Model};
-use crate::{elements::Element, models::parse_model};
+use crate::circuit::Circuit;
+use crate::constants::GROUND_ALIASES;
+use crate::expression::{resolve_parameters, substitute_expressions};
+use crate::measure::parse_measure;
+use crate::options::parse_options;
 use crate::{elements::subcircuit::parse_subcircuits, prelude::*};
+use crate::{
+    elements::{Element, describe_element_error, parse_element_variant},
+    models::parse_model_variant,
+};
 use std::{
     collections::HashSet,
     fs::File,
     io::{BufReader, Read},
-    path::Path,
+    path::{Path, PathBuf},
 };
 /// Parses a SPICE-like netlist and extracts circuit elements into structured data.
 ///
@@ -14,10 +21,17 @@ use std::{
 /// The parser follows these rules:
 /// - It is **case-insensitive**.
 /// - It treats any sequence of spaces or tabs as a **single space**.
-/// - Each line describes **one circuit element** entirely.
+/// - Each line describes **one circuit element** entirely. A long card may be wrapped onto
+///   continuation lines starting with `+`, which are joined onto the previous line first.
 /// - The **order of lines** in the file is **irrelevant**.
-/// - Any text following a `%` or `*` character is a **comment** and ignored.
+/// - Any text following a `%` or `;` character is a **comment** and ignored. A `*` starts a
+///   comment only as the first character of a line.
 /// - Circuit node names are **non-negative integers**, where `0` is reserved for **ground**.
+///   The aliases in `GROUND_ALIASES` (`gnd`, `GND`) are ground too and are renamed to `0`.
+/// - Nodes declared with `.global` are shared with every subcircuit without being passed
+///   as ports.
+/// - Brace-delimited expressions such as `{rload*2}` are evaluated against the `.param`
+///   definitions before any element is parsed.
 ///
 /// # Parameters
 /// - `input`: A string containing the netlist text.
@@ -25,16 +39,62 @@ use std::{
 /// # Returns
 /// - A `Result<Circuit, Error>`.
 pub fn parse_circuit_description(input: &str) -> Result<Circuit> {
-    let mut nodes: HashSet<String> = HashSet::new();
-    let mut index_counter = 0;
+    parse_circuit_description_with_ground_aliases(input, &GROUND_ALIASES)
+}
+
+/// Parses a netlist like [`parse_circuit_description`], treating every node named in
+/// `ground_aliases` as ground instead of the default `GROUND_ALIASES`. The node `0` is
+/// always ground.
+pub fn parse_circuit_description_with_ground_aliases(
+    input: &str,
+    ground_aliases: &[&str],
+) -> Result<Circuit> {
     let mut inside_control_block = false;
     let mut inside_subckt_block = false;
     let mut circuit = Circuit::empty_circuit();
 
+    // A `;` comment may follow any token, so it is dropped before lines are joined.
+    let input = input
+        .lines()
+        .map(strip_inline_comment)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let input = join_continuation_lines(&input);
+    let input = input.as_str();
+
+    // Parameters pass: resolve all `.param` cards and substitute `{...}` expressions.
+    // Subcircuit bodies are substituted on instantiation, since they may reference the
+    // parameters of the subcircuit.
+    circuit.parameters = parse_parameter_cards(input)?;
+    let mut inside_subckt_body = false;
+    let input = input
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if is_card(trimmed, ".subckt") {
+                inside_subckt_body = true;
+            } else if is_card(trimmed, ".ends") {
+                inside_subckt_body = false;
+            }
+            if inside_subckt_body || trimmed.starts_with('%') || trimmed.starts_with('*') {
+                Ok(line.to_string())
+            } else {
+                substitute_expressions(line, &circuit.parameters)
+            }
+        })
+        .collect::<Result<Vec<_>>>()?
+        .join("\n");
+    let input = input.as_str();
+
     // First pass: Parse subcircuit definitions
-    let subcircuit_definitions = parse_subcircuits(input)
+    let subcircuit_definitions = parse_subcircuits(input, &circuit.parameters)
         .map_err(|e| Error::InvalidFormat(format!("Failed to parse subcircuits: {}", e)))?;
 
+    // Ground and the `.global` nodes keep their names inside subcircuits.
+    let mut global_nodes = parse_global_cards(input)?;
+    global_nodes.insert("0".to_string());
+    global_nodes.extend(ground_aliases.iter().map(|alias| alias.to_string()));
+
     for (line_num, line) in input.lines().enumerate() {
         let current_line = line_num + 1;
 
@@ -75,26 +135,105 @@ pub fn parse_circuit_description(input: &str) -> Result<Circuit> {
             continue;
         }
 
+        if is_card(line, ".param") || is_card(line, ".global") {
+            continue;
+        }
+
+        if is_card(line, ".temp") {
+            let line_without_comment = line.split('%').next().unwrap_or("").trim();
+            let (_, temperature) =
+                all_consuming(preceded((tag_no_case(".temp"), space1), value_parser))
+                    .parse(line_without_comment)
+                    .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
+
+            circuit.temperature = Some(temperature);
+            continue;
+        }
+
+        if is_card(line, ".ic") {
+            let line_without_comment = line.split('%').next().unwrap_or("").trim();
+            let (_, conditions) = all_consuming(parse_initial_conditions)
+                .parse(line_without_comment)
+                .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
+
+            circuit.initial_conditions.extend(conditions);
+            continue;
+        }
+
+        if is_card(line, ".nodeset") {
+            let line_without_comment = line.split('%').next().unwrap_or("").trim();
+            let (_, nodeset) = all_consuming(parse_nodeset)
+                .parse(line_without_comment)
+                .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
+
+            circuit.nodesets.extend(nodeset);
+            continue;
+        }
+
+        if is_card(line, ".options") || is_card(line, ".option") {
+            let line_without_comment = line.split('%').next().unwrap_or("").trim();
+            let (_, options) = all_consuming(parse_options)
+                .parse(line_without_comment)
+                .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
+
+            for (name, value) in options {
+                circuit.options.set(&name, value);
+            }
+            continue;
+        }
+
+        if is_card(line, ".save") || is_card(line, ".probe") {
+            let line_without_comment = line.split('%').next().unwrap_or("").trim();
+            let (_, signals) = all_consuming(parse_save)
+                .parse(line_without_comment)
+                .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
+
+            circuit.saves.extend(signals);
+            continue;
+        }
+
+        if is_card(line, ".measure") || is_card(line, ".meas") {
+            // `%` also marks the `RISE` levels (`10% 90%`), so only a `%` that starts a word
+            // begins a comment here.
+            let comment_start = line
+                .char_indices()
+                .find(|&(i, c)| c == '%' && line[..i].ends_with(char::is_whitespace))
+                .map_or(line.len(), |(i, _)| i);
+            let line_without_comment = line[..comment_start].trim();
+            let (_, measurement) = all_consuming(parse_measure)
+                .parse(line_without_comment)
+                .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
+
+            circuit.measurements.push(measurement);
+            continue;
+        }
+
         if line.to_lowercase().starts_with(".model") {
-            let model = parse_model(line).map_err(|e| Error::ParseError {
-                line: current_line,
-                message: e.to_string(),
-            })?;
+            let line_without_comment = strip_comment(line);
+            let (_, model) = parse_model_variant
+                .parse(line_without_comment)
+                .map_err(|e| nom_error(current_line, line, line_without_comment, e))?;
 
             circuit.models.insert(model.name().to_string(), model);
             continue;
         }
 
-        let element = parse_element(line).map_err(|e| Error::ParseError {
-            line: current_line,
-            message: e.to_string(),
+        let (_, element) = parse_element_variant(line).map_err(|e| {
+            let mut error = nom_error(current_line, line, line, e);
+            if let Error::ParseError { message, .. } = &mut error {
+                *message = describe_element_error(line, message);
+            }
+            error
         })?;
 
         match element {
             Element::SubcktInstance(instance) => {
-                circuit
-                    .elements
-                    .append(&mut instance.instantiate(&subcircuit_definitions)?);
+                circuit.elements.append(&mut instance.instantiate(
+                    &subcircuit_definitions,
+                    &circuit.parameters,
+                    &global_nodes,
+                    &mut circuit.models,
+                )?);
             }
             _ => {
                 circuit.elements.push(element);
@@ -102,63 +241,199 @@ pub fn parse_circuit_description(input: &str) -> Result<Circuit> {
         }
     }
 
-    for element in circuit.elements.iter() {
-        if element.is_g2() {
-            circuit
-                .index_map
-                .insert(format!("I({element})"), index_counter);
-            index_counter += 1;
+    if circuit.is_empty() {
+        return Err(Error::EmptyNetlist);
+    }
+
+    circuit.subcircuits = subcircuit_definitions;
+    circuit.normalize_ground(ground_aliases);
+    circuit.assign_indices();
+    circuit.resolve_models()?;
+    Ok(circuit)
+}
+
+/// Returns a [`Error::ParseError`] for the netlist `line` on line `line_number`, which failed
+/// to parse. The column is where nom stopped within `parsed`, the part of `line` that was
+/// handed to the parser, which starts where `line` starts.
+fn nom_error(
+    line_number: usize,
+    line: &str,
+    parsed: &str,
+    error: nom::Err<nom::error::Error<&str>>,
+) -> Error {
+    let column = match &error {
+        nom::Err::Error(e) | nom::Err::Failure(e) if parsed.ends_with(e.input) => {
+            Some(parsed.len() - e.input.len())
         }
+        _ => None,
+    };
+    Error::ParseError {
+        file: None,
+        line: line_number,
+        message: error.to_string(),
+        source_line: line.to_string(),
+        column,
+    }
+}
+
+/// Joins every continuation line, one whose first non-blank character is `+`, onto the
+/// previous card, skipping over blank and comment lines in between.
+///
+/// A trailing `%` comment of the previous card is dropped before joining, since it would
+/// otherwise swallow the continued text. The continuation line itself is left blank, so every
+/// card keeps the line number it starts on in error messages.
+fn join_continuation_lines(input: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut last_card: Option<usize> = None;
 
-        for node in &element.nodes() {
-            if nodes.insert(node.to_string()) {
-                // Skip adding the ground node to the index map
-                if *node == "0" {
-                    continue;
+    for line in input.lines() {
+        let trimmed = line.trim_start();
+        match (trimmed.strip_prefix('+'), last_card) {
+            (Some(continuation), Some(card)) => {
+                let previous = &mut lines[card];
+                // A `%` starting a word begins a comment, while `tol=5%` or `10%` do not.
+                let comment_start = previous
+                    .char_indices()
+                    .find(|&(i, c)| c == '%' && previous[..i].ends_with(char::is_whitespace))
+                    .map_or(previous.len(), |(i, _)| i);
+                previous.truncate(comment_start);
+                let joined = format!("{} {}", previous.trim_end(), continuation.trim());
+                *previous = joined;
+                lines.push(String::new());
+            }
+            _ => {
+                let is_comment = trimmed.starts_with('%') || trimmed.starts_with('*');
+                if !trimmed.is_empty() && !is_comment {
+                    last_card = Some(lines.len());
                 }
-                circuit
-                    .index_map
-                    .insert(format!("V({node})"), index_counter);
-                index_counter += 1;
+                lines.push(line.to_string());
             }
         }
     }
 
-    if circuit.is_empty() {
-        return Err(Error::EmptyNetlist);
+    lines.join("\n")
+}
+
+/// Returns whether the first token of `line` is the dot card `card`.
+fn is_card(line: &str, card: &str) -> bool {
+    line.split_whitespace()
+        .next()
+        .is_some_and(|token| token.eq_ignore_ascii_case(card))
+}
+
+/// Collects the `.param` cards of the netlist and evaluates them. Since the order of lines
+/// is irrelevant, parameters may reference parameters defined further down.
+fn parse_parameter_cards(input: &str) -> Result<HashMap<String, f64>> {
+    let mut definitions = HashMap::new();
+
+    for (line_num, line) in input.lines().enumerate() {
+        let line = line.split('%').next().unwrap_or("").trim();
+        if !is_card(line, ".param") {
+            continue;
+        }
+
+        let (_, parameters) = all_consuming(parse_parameters)
+            .parse(line)
+            .map_err(|e| nom_error(line_num + 1, line, line, e))?;
+        definitions.extend(parameters);
     }
 
-    // --- Second pass: Apply model parameters to elements ---
-    for element in circuit.elements.iter_mut() {
-        if let Element::Diode(diode) = element {
-            match circuit.models.get(&diode.model_name) {
-                Some(Model::Diode(model)) => {
-                    diode.model = model.clone();
-                }
-                _ => todo!(),
-            }
+    resolve_parameters(&definitions)
+}
+
+/// Collects the node names of the `.global` cards of the netlist, e.g. `.global vdd vss`.
+fn parse_global_cards(input: &str) -> Result<HashSet<String>> {
+    let mut global_nodes = HashSet::new();
+
+    for (line_num, line) in input.lines().enumerate() {
+        let line = line.split('%').next().unwrap_or("").trim();
+        if !is_card(line, ".global") {
+            continue;
         }
-        if let Element::NMOSFET(mosfet) = element {
-            match circuit.models.get(&mosfet.model_name) {
-                Some(Model::NMosfet(model)) => {
-                    mosfet.model = model.clone();
-                }
-                _ => todo!(),
-            }
+
+        let nodes: Vec<&str> = line.split_whitespace().skip(1).collect();
+        if nodes.is_empty() {
+            return Err(Error::ParseError {
+                file: None,
+                line: line_num + 1,
+                message: "Expected at least one node after .global".to_string(),
+                source_line: line.to_string(),
+                column: Some(line.len()),
+            });
         }
+        global_nodes.extend(nodes.into_iter().map(str::to_string));
     }
 
-    // Convert HashSet to Vec for the final Circuit struct if needed
-    circuit.nodes = nodes.into_iter().collect();
-    Ok(circuit)
+    Ok(global_nodes)
 }
 
+/// Parses a netlist file, splicing in the files referenced by `.include` (or `.inc`) cards.
+///
+/// Include paths are resolved relative to the directory of the including file. A
+/// [`Error::ParseError`] names the file the failing line was read from and its line number
+/// within that file.
 pub fn parse_circuit_description_file(file_path: &Path) -> Result<Circuit> {
+    let mut contents = String::new();
+    let mut origins = Vec::new();
+    read_with_includes(file_path, &mut HashSet::new(), &mut contents, &mut origins)?;
+    parse_circuit_description(&contents).map_err(|mut error| {
+        if let Error::ParseError { file, line, .. } = &mut error
+            && let Some((origin_file, origin_line)) = origins.get(line.wrapping_sub(1))
+        {
+            *file = Some(origin_file.clone());
+            *line = *origin_line;
+        }
+        error
+    })
+}
+
+/// Reads a netlist file and recursively replaces its `.include` cards by the contents of
+/// the included files, appending the result to `spliced`. For every appended line, `origins`
+/// receives the file it was read from and its line number within that file. `stack` holds the
+/// canonicalized paths of the files currently being read, so an include cycle is reported
+/// instead of recursing forever.
+fn read_with_includes(
+    file_path: &Path,
+    stack: &mut HashSet<PathBuf>,
+    spliced: &mut String,
+    origins: &mut Vec<(PathBuf, usize)>,
+) -> Result<()> {
+    let canonical_path = file_path
+        .canonicalize()
+        .map_err(|e| Error::Unexpected(format!("{}: {e}", file_path.display())))?;
+    if !stack.insert(canonical_path.clone()) {
+        return Err(Error::InvalidFormat(format!(
+            "Include cycle detected at '{}'",
+            file_path.display()
+        )));
+    }
+
     let file = File::open(file_path).map_err(|e| Error::Unexpected(e.to_string()))?;
     let mut reader = BufReader::new(file);
     let mut contents = String::new();
     reader
         .read_to_string(&mut contents)
         .map_err(|e| Error::Unexpected(e.to_string()))?;
-    parse_circuit_description(&contents)
+
+    let directory = canonical_path.parent().unwrap_or_else(|| Path::new("."));
+    for (line_num, line) in contents.lines().enumerate() {
+        let trimmed = line.split(['%', ';']).next().unwrap_or("").trim();
+        if is_card(trimmed, ".include") || is_card(trimmed, ".inc") {
+            let include_path = trimmed
+                .split_once(char::is_whitespace)
+                .map(|(_, path)| path.trim().trim_matches('"'))
+                .filter(|path| !path.is_empty())
+                .ok_or_else(|| {
+                    Error::InvalidFormat(format!("Missing path in include card '{trimmed}'"))
+                })?;
+            read_with_includes(&directory.join(include_path), stack, spliced, origins)?;
+        } else {
+            spliced.push_str(line);
+            spliced.push('\n');
+            origins.push((file_path.to_path_buf(), line_num + 1));
+        }
+    }
+
+    stack.remove(&canonical_path);
+    Ok(())
 }
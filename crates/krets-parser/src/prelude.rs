@@ -3,16 +3,28 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub use crate::elements::Element;
 pub use crate::elements::bjt::parse_bjt;
 pub use crate::elements::capacitor::parse_capacitor;
+pub use crate::elements::cccs::parse_cccs;
+pub use crate::elements::ccvs::parse_ccvs;
 pub use crate::elements::current_source::parse_current_source;
 pub use crate::elements::diode::parse_diode;
 pub use crate::elements::inductor::parse_inductor;
+pub use crate::elements::mutual_inductance::parse_mutual_inductance;
 pub use crate::elements::nmosfet::parse_nmosfet;
 pub use crate::elements::parse_element;
+pub use crate::elements::pmosfet::parse_pmosfet;
 pub use crate::elements::resistor::parse_resistor;
 pub use crate::elements::subcircuit::parse_subckt_instance;
+pub use crate::elements::switch::parse_switch;
+pub use crate::elements::transmission_line::parse_transmission_line;
+pub use crate::elements::vccs::parse_vccs;
+pub use crate::elements::vcvs::parse_vcvs;
 pub use crate::elements::voltage_source::parse_voltage_source;
 pub use crate::utils::parse_value;
-pub use crate::utils::{alphanumeric_or_underscore1, parse_key_value, value_parser};
+pub use crate::utils::{
+    alphanumeric_or_underscore1, normalize_identifier, parse_element_parameter,
+    parse_initial_conditions, parse_key_value, parse_nodeset, parse_parameters, parse_save,
+    parse_signal, percentage, strip_comment, strip_inline_comment, value_parser,
+};
 pub use nom::combinator::map;
 pub use nom::{
     IResult, Parser,
@@ -1,9 +1,12 @@
 use nom::{
     IResult, Parser,
-    bytes::complete::{is_not, tag, take_while1},
-    character::complete::space0,
+    branch::alt,
+    bytes::complete::{is_not, tag, tag_no_case, take_while1},
+    character::complete::{space0, space1},
     combinator::map_res,
-    sequence::{preceded, separated_pair},
+    multi::many1,
+    number::complete::recognize_float,
+    sequence::{delimited, preceded, separated_pair, terminated},
 };
 
 use crate::prelude::*;
@@ -24,43 +27,62 @@ use crate::prelude::*;
 /// - `G`: giga (1e9)
 /// - `T`: tera (1e12)
 ///
+/// `MEG` takes precedence over `M`, so `4.7meg` is 4.7e6. As in SPICE, any letters following
+/// a magnitude suffix are treated as a unit and ignored (e.g. `1kOhm`). Without a magnitude
+/// suffix only a bare unit name (`ohm`, `v`, `a`, `h`, `hz`, `s`) is accepted.
+///
+/// Every value of a netlist goes through this function (see [`value_parser`]), so tools
+/// reading values entered by a user get the same semantics as the netlist. The whole string
+/// must be a single value; surrounding whitespace is not trimmed.
+///
 /// # Arguments
 /// - `s`: The string slice to parse (e.g., "1.5k", "10u", "1e-6").
 ///
 /// # Returns
 /// - A `Result<f64>` containing the parsed floating-point number, or an `Error`.
 pub fn parse_value(s: &str) -> Result<f64> {
-    let s_upper = s.to_uppercase();
-
-    // Check for a known suffix first. If no suffix is found, the whole string is treated as the number.
-    let (num_part_str, multiplier) = if s_upper.ends_with("MEG") {
-        // "MEG" is a special 3-character case.
-        (&s_upper[..s_upper.len() - 3], 1e6)
-    } else if let Some(last_char) = s_upper.chars().last() {
-        // Check for single-character suffixes.
-        match last_char {
-            'F' => (&s_upper[..s_upper.len() - 1], 1e-15),
-            'P' => (&s_upper[..s_upper.len() - 1], 1e-12),
-            'N' => (&s_upper[..s_upper.len() - 1], 1e-9),
-            'U' => (&s_upper[..s_upper.len() - 1], 1e-6),
-            'M' => (&s_upper[..s_upper.len() - 1], 1e-3),
-            'K' => (&s_upper[..s_upper.len() - 1], 1e3),
-            'G' => (&s_upper[..s_upper.len() - 1], 1e9),
-            'T' => (&s_upper[..s_upper.len() - 1], 1e12),
-            // If the last character is not a known suffix, assume the whole string is the number.
-            _ => (s_upper.as_str(), 1.0),
-        }
+    let invalid = || Error::InvalidFloatValue(format!("Invalid numeric value '{}'", s));
+
+    // Split off the numeric part. `recognize_float` handles signs, decimals and exponents.
+    let (rest, num_part_str) =
+        recognize_float::<&str, nom::error::Error<&str>>(s).map_err(|_| invalid())?;
+
+    // `f64::parse` handles standard float formats, including scientific notation.
+    let base_val: f64 = num_part_str.parse().map_err(|_| invalid())?;
+
+    let rest = rest.to_lowercase();
+
+    // Check for a known suffix. "MEG" is a special 3-character case and must win over "M".
+    let (multiplier, unit) = if let Some(unit) = rest.strip_prefix("meg") {
+        (Some(1e6), unit)
     } else {
-        // Handle empty string case.
-        (s_upper.as_str(), 1.0)
+        let multiplier = match rest.chars().next() {
+            Some('f') => Some(1e-15),
+            Some('p') => Some(1e-12),
+            Some('n') => Some(1e-9),
+            Some('u') => Some(1e-6),
+            Some('m') => Some(1e-3),
+            Some('k') => Some(1e3),
+            Some('g') => Some(1e9),
+            Some('t') => Some(1e12),
+            _ => None,
+        };
+        match multiplier {
+            Some(_) => (multiplier, &rest[1..]),
+            None => (None, rest.as_str()),
+        }
     };
 
-    // `f64::parse` handles standard float formats, including scientific notation.
-    let base_val: f64 = num_part_str
-        .parse()
-        .map_err(|_| Error::InvalidFloatValue(format!("Invalid numeric value '{}'", s)))?;
+    // Whatever remains must be a unit name, which carries no numeric meaning.
+    let valid_unit = match multiplier {
+        Some(_) => unit.chars().all(|c| c.is_ascii_alphabetic()),
+        None => matches!(unit, "" | "ohm" | "v" | "a" | "h" | "hz" | "s"),
+    };
+    if !valid_unit {
+        return Err(invalid());
+    }
 
-    Ok(base_val * multiplier)
+    Ok(base_val * multiplier.unwrap_or(1.0))
 }
 
 /// Parses a string consisting of alphanumeric characters and underscores.
@@ -68,6 +90,16 @@ pub fn alphanumeric_or_underscore1(input: &str) -> IResult<&str, &str> {
     take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_').parse(input)
 }
 
+/// Normalizes a reference to another element (e.g. `vsense`) so that it matches the
+/// format returned by `Element::identifier()`, which always uses an uppercase prefix.
+pub fn normalize_identifier(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
 /// A nom parser that recognizes a value token and parses it using our custom logic.
 pub fn value_parser(input: &str) -> IResult<&str, f64> {
     // 1. Recognize a token (any sequence of chars that isn't a space or parenthesis).
@@ -87,6 +119,142 @@ pub fn parse_key_value(input: &str) -> IResult<&str, (&str, f64)> {
     .parse(input)
 }
 
+/// Parses a percentage such as `5%` into a fraction.
+pub fn percentage(input: &str) -> IResult<&str, f64> {
+    map_res(terminated(recognize_float, tag("%")), |s: &str| {
+        s.parse::<f64>().map(|value| value / 100.0)
+    })
+    .parse(input)
+}
+
+/// Parses a key=value pair of an element, where the value may also be a percentage such as
+/// `tol=5%`.
+pub fn parse_element_parameter(input: &str) -> IResult<&str, (&str, f64)> {
+    separated_pair(
+        alphanumeric_or_underscore1,
+        preceded(space0, tag("=")),
+        preceded(space0, alt((percentage, value_parser))),
+    )
+    .parse(input)
+}
+
+/// Strips a trailing `;` or `%` comment from an element line. A `%` ending the value of a
+/// key=value pair, as in `tol=5%`, is a percentage rather than the start of a comment.
+pub fn strip_comment(line: &str) -> &str {
+    let line = strip_inline_comment(line);
+    let is_percentage = |i: usize| {
+        let before = &line[..i];
+        let token_start = before.rfind(char::is_whitespace).map_or(0, |j| j + 1);
+        let (key, value) = match before[token_start..].split_once('=') {
+            Some((_, value)) => (true, value),
+            None => (
+                before[..token_start].trim_end().ends_with('='),
+                &before[token_start..],
+            ),
+        };
+        key && value.ends_with(|c: char| c.is_ascii_digit() || c == '.')
+    };
+    let comment_start = line
+        .char_indices()
+        .find(|&(i, c)| c == '%' && !is_percentage(i))
+        .map_or(line.len(), |(i, _)| i);
+    line[..comment_start].trim()
+}
+
+/// Strips a trailing `;` comment, which may follow any token of any card.
+pub fn strip_inline_comment(line: &str) -> &str {
+    line.split(';').next().unwrap_or("")
+}
+
+/// Parses an initial condition card such as `.ic V(out)=1.5 V(in)=0`.
+///
+/// The node voltages are returned keyed like the MNA index map, e.g. `V(out)`.
+pub fn parse_initial_conditions(input: &str) -> IResult<&str, Vec<(String, f64)>> {
+    let (input, _) = tag_no_case(".ic").parse(input)?;
+    node_voltages(input)
+}
+
+/// Parses a nodeset card such as `.nodeset V(q)=5 V(qb)=0`, keyed like `parse_initial_conditions`.
+pub fn parse_nodeset(input: &str) -> IResult<&str, Vec<(String, f64)>> {
+    let (input, _) = tag_no_case(".nodeset").parse(input)?;
+    node_voltages(input)
+}
+
+/// Parses a signal, `V(node)` or `I(element)`, keyed like the MNA index map.
+pub fn parse_signal(input: &str) -> IResult<&str, String> {
+    alt((
+        map(
+            preceded(
+                tag_no_case("V"),
+                delimited(tag("("), alphanumeric_or_underscore1, tag(")")),
+            ),
+            |node| format!("V({node})"),
+        ),
+        map(
+            preceded(
+                tag_no_case("I"),
+                delimited(tag("("), alphanumeric_or_underscore1, tag(")")),
+            ),
+            |element| format!("I({})", normalize_identifier(element)),
+        ),
+    ))
+    .parse(input)
+}
+
+/// Parses a save card such as `.save V(out) I(R1)` (or `.probe`) into the saved signals.
+pub fn parse_save(input: &str) -> IResult<&str, Vec<String>> {
+    let (input, _) = alt((tag_no_case(".save"), tag_no_case(".probe"))).parse(input)?;
+    let (input, signals) = many1(preceded(space1, parse_signal)).parse(input)?;
+    let (input, _) = space0(input)?;
+    Ok((input, signals))
+}
+
+/// Parses the `V(node)=value` pairs following an `.ic` or `.nodeset` card.
+fn node_voltages(input: &str) -> IResult<&str, Vec<(String, f64)>> {
+    let (input, voltages) = many1(preceded(
+        space1,
+        separated_pair(
+            delimited(tag_no_case("V("), alphanumeric_or_underscore1, tag(")")),
+            (space0, tag("="), space0),
+            value_parser,
+        ),
+    ))
+    .parse(input)?;
+    let (input, _) = space0(input)?;
+
+    let voltages = voltages
+        .into_iter()
+        .map(|(node, value)| (format!("V({node})"), value))
+        .collect();
+
+    Ok((input, voltages))
+}
+
+/// Parses a parameter card such as `.param rload=1k gain={rload/100}`.
+///
+/// The definitions are returned unevaluated, with the parameter names lowercased and any
+/// braces around the expression removed, since parameters may reference each other.
+pub fn parse_parameters(input: &str) -> IResult<&str, Vec<(String, String)>> {
+    let (input, _) = tag_no_case(".param").parse(input)?;
+    let (input, definitions) = many1(preceded(
+        space1,
+        separated_pair(
+            alphanumeric_or_underscore1,
+            (space0, tag("="), space0),
+            alt((delimited(tag("{"), is_not("}"), tag("}")), is_not(" \t"))),
+        ),
+    ))
+    .parse(input)?;
+    let (input, _) = space0(input)?;
+
+    let definitions = definitions
+        .into_iter()
+        .map(|(name, expression)| (name.to_lowercase(), expression.trim().to_string()))
+        .collect();
+
+    Ok((input, definitions))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,7 +273,91 @@ mod tests {
         assert!((parse_value("1e-6").unwrap() - 1e-6).abs() < epsilon);
         assert!((parse_value("1.23E-9").unwrap() - 1.23e-9).abs() < epsilon);
 
+        // Units after a magnitude suffix are ignored, and "MEG" wins over "M".
+        assert!((parse_value("1kOhm").unwrap() - 1000.0).abs() < epsilon);
+        assert!((parse_value("4.7meg").unwrap() - 4.7e6).abs() < 1e-9);
+        assert!((parse_value("2.2MegOhm").unwrap() - 2.2e6).abs() < 1e-9);
+        assert!((parse_value("5m").unwrap() - 5e-3).abs() < epsilon);
+        assert!((parse_value("100ohm").unwrap() - 100.0).abs() < epsilon);
+        assert!((parse_value("-3V").unwrap() - -3.0).abs() < epsilon);
+
         assert!(parse_value("1.5x").is_err());
+        assert!(parse_value("1k2").is_err());
+        assert!(parse_value("").is_err());
         assert!(parse_value("garbage").is_err());
     }
+
+    #[test]
+    fn test_parse_element_parameter() {
+        assert_eq!(parse_element_parameter("tol=5%"), Ok(("", ("tol", 0.05))));
+        assert_eq!(
+            parse_element_parameter("tol = 1e-2"),
+            Ok(("", ("tol", 0.01)))
+        );
+        assert_eq!(parse_element_parameter("tc1=1m"), Ok(("", ("tc1", 1e-3))));
+        assert!(parse_element_parameter("tol=").is_err());
+    }
+
+    #[test]
+    fn test_strip_comment() {
+        assert_eq!(
+            strip_comment("R1 1 0 1k tol=5% % comment"),
+            "R1 1 0 1k tol=5%"
+        );
+        assert_eq!(strip_comment("R1 1 0 1k tol = 5%"), "R1 1 0 1k tol = 5%");
+        assert_eq!(strip_comment("R1 1 0 1k%comment"), "R1 1 0 1k");
+        assert_eq!(strip_comment("C1 1 0 1e-6%comment"), "C1 1 0 1e-6");
+        assert_eq!(strip_comment("% comment"), "");
+        assert_eq!(strip_comment(" R1 1 0 1k "), "R1 1 0 1k");
+        assert_eq!(strip_comment("R1 1 0 1k ; load"), "R1 1 0 1k");
+        assert_eq!(
+            strip_comment("R1 1 0 1k tol=5%; 5% % part"),
+            "R1 1 0 1k tol=5%"
+        );
+        assert_eq!(strip_comment("R1 1 0 1k % 50; 50"), "R1 1 0 1k");
+    }
+
+    #[test]
+    fn test_parse_initial_conditions() {
+        let (rest, conditions) = parse_initial_conditions(".ic V(out)=1.5 v(in) = 2m").unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            conditions,
+            vec![("V(out)".to_string(), 1.5), ("V(in)".to_string(), 2e-3)]
+        );
+    }
+
+    #[test]
+    fn test_parse_nodeset() {
+        let (rest, nodeset) = parse_nodeset(".NODESET V(q)=5 v(qb) = 0").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            nodeset,
+            vec![("V(q)".to_string(), 5.0), ("V(qb)".to_string(), 0.0)]
+        );
+        assert!(parse_nodeset(".nodeset").is_err());
+    }
+
+    #[test]
+    fn test_parse_initial_conditions_invalid() {
+        assert!(parse_initial_conditions(".ic").is_err());
+        assert!(parse_initial_conditions(".ic I(V1)=1").is_err());
+        assert!(parse_initial_conditions(".ic V(out)").is_err());
+    }
+
+    #[test]
+    fn test_parse_parameters() {
+        let (rest, definitions) =
+            parse_parameters(".param Rload=1k gain = { rload / 10 }").unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            definitions,
+            vec![
+                ("rload".to_string(), "1k".to_string()),
+                ("gain".to_string(), "rload / 10".to_string())
+            ]
+        );
+        assert!(parse_parameters(".param").is_err());
+        assert!(parse_parameters(".param rload").is_err());
+    }
 }
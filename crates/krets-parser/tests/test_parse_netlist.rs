@@ -1,6 +1,25 @@
 #[cfg(test)]
 mod tests {
-    use krets_parser::{elements::Element, parser::parse_circuit_description, prelude::*};
+    use krets_parser::{
+        elements::Element,
+        models::Model,
+        parser::{
+            parse_circuit_description, parse_circuit_description_file,
+            parse_circuit_description_with_ground_aliases,
+        },
+        prelude::*,
+    };
+    use std::{env, path::Path};
+
+    // Function to get the circuits directory path
+    fn circuits_dir() -> std::path::PathBuf {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+        Path::new(&manifest_dir)
+            .parent() // Go up from crates/krets-parser
+            .and_then(Path::parent) // Go up from crates
+            .unwrap()
+            .join("circuits")
+    }
 
     #[test]
     fn test_parse_empty_netlist() {
@@ -36,6 +55,22 @@ R8 4 0 10";
         assert_eq!(circuit.nodes.len(), 9);
     }
 
+    #[test]
+    fn test_circuit_sizing() {
+        let netlist = "V1 in 0 1\nR1 in out 1k\nR2 out 0 2k\nI1 0 out 1m\nC1 out gnd 1u\n";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        // `gnd` is an alias of ground, so only `in` and `out` are nodes.
+        assert_eq!(circuit.node_count(), 2);
+        assert_eq!(circuit.branch_count(), 2);
+        assert_eq!(circuit.mna_size(), 4);
+        assert_eq!(circuit.mna_size(), circuit.index_map.len());
+        assert_eq!(
+            circuit.unknowns(),
+            vec!["I(V1)", "V(in)", "V(out)", "I(I1)"]
+        );
+    }
+
     #[test]
     fn test_parse_rectifier() {
         let netlist = "
@@ -100,4 +135,760 @@ r0 plus minus 1k
 
         assert_eq!(circuit.elements.len(), 2);
     }
+
+    #[test]
+    fn test_semicolon_comments() {
+        let netlist = "
+* A full line comment; with a semicolon
+V1 in 0 DC 1 ; supply
+R1 in out 1k ; load
+  * an indented full line comment
+D1 out 0 DSTAR; the *fast* one
+.model DSTAR D (is=1e-14 ; saturation current
++ n=2) ; emission coefficient on the continuation line
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        assert_eq!(circuit.elements.len(), 3);
+        let diode = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::Diode(diode) => Some(diode),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(diode.model_name, "DSTAR");
+        assert_eq!(diode.model.saturation_current, 1e-14);
+        assert_eq!(diode.model.emission_coefficient, 2.0);
+    }
+
+    #[test]
+    fn test_continuation_lines() {
+        let netlist = "
+V1 in 0 DC 1
+D1 in mid DMOD
+X1 mid out
+* a comment between continuation lines
++ rc R=2k % the load
+.model DMOD D (is=1e-9
++ n=1.5 % emission coefficient
+
++   rs=10)
+.subckt rc a b R=1k
+R1 a b {R}
+R2 b 0 1k
+.ends
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        let diode = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::Diode(diode) => Some(diode),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(diode.model.saturation_current, 1e-9);
+        assert_eq!(diode.model.emission_coefficient, 1.5);
+        assert_eq!(diode.model.parasitic_resistance, 10.0);
+
+        let load = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::Resistor(r) if r.identifier() == "R1_1" => Some(r.value),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(load, 2e3);
+    }
+
+    #[test]
+    fn test_parse_error_points_at_column() {
+        let error = parse_circuit_description("V1 in 0 1\nR1 in 0 abc % load\n").unwrap_err();
+        assert!(matches!(
+            error,
+            Error::ParseError {
+                line: 2,
+                column: Some(8),
+                ..
+            }
+        ));
+
+        let message = error.to_string();
+        assert!(message.starts_with("Parse error on line 2: "), "{message}");
+        assert!(
+            message.ends_with("\n  |\n2 | R1 in 0 abc % load\n  |         ^"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_names_the_attempted_element() {
+        let error = parse_circuit_description("V1 in 0 1\nR1 in 0 abc\n").unwrap_err();
+        assert!(matches!(error, Error::ParseError { line: 2, .. }));
+        let message = error.to_string();
+        assert!(
+            message.starts_with("Parse error on line 2: invalid resistor 'R1': "),
+            "{message}"
+        );
+
+        let error = krets_parser::elements::parse_element("R1 in 0").unwrap_err();
+        assert!(
+            matches!(&error, Error::InvalidElementFormat(message) if message.starts_with("invalid resistor 'R1'")),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_misspelled_resistor_names_the_resistor() {
+        // Anything may follow the device letter, so the name alone is no typo.
+        let element = krets_parser::elements::parse_element("Rr1 1 0 1k").unwrap();
+        assert!(matches!(element, Element::Resistor(_)));
+
+        let error = parse_circuit_description("V1 1 0 1\nRr1 1 0 k1\n").unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.starts_with("Parse error on line 2: invalid resistor 'Rr1': "),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_device_letter() {
+        let error = parse_circuit_description("V1 in 0 1\nZ1 in 0 1k\n").unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.starts_with(
+                "Parse error on line 2: unknown element type 'Z' of 'Z1', expected one of R, C, L, V, I, D, Q, M, X, "
+            ),
+            "{message}"
+        );
+
+        let error = krets_parser::elements::parse_element("p1 in 0 1u").unwrap_err();
+        assert!(
+            matches!(&error, Error::UnknownElementType(message) if message.starts_with("unknown element type 'P' of 'p1', expected one of R, C, L, ")),
+            "{error}"
+        );
+        let error = krets_parser::elements::parse_element("1R in 0 1k").unwrap_err();
+        assert!(
+            matches!(&error, Error::UnknownElementType(message) if message.starts_with("'1R' does not start with a known element letter, expected one of R, ")),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_continuation_keeps_line_numbers() {
+        let netlist = "V1 in 0\n+ DC 1\nR1 in 0 abc\n";
+        let result = parse_circuit_description(netlist);
+        assert!(matches!(result, Err(Error::ParseError { line: 3, .. })));
+    }
+
+    #[test]
+    fn test_parameterized_subckt() {
+        let netlist = "
+V1 in 0 DC 1
+X1 in mid rc R=2k
+X2 mid out rc r={rload * 2}
+X3 out 0 rc
+.param rload=5k
+
+.subckt rc a b R=1k C=1u
+R1 a b {R}
+C1 b 0 {C / 2}
+.ends
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        let value = |identifier: &str| {
+            circuit
+                .elements
+                .iter()
+                .find_map(|element| match element {
+                    Element::Resistor(r) if r.identifier() == identifier => Some(r.value),
+                    Element::Capacitor(c) if c.identifier() == identifier => Some(c.value),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(value("R1_1"), 2e3);
+        assert_eq!(value("R2_1"), 10e3);
+        assert_eq!(value("R3_1"), 1e3);
+        assert_eq!(value("C1_1"), 0.5e-6);
+    }
+
+    #[test]
+    fn test_subckt_local_model() {
+        let netlist = "
+V1 in 0 1
+D1 in a DMOD
+X1 a out clamp
+X2 a out clamp
+.model DMOD D (is=1e-12)
+.model DSW D (is=1e-10)
+.subckt clamp a b
+D1 a b DMOD
+D2 b 0 DSW
+.model DMOD D (is=1e-15 n=2)
+.ends
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        let diode = |identifier: &str| {
+            circuit
+                .elements
+                .iter()
+                .find_map(|element| match element {
+                    Element::Diode(d) if d.identifier() == identifier => Some(d),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        // The top-level diode keeps the top-level model.
+        assert_eq!(diode("D1").model.saturation_current, 1e-12);
+        // Internal diodes use the local model, one copy per instance.
+        for instance in ["1", "2"] {
+            let local = diode(&format!("D{instance}_1"));
+            assert_eq!(local.model_name, format!("{instance}_DMOD"));
+            assert_eq!(local.model.saturation_current, 1e-15);
+            assert_eq!(local.model.emission_coefficient, 2.0);
+            assert!(circuit.models.contains_key(&local.model_name));
+        }
+        // Models not declared in the subcircuit fall back to the top-level ones.
+        assert_eq!(diode("D1_2").model_name, "DSW");
+        assert_eq!(diode("D1_2").model.saturation_current, 1e-10);
+    }
+
+    #[test]
+    fn test_global_and_ground_nodes_in_subckt() {
+        let netlist = "
+VDD vdd 0 DC 5
+X1 in out buf
+.global vdd
+.subckt buf a b
+R1 vdd b 1k
+R2 b gnd 1k
+R3 a mid 1k
+.ends
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        let mut nodes = circuit.nodes.clone();
+        nodes.sort();
+        assert_eq!(nodes, vec!["0", "1_mid", "in", "out", "vdd"]);
+        assert!(!circuit.index_map.contains_key("V(gnd)"));
+    }
+
+    #[test]
+    fn test_custom_ground_aliases() {
+        let netlist = "V1 in vss 1\nR1 in gnd 1k\n";
+        let circuit = parse_circuit_description_with_ground_aliases(netlist, &["vss"]).unwrap();
+
+        assert!(!circuit.index_map.contains_key("V(vss)"));
+        assert!(circuit.index_map.contains_key("V(gnd)"));
+    }
+
+    #[test]
+    fn test_parse_empty_global_card() {
+        assert!(matches!(
+            parse_circuit_description("R1 a 0 1k\n.global\n"),
+            Err(Error::ParseError { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_duplicate_element() {
+        let circuit = parse_circuit_description("V1 in 0 1\nR1 in 0 1k\nR1 in 0 2k\n").unwrap();
+        let findings = circuit.validate().unwrap_err();
+
+        assert_eq!(findings.len(), 1);
+        assert!(matches!(&findings[0], Error::DuplicateElement(name) if name == "R1"));
+    }
+
+    #[test]
+    fn test_validate_shorted_source_loop() {
+        let circuit = parse_circuit_description(
+            "V1 a 0 1\nV2 a 0 2\nE1 b a a 0 2\nV3 b 0 1\nR1 b 0 1k\nR2 c a 1k\n",
+        )
+        .unwrap();
+        let findings = circuit.validate().unwrap_err();
+
+        let loops: Vec<&str> = findings
+            .iter()
+            .filter_map(|finding| match finding {
+                Error::VoltageSourceLoop(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(loops, vec!["V2", "V3"]);
+        assert!(
+            findings
+                .iter()
+                .any(|finding| matches!(finding, Error::DanglingNode(node) if node == "c"))
+        );
+    }
+
+    #[test]
+    fn test_validate_shorted_by_zero_ohm_resistor() {
+        let circuit = parse_circuit_description("V1 a 0 1\nR1 a b 0\nR2 b 0 0\n").unwrap();
+        let findings = circuit.validate().unwrap_err();
+        assert!(matches!(findings.as_slice(), [Error::VoltageSourceLoop(name)] if name == "R2"));
+    }
+
+    #[test]
+    fn test_validate_unconnected_pin() {
+        let circuit = parse_circuit_description(
+            "V1 in 0 1\nX1 in out 0 div\nR1 out 0 1k\n.subckt div a b c\nR1 a b 1k\n.ends\n",
+        )
+        .unwrap();
+        let findings = circuit.validate().unwrap_err();
+
+        assert_eq!(findings.len(), 1);
+        assert!(matches!(
+            &findings[0],
+            Error::UnconnectedPin { subcircuit, pin } if subcircuit == "div" && pin == "c"
+        ));
+
+        let circuit = parse_circuit_description_file(
+            &circuits_dir().join("voltage_divider/voltage_divider.cir"),
+        )
+        .unwrap();
+        assert!(circuit.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parameterized_subckt_unknown_parameter() {
+        let netlist = "
+X1 in out rc L=2k
+.subckt rc a b R=1k
+R1 a b {R}
+.ends
+";
+        assert!(matches!(
+            parse_circuit_description(netlist),
+            Err(Error::InvalidFormat(message)) if message.contains("'l'")
+        ));
+    }
+
+    #[test]
+    fn test_parse_mutual_inductance() {
+        let netlist = "
+V1 in 0 AC 1
+L1 in 0 1m
+L2 out 0 4m
+K1 L1 L2 0.5
+R1 out 0 1k
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        let coupling = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::MutualInductance(k) => Some(k),
+                _ => None,
+            })
+            .unwrap();
+        assert!((coupling.inductance - 1e-3).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_parse_mutual_inductance_unknown_inductor() {
+        let netlist = "
+L1 in 0 1m
+K1 L1 L2 0.5
+";
+        let result = parse_circuit_description(netlist);
+        assert!(matches!(result, Err(Error::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_parse_mutual_inductance_invalid_coupling() {
+        let netlist = "
+L1 in 0 1m
+L2 out 0 1m
+K1 L1 L2 1.2
+";
+        let result = parse_circuit_description(netlist);
+        assert!(matches!(result, Err(Error::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_parse_initial_conditions_card() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in out 1k
+C1 out 0 1u
+.ic V(out)=0.5 V(in)=0 % preset the capacitor
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        assert_eq!(circuit.elements.len(), 3);
+        assert_eq!(circuit.initial_conditions.len(), 2);
+        assert_eq!(circuit.initial_conditions["V(out)"], 0.5);
+    }
+
+    #[test]
+    fn test_parse_save_cards() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in out 1k
+C1 out 0 1u
+.save V(out) i(v1) % keep the output
+.probe I(R1)
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        assert_eq!(circuit.saves, vec!["V(out)", "I(V1)", "I(R1)"]);
+
+        let reparsed = parse_circuit_description(&circuit.to_netlist_string()).unwrap();
+        assert_eq!(reparsed.saves, circuit.saves);
+
+        assert!(parse_circuit_description("R1 in 0 1k\n.save\n").is_err());
+        assert!(parse_circuit_description("R1 in 0 1k\n.save out\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_measure_cards() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in out 1k
+C1 out 0 1u
+.measure tran vmax MAX V(out) FROM=1m % skip the start-up
+.meas tran trise RISE V(out) 10% 90%
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        assert_eq!(circuit.elements.len(), 3);
+        assert_eq!(circuit.measurements.len(), 2);
+        assert_eq!(circuit.measurements[0].name, "vmax");
+        assert_eq!(circuit.measurements[0].from, Some(1e-3));
+        assert_eq!(circuit.measurements[1].target, "V(out)");
+    }
+
+    #[test]
+    fn test_parse_invalid_measure_card() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in 0 1k
+.measure tran vmax MAX V(in) AT=1m
+";
+        assert!(matches!(
+            parse_circuit_description(netlist),
+            Err(Error::ParseError { line: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_parameterized_netlist() {
+        let netlist = "
+V1 in 0 DC {vsupply / 2}
+R1 in out {rload*2}
+R2 out 0 {(rload + rbias) / 2}
+* Parameters may be defined after their use and reference each other.
+.param rload=1k
+.param rbias={rload * 3} vsupply=10
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        assert_eq!(circuit.parameters["rbias"], 3e3);
+
+        let value = |identifier: &str| {
+            circuit
+                .elements
+                .iter()
+                .find_map(|element| match element {
+                    Element::Resistor(r) if r.identifier() == identifier => Some(r.value),
+                    Element::VoltageSource(v) if v.identifier() == identifier => Some(v.dc_value),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(value("R1"), 2e3);
+        assert_eq!(value("R2"), 2e3);
+        assert_eq!(value("V1"), 5.0);
+    }
+
+    #[test]
+    fn test_parse_unknown_parameter() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in 0 {rload}
+.param rl=1k
+";
+        assert!(matches!(
+            parse_circuit_description(netlist),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_include_library() {
+        let path = circuits_dir().join("diode_include/diode_include.cir");
+        let circuit = parse_circuit_description_file(&path).unwrap();
+
+        let diode = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::Diode(d) => Some(d),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(diode.model.saturation_current, 2e-14);
+        assert_eq!(diode.model.emission_coefficient, 1.5);
+
+        // V1, R1, D1 and the two resistors of the included subcircuit.
+        assert_eq!(circuit.elements.len(), 5);
+    }
+
+    #[test]
+    fn test_parse_include_cycle() {
+        let path = circuits_dir().join("include_cycle/a.cir");
+        assert!(matches!(
+            parse_circuit_description_file(&path),
+            Err(Error::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_after_include() {
+        let path = circuits_dir().join("include_error/bad_after_include.cir");
+        let error = parse_circuit_description_file(&path).unwrap_err();
+        let Error::ParseError { file, line, .. } = &error else {
+            panic!("{error}");
+        };
+        assert_eq!(file.as_deref(), Some(path.as_path()));
+        assert_eq!(*line, 4);
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("Parse error in {} on line 4: ", path.display())),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_inside_include() {
+        let path = circuits_dir().join("include_error/bad_in_include.cir");
+        let error = parse_circuit_description_file(&path).unwrap_err();
+        let Error::ParseError { file, line, .. } = &error else {
+            panic!("{error}");
+        };
+        assert!(file.as_ref().unwrap().ends_with("lib/bad.lib"), "{error}");
+        assert_eq!(*line, 3);
+        assert!(error.to_string().contains("bad.lib on line 3: "), "{error}");
+    }
+
+    #[test]
+    fn test_parse_temperature_card() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in 0 1k tc1=1m
+.temp 125 % hot corner
+";
+        let mut circuit = parse_circuit_description(netlist).unwrap();
+        assert_eq!(circuit.temperature, Some(125.0));
+
+        circuit.set_temperature(125.0);
+        let resistor = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::Resistor(r) => Some(r),
+                _ => None,
+            })
+            .unwrap();
+        assert!((resistor.resistance() - 1000.0 * (1.0 + 1e-3 * 98.15)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_options_card() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in 0 1k
+.options reltol=1e-4 itl1=200 post
+.option GMIN=1p
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        assert_eq!(circuit.options.reltol, Some(1e-4));
+        assert_eq!(circuit.options.itl1, Some(200));
+        assert_eq!(circuit.options.gmin, Some(1e-12));
+        assert_eq!(circuit.options.abstol, None);
+        assert_eq!(circuit.options.vntol, None);
+
+        let reparsed = parse_circuit_description(&circuit.to_netlist_string()).unwrap();
+        assert_eq!(reparsed.options, circuit.options);
+
+        assert!(parse_circuit_description("R1 in 0 1k\n.options reltol=\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_bjt_model() {
+        let netlist = "
+VCC vcc 0 5
+RB vcc b 100k
+RC vcc c 1k
+QN1 c b 0 QMOD
+QN2 c b 0 0.7
+.model QMOD NPN (BF=100 IS=1e-15 VAF=60)
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        let bjt = |identifier: &str| {
+            circuit
+                .elements
+                .iter()
+                .find_map(|element| match element {
+                    Element::BJT(q) if q.identifier() == identifier => Some(q),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        assert_eq!(bjt("Q1").model.forward_beta, 100.0);
+        assert_eq!(bjt("Q1").model.saturation_current, 1e-15);
+        assert_eq!(bjt("Q1").model.forward_early_voltage, 60.0);
+        assert_eq!(bjt("Q1").model.name, "QMOD");
+        // The legacy value is still accepted and leaves the default model in place.
+        assert_eq!(bjt("Q2").value, Some(0.7));
+        assert_eq!(bjt("Q2").model.name, "");
+
+        assert!(matches!(
+            parse_circuit_description("QN1 c b 0 QMOD\nR1 c 0 1k\n"),
+            Err(Error::UndefinedModel(name)) if name == "QMOD"
+        ));
+        assert!(matches!(
+            parse_circuit_description("QP1 c b 0 QMOD\nR1 c 0 1k\n.model QMOD NPN (BF=100)\n"),
+            Err(Error::InvalidModelType(_))
+        ));
+    }
+
+    #[test]
+    fn test_subckt_local_bjt_model() {
+        let netlist = "
+VCC vcc 0 5
+X1 vcc in amp
+RB vcc in 100k
+.model QMOD NPN (BF=100)
+.subckt amp vcc b
+QN1 vcc b 0 QMOD
+.model QMOD NPN (BF=250)
+.ends
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        let bjt = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::BJT(q) => Some(q),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(bjt.identifier(), "Q1_1");
+        assert_eq!(bjt.model_name.as_deref(), Some("1_QMOD"));
+        assert_eq!(bjt.model.forward_beta, 250.0);
+        assert!(matches!(circuit.models["1_QMOD"], Model::Npn(_)));
+    }
+
+    #[test]
+    fn test_netlist_round_trip() {
+        let netlist = "* Every element type
+V1 in 0 DC 5 AC 1
+V2 clk 0 PULSE(0 5 1n 1n 1n 5u 10u)
+V3 sig 0 SIN(0 1 1k 0 0 0)
+I1 0 n1 1m
+R1 in n1 1k tc1=1e-3
+C1 n1 0 1u ic=2.5 G2
+L1 n1 n2 1m ic=1m
+L2 n2 0 2m
+K1 L1 L2 0.9
+D1 n2 0 DMOD
+QN1 c b 0 100
+QP2 0 b c QMOD
+RC in c 10k
+RB in b 100k
+MN1 d in 0 0 NMOD w=2u l=1u m=2
+MP2 d in in in PMOD
+RD in d 1k
+E1 e 0 in 0 2
+G1 0 g in 0 1m
+RG g 0 1k
+H1 h 0 V1 10
+F1 0 f V1 5
+RF f 0 1k
+S1 in s clk 0 SMOD
+RS s 0 1k
+W1 in w V1 WMOD
+RW w 0 1k
+.model DMOD D (is=1e-14 n=1.5)
+.model NMOD NMOS (kp=100u vto=1.0 lambda=0.01)
+.model PMOD PMOS (kp=50u vto=-1.0)
+.model SMOD SW (vt=2.5 vh=0.5)
+.model WMOD CSW (it=1m ron=10)
+.model QMOD PNP (bf=50 is=2e-15 vaf=80)
+.ic V(n1)=1.5
+.temp 50
+";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        let serialized = circuit.to_netlist_string();
+        let reparsed = parse_circuit_description(&serialized).unwrap();
+
+        assert_eq!(reparsed.elements.len(), circuit.elements.len());
+        assert_eq!(reparsed.index_map, circuit.index_map);
+        assert_eq!(reparsed.models, circuit.models);
+        assert_eq!(reparsed.initial_conditions, circuit.initial_conditions);
+        assert_eq!(reparsed.temperature, circuit.temperature);
+        for (element, reparsed_element) in circuit.elements.iter().zip(&reparsed.elements) {
+            assert_eq!(
+                element.to_netlist_line(),
+                reparsed_element.to_netlist_line()
+            );
+        }
+
+        // Serialization is stable once round-tripped.
+        assert_eq!(reparsed.to_netlist_string(), serialized);
+    }
+
+    #[test]
+    fn test_node_order_is_deterministic() {
+        let netlist = "V1 in 0 5\nR1 in mid 1k\nR2 mid out 1k\nR3 out 0 1k\nC1 mid 0 1u\n";
+        let first = parse_circuit_description(netlist).unwrap();
+        let second = parse_circuit_description(netlist).unwrap();
+
+        assert_eq!(first.index_map, second.index_map);
+        assert_eq!(first.nodes, second.nodes);
+        // Nodes are listed in the order they first appear.
+        assert_eq!(first.nodes, vec!["in", "0", "mid", "out"]);
+        assert_eq!(first.index_map["I(V1)"], 0);
+        assert_eq!(first.index_map["V(in)"], 1);
+        assert_eq!(first.index_map["V(mid)"], 2);
+        assert_eq!(first.index_map["V(out)"], 3);
+    }
+
+    #[test]
+    fn test_typed_element_accessors() {
+        let netlist = "V1 in 0 5\nV2 aux 0 1\nI1 0 mid 1m\nR1 in mid 1k\nR2 mid out 1k G2\n\
+                       R3 out 0 1k\nL1 aux 0 1m\nC1 mid 0 1u\nD1 out 0 DMOD\n\
+                       .model DMOD D (is=1e-9)\n";
+        let circuit = parse_circuit_description(netlist).unwrap();
+
+        let sources: Vec<String> = circuit.voltage_sources().map(|v| v.identifier()).collect();
+        assert_eq!(sources, ["V1", "V2"]);
+        assert_eq!(circuit.current_sources().count(), 1);
+        assert_eq!(circuit.resistors().count(), 3);
+        assert_eq!(circuit.resistors().filter(|r| r.g2).count(), 1);
+
+        // The voltage sources, the current source, the group 2 resistor and the inductor.
+        let g2: Vec<String> = circuit.g2_elements().map(Element::identifier).collect();
+        assert_eq!(g2, ["V1", "V2", "I1", "R2", "L1"]);
+        assert_eq!(g2.len(), circuit.branch_count());
+
+        let nonlinear: Vec<String> = circuit
+            .nonlinear_elements()
+            .map(Element::identifier)
+            .collect();
+        assert_eq!(nonlinear, ["D1"]);
+    }
 }
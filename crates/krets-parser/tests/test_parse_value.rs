@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::{
+        elements::Element, error::Error, parser::parse_circuit_description, utils::parse_value,
+    };
+
+    /// Returns the value of `R1` in a netlist where it is written as `value`.
+    fn netlist_value(value: &str) -> f64 {
+        let circuit = parse_circuit_description(&format!("V1 a 0 1\nR1 a 0 {value}\n")).unwrap();
+        circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::Resistor(r) => Some(r.value),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_value_matches_netlist() {
+        for (input, expected) in [
+            ("100", 100.0),
+            ("0.5", 0.5),
+            ("1e3", 1e3),
+            ("2.2E-6", 2.2e-6),
+            ("4.7k", 4.7e3),
+            ("10u", 10e-6),
+            ("3.3MEG", 3.3e6),
+            ("1kOhm", 1e3),
+            ("47ohm", 47.0),
+        ] {
+            let value = parse_value(input).unwrap();
+            assert!(
+                (value - expected).abs() <= 1e-12 * expected,
+                "{input} parsed as {value}"
+            );
+            assert_eq!(value, netlist_value(input), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_value_rejects_malformed_input() {
+        for input in ["", "k", "4.7x", "1k2", " 4.7k", "4.7k ", "1e", "--1"] {
+            match parse_value(input) {
+                Err(Error::InvalidFloatValue(message)) => {
+                    assert!(message.contains(&format!("'{input}'")), "{message}")
+                }
+                result => panic!("{input:?} parsed as {result:?}"),
+            }
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use arrow::array::{ArrayRef, Float64Array, RecordBatch};
+use arrow::datatypes::{DataType, Field, Schema};
 use faer::c64;
+use krets_solver::AnalysisResult;
 use log::info;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::f64::consts::PI;
 use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
+use std::sync::Arc;
 
 /// Ensures the filename ends with `.parquet`
 fn ensure_parquet_extension(filename: &str) -> String {
@@ -16,29 +23,122 @@ fn ensure_parquet_extension(filename: &str) -> String {
     }
 }
 
+/// Ensures the filename ends with `.csv`
+fn ensure_csv_extension(filename: &str) -> String {
+    let path = Path::new(filename);
+    if path.extension().and_then(|e| e.to_str()) == Some("csv") {
+        filename.to_string()
+    } else {
+        format!("{filename}.csv")
+    }
+}
+
+/// Ensures the filename ends with `.json`
+fn ensure_json_extension(filename: &str) -> String {
+    let path = Path::new(filename);
+    if path.extension().and_then(|e| e.to_str()) == Some("json") {
+        filename.to_string()
+    } else {
+        format!("{filename}.json")
+    }
+}
+
+/// Collects the unique keys of all rows in sorted order, with `first` moved to the front if present.
+fn sorted_headers<V>(data: &[HashMap<String, V>], first: Option<&str>) -> Vec<String> {
+    let mut all_headers = data
+        .iter()
+        .flat_map(|row| row.keys().cloned())
+        .collect::<std::collections::HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    all_headers.sort();
+
+    if let Some(first) = first
+        && let Some(pos) = all_headers.iter().position(|h| h == first)
+    {
+        let header = all_headers.remove(pos);
+        all_headers.insert(0, header);
+    }
+    all_headers
+}
+
+/// Writes a header row followed by one comma-separated line per row. Missing values are
+/// written as empty cells.
+fn write_csv(filename: &str, headers: &[String], rows: &[Vec<Option<f64>>]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    writeln!(writer, "{}", headers.join(","))?;
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| cell.map(|v| v.to_string()).unwrap_or_default())
+            .collect();
+        writeln!(writer, "{}", cells.join(","))?;
+    }
+    writer.flush()
+}
+
+/// Converts a complex value to its magnitude and phase in degrees.
+fn magnitude_phase_deg(v: &c64) -> (f64, f64) {
+    let mag = (v.re * v.re + v.im * v.im).sqrt();
+    let phase = v.im.atan2(v.re) * 180.0 / PI;
+    (mag, phase)
+}
+
+/// Returns the group delay `-dφ/dω` in seconds of `signal` at every point of an AC sweep.
+///
+/// The phase is unwrapped across adjacent frequency points before it is differentiated, so
+/// the ±180° wrap of the phase does not show up as a spike. Interior points use a central
+/// difference and the first and last points one-sided differences. Points where the signal
+/// or the frequency is missing are `None`, as are all points of a sweep with fewer than two
+/// usable points.
+pub fn group_delay(data: &[HashMap<String, c64>], signal: &str) -> Vec<Option<f64>> {
+    let mut group_delay = vec![None; data.len()];
+
+    // (row index, angular frequency, unwrapped phase in radians)
+    let mut points: Vec<(usize, f64, f64)> = Vec::new();
+    for (index, row) in data.iter().enumerate() {
+        let (Some(frequency), Some(value)) = (row.get("frequency"), row.get(signal)) else {
+            continue;
+        };
+        let mut phase = value.im.atan2(value.re);
+        if let Some(&(_, _, previous)) = points.last() {
+            phase -= 2.0 * PI * ((phase - previous) / (2.0 * PI)).round();
+        }
+        points.push((index, 2.0 * PI * frequency.re, phase));
+    }
+
+    if points.len() < 2 {
+        return group_delay;
+    }
+    for i in 0..points.len() {
+        let (before, after) = (
+            points[i.saturating_sub(1)],
+            points[(i + 1).min(points.len() - 1)],
+        );
+        group_delay[points[i].0] = Some(-(after.2 - before.2) / (after.1 - before.1));
+    }
+    group_delay
+}
+
 /// Writes a single operating point result (`HashMap`<String, f64>) to a Parquet file.
+///
+/// The columns are sorted by name, so the schema is the same from run to run, and hold a
+/// single row of values.
 pub fn write_op_results_to_parquet(
     data: &HashMap<String, f64>,
     filename: &str,
 ) -> Result<(), PolarsError> {
     let filename = ensure_parquet_extension(filename);
 
-    // Create a vector of Series, where each Series is a new column.
-    let series: Vec<Series> = data
-        .iter()
-        // For each (key, value) pair...
-        .map(|(name, value)| {
-            // Create a Series. The 'name' is the column header.
-            // The value is wrapped in a slice `&[*value]` to create a column with a single row.
-            Series::new(name.into(), &[*value])
+    // Each signal becomes a column with a single row.
+    let columns: Vec<Column> = sorted_headers(std::slice::from_ref(data), None)
+        .into_iter()
+        .map(|name| {
+            let value = data[&name];
+            Series::new(name.into(), &[value]).into_column()
         })
         .collect();
 
-    let mut columns = vec![];
-    for serie in series {
-        columns.push(serie.into_column());
-    }
-
     // Create a DataFrame from the vector of columns.
     let mut df = DataFrame::new(columns)?;
 
@@ -62,13 +162,7 @@ pub fn write_dc_results_to_parquet(
     let filename = ensure_parquet_extension(filename);
 
     // Get all unique column names from all steps and sort them
-    let mut all_headers = data
-        .iter()
-        .flat_map(|row| row.keys().cloned())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect::<Vec<_>>();
-    all_headers.sort();
+    let all_headers = sorted_headers(data, None);
 
     // Create columns
     let mut columns = Vec::new();
@@ -87,8 +181,51 @@ pub fn write_dc_results_to_parquet(
     Ok(())
 }
 
-pub fn write_tran_results_to_parquet(
+/// Reshapes DC sweep results into a long-format table with the columns `sweep`, `variable`
+/// and `value`, one row per sweep point and signal.
+///
+/// `sweep` names the column holding the swept quantity, e.g. `step` for the index of the sweep
+/// point or `V(in)` for the node driven by the swept source. Every other column except `step`
+/// is a signal, listed in sorted order within every sweep point. Signals missing from a point,
+/// and points without a `sweep` value, have no rows.
+pub fn dc_results_to_long(
     data: &[HashMap<String, f64>],
+    sweep: &str,
+) -> Result<DataFrame, PolarsError> {
+    let variables: Vec<String> = sorted_headers(data, None)
+        .into_iter()
+        .filter(|header| header != sweep && header != "step")
+        .collect();
+
+    let mut sweep_values = Vec::new();
+    let mut variable = Vec::new();
+    let mut value = Vec::new();
+    for row in data {
+        let Some(&sweep_value) = row.get(sweep) else {
+            continue;
+        };
+        for name in &variables {
+            if let Some(&v) = row.get(name) {
+                sweep_values.push(sweep_value);
+                variable.push(name.as_str());
+                value.push(v);
+            }
+        }
+    }
+
+    DataFrame::new(vec![
+        Series::new("sweep".into(), sweep_values).into_column(),
+        Series::new("variable".into(), variable).into_column(),
+        Series::new("value".into(), value).into_column(),
+    ])
+}
+
+/// Writes DC sweep results to a Parquet file in the long format of [`dc_results_to_long`],
+/// which suits faceted plots better than the one column per signal of
+/// [`write_dc_results_to_parquet`].
+pub fn write_dc_results_to_long_parquet(
+    data: &[HashMap<String, f64>],
+    sweep: &str,
     filename: &str,
 ) -> Result<(), PolarsError> {
     if data.is_empty() {
@@ -96,22 +233,28 @@ pub fn write_tran_results_to_parquet(
     }
 
     let filename = ensure_parquet_extension(filename);
+    let mut df = dc_results_to_long(data, sweep)?;
 
-    // Collect all unique headers
-    let mut all_headers = data
-        .iter()
-        .flat_map(|row| row.keys().cloned())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect::<Vec<_>>();
+    let mut file = File::create(&filename).map_err(PolarsError::from)?;
+    ParquetWriter::new(&mut file).finish(&mut df)?;
 
-    // If a "time" column exists, ensure it's first
-    all_headers.sort();
-    if let Some(pos) = all_headers.iter().position(|h| h == "time") {
-        all_headers.remove(pos);
-        all_headers.insert(0, "time".to_string());
+    info!("Saved long-format DC sweep results to {filename}");
+    Ok(())
+}
+
+pub fn write_tran_results_to_parquet(
+    data: &[HashMap<String, f64>],
+    filename: &str,
+) -> Result<(), PolarsError> {
+    if data.is_empty() {
+        return Ok(());
     }
 
+    let filename = ensure_parquet_extension(filename);
+
+    // Collect all unique headers, with the "time" column first if it exists
+    let all_headers = sorted_headers(data, Some("time"));
+
     // Build columns
     let mut columns = Vec::with_capacity(all_headers.len());
     for header in &all_headers {
@@ -128,6 +271,139 @@ pub fn write_tran_results_to_parquet(
     Ok(())
 }
 
+/// Summary statistics of a transient waveform over the full simulated time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformSummary {
+    /// The root mean square value.
+    pub rms: f64,
+    /// The time-averaged value.
+    pub mean: f64,
+    /// The largest absolute value.
+    pub peak: f64,
+}
+
+/// Computes the RMS, mean and peak of every non-`time` column of transient results.
+///
+/// The RMS and mean are time-weighted, integrating the samples with the trapezoidal rule, so
+/// they are not skewed by the adaptive time step. Missing and NaN entries are skipped. A column
+/// with a single valid sample is summarized by that sample, and a column without any is left
+/// out.
+pub fn tran_summaries(data: &[HashMap<String, f64>]) -> HashMap<String, WaveformSummary> {
+    sorted_headers(data, Some("time"))
+        .into_iter()
+        .filter(|header| header != "time")
+        .filter_map(|header| {
+            let samples: Vec<(f64, f64)> = data
+                .iter()
+                .filter_map(|row| Some((*row.get("time")?, *row.get(&header)?)))
+                .filter(|(time, value)| !time.is_nan() && !value.is_nan())
+                .collect();
+            let summary = waveform_summary(&samples)?;
+            Some((header, summary))
+        })
+        .collect()
+}
+
+/// Summarizes `(time, value)` samples, see [`tran_summaries`].
+fn waveform_summary(samples: &[(f64, f64)]) -> Option<WaveformSummary> {
+    let &(first_time, first_value) = samples.first()?;
+    let peak = samples
+        .iter()
+        .map(|(_, value)| value.abs())
+        .fold(0.0, f64::max);
+
+    let duration = samples.last().map_or(0.0, |(time, _)| time - first_time);
+    if duration <= 0.0 {
+        return Some(WaveformSummary {
+            rms: first_value.abs(),
+            mean: first_value,
+            peak,
+        });
+    }
+
+    let (mut integral, mut square_integral) = (0.0, 0.0);
+    for window in samples.windows(2) {
+        let (t0, v0) = window[0];
+        let (t1, v1) = window[1];
+        let dt = t1 - t0;
+        integral += 0.5 * (v0 + v1) * dt;
+        square_integral += 0.5 * (v0 * v0 + v1 * v1) * dt;
+    }
+
+    Some(WaveformSummary {
+        rms: (square_integral / duration).sqrt(),
+        mean: integral / duration,
+        peak,
+    })
+}
+
+/// Writes the [`tran_summaries`] of transient results to a Parquet file, as one row per
+/// waveform with the columns `column`, `rms`, `mean` and `peak`.
+///
+/// This is meant as a companion to [`write_tran_results_to_parquet`], which keeps the
+/// per-timestep table.
+pub fn write_tran_summary_to_parquet(
+    data: &[HashMap<String, f64>],
+    filename: &str,
+) -> Result<(), PolarsError> {
+    let summaries = tran_summaries(data);
+    if summaries.is_empty() {
+        return Ok(());
+    }
+
+    let filename = ensure_parquet_extension(filename);
+
+    let mut names: Vec<&String> = summaries.keys().collect();
+    names.sort();
+    let column = |f: fn(&WaveformSummary) -> f64| -> Vec<f64> {
+        names.iter().map(|name| f(&summaries[*name])).collect()
+    };
+
+    let mut df = DataFrame::new(vec![
+        Series::new(
+            "column".into(),
+            names.iter().map(|name| name.as_str()).collect::<Vec<_>>(),
+        )
+        .into_column(),
+        Series::new("rms".into(), column(|s| s.rms)).into_column(),
+        Series::new("mean".into(), column(|s| s.mean)).into_column(),
+        Series::new("peak".into(), column(|s| s.peak)).into_column(),
+    ])?;
+
+    let mut file = File::create(&filename).map_err(PolarsError::from)?;
+    ParquetWriter::new(&mut file).finish(&mut df)?;
+
+    info!("Saved transient summary to {filename}");
+    Ok(())
+}
+
+/// The Arrow field metadata key holding the unit of a column.
+pub const UNIT_METADATA_KEY: &str = "unit";
+
+/// Returns the unit of a column written by [`write_ac_results_to_parquet`], if known.
+///
+/// Magnitudes of node voltages `V(..)` are in Volts and of branch currents `I(..)` in
+/// Amperes; other signals have no unit.
+pub fn ac_column_unit(name: &str) -> Option<&'static str> {
+    if name == "frequency" {
+        return Some("Hz");
+    }
+    if name.ends_with("_phase_deg") {
+        return Some("deg");
+    }
+    if name.ends_with("_groupdelay") {
+        return Some("s");
+    }
+    let signal = name.strip_suffix("_mag")?;
+    if signal.starts_with("V(") {
+        Some("V")
+    } else if signal.starts_with("I(") {
+        Some("A")
+    } else {
+        None
+    }
+}
+
 /// Writes AC sweep results (Vec<HashMap<String, c64>>) to a Parquet file.
 ///
 /// The input is a vector where each entry corresponds to one frequency point.
@@ -136,34 +412,28 @@ pub fn write_tran_results_to_parquet(
 ///
 /// The output Parquet will contain:
 /// - A `frequency` column (f64)
-/// - For every other key `K`, two columns: `K_mag` and `K_phase_deg` (both f64)
+/// - For every other key `K`, three columns: `K_mag`, `K_phase_deg` and `K_groupdelay`
+///   (all f64), the last one computed with [`group_delay`]
+///
+/// Every column with a known [`ac_column_unit`] carries it in the Arrow field metadata
+/// under [`UNIT_METADATA_KEY`].
 pub fn write_ac_results_to_parquet(
     data: &[HashMap<String, c64>],
     filename: &str,
-) -> Result<(), PolarsError> {
+) -> Result<(), ParquetError> {
     if data.is_empty() {
         return Ok(());
     }
 
     let filename = ensure_parquet_extension(filename);
 
-    // Collect all unique headers
-    let mut all_headers = data
-        .iter()
-        .flat_map(|row| row.keys().cloned())
-        .collect::<std::collections::HashSet<_>>()
-        .into_iter()
-        .collect::<Vec<_>>();
-
-    // Ensure stable order and put frequency first if present
-    all_headers.sort();
-    let mut signal_headers: Vec<String> = all_headers
+    // Ensure stable order; the frequency column is handled separately
+    let signal_headers: Vec<String> = sorted_headers(data, None)
         .into_iter()
         .filter(|h| h != "frequency")
         .collect();
-    signal_headers.sort();
 
-    let mut columns: Vec<polars::prelude::Column> = Vec::new();
+    let mut columns: Vec<(String, Vec<Option<f64>>)> = Vec::new();
 
     // Frequency column (if present) — extract real part only
     {
@@ -172,37 +442,268 @@ pub fn write_ac_results_to_parquet(
             .map(|row| row.get("frequency").map(|v| v.re))
             .collect();
         // Include frequency even if all None — remains a valid nullable column
-        columns.push(Series::new("frequency".into(), freq_values).into_column());
+        columns.push(("frequency".to_string(), freq_values));
     }
 
     // For each other header, create magnitude and phase columns
     for header in signal_headers {
         let mag_name = format!("{}_mag", header);
         let phase_name = format!("{}_phase_deg", header);
+        let group_delay_name = format!("{}_groupdelay", header);
 
         let (mag_values, phase_values): (Vec<Option<f64>>, Vec<Option<f64>>) = data
             .iter()
-            .map(|row| {
-                row.get(&header).map(|v| {
-                    let mag = (v.re * v.re + v.im * v.im).sqrt();
-                    let phase = v.im.atan2(v.re) * 180.0 / PI;
-                    (mag, phase)
-                })
-            })
+            .map(|row| row.get(&header).map(magnitude_phase_deg))
             .map(|opt| match opt {
                 Some((m, p)) => (Some(m), Some(p)),
                 None => (None, None),
             })
             .unzip();
 
-        columns.push(Series::new(mag_name.into(), mag_values).into_column());
-        columns.push(Series::new(phase_name.into(), phase_values).into_column());
+        columns.push((mag_name, mag_values));
+        columns.push((phase_name, phase_values));
+        columns.push((group_delay_name, group_delay(data, &header)));
     }
 
-    let mut df = DataFrame::new(columns)?;
-    let mut file = File::create(&filename).map_err(PolarsError::from)?;
-    ParquetWriter::new(&mut file).finish(&mut df)?;
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|(name, _)| {
+            let field = Field::new(name, DataType::Float64, true);
+            match ac_column_unit(name) {
+                Some(unit) => field.with_metadata(HashMap::from([(
+                    UNIT_METADATA_KEY.to_string(),
+                    unit.to_string(),
+                )])),
+                None => field,
+            }
+        })
+        .collect();
+    let arrays: Vec<ArrayRef> = columns
+        .into_iter()
+        .map(|(_, values)| Arc::new(Float64Array::from(values)) as ArrayRef)
+        .collect();
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+    let file = File::create(&filename)?;
+    let mut writer = ArrowWriter::try_new(file, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    info!("Saved AC sweep results to {filename}");
+    Ok(())
+}
+
+/// Writes a single operating point result (`HashMap`<String, f64>) to a CSV file.
+///
+/// The columns are sorted by name and hold a single row of values.
+pub fn write_op_results_to_csv(data: &HashMap<String, f64>, filename: &str) -> std::io::Result<()> {
+    let filename = ensure_csv_extension(filename);
+
+    let headers = sorted_headers(std::slice::from_ref(data), None);
+    let row = headers.iter().map(|h| data.get(h).copied()).collect();
+    write_csv(&filename, &headers, &[row])?;
+
+    info!("Saved OP results to {filename}");
+    Ok(())
+}
+
+/// Writes DC sweep results (Vec<`HashMap`<String, f64>>) to a CSV file, with the same
+/// column order as [`write_dc_results_to_parquet`].
+pub fn write_dc_results_to_csv(
+    data: &[HashMap<String, f64>],
+    filename: &str,
+) -> std::io::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    let filename = ensure_csv_extension(filename);
+
+    let headers = sorted_headers(data, None);
+    let rows: Vec<Vec<Option<f64>>> = data
+        .iter()
+        .map(|row| headers.iter().map(|h| row.get(h).copied()).collect())
+        .collect();
+    write_csv(&filename, &headers, &rows)?;
+
+    info!("Saved DC sweep results to {filename}");
+    Ok(())
+}
+
+/// Writes transient results (Vec<`HashMap`<String, f64>>) to a CSV file, with the
+/// `time` column first like [`write_tran_results_to_parquet`].
+pub fn write_tran_results_to_csv(
+    data: &[HashMap<String, f64>],
+    filename: &str,
+) -> std::io::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    let filename = ensure_csv_extension(filename);
+
+    let headers = sorted_headers(data, Some("time"));
+    let rows: Vec<Vec<Option<f64>>> = data
+        .iter()
+        .map(|row| headers.iter().map(|h| row.get(h).copied()).collect())
+        .collect();
+    write_csv(&filename, &headers, &rows)?;
+
+    info!("Saved transient results to {filename}");
+    Ok(())
+}
+
+/// Writes AC sweep results (Vec<HashMap<String, c64>>) to a CSV file.
+///
+/// The columns match [`write_ac_results_to_parquet`]: a `frequency` column first,
+/// followed by `K_mag`, `K_phase_deg` and `K_groupdelay` for every other key `K`.
+pub fn write_ac_results_to_csv(
+    data: &[HashMap<String, c64>],
+    filename: &str,
+) -> std::io::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+
+    let filename = ensure_csv_extension(filename);
+
+    let signal_headers: Vec<String> = sorted_headers(data, None)
+        .into_iter()
+        .filter(|h| h != "frequency")
+        .collect();
+
+    let mut headers = vec!["frequency".to_string()];
+    for header in &signal_headers {
+        headers.push(format!("{header}_mag"));
+        headers.push(format!("{header}_phase_deg"));
+        headers.push(format!("{header}_groupdelay"));
+    }
+
+    let group_delays: Vec<Vec<Option<f64>>> = signal_headers
+        .iter()
+        .map(|header| group_delay(data, header))
+        .collect();
+    let rows: Vec<Vec<Option<f64>>> = data
+        .iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let mut cells = vec![row.get("frequency").map(|v| v.re)];
+            for (header, group_delay) in signal_headers.iter().zip(&group_delays) {
+                let value = row.get(header).map(magnitude_phase_deg);
+                cells.push(value.map(|(mag, _)| mag));
+                cells.push(value.map(|(_, phase)| phase));
+                cells.push(group_delay[index]);
+            }
+            cells
+        })
+        .collect();
+    write_csv(&filename, &headers, &rows)?;
 
     info!("Saved AC sweep results to {filename}");
     Ok(())
 }
+
+/// Writes AC sweep results (Vec<HashMap<String, c64>>) to a Touchstone file (`.s1p`/`.s2p`).
+///
+/// The AC analysis only has a single excitation, so the S-parameters are derived from the
+/// node voltages under the following convention: port 1 is driven by an AC source of unit
+/// amplitude in series with the reference impedance `z0`, so the incident wave at port 1 is
+/// `0.5` V. Its reflection is then `S11 = 2 * V(port 1) - 1`. With a second port, which
+/// must be terminated in `z0` inside the netlist, the transmission is `S21 = 2 * V(port 2)`.
+/// `S12` and `S22` would need an excitation at port 2 and are written as zero.
+///
+/// `ports` holds the port node names (e.g. `["in"]`), and must contain one or two nodes.
+pub fn write_ac_results_to_touchstone(
+    data: &[HashMap<String, c64>],
+    ports: &[&str],
+    z0: f64,
+    filename: &str,
+) -> std::io::Result<()> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    if !(1..=2).contains(&ports.len()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Touchstone export supports 1 or 2 ports, got {}",
+                ports.len()
+            ),
+        ));
+    }
+
+    let extension = format!("s{}p", ports.len());
+    let filename = if Path::new(filename).extension().and_then(|e| e.to_str()) == Some(&extension) {
+        filename.to_string()
+    } else {
+        format!("{filename}.{extension}")
+    };
+
+    let mut writer = BufWriter::new(File::create(&filename)?);
+    writeln!(writer, "! Generated by krets")?;
+    if ports.len() == 2 {
+        writeln!(
+            writer,
+            "! S12 and S22 are not computed (single excitation at port 1)"
+        )?;
+    }
+    writeln!(writer, "# HZ S MA R {z0}")?;
+
+    for row in data {
+        let voltage = |node: &str| {
+            row.get(&format!("V({node})")).copied().ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Port node '{node}' not found in the AC results"),
+                )
+            })
+        };
+        let frequency = row.get("frequency").map_or(0.0, |v| v.re);
+
+        let s11 = voltage(ports[0])? * 2.0 - c64::new(1.0, 0.0);
+        let mut s_parameters = vec![s11];
+        if let Some(port) = ports.get(1) {
+            // Touchstone orders 2-port data as S11 S21 S12 S22.
+            s_parameters.extend([voltage(port)? * 2.0, c64::ZERO, c64::ZERO]);
+        }
+
+        let cells: Vec<String> = s_parameters
+            .iter()
+            .map(|s| {
+                let (mag, phase) = magnitude_phase_deg(s);
+                format!("{mag} {phase}")
+            })
+            .collect();
+        writeln!(writer, "{frequency} {}", cells.join(" "))?;
+    }
+    writer.flush()?;
+
+    info!("Saved AC sweep results to {filename}");
+    Ok(())
+}
+
+/// Writes any analysis result to a JSON file, for consumers such as web front-ends.
+///
+/// The file holds a single object `{"analysis": ..., "data": ...}`, where `analysis` names the
+/// analysis in snake case and `data` is laid out as follows:
+///
+/// | `analysis`                      | `data`                                              |
+/// |---------------------------------|-----------------------------------------------------|
+/// | `op`, `tf`                      | `{"V(out)": 1.5, ...}`                              |
+/// | `dc`, `transient`, `noise`      | `[{"time": 0.0, "V(out)": 1.5, ...}, ...]`          |
+/// | `ac`                            | `[{"V(out)": {"re", "im", "mag", "phase"}, ...}, ...]` |
+/// | `pole_zero`                     | `{"poles": [{"re", "im", "mag", "phase"}, ...], "zeros": [...]}` |
+///
+/// AC values are complex, with their magnitude and phase in degrees alongside the real and
+/// imaginary parts; the `frequency` column of an AC row is complex too, with a zero
+/// imaginary part. Keys are sorted, and non-finite values are written as `null`.
+pub fn write_results_to_json(result: &AnalysisResult, filename: &str) -> std::io::Result<()> {
+    let filename = ensure_json_extension(filename);
+    let mut writer = BufWriter::new(File::create(&filename)?);
+    serde_json::to_writer(&mut writer, result)?;
+    writer.flush()?;
+
+    info!("Saved results to {filename}");
+    Ok(())
+}
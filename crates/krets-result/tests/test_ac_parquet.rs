@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{AcAnalysis, AcSweep, Analysis};
+    use krets_result::{UNIT_METADATA_KEY, write_ac_results_to_parquet};
+    use krets_solver::{config::SolverConfig, solver::Solver};
+    use polars::prelude::*;
+    use std::{env, fs::File, path::Path};
+
+    // Function to get the project root path at runtime
+    fn manifest_dir() -> String {
+        env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string())
+    }
+
+    // Function to get the circuits directory path
+    fn circuits_dir() -> String {
+        // Adjust the path to navigate from the crate's root to the workspace root's circuits dir
+        Path::new(&manifest_dir())
+            .parent() // Go up from crates/krets-result
+            .and_then(Path::parent) // Go up from crates
+            .unwrap()
+            .join("circuits/")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_low_pass_filter_ac_parquet_round_trip() {
+        let path = Path::new(&circuits_dir()).join("low_pass_filter/low_pass_filter.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let analysis = Analysis::Ac(AcAnalysis {
+            sweep: AcSweep::Decade {
+                points_per_decade: 10,
+            },
+            fstart: 10.0,
+            fstop: 1e5,
+        });
+        let solution = solver.solve(analysis).unwrap().into_ac();
+
+        let output = env::temp_dir().join("krets_low_pass_filter_ac.parquet");
+        write_ac_results_to_parquet(&solution, output.to_str().unwrap()).unwrap();
+
+        let df = ParquetReader::new(File::open(&output).unwrap())
+            .finish()
+            .unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(df.height(), solution.len());
+
+        let frequency: Vec<f64> = df
+            .column("frequency")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        let magnitude: Vec<f64> = df
+            .column("V(out)_mag")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+
+        assert!(frequency.windows(2).all(|w| w[1] > w[0]));
+        assert!(magnitude.windows(2).all(|w| w[1] < w[0]));
+
+        // Far below the 159 Hz corner the filter passes the input, far above it attenuates it.
+        assert!((magnitude[0] - 1.0).abs() < 1e-2);
+        assert!(*magnitude.last().unwrap() < 2e-3);
+    }
+
+    #[test]
+    fn test_low_pass_filter_ac_parquet_units_round_trip() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let path = Path::new(&circuits_dir()).join("low_pass_filter/low_pass_filter.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let analysis = Analysis::Ac(AcAnalysis {
+            sweep: AcSweep::Decade {
+                points_per_decade: 2,
+            },
+            fstart: 10.0,
+            fstop: 1e3,
+        });
+        let solution = solver.solve(analysis).unwrap().into_ac();
+
+        let output = env::temp_dir().join("krets_low_pass_filter_ac_units.parquet");
+        write_ac_results_to_parquet(&solution, output.to_str().unwrap()).unwrap();
+
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&output).unwrap()).unwrap();
+        let schema = builder.schema().clone();
+        std::fs::remove_file(&output).unwrap();
+
+        let unit = |name: &str| {
+            schema
+                .field_with_name(name)
+                .unwrap()
+                .metadata()
+                .get(UNIT_METADATA_KEY)
+                .cloned()
+        };
+        assert_eq!(unit("frequency").as_deref(), Some("Hz"));
+        assert_eq!(unit("V(out)_mag").as_deref(), Some("V"));
+        assert_eq!(unit("V(out)_phase_deg").as_deref(), Some("deg"));
+        assert_eq!(unit("V(out)_groupdelay").as_deref(), Some("s"));
+        assert_eq!(unit("I(V1)_mag").as_deref(), Some("A"));
+    }
+}
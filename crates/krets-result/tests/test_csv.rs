@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use faer::c64;
+    use krets_result::{write_ac_results_to_csv, write_tran_results_to_csv};
+    use std::{collections::HashMap, env};
+
+    // Reads a CSV file back into its header and rows of raw cells.
+    fn read_csv(path: &std::path::Path) -> (Vec<String>, Vec<Vec<String>>) {
+        let content = std::fs::read_to_string(path).unwrap();
+        let mut lines = content.lines();
+        let header = lines
+            .next()
+            .unwrap()
+            .split(',')
+            .map(str::to_string)
+            .collect();
+        let rows = lines
+            .map(|line| line.split(',').map(str::to_string).collect())
+            .collect();
+        (header, rows)
+    }
+
+    #[test]
+    fn test_tran_csv_round_trip() {
+        let data = vec![
+            HashMap::from([
+                ("time".to_string(), 0.0),
+                ("V(out)".to_string(), 0.0),
+                ("I(V1)".to_string(), -1e-3),
+            ]),
+            HashMap::from([("time".to_string(), 1e-6), ("V(out)".to_string(), 0.5)]),
+        ];
+
+        // The extension is appended when missing.
+        let output = env::temp_dir().join("krets_tran_round_trip");
+        write_tran_results_to_csv(&data, output.to_str().unwrap()).unwrap();
+        let output = output.with_extension("csv");
+        let (header, rows) = read_csv(&output);
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(header, vec!["time", "I(V1)", "V(out)"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0].parse::<f64>().unwrap(), 0.0);
+        assert_eq!(rows[0][1].parse::<f64>().unwrap(), -1e-3);
+        assert_eq!(rows[1][0].parse::<f64>().unwrap(), 1e-6);
+        assert_eq!(rows[1][2].parse::<f64>().unwrap(), 0.5);
+
+        // Missing values are written as empty cells.
+        assert_eq!(rows[1][1], "");
+    }
+
+    #[test]
+    fn test_ac_csv_round_trip() {
+        let data = vec![HashMap::from([
+            ("frequency".to_string(), c64::new(1e3, 0.0)),
+            ("V(out)".to_string(), c64::new(0.0, -2.0)),
+        ])];
+
+        let output = env::temp_dir().join("krets_ac_round_trip.csv");
+        write_ac_results_to_csv(&data, output.to_str().unwrap()).unwrap();
+        let (header, rows) = read_csv(&output);
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(
+            header,
+            vec![
+                "frequency",
+                "V(out)_mag",
+                "V(out)_phase_deg",
+                "V(out)_groupdelay"
+            ]
+        );
+        let row: Vec<f64> = rows[0][..3].iter().map(|v| v.parse().unwrap()).collect();
+        assert_eq!(row, vec![1e3, 2.0, -90.0]);
+
+        // A single frequency point has no group delay.
+        assert_eq!(rows[0][3], "");
+    }
+}
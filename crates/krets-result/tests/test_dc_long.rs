@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use krets_result::{dc_results_to_long, write_dc_results_to_long_parquet};
+    use polars::prelude::*;
+    use std::{collections::HashMap, env, fs::File};
+
+    // A sweep of V1 driving `in` over three points, with `out` at half its voltage.
+    fn sweep_rows() -> Vec<HashMap<String, f64>> {
+        [0.0, 1.0, 2.0]
+            .into_iter()
+            .enumerate()
+            .map(|(step, vin)| {
+                HashMap::from([
+                    ("step".to_string(), step as f64),
+                    ("V(in)".to_string(), vin),
+                    ("V(out)".to_string(), vin / 2.0),
+                ])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dc_results_to_long() {
+        let df = dc_results_to_long(&sweep_rows(), "step").unwrap();
+        assert_eq!(df.height(), 3 * 2);
+        assert_eq!(
+            df.get_column_names_str(),
+            vec!["sweep", "variable", "value"]
+        );
+
+        let variable = df.column("variable").unwrap().str().unwrap();
+        let value = df.column("value").unwrap().f64().unwrap();
+        let sweep = df.column("sweep").unwrap().f64().unwrap();
+        assert_eq!(variable.get(4), Some("V(in)"));
+        assert_eq!(sweep.get(5), Some(2.0));
+        assert_eq!(value.get(5), Some(1.0));
+
+        // Sweeping over a node voltage leaves the other signal.
+        let df = dc_results_to_long(&sweep_rows(), "V(in)").unwrap();
+        assert_eq!(df.height(), 3);
+        let variable = df.column("variable").unwrap().str().unwrap();
+        assert!(variable.into_no_null_iter().all(|v| v == "V(out)"));
+    }
+
+    #[test]
+    fn test_dc_results_to_long_omits_missing_values() {
+        let mut data = sweep_rows();
+        data[1].remove("V(out)");
+        data[2].remove("step");
+
+        let df = dc_results_to_long(&data, "step").unwrap();
+        assert_eq!(df.height(), 3);
+        let sweep = df.column("sweep").unwrap().f64().unwrap();
+        assert_eq!(
+            sweep.into_no_null_iter().collect::<Vec<_>>(),
+            [0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_write_dc_results_to_long_parquet() {
+        let output = env::temp_dir().join("krets_dc_long.parquet");
+        write_dc_results_to_long_parquet(&sweep_rows(), "step", output.to_str().unwrap()).unwrap();
+
+        let df = ParquetReader::new(File::open(&output).unwrap())
+            .finish()
+            .unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        assert!(df.equals(&dc_results_to_long(&sweep_rows(), "step").unwrap()));
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use faer::c64;
+    use krets_parser::analyses::{AcAnalysis, AcSweep, Analysis};
+    use krets_result::group_delay;
+    use krets_solver::{config::SolverConfig, solver::Solver};
+    use std::collections::HashMap;
+    use std::f64::consts::PI;
+
+    fn solve_ac(netlist: &str, fstart: f64, fstop: f64) -> Vec<HashMap<String, c64>> {
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let analysis = Analysis::Ac(AcAnalysis {
+            sweep: AcSweep::Decade {
+                points_per_decade: 50,
+            },
+            fstart,
+            fstop,
+        });
+        solver.solve(analysis).unwrap().into_ac()
+    }
+
+    fn frequency(row: &HashMap<String, c64>) -> f64 {
+        row["frequency"].re
+    }
+
+    #[test]
+    fn test_single_pole_rc_group_delay() {
+        // RC = 1 ms, so the corner is at 1 / (2π RC) ≈ 159 Hz.
+        let rc = 1e-3;
+        let corner = 1.0 / (2.0 * PI * rc);
+        let data = solve_ac("V1 in 0 AC 1\nR1 in out 1k\nC1 out 0 1u\n", 1.0, 1e5);
+        let delay: Vec<f64> = group_delay(&data, "V(out)")
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+
+        // The delay of a single pole is RC / (1 + (ωRC)²): flat at RC far below the corner,
+        // halved at the corner and rolling off above it.
+        for (row, &tau) in data.iter().zip(&delay) {
+            let omega_rc = 2.0 * PI * frequency(row) * rc;
+            let expected = rc / (1.0 + omega_rc * omega_rc);
+            assert!(
+                (tau - expected).abs() < 0.01 * rc,
+                "group delay {tau} at {} Hz, expected {expected}",
+                frequency(row)
+            );
+        }
+        assert!((delay[0] - rc).abs() < 1e-3 * rc);
+
+        // The delay falls off fastest per decade around the corner frequency.
+        let steepest = (1..data.len())
+            .max_by(|&a, &b| {
+                let drop = |i: usize| delay[i - 1] - delay[i];
+                drop(a).total_cmp(&drop(b))
+            })
+            .unwrap();
+        let f = frequency(&data[steepest]);
+        assert!(
+            f > corner / 2.0 && f < corner * 2.0,
+            "steepest drop at {f} Hz"
+        );
+    }
+
+    #[test]
+    fn test_resonant_low_pass_group_delay_peaks_at_resonance() {
+        // L = 1 mH and C = 1 µF resonate at 1 / (2π √(LC)) ≈ 5.03 kHz with Q = 3.2.
+        let resonance = 1.0 / (2.0 * PI * (1e-3_f64 * 1e-6).sqrt());
+        let data = solve_ac(
+            "V1 in 0 AC 1\nR1 in mid 10\nL1 mid out 1m\nC1 out 0 1u\n",
+            100.0,
+            1e6,
+        );
+        let delay = group_delay(&data, "V(out)");
+
+        let peak = (0..data.len())
+            .max_by(|&a, &b| delay[a].unwrap().total_cmp(&delay[b].unwrap()))
+            .unwrap();
+        let f = frequency(&data[peak]);
+        assert!(
+            f > 0.9 * resonance && f < 1.1 * resonance,
+            "group delay peaks at {f} Hz"
+        );
+        // The phase passes through -180° above resonance without a spike in the delay.
+        assert!(delay.iter().all(|tau| tau.unwrap() > 0.0));
+    }
+
+    #[test]
+    fn test_group_delay_unwraps_phase() {
+        // A pure delay of 1 ms wraps its phase every kilohertz.
+        let delay = 1e-3;
+        let data: Vec<HashMap<String, c64>> = (0..100)
+            .map(|i| {
+                let f = 100.0 * i as f64;
+                let phase = -2.0 * PI * f * delay;
+                HashMap::from([
+                    ("frequency".to_string(), c64::new(f, 0.0)),
+                    ("V(out)".to_string(), c64::new(phase.cos(), phase.sin())),
+                ])
+            })
+            .collect();
+
+        for tau in group_delay(&data, "V(out)") {
+            assert!((tau.unwrap() - delay).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_group_delay_skips_missing_points() {
+        let mut data: Vec<HashMap<String, c64>> = [10.0, 20.0, 30.0]
+            .into_iter()
+            .map(|f| {
+                HashMap::from([
+                    ("frequency".to_string(), c64::new(f, 0.0)),
+                    ("V(out)".to_string(), c64::new(1.0, 0.0)),
+                ])
+            })
+            .collect();
+        data[1].remove("V(out)");
+
+        assert_eq!(
+            group_delay(&data, "V(out)"),
+            vec![Some(0.0), None, Some(0.0)]
+        );
+        assert_eq!(group_delay(&data[..1], "V(out)"), vec![None]);
+    }
+}
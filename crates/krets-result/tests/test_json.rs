@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use faer::c64;
+    use krets_result::write_results_to_json;
+    use krets_solver::AnalysisResult;
+    use serde_json::Value;
+    use std::{collections::HashMap, env};
+
+    // Writes `result` to a JSON file and parses it back.
+    fn round_trip(result: &AnalysisResult, name: &str) -> Value {
+        let output = env::temp_dir().join(name);
+        write_results_to_json(result, output.to_str().unwrap()).unwrap();
+        let output = output.with_extension("json");
+        let content = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+
+    fn real_row() -> HashMap<String, f64> {
+        HashMap::from([("V(out)".to_string(), 1.5), ("I(V1)".to_string(), -1e-3)])
+    }
+
+    #[test]
+    fn test_op_and_tf_json() {
+        for (result, analysis) in [
+            (AnalysisResult::Op(real_row()), "op"),
+            (AnalysisResult::Tf(real_row()), "tf"),
+        ] {
+            let json = round_trip(&result, &format!("krets_{analysis}"));
+            assert_eq!(json["analysis"], analysis);
+            assert_eq!(json["data"]["V(out)"], 1.5);
+            assert_eq!(json["data"]["I(V1)"], -1e-3);
+        }
+    }
+
+    #[test]
+    fn test_row_results_json() {
+        let rows = vec![real_row(), real_row()];
+        let measurements = HashMap::from([("vmax".to_string(), 1.5)]);
+        for (result, analysis) in [
+            (AnalysisResult::Dc(rows.clone(), measurements.clone()), "dc"),
+            (
+                AnalysisResult::Transient(rows.clone(), measurements),
+                "transient",
+            ),
+            (AnalysisResult::Noise(rows), "noise"),
+        ] {
+            let json = round_trip(&result, &format!("krets_{analysis}.json"));
+            assert_eq!(json["analysis"], analysis);
+            let data = json["data"].as_array().unwrap();
+            assert_eq!(data.len(), 2);
+            assert_eq!(data[1]["V(out)"], 1.5);
+            if analysis != "noise" {
+                assert_eq!(json["measurements"]["vmax"], 1.5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ac_json() {
+        let result = AnalysisResult::Ac(vec![HashMap::from([
+            ("frequency".to_string(), c64::new(1e3, 0.0)),
+            ("V(out)".to_string(), c64::new(0.0, -2.0)),
+        ])]);
+        let json = round_trip(&result, "krets_ac.json");
+
+        assert_eq!(json["analysis"], "ac");
+        let value = &json["data"][0]["V(out)"];
+        assert_eq!(value["re"], 0.0);
+        assert_eq!(value["im"], -2.0);
+        assert_eq!(value["mag"], 2.0);
+        assert_eq!(value["phase"], -90.0);
+        assert_eq!(json["data"][0]["frequency"]["re"], 1e3);
+    }
+
+    #[test]
+    fn test_pole_zero_json() {
+        let result = AnalysisResult::PoleZero {
+            poles: vec![c64::new(-1e3, 0.0)],
+            zeros: vec![],
+        };
+        let json = round_trip(&result, "krets_pole_zero.json");
+
+        assert_eq!(json["analysis"], "pole_zero");
+        assert_eq!(json["data"]["poles"][0]["re"], -1e3);
+        assert_eq!(json["data"]["poles"][0]["phase"], 180.0);
+        assert!(json["data"]["zeros"].as_array().unwrap().is_empty());
+    }
+}
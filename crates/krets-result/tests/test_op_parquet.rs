@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use krets_result::write_op_results_to_parquet;
+    use polars::prelude::*;
+    use std::{collections::HashMap, env, fs::File};
+
+    #[test]
+    fn test_op_parquet_columns_are_sorted() {
+        let data: HashMap<String, f64> = [("V(out)", 5.0), ("I(V1)", -5e-3), ("V(in)", 10.0)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+
+        let read_columns = |name: &str| {
+            let output = env::temp_dir().join(name);
+            write_op_results_to_parquet(&data, output.to_str().unwrap()).unwrap();
+            let df = ParquetReader::new(File::open(&output).unwrap())
+                .finish()
+                .unwrap();
+            std::fs::remove_file(&output).unwrap();
+            df
+        };
+        let first = read_columns("krets_op_order_1.parquet");
+        let second = read_columns("krets_op_order_2.parquet");
+
+        assert_eq!(first.height(), 1);
+        assert_eq!(first.get_column_names(), ["I(V1)", "V(in)", "V(out)"]);
+        assert_eq!(first.schema(), second.schema());
+        assert_eq!(
+            first.column("V(out)").unwrap().f64().unwrap().get(0),
+            Some(5.0)
+        );
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, TransientAnalysis};
+    use krets_result::write_tran_results_to_parquet;
+    use krets_solver::{config::SolverConfig, solver::Solver};
+    use polars::prelude::*;
+    use std::{env, fs::File};
+
+    #[test]
+    fn test_saved_signals_parquet() {
+        let netlist = "
+V1 in 0 DC 1
+R1 in mid 1k
+R2 mid out 1k
+C1 out 0 1u
+.save V(out) I(V1) V(missing)
+";
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let analysis = Analysis::Transient(TransientAnalysis {
+            time_step: 1e-5,
+            stop_time: 1e-4,
+            use_initial_conditions: false,
+        });
+        let solution = solver.solve(analysis).unwrap().into_transient();
+
+        let output = env::temp_dir().join("krets_saved_signals.parquet");
+        write_tran_results_to_parquet(&solution, output.to_str().unwrap()).unwrap();
+        let df = ParquetReader::new(File::open(&output).unwrap())
+            .finish()
+            .unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        let columns: Vec<&str> = df.get_column_names().iter().map(|c| c.as_str()).collect();
+        assert_eq!(columns, vec!["time", "I(V1)", "V(out)"]);
+        assert!(df.height() > 1);
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use faer::c64;
+    use krets_result::write_ac_results_to_touchstone;
+    use std::{collections::HashMap, env};
+
+    #[test]
+    fn test_one_port_touchstone() {
+        // A matched, an open and a shorted port.
+        let data: Vec<HashMap<String, c64>> = [(1e6, 0.5), (2e6, 1.0), (3e6, 0.0)]
+            .into_iter()
+            .map(|(frequency, v)| {
+                HashMap::from([
+                    ("frequency".to_string(), c64::new(frequency, 0.0)),
+                    ("V(in)".to_string(), c64::new(v, 0.0)),
+                ])
+            })
+            .collect();
+
+        let output = env::temp_dir().join("krets_one_port");
+        write_ac_results_to_touchstone(&data, &["in"], 50.0, output.to_str().unwrap()).unwrap();
+        let output = output.with_extension("s1p");
+        let content = std::fs::read_to_string(&output).unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        let mut lines = content.lines().filter(|line| !line.starts_with('!'));
+        assert_eq!(lines.next().unwrap(), "# HZ S MA R 50");
+
+        let rows: Vec<Vec<f64>> = lines
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|v| v.parse().unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], vec![1e6, 0.0, 0.0]);
+        assert_eq!(rows[1], vec![2e6, 1.0, 0.0]);
+        assert_eq!(rows[2], vec![3e6, 1.0, 180.0]);
+    }
+
+    #[test]
+    fn test_touchstone_empty_and_invalid_ports() {
+        let output = env::temp_dir().join("krets_invalid_ports.s3p");
+        assert!(write_ac_results_to_touchstone(&[], &["a", "b", "c"], 50.0, "unused").is_ok());
+
+        let data = vec![HashMap::from([("V(a)".to_string(), c64::new(1.0, 0.0))])];
+        assert!(
+            write_ac_results_to_touchstone(&data, &["a", "b", "c"], 50.0, output.to_str().unwrap())
+                .is_err()
+        );
+        assert!(!output.exists());
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use krets_result::{tran_summaries, write_tran_summary_to_parquet};
+    use polars::prelude::*;
+    use std::{collections::HashMap, env, f64::consts::PI, fs::File};
+
+    // Two periods of a 1 kHz sine with a 3 V amplitude and a 1 V offset.
+    fn sine_rows() -> Vec<HashMap<String, f64>> {
+        let steps = 2000;
+        (0..=steps)
+            .map(|i| {
+                let time = 2e-3 * i as f64 / steps as f64;
+                HashMap::from([
+                    ("time".to_string(), time),
+                    (
+                        "V(out)".to_string(),
+                        1.0 + 3.0 * (2.0 * PI * 1e3 * time).sin(),
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sine_summary() {
+        let mut data = sine_rows();
+        // Missing and NaN entries are skipped.
+        data[10].remove("V(out)");
+        data[20].insert("V(out)".to_string(), f64::NAN);
+
+        let summaries = tran_summaries(&data);
+        assert!(!summaries.contains_key("time"));
+
+        let summary = summaries["V(out)"];
+        let expected_rms = (1.0f64 + 3.0 * 3.0 / 2.0).sqrt();
+        assert!((summary.rms - expected_rms).abs() < 1e-4, "{summary:?}");
+        assert!((summary.mean - 1.0).abs() < 1e-4, "{summary:?}");
+        assert!((summary.peak - 4.0).abs() < 1e-4, "{summary:?}");
+    }
+
+    #[test]
+    fn test_sine_summary_parquet() {
+        let output = env::temp_dir().join("krets_tran_summary.parquet");
+        write_tran_summary_to_parquet(&sine_rows(), output.to_str().unwrap()).unwrap();
+
+        let df = ParquetReader::new(File::open(&output).unwrap())
+            .finish()
+            .unwrap();
+        std::fs::remove_file(&output).unwrap();
+
+        assert_eq!(df.height(), 1);
+        let column = df.column("column").unwrap().str().unwrap();
+        assert_eq!(column.get(0), Some("V(out)"));
+        let rms = df.column("rms").unwrap().f64().unwrap().get(0).unwrap();
+        assert!((rms - 5.5f64.sqrt()).abs() < 1e-4);
+    }
+}
@@ -1,19 +1,26 @@
 use criterion::{Criterion, criterion_group, criterion_main};
-use krets_parser::analyses::{AcAnalysis, AcSweep, Analysis, DcAnalysis, TransientAnalysis};
-use krets_solver::{config::SolverConfig, solver::Solver};
+use krets_parser::analyses::{
+    AcAnalysis, AcSweep, Analysis, DcAnalysis, DcSweepType, TransientAnalysis,
+};
+use krets_solver::{
+    config::{LinearSolver, SolverConfig},
+    solver::Solver,
+};
 use std::hint::black_box;
 use std::path::Path;
+use std::sync::Arc;
 
 fn benchmark_resistor_ladder_500(c: &mut Criterion) {
     let path = Path::new("../../circuits/resistor_ladder_500/resistor_ladder_500.cir");
-    let circuit = krets_parser::parser::parse_circuit_description_file(path).unwrap();
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
     let config = SolverConfig::default();
     let analysis = Analysis::Op;
 
     c.bench_function("resistor_ladder_500", |b| {
         b.iter(|| {
             // Re-initialize the solver in each iteration to benchmark the full setup and solve.
-            let mut solver = Solver::new(circuit.clone(), config.clone());
+            // The solvers share the parsed circuit instead of copying it.
+            let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
             let solution = solver.solve(analysis.clone());
             let _ = black_box(solution);
         })
@@ -22,13 +29,13 @@ fn benchmark_resistor_ladder_500(c: &mut Criterion) {
 
 fn benchmark_resistor_ladder_1000(c: &mut Criterion) {
     let path = Path::new("../../circuits/resistor_ladder_1000/resistor_ladder_1000.cir");
-    let circuit = krets_parser::parser::parse_circuit_description_file(path).unwrap();
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
     let config = SolverConfig::default();
     let analysis = Analysis::Op;
 
     c.bench_function("resistor_ladder_1000", |b| {
         b.iter(|| {
-            let mut solver = Solver::new(circuit.clone(), config.clone());
+            let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
             let solution = solver.solve(analysis.clone());
             let _ = black_box(solution);
         })
@@ -37,34 +44,62 @@ fn benchmark_resistor_ladder_1000(c: &mut Criterion) {
 
 fn benchmark_resistor_ladder_5000(c: &mut Criterion) {
     let path = Path::new("../../circuits/resistor_ladder_5000/resistor_ladder_5000.cir");
-    let circuit = krets_parser::parser::parse_circuit_description_file(path).unwrap();
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
     let config = SolverConfig::default();
     let analysis = Analysis::Op;
 
     c.bench_function("resistor_ladder_5000", |b| {
         b.iter(|| {
-            let mut solver = Solver::new(circuit.clone(), config.clone());
+            let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
             let solution = solver.solve(analysis.clone());
             let _ = black_box(solution);
         })
     });
 }
 
+fn benchmark_resistor_ladder_5000_iterative(c: &mut Criterion) {
+    let path = Path::new("../../circuits/resistor_ladder_5000/resistor_ladder_5000.cir");
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
+    let analysis = Analysis::Op;
+
+    // Compare against `resistor_ladder_5000`, which uses the default LU solver.
+    for (name, linear_solver) in [
+        ("resistor_ladder_5000_cg", LinearSolver::IterativeCg),
+        (
+            "resistor_ladder_5000_bicgstab",
+            LinearSolver::IterativeBicgstab,
+        ),
+    ] {
+        let config = SolverConfig {
+            linear_solver,
+            ..SolverConfig::default()
+        };
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
+                let solution = solver.solve(analysis.clone());
+                let _ = black_box(solution);
+            })
+        });
+    }
+}
+
 fn benchmark_dc_voltage_divider(c: &mut Criterion) {
     let path = Path::new("../../circuits/voltage_divider/voltage_divider.cir");
-    let circuit = krets_parser::parser::parse_circuit_description_file(path).unwrap();
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
     let config = SolverConfig::default();
     let dc_analysis = DcAnalysis {
         element: "V1".to_string(),
         start: 0.0,
         stop: 1.0,
-        step_size: 0.01, // 100 steps
+        sweep: DcSweepType::Linear { step_size: 0.01 }, // 100 steps
+        ..Default::default()
     };
     let analysis = Analysis::Dc(dc_analysis);
 
     c.bench_function("dc_voltage_divider_100_steps", |b| {
         b.iter(|| {
-            let mut solver = Solver::new(circuit.clone(), config.clone());
+            let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
             let solution = solver.solve(analysis.clone());
             let _ = black_box(solution);
         })
@@ -73,7 +108,7 @@ fn benchmark_dc_voltage_divider(c: &mut Criterion) {
 
 fn benchmark_ac_low_pass_filter(c: &mut Criterion) {
     let path = Path::new("../../circuits/low_pass_filter/low_pass_filter.cir");
-    let circuit = krets_parser::parser::parse_circuit_description_file(path).unwrap();
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
     let config = SolverConfig::default();
     let ac_analysis = AcAnalysis {
         sweep: AcSweep::Linear { total_points: 100 },
@@ -84,7 +119,7 @@ fn benchmark_ac_low_pass_filter(c: &mut Criterion) {
 
     c.bench_function("ac_low_pass_filter_100_points", |b| {
         b.iter(|| {
-            let mut solver = Solver::new(circuit.clone(), config.clone());
+            let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
             let solution = solver.solve(analysis.clone());
             let _ = black_box(solution);
         })
@@ -93,17 +128,18 @@ fn benchmark_ac_low_pass_filter(c: &mut Criterion) {
 
 fn benchmark_tran_dual_rc_ladder(c: &mut Criterion) {
     let path = Path::new("../../circuits/dual_rc_ladder/dual_rc_ladder.cir");
-    let circuit = krets_parser::parser::parse_circuit_description_file(path).unwrap();
+    let circuit = Arc::new(krets_parser::parser::parse_circuit_description_file(path).unwrap());
     let config = SolverConfig::default();
     let tran_analysis = TransientAnalysis {
         time_step: 50e-6, // 50us
         stop_time: 50e-3, // 50ms (1000 steps)
+        use_initial_conditions: false,
     };
     let analysis = Analysis::Transient(tran_analysis);
 
     c.bench_function("tran_dual_rc_ladder_1000_steps", |b| {
         b.iter(|| {
-            let mut solver = Solver::new(circuit.clone(), config.clone());
+            let mut solver = Solver::new(Arc::clone(&circuit), config.clone());
             let solution = solver.solve(analysis.clone());
             let _ = black_box(solution);
         })
@@ -115,6 +151,7 @@ criterion_group!(
     benchmark_resistor_ladder_500,
     benchmark_resistor_ladder_1000,
     benchmark_resistor_ladder_5000,
+    benchmark_resistor_ladder_5000_iterative,
     benchmark_dc_voltage_divider,
     benchmark_ac_low_pass_filter,
     benchmark_tran_dual_rc_ladder
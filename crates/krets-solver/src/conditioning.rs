@@ -0,0 +1,277 @@
+use crate::{
+    config::LinearSolver,
+    iterative::solve_iterative,
+    mna::{Reduction, is_symmetric},
+    prelude::*,
+};
+use faer::{Mat, Side, prelude::Solve, sparse::SparseColMat};
+use log::{info, warn};
+
+/// The number of `A⁻¹` and `A⁻ᵀ` solve pairs the condition number estimator may use.
+const ESTIMATOR_ITERATIONS: usize = 5;
+
+/// The relative difference below which two mirrored matrix entries count as equal when
+/// deciding whether to use a Cholesky factorization.
+const SYMMETRY_TOLERANCE: f64 = 1e-12;
+
+/// Returns the 1-norm of the matrix, its largest absolute column sum.
+///
+/// The `triplets` must already be summed, see [`sum_triplets`].
+pub fn one_norm(triplets: &[Triplet<usize, usize, f64>], size: usize) -> f64 {
+    let mut column_sums = vec![0.0; size];
+    for triplet in triplets {
+        column_sums[triplet.col] += triplet.val.abs();
+    }
+    column_sums.into_iter().fold(0.0, f64::max)
+}
+
+/// Estimates the 1-norm condition number `‖A‖₁ ‖A⁻¹‖₁` of the matrix of `triplets`, given
+/// its factorization, e.g. a sparse LU or Cholesky factorization.
+///
+/// `‖A⁻¹‖₁` is estimated with Hager's method, which only needs a few solves with the existing
+/// factorization instead of forming the inverse. The estimate is a lower bound that is
+/// usually within a small factor of the exact value.
+pub fn estimate_condition_number(
+    triplets: &[Triplet<usize, usize, f64>],
+    size: usize,
+    factorization: &impl Solve<f64>,
+) -> f64 {
+    if size == 0 {
+        return 0.0;
+    }
+
+    let mut x = Mat::from_fn(size, 1, |_, _| 1.0 / size as f64);
+    let mut inverse_norm = 0.0;
+    for _ in 0..ESTIMATOR_ITERATIONS {
+        let y = factorization.solve(&x);
+        inverse_norm = (0..size).map(|i| y[(i, 0)].abs()).sum::<f64>();
+
+        let signs = Mat::from_fn(size, 1, |i, _| y[(i, 0)].signum());
+        let z = factorization.solve_transpose(&signs);
+        let (j, z_max) = (0..size)
+            .map(|i| (i, z[(i, 0)].abs()))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        let z_dot_x: f64 = (0..size).map(|i| z[(i, 0)] * x[(i, 0)]).sum();
+        // No unit vector increases the estimate any further.
+        if z_max <= z_dot_x {
+            break;
+        }
+        x = Mat::from_fn(size, 1, |i, _| if i == j { 1.0 } else { 0.0 });
+    }
+
+    one_norm(triplets, size) * inverse_norm
+}
+
+/// Row and column scaling factors that equilibrate a matrix, so that the largest entry of
+/// every row and column of `R A C` has a magnitude close to one.
+///
+/// The factors are powers of two, so scaling introduces no rounding errors of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equilibration {
+    /// The diagonal of `R`.
+    pub row_scale: Vec<f64>,
+    /// The diagonal of `C`.
+    pub col_scale: Vec<f64>,
+}
+
+impl Equilibration {
+    /// Computes the scaling of the matrix of the summed `triplets`: first every row is scaled
+    /// by its largest entry, then every column of the row-scaled matrix by its largest entry.
+    /// Empty rows and columns are left unscaled.
+    pub fn new(triplets: &[Triplet<usize, usize, f64>], size: usize) -> Self {
+        let mut row_max = vec![0.0_f64; size];
+        for triplet in triplets {
+            row_max[triplet.row] = row_max[triplet.row].max(triplet.val.abs());
+        }
+        let row_scale: Vec<f64> = row_max.into_iter().map(power_of_two_inverse).collect();
+
+        let mut col_max = vec![0.0_f64; size];
+        for triplet in triplets {
+            let scaled = (row_scale[triplet.row] * triplet.val).abs();
+            col_max[triplet.col] = col_max[triplet.col].max(scaled);
+        }
+        let col_scale = col_max.into_iter().map(power_of_two_inverse).collect();
+
+        Equilibration {
+            row_scale,
+            col_scale,
+        }
+    }
+
+    /// Returns the triplets of the scaled matrix `R A C`.
+    pub fn scale_matrix(
+        &self,
+        triplets: &[Triplet<usize, usize, f64>],
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        triplets
+            .iter()
+            .map(|&Triplet { row, col, val }| {
+                Triplet::new(row, col, self.row_scale[row] * val * self.col_scale[col])
+            })
+            .collect()
+    }
+
+    /// Scales the right-hand side `b` to `R b`.
+    pub fn scale_rhs(&self, b: &mut Mat<f64>) {
+        for (i, scale) in self.row_scale.iter().enumerate() {
+            b[(i, 0)] *= scale;
+        }
+    }
+
+    /// Recovers the solution `x = C y` of the original system from the solution `y` of the
+    /// scaled system.
+    pub fn unscale_solution(&self, y: &mut Mat<f64>) {
+        for (i, scale) in self.col_scale.iter().enumerate() {
+            y[(i, 0)] *= scale;
+        }
+    }
+}
+
+/// Returns the power of two closest to `1 / max`, or one for an empty row or column.
+fn power_of_two_inverse(max: f64) -> f64 {
+    if max > 0.0 && max.is_finite() {
+        2.0_f64.powi(-(max.log2().round() as i32))
+    } else {
+        1.0
+    }
+}
+
+/// Solves the linear system of the summed conductance `g_triplets` and excitation
+/// `e_triplets`, which has `size` unknowns.
+///
+/// With `config.equilibrate`, the system is scaled with [`Equilibration`] before the LU
+/// factorization and the solution is unscaled afterwards. With `config.check_conditioning`,
+/// the condition number of the factorized matrix is estimated and a warning is logged when it
+/// exceeds `config.condition_threshold`.
+///
+/// With an iterative `config.linear_solver`, the system is first solved iteratively, and only
+/// factorized when that does not converge.
+pub fn solve_linear_system(
+    g_triplets: &[Triplet<usize, usize, f64>],
+    e_triplets: &[Triplet<usize, usize, f64>],
+    size: usize,
+    config: &SolverConfig,
+) -> Result<Mat<f64>> {
+    let equilibration = config
+        .equilibrate
+        .then(|| Equilibration::new(g_triplets, size));
+    let scaled_triplets;
+    let g_triplets = match &equilibration {
+        Some(equilibration) => {
+            scaled_triplets = equilibration.scale_matrix(g_triplets);
+            &scaled_triplets
+        }
+        None => g_triplets,
+    };
+
+    let mut b = Mat::zeros(size, 1);
+    for &Triplet { row, col, val } in e_triplets {
+        b[(row, col)] = val;
+    }
+    if let Some(equilibration) = &equilibration {
+        equilibration.scale_rhs(&mut b);
+    }
+
+    let iterative = match config.linear_solver {
+        LinearSolver::LuDirect => None,
+        method => {
+            let x = solve_iterative(
+                g_triplets,
+                &b,
+                size,
+                method,
+                config.linear_relative_tolerance,
+                config.linear_maximum_iterations,
+            );
+            if x.is_none() {
+                info!("{method:?} did not converge, falling back to LU factorization");
+            }
+            x
+        }
+    };
+
+    let mut x = match iterative {
+        Some(x) => x,
+        None => factorize_and_solve(g_triplets, &b, size, config)?,
+    };
+    if let Some(equilibration) = &equilibration {
+        equilibration.unscale_solution(&mut x);
+    }
+    Ok(x)
+}
+
+/// Solves the system with a sparse Cholesky factorization if possible, see
+/// [`solve_cholesky`], and with a sparse LU factorization otherwise. The conditioning of the
+/// factorized matrix is checked when `config.check_conditioning` is set.
+fn factorize_and_solve(
+    g_triplets: &[Triplet<usize, usize, f64>],
+    b: &Mat<f64>,
+    size: usize,
+    config: &SolverConfig,
+) -> Result<Mat<f64>> {
+    if let Some(x) = solve_cholesky(g_triplets, b, size, config) {
+        return Ok(x);
+    }
+
+    let lu = SparseColMat::try_new_from_triplets(size, size, g_triplets)
+        .map_err(|_| Error::MatrixBuild)?
+        .sp_lu()
+        .map_err(|_| Error::MatrixDecomposition)?;
+    check_conditioning(g_triplets, size, &lu, config);
+    Ok(lu.solve(b))
+}
+
+/// Solves the system of the summed `g_triplets` with a sparse Cholesky factorization, which
+/// takes about half the work of an LU factorization and needs no pivoting.
+///
+/// The singleton rows and columns of the sources are eliminated first, see [`Reduction`].
+/// The Cholesky factorization is only attempted when the remaining matrix is symmetric, as
+/// for networks of resistors, independent sources and diodes, and succeeds only when it is
+/// also positive definite. Returns `None` otherwise, so the caller can use LU instead.
+pub fn solve_cholesky(
+    g_triplets: &[Triplet<usize, usize, f64>],
+    b: &Mat<f64>,
+    size: usize,
+    config: &SolverConfig,
+) -> Option<Mat<f64>> {
+    let rhs: Vec<f64> = (0..size).map(|i| b[(i, 0)]).collect();
+    let reduction = Reduction::new(g_triplets, &rhs, size)?;
+    let reduced_size = reduction.size();
+    if !is_symmetric(&reduction.triplets, SYMMETRY_TOLERANCE) {
+        return None;
+    }
+
+    let mut y = Vec::new();
+    if reduced_size > 0 {
+        let llt =
+            SparseColMat::try_new_from_triplets(reduced_size, reduced_size, &reduction.triplets)
+                .ok()?
+                .sp_cholesky(Side::Lower)
+                .ok()?;
+        check_conditioning(&reduction.triplets, reduced_size, &llt, config);
+        let solution = llt.solve(Mat::from_fn(reduced_size, 1, |i, _| reduction.b[i]));
+        y = (0..reduced_size).map(|i| solution[(i, 0)]).collect();
+    }
+    let x = reduction.expand(g_triplets, &rhs, &y);
+    Some(Mat::from_fn(size, 1, |i, _| x[i]))
+}
+
+/// Logs a warning when `config.check_conditioning` is set and the condition number of the
+/// matrix of `triplets` exceeds `config.condition_threshold`.
+fn check_conditioning(
+    triplets: &[Triplet<usize, usize, f64>],
+    size: usize,
+    factorization: &impl Solve<f64>,
+    config: &SolverConfig,
+) {
+    if config.check_conditioning {
+        let condition_number = estimate_condition_number(triplets, size, factorization);
+        if condition_number > config.condition_threshold {
+            warn!(
+                "The conductance matrix is ill-conditioned (condition number {condition_number:e}); \
+                 the results may be inaccurate."
+            );
+        }
+    }
+}
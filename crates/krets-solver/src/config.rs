@@ -1,3 +1,44 @@
+use krets_parser::{
+    constants::{TEMPERATURE, ZERO_CELSIUS},
+    options::SimulatorOptions,
+};
+
+/// Numerical integration method used to discretize capacitors and inductors in transient analysis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntegrationMethod {
+    /// First-order backward Euler: robust, but dissipative.
+    #[default]
+    BackwardEuler,
+    /// Second-order Gear (BDF2), built from the previous two time points. The first step,
+    /// which has only one previous point, falls back to backward Euler.
+    Gear2,
+}
+
+/// Homotopy the operating point falls back to when plain Newton-Raphson does not converge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Homotopy {
+    /// Gmin stepping over `SolverConfig::gmin_steps` stages.
+    #[default]
+    GminStepping,
+    /// Source stepping: all independent sources are ramped up from zero to their full value
+    /// in `steps` equal stages.
+    SourceStepping { steps: usize },
+}
+
+/// Method used to solve the linear system of every Newton-Raphson iteration of the operating
+/// point.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinearSolver {
+    /// Sparse LU factorization.
+    #[default]
+    LuDirect,
+    /// Jacobi-preconditioned conjugate gradients, for symmetric positive definite matrices
+    /// such as those of resistor networks driven by current sources.
+    IterativeCg,
+    /// Jacobi-preconditioned BiCGSTAB, which also handles unsymmetric matrices.
+    IterativeBicgstab,
+}
+
 /// Configuration structure for controlling solver parameters across different simulation types
 
 #[derive(Clone, Debug)]
@@ -20,6 +61,63 @@ pub struct SolverConfig {
 
     /// Minimum conductance (inverse of resistance) considered by the solver
     pub minimum_conductance: f64,
+
+    /// Initial conductance (in siemens) connected from every node to ground when the
+    /// operating point falls back to gmin stepping
+    pub gmin: f64,
+
+    /// Number of gmin stepping stages; 0 disables the gmin stepping fallback
+    pub gmin_steps: usize,
+
+    /// Homotopy used by the operating point when Newton-Raphson does not converge
+    pub homotopy: Homotopy,
+
+    /// Integration method of the capacitor and inductor companion models in transient analysis
+    pub integration_method: IntegrationMethod,
+
+    /// Selects adaptive time stepping for transient analysis instead of the fixed `time_step`.
+    pub adaptive_time_step: bool,
+
+    /// Relative tolerance on the local truncation error of an adaptive transient step
+    pub reltol: f64,
+
+    /// Absolute tolerance (in volts) on the local truncation error of an adaptive transient step
+    pub abstol: f64,
+
+    /// Largest time step (in seconds) the adaptive transient solver may take
+    pub max_step: f64,
+
+    /// Smallest time step (in seconds) the adaptive transient solver may take before giving up
+    pub min_step: f64,
+
+    /// Adds the current through every element, e.g. `I(R1)`, to operating point and DC sweep
+    /// results, not only the branch currents of group-2 elements
+    pub probe_currents: bool,
+
+    /// Circuit temperature in degrees Celsius, unless the netlist sets one with `.temp`
+    pub temperature: f64,
+
+    /// Scales the rows and columns of the operating point matrix to unit magnitude before
+    /// the LU factorization, which improves the accuracy of badly scaled circuits
+    pub equilibrate: bool,
+
+    /// Estimates the condition number of the operating point matrix on every Newton-Raphson
+    /// iteration and warns when it exceeds `condition_threshold`
+    pub check_conditioning: bool,
+
+    /// Condition number above which the operating point matrix is reported as ill-conditioned
+    pub condition_threshold: f64,
+
+    /// Method that solves the linear system of the operating point. The iterative methods fall
+    /// back to LU when they do not converge.
+    pub linear_solver: LinearSolver,
+
+    /// Relative residual an iterative linear solve must reach, independent of the
+    /// Newton-Raphson `relative_tolerance`
+    pub linear_relative_tolerance: f64,
+
+    /// Maximum number of iterations of an iterative linear solve before it falls back to LU
+    pub linear_maximum_iterations: usize,
 }
 
 /// Default configuration for the solver, providing reasonable defaults for all parameters.
@@ -32,6 +130,51 @@ impl Default for SolverConfig {
             maximum_iterations: 300,
             minimum_resistance: 1e-3,
             minimum_conductance: 1e-12,
+            gmin: 1e-2,
+            gmin_steps: 10,
+            homotopy: Homotopy::default(),
+            integration_method: IntegrationMethod::default(),
+            adaptive_time_step: false,
+            reltol: 1e-3,
+            abstol: 1e-6,
+            max_step: f64::INFINITY,
+            min_step: 1e-15,
+            probe_currents: false,
+            temperature: TEMPERATURE - ZERO_CELSIUS,
+            equilibrate: false,
+            check_conditioning: false,
+            condition_threshold: 1e12,
+            linear_solver: LinearSolver::default(),
+            linear_relative_tolerance: 1e-12,
+            linear_maximum_iterations: 10_000,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Overrides the tolerances and limits set by the `.options` cards of a netlist.
+    ///
+    /// `reltol` sets both the Newton-Raphson `relative_tolerance` and the `reltol` of the
+    /// adaptive transient step, `abstol` the `current_absolute_tolerance`, `vntol` the
+    /// `voltage_absolute_tolerance` and `itl1` the `maximum_iterations`. `gmin` is the
+    /// conductance SPICE leaves across junctions, the final `minimum_conductance` of gmin
+    /// stepping rather than its initial `gmin`.
+    pub fn apply_options(&mut self, options: &SimulatorOptions) {
+        if let Some(reltol) = options.reltol {
+            self.relative_tolerance = reltol;
+            self.reltol = reltol;
+        }
+        if let Some(abstol) = options.abstol {
+            self.current_absolute_tolerance = abstol;
+        }
+        if let Some(vntol) = options.vntol {
+            self.voltage_absolute_tolerance = vntol;
+        }
+        if let Some(itl1) = options.itl1 {
+            self.maximum_iterations = itl1;
+        }
+        if let Some(gmin) = options.gmin {
+            self.minimum_conductance = gmin;
         }
     }
 }
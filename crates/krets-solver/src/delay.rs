@@ -0,0 +1,107 @@
+use crate::{prelude::*, stampable::stamp_incident_waves};
+use krets_parser::{
+    circuit::Circuit, elements::Element, elements::transmission_line::TransmissionLine,
+};
+use std::collections::VecDeque;
+
+/// The history of a transmission line during a transient analysis.
+///
+/// A lossless line is modeled at each port by its characteristic impedance `Z0` in series with
+/// the wave arriving from the other port. The wave a port launches into the line is
+/// `a = V + Z0 * I`, with the port voltage `V` and the current `I` into the line, and it
+/// arrives at the other port one delay `TD` later. The launched waves of the accepted time
+/// points are kept for as long as they are still in flight, and the arriving waves are
+/// interpolated linearly between them. Before the first delay has passed, the waves of the
+/// initial solution arrive, so a line starting at its operating point stays there.
+pub(crate) struct DelayLine {
+    line: TransmissionLine,
+    /// The time and the waves launched at the first and second port of the accepted time
+    /// points, oldest first.
+    history: VecDeque<(f64, f64, f64)>,
+}
+
+impl DelayLine {
+    /// Returns the history of every transmission line of `circuit`, starting from the initial
+    /// solution at t=0.
+    pub(crate) fn for_circuit(circuit: &Circuit, initial: &HashMap<String, f64>) -> Vec<Self> {
+        circuit
+            .elements
+            .iter()
+            .filter_map(|element| match element {
+                Element::TransmissionLine(line) => Some(DelayLine::new(line.clone(), initial)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn new(line: TransmissionLine, initial: &HashMap<String, f64>) -> Self {
+        // In a steady state the current into the second port is the opposite of the first.
+        let (v1, v2, i1) = port_quantities(&line, initial);
+        let history = VecDeque::from([(0.0, v1 + line.z0 * i1, v2 - line.z0 * i1)]);
+        DelayLine { line, history }
+    }
+
+    /// Returns the delay of the line in seconds.
+    pub(crate) fn delay(&self) -> f64 {
+        self.line.td
+    }
+
+    /// Returns the waves `(e1, e2)` arriving at the first and second port at `time`.
+    fn incident(&self, time: f64) -> (f64, f64) {
+        let launched = time - self.line.td;
+        let after = self.history.partition_point(|&(t, _, _)| t <= launched);
+        let (a1, a2) = match (after.checked_sub(1), self.history.get(after)) {
+            (Some(before), Some(&(t1, a1_next, a2_next))) => {
+                let (t0, a1, a2) = self.history[before];
+                let fraction = (launched - t0) / (t1 - t0);
+                (
+                    a1 + fraction * (a1_next - a1),
+                    a2 + fraction * (a2_next - a2),
+                )
+            }
+            // Before the initial solution, or later than the last accepted time point when the
+            // step exceeds the delay.
+            (None, _) => (self.history[0].1, self.history[0].2),
+            (Some(last), None) => (self.history[last].1, self.history[last].2),
+        };
+        (a2, a1)
+    }
+
+    /// Returns the excitation stamp of the waves arriving at the ports at `time`.
+    pub(crate) fn stamp(
+        &self,
+        index_map: &HashMap<String, usize>,
+        time: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let (e1, e2) = self.incident(time);
+        stamp_incident_waves(&self.line, index_map, e1, e2)
+    }
+
+    /// Records the waves launched at the accepted time point `solution`, and forgets those
+    /// that have arrived at the other port before any later time point.
+    pub(crate) fn accept(&mut self, solution: &HashMap<String, f64>) {
+        let time = solution.get("time").copied().unwrap_or(0.0);
+        let (_, e2) = self.incident(time);
+        let (v1, v2, i1) = port_quantities(&self.line, solution);
+        // The second port is the Norton equivalent of `Z0` in series with `e2`.
+        let i2 = (v2 - e2) / self.line.z0;
+        self.history
+            .push_back((time, v1 + self.line.z0 * i1, v2 + self.line.z0 * i2));
+
+        while self.history.len() > 1 && self.history[1].0 <= time - self.line.td {
+            self.history.pop_front();
+        }
+    }
+}
+
+/// Returns the voltages of both ports of `line` and the current into its first port.
+fn port_quantities(line: &TransmissionLine, solution: &HashMap<String, f64>) -> (f64, f64, f64) {
+    let voltage = |node: &str| solution.get(&format!("V({node})")).copied().unwrap_or(0.0);
+    let v1 = voltage(&line.plus1) - voltage(&line.minus1);
+    let v2 = voltage(&line.plus2) - voltage(&line.minus2);
+    let i1 = solution
+        .get(&format!("I({})", line.identifier()))
+        .copied()
+        .unwrap_or(0.0);
+    (v1, v2, i1)
+}
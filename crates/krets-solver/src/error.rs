@@ -10,9 +10,19 @@ pub enum Error {
     #[error("Element '{0}' not found in the netlist")]
     ElementNotFound(String),
 
-    // Error when maximum iterations are exceeded
-    #[error("Maximum iterations exceeded: {0}")]
-    MaximumIterationsExceeded(usize),
+    // Error when a node referenced by the analysis is not found in the netlist
+    #[error("Node '{0}' not found in the netlist")]
+    NodeNotFound(String),
+
+    // Error when Newton-Raphson does not converge within the maximum number of iterations
+    #[error(
+        "No convergence after {iterations} iterations: {worst_node} still changed by {delta:e}"
+    )]
+    ConvergenceFailed {
+        iterations: usize,
+        worst_node: String,
+        delta: f64,
+    },
 
     // Error indicating that the format of an element in the input string is invalid.
     #[error("Invalid element format: {0}")]
@@ -30,6 +40,30 @@ pub enum Error {
     #[error("Matrix build failed")]
     MatrixBuild,
 
+    // Error when a node has no DC path to ground, which would leave the MNA matrix singular
+    #[error("Node '{0}' has no DC path to ground")]
+    FloatingNode(String),
+
+    // Error when the adaptive transient solver cannot meet its tolerances.
+    #[error("Time step too small at t = {0}s")]
+    TimeStepTooSmall(f64),
+
+    // Error when a `.measure` card cannot be evaluated on the analysis result.
+    #[error("Measurement failed: {0}")]
+    MeasurementFailed(String),
+
+    // Error when the frequency range of an AC or noise sweep cannot be swept.
+    #[error("Invalid frequency range from {fstart}Hz to {fstop}Hz: {reason}")]
+    InvalidFrequencyRange {
+        fstart: f64,
+        fstop: f64,
+        reason: String,
+    },
+
+    // Error when an analysis cannot be run in the requested context, e.g. inside a sweep.
+    #[error("Unsupported analysis: {0}")]
+    UnsupportedAnalysis(String),
+
     // Error indicating that the matrix could not be decomposed.
     #[error("Matrix decomposition failed")]
     MatrixDecomposition,
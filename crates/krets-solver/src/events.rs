@@ -0,0 +1,95 @@
+use crate::prelude::*;
+
+/// The direction in which a signal must cross the level of a [`CrossingEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// The signal rises through the level.
+    Rising,
+    /// The signal falls through the level.
+    Falling,
+    /// The signal crosses the level in either direction.
+    Either,
+}
+
+/// A threshold crossing of a signal to detect during a transient analysis, e.g. `V(out)`
+/// rising through 2.5V.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossingEvent {
+    /// The watched signal, keyed like the MNA index map (e.g. `V(out)` or `I(V1)`).
+    pub signal: String,
+    /// The level the signal crosses.
+    pub level: f64,
+    /// The direction of the crossings to report.
+    pub edge: Edge,
+}
+
+impl CrossingEvent {
+    /// Creates an event for `signal` crossing `level` in the direction of `edge`.
+    pub fn new(signal: &str, level: f64, edge: Edge) -> Self {
+        CrossingEvent {
+            signal: signal.to_string(),
+            level,
+            edge,
+        }
+    }
+}
+
+/// Finds the crossings of a [`CrossingEvent`] as the time points of a transient analysis
+/// arrive in order of time.
+///
+/// A crossing lies between two consecutive time points, one on each side of the level. Its
+/// time is interpolated linearly over the actual step between them, so it does not depend on
+/// the points being equally spaced. A time point exactly at the level counts as the crossing
+/// itself, and the signal has to leave the level again before it can cross once more.
+#[derive(Debug, Clone)]
+pub struct CrossingDetector {
+    event: CrossingEvent,
+    /// The time and value of the signal at the previous time point.
+    previous: Option<(f64, f64)>,
+    crossings: Vec<f64>,
+}
+
+impl CrossingDetector {
+    pub fn new(event: CrossingEvent) -> Self {
+        CrossingDetector {
+            event,
+            previous: None,
+            crossings: Vec::new(),
+        }
+    }
+
+    /// Checks the step from the previous time point to `row` for a crossing. Rows without a
+    /// `time` entry or without the watched signal are ignored.
+    pub fn observe(&mut self, row: &HashMap<String, f64>) {
+        let (Some(&time), Some(&value)) = (row.get("time"), row.get(&self.event.signal)) else {
+            return;
+        };
+
+        if let Some((previous_time, previous_value)) = self.previous {
+            let level = self.event.level;
+            let rising = previous_value < level && value >= level;
+            let falling = previous_value > level && value <= level;
+            let wanted = match self.event.edge {
+                Edge::Rising => rising,
+                Edge::Falling => falling,
+                Edge::Either => rising || falling,
+            };
+            if wanted {
+                let h = time - previous_time;
+                let fraction = (level - previous_value) / (value - previous_value);
+                self.crossings.push(previous_time + fraction * h);
+            }
+        }
+        self.previous = Some((time, value));
+    }
+
+    /// Returns the times of the crossings found so far, in increasing order.
+    pub fn crossings(&self) -> &[f64] {
+        &self.crossings
+    }
+
+    /// Consumes the detector, returning the times of its crossings.
+    pub fn into_crossings(self) -> Vec<f64> {
+        self.crossings
+    }
+}
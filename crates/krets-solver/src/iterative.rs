@@ -0,0 +1,176 @@
+use crate::{config::LinearSolver, mna::Reduction, prelude::*};
+use faer::Mat;
+
+/// Solves `A x = b` with a Jacobi-preconditioned Krylov method, where `A` is the matrix of the
+/// summed `triplets` with `size` unknowns.
+///
+/// Rows and columns with a single entry are eliminated first, see [`Reduction`]. For a network
+/// of resistors and independent sources this leaves the symmetric positive definite nodal
+/// matrix of the nodes that are not fixed by a grounded voltage source, which conjugate
+/// gradients require. BiCGSTAB also handles the unsymmetric matrices of controlled sources.
+///
+/// The iteration stops once the residual of the reduced system drops below
+/// `relative_tolerance` times its right-hand side. Returns `None` when that does not happen
+/// within `maximum_iterations` iterations or the method breaks down, so the caller can fall
+/// back to a direct solve. `LuDirect` is not an iterative method and always returns `None`.
+pub fn solve_iterative(
+    triplets: &[Triplet<usize, usize, f64>],
+    b: &Mat<f64>,
+    size: usize,
+    method: LinearSolver,
+    relative_tolerance: f64,
+    maximum_iterations: usize,
+) -> Option<Mat<f64>> {
+    let b: Vec<f64> = (0..size).map(|i| b[(i, 0)]).collect();
+    let reduction = Reduction::new(triplets, &b, size)?;
+    let system = System::new(&reduction.triplets, reduction.size());
+    let tolerance = relative_tolerance * norm(&reduction.b);
+
+    let y = if tolerance == 0.0 {
+        Some(vec![0.0; reduction.size()])
+    } else {
+        match method {
+            LinearSolver::LuDirect => None,
+            LinearSolver::IterativeCg => system.cg(&reduction.b, tolerance, maximum_iterations),
+            LinearSolver::IterativeBicgstab => {
+                system.bicgstab(&reduction.b, tolerance, maximum_iterations)
+            }
+        }
+    }?;
+    let x = reduction.expand(triplets, &b, &y);
+    Some(Mat::from_fn(size, 1, |i, _| x[i]))
+}
+
+/// A matrix in triplet form together with its Jacobi preconditioner.
+struct System<'a> {
+    triplets: &'a [Triplet<usize, usize, f64>],
+    /// The inverse of the diagonal. Rows without a diagonal entry are left unpreconditioned.
+    inverse_diagonal: Vec<f64>,
+}
+
+impl<'a> System<'a> {
+    fn new(triplets: &'a [Triplet<usize, usize, f64>], size: usize) -> Self {
+        let mut inverse_diagonal = vec![1.0; size];
+        for triplet in triplets {
+            if triplet.row == triplet.col && triplet.val != 0.0 {
+                inverse_diagonal[triplet.row] = 1.0 / triplet.val;
+            }
+        }
+        System {
+            triplets,
+            inverse_diagonal,
+        }
+    }
+
+    fn multiply(&self, x: &[f64]) -> Vec<f64> {
+        let mut y = vec![0.0; x.len()];
+        for &Triplet { row, col, val } in self.triplets {
+            y[row] += val * x[col];
+        }
+        y
+    }
+
+    fn precondition(&self, r: &[f64]) -> Vec<f64> {
+        r.iter()
+            .zip(&self.inverse_diagonal)
+            .map(|(r, d)| r * d)
+            .collect()
+    }
+
+    /// Preconditioned conjugate gradients, starting from zero.
+    fn cg(&self, b: &[f64], tolerance: f64, maximum_iterations: usize) -> Option<Vec<f64>> {
+        let mut x = vec![0.0; b.len()];
+        let mut r = b.to_vec();
+        let mut z = self.precondition(&r);
+        let mut p = z.clone();
+        let mut rz = dot(&r, &z);
+
+        for _ in 0..maximum_iterations {
+            let ap = self.multiply(&p);
+            let p_ap = dot(&p, &ap);
+            if p_ap == 0.0 || !p_ap.is_finite() {
+                return None;
+            }
+            let alpha = rz / p_ap;
+            axpy(alpha, &p, &mut x);
+            axpy(-alpha, &ap, &mut r);
+            if norm(&r) <= tolerance {
+                return Some(x);
+            }
+
+            z = self.precondition(&r);
+            let rz_next = dot(&r, &z);
+            let beta = rz_next / rz;
+            rz = rz_next;
+            for (p, z) in p.iter_mut().zip(&z) {
+                *p = z + beta * *p;
+            }
+        }
+        None
+    }
+
+    /// Right-preconditioned BiCGSTAB, starting from zero.
+    fn bicgstab(&self, b: &[f64], tolerance: f64, maximum_iterations: usize) -> Option<Vec<f64>> {
+        let size = b.len();
+        let mut x = vec![0.0; size];
+        let mut r = b.to_vec();
+        let r_hat = r.clone();
+        let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+        let mut v = vec![0.0; size];
+        let mut p = vec![0.0; size];
+
+        for _ in 0..maximum_iterations {
+            let rho_next = dot(&r_hat, &r);
+            if rho_next == 0.0 || omega == 0.0 {
+                return None;
+            }
+            let beta = (rho_next / rho) * (alpha / omega);
+            rho = rho_next;
+            for i in 0..size {
+                p[i] = r[i] + beta * (p[i] - omega * v[i]);
+            }
+
+            let y = self.precondition(&p);
+            v = self.multiply(&y);
+            alpha = rho / dot(&r_hat, &v);
+            if !alpha.is_finite() {
+                return None;
+            }
+            axpy(alpha, &y, &mut x);
+            let mut s = r;
+            axpy(-alpha, &v, &mut s);
+            if norm(&s) <= tolerance {
+                return Some(x);
+            }
+
+            let z = self.precondition(&s);
+            let t = self.multiply(&z);
+            omega = dot(&t, &s) / dot(&t, &t);
+            if !omega.is_finite() {
+                return None;
+            }
+            axpy(omega, &z, &mut x);
+            r = s;
+            axpy(-omega, &t, &mut r);
+            if norm(&r) <= tolerance {
+                return Some(x);
+            }
+        }
+        None
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// Computes `y += alpha * x`.
+fn axpy(alpha: f64, x: &[f64], y: &mut [f64]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += alpha * x;
+    }
+}
@@ -1,9 +1,19 @@
+pub mod conditioning;
 pub mod config;
+pub(crate) mod delay;
 pub mod error;
+pub mod events;
+pub mod iterative;
+pub mod measure;
+pub mod mna;
 pub mod prelude;
+pub mod report;
 pub mod solver;
 pub mod stampable;
+pub mod topology;
 use crate::prelude::*;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::collections::BTreeMap;
 
 /// Holds the output data from a completed analysis.
 ///
@@ -16,8 +26,9 @@ pub enum AnalysisResult {
     Op(HashMap<String, f64>),
 
     /// Result of a DC Sweep analysis.
-    /// A vector of HashMaps, where each map is the solution at one sweep point.
-    Dc(Vec<HashMap<String, f64>>),
+    /// A vector of HashMaps, where each map is the solution at one sweep point, and the values
+    /// of the `.measure dc` cards keyed by measurement name.
+    Dc(Vec<HashMap<String, f64>>, HashMap<String, f64>),
 
     /// Result of an AC Small-Signal Analysis.
     /// A vector of HashMaps, where each map is the solution at one frequency.
@@ -25,8 +36,27 @@ pub enum AnalysisResult {
 
     /// Result of a Transient analysis.
     /// A vector of HashMaps, where each map is the solution at one
-    /// time step.
-    Transient(Vec<HashMap<String, f64>>),
+    /// time step, and the values of the `.measure tran` cards keyed by measurement name.
+    Transient(Vec<HashMap<String, f64>>, HashMap<String, f64>),
+
+    /// Result of an AC Noise analysis.
+    /// A vector of HashMaps, where each map holds the noise spectral
+    /// densities at one frequency.
+    Noise(Vec<HashMap<String, f64>>),
+
+    /// Result of a DC Transfer Function analysis.
+    /// A single HashMap holding the small-signal `gain` from the input to the output, the
+    /// `input_resistance` seen by the input source and the `output_resistance` at the output.
+    Tf(HashMap<String, f64>),
+
+    /// Result of a DC Sensitivity analysis.
+    /// A single HashMap holding the derivative of the output voltage with respect to the value
+    /// of every resistor and independent source, keyed by the element identifier.
+    Sensitivity(HashMap<String, f64>),
+
+    /// Result of a Pole-Zero analysis.
+    /// The finite poles and zeros of the transfer function, in rad/s.
+    PoleZero { poles: Vec<c64>, zeros: Vec<c64> },
 }
 
 impl AnalysisResult {
@@ -47,7 +77,7 @@ impl AnalysisResult {
     /// Panics if the result is not `AnalysisResult::Dc`.
     pub fn into_dc(self) -> Vec<HashMap<String, f64>> {
         match self {
-            AnalysisResult::Dc(result) => result,
+            AnalysisResult::Dc(result, _) => result,
             _ => panic!("Called `into_dc()` on a non-Dc result"),
         }
     }
@@ -69,8 +99,203 @@ impl AnalysisResult {
     /// Panics if the result is not `AnalysisResult::Transient`.
     pub fn into_transient(self) -> Vec<HashMap<String, f64>> {
         match self {
-            AnalysisResult::Transient(result) => result,
+            AnalysisResult::Transient(result, _) => result,
             _ => panic!("Called `into_transient()` on a non-Transient result"),
         }
     }
+
+    /// Unwraps the `AnalysisResult` to get the `Noise` result.
+    ///
+    /// # Panics
+    /// Panics if the result is not `AnalysisResult::Noise`.
+    pub fn into_noise(self) -> Vec<HashMap<String, f64>> {
+        match self {
+            AnalysisResult::Noise(result) => result,
+            _ => panic!("Called `into_noise()` on a non-Noise result"),
+        }
+    }
+
+    /// Unwraps the `AnalysisResult` to get the `Tf` result.
+    ///
+    /// # Panics
+    /// Panics if the result is not `AnalysisResult::Tf`.
+    pub fn into_tf(self) -> HashMap<String, f64> {
+        match self {
+            AnalysisResult::Tf(result) => result,
+            _ => panic!("Called `into_tf()` on a non-Tf result"),
+        }
+    }
+
+    /// Unwraps the `AnalysisResult` to get the `Sensitivity` result.
+    ///
+    /// # Panics
+    /// Panics if the result is not `AnalysisResult::Sensitivity`.
+    pub fn into_sensitivity(self) -> HashMap<String, f64> {
+        match self {
+            AnalysisResult::Sensitivity(result) => result,
+            _ => panic!("Called `into_sensitivity()` on a non-Sensitivity result"),
+        }
+    }
+
+    /// Unwraps the `AnalysisResult` to get the `PoleZero` result as `(poles, zeros)`.
+    ///
+    /// # Panics
+    /// Panics if the result is not `AnalysisResult::PoleZero`.
+    pub fn into_pole_zero(self) -> (Vec<c64>, Vec<c64>) {
+        match self {
+            AnalysisResult::PoleZero { poles, zeros } => (poles, zeros),
+            _ => panic!("Called `into_pole_zero()` on a non-PoleZero result"),
+        }
+    }
+
+    /// Returns the value of the signal `name`, e.g. `V(out)`, of an `Op` result.
+    ///
+    /// Returns `None` if the result is not an `Op` result or has no such signal.
+    pub fn op_value(&self, name: &str) -> Option<f64> {
+        match self {
+            AnalysisResult::Op(result) => result.get(name).copied(),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of the signal `name` at every point of a `Dc` result.
+    ///
+    /// Returns `None` if the result is not a `Dc` result or a point lacks the signal.
+    pub fn dc_series(&self, name: &str) -> Option<Vec<f64>> {
+        match self {
+            AnalysisResult::Dc(rows, _) => series(rows, name),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of the signal `name` at every frequency of an `Ac` result.
+    ///
+    /// Returns `None` if the result is not an `Ac` result or a frequency lacks the signal.
+    pub fn ac_series(&self, name: &str) -> Option<Vec<c64>> {
+        match self {
+            AnalysisResult::Ac(rows) => series(rows, name),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of the signal `name` at every time point of a `Transient` result.
+    ///
+    /// Returns `None` if the result is not a `Transient` result or a time point lacks the
+    /// signal.
+    pub fn tran_series(&self, name: &str) -> Option<Vec<f64>> {
+        match self {
+            AnalysisResult::Transient(rows, _) => series(rows, name),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of the `.measure` cards evaluated on a `Dc` or `Transient` result,
+    /// keyed by measurement name.
+    ///
+    /// Returns `None` for the other analyses, which have no measurements.
+    pub fn measurements(&self) -> Option<&HashMap<String, f64>> {
+        match self {
+            AnalysisResult::Dc(_, measurements) | AnalysisResult::Transient(_, measurements) => {
+                Some(measurements)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Collects the values of `name` from every row, or `None` if a row lacks it.
+fn series<V: Copy>(rows: &[HashMap<String, V>], name: &str) -> Option<Vec<V>> {
+    rows.iter().map(|row| row.get(name).copied()).collect()
+}
+
+/// Serializes a complex value as `{re, im, mag, phase}`, with the phase in degrees.
+struct ComplexValue<'a>(&'a c64);
+
+impl Serialize for ComplexValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let value = self.0;
+        let mut state = serializer.serialize_struct("Complex", 4)?;
+        state.serialize_field("re", &value.re)?;
+        state.serialize_field("im", &value.im)?;
+        state.serialize_field("mag", &value.norm())?;
+        state.serialize_field("phase", &value.arg().to_degrees())?;
+        state.end()
+    }
+}
+
+/// Orders the entries of a result row by key, so the serialized output is stable.
+fn sorted<V>(row: &HashMap<String, V>) -> BTreeMap<&str, &V> {
+    row.iter()
+        .map(|(key, value)| (key.as_str(), value))
+        .collect()
+}
+
+/// Orders the entries of an AC result row by key, see [`sorted`].
+fn sorted_complex(row: &HashMap<String, c64>) -> BTreeMap<&str, ComplexValue<'_>> {
+    row.iter()
+        .map(|(key, value)| (key.as_str(), ComplexValue(value)))
+        .collect()
+}
+
+/// Serializes the result as an object `{"analysis": ..., "data": ...}`.
+///
+/// `analysis` names the variant in snake case (`op`, `dc`, `ac`, `transient`, `noise`, `tf`
+/// or `pole_zero`). `data` holds:
+/// - `op` and `tf`: a single object mapping every quantity to its value.
+/// - `dc`, `transient` and `noise`: an array with one such object per sweep point, time step
+///   or frequency.
+/// - `ac`: an array with one object per frequency, mapping every quantity to a complex value
+///   `{re, im, mag, phase}` with the phase in degrees.
+/// - `pole_zero`: an object with the arrays `poles` and `zeros` of complex values.
+///
+/// `dc` and `transient` results also have a `measurements` object mapping the name of every
+/// `.measure` card to its value.
+///
+/// The keys of every object are sorted. Non-finite values serialize as the format's null.
+impl Serialize for AnalysisResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AnalysisResult", 3)?;
+        match self {
+            AnalysisResult::Op(row) => {
+                state.serialize_field("analysis", "op")?;
+                state.serialize_field("data", &sorted(row))?;
+            }
+            AnalysisResult::Dc(rows, measurements) => {
+                state.serialize_field("analysis", "dc")?;
+                state.serialize_field("data", &rows.iter().map(sorted).collect::<Vec<_>>())?;
+                state.serialize_field("measurements", &sorted(measurements))?;
+            }
+            AnalysisResult::Ac(rows) => {
+                state.serialize_field("analysis", "ac")?;
+                let rows: Vec<_> = rows.iter().map(sorted_complex).collect();
+                state.serialize_field("data", &rows)?;
+            }
+            AnalysisResult::Transient(rows, measurements) => {
+                state.serialize_field("analysis", "transient")?;
+                state.serialize_field("data", &rows.iter().map(sorted).collect::<Vec<_>>())?;
+                state.serialize_field("measurements", &sorted(measurements))?;
+            }
+            AnalysisResult::Noise(rows) => {
+                state.serialize_field("analysis", "noise")?;
+                state.serialize_field("data", &rows.iter().map(sorted).collect::<Vec<_>>())?;
+            }
+            AnalysisResult::Tf(row) => {
+                state.serialize_field("analysis", "tf")?;
+                state.serialize_field("data", &sorted(row))?;
+            }
+            AnalysisResult::Sensitivity(row) => {
+                state.serialize_field("analysis", "sensitivity")?;
+                state.serialize_field("data", &sorted(row))?;
+            }
+            AnalysisResult::PoleZero { poles, zeros } => {
+                state.serialize_field("analysis", "pole_zero")?;
+                let data = BTreeMap::from([
+                    ("poles", poles.iter().map(ComplexValue).collect::<Vec<_>>()),
+                    ("zeros", zeros.iter().map(ComplexValue).collect()),
+                ]);
+                state.serialize_field("data", &data)?;
+            }
+        }
+        state.end()
+    }
 }
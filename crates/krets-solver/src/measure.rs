@@ -0,0 +1,82 @@
+use crate::prelude::*;
+use krets_parser::measure::{MeasureFunction, Measurement};
+
+/// Evaluates a measurement over the rows of a DC sweep or transient result.
+///
+/// `axis` is the key of the sweep variable in each row (`time` or `step`). Only the rows whose
+/// sweep variable lies inside the `FROM`/`TO` window of the measurement are considered.
+pub fn measure(
+    measurement: &Measurement,
+    rows: &[HashMap<String, f64>],
+    axis: &str,
+) -> Result<f64> {
+    let mut samples = Vec::with_capacity(rows.len());
+    for row in rows {
+        let x = *row
+            .get(axis)
+            .ok_or_else(|| Error::Unexpected(format!("Result row has no '{axis}' entry")))?;
+        let y = *row
+            .get(&measurement.target)
+            .ok_or_else(|| Error::NodeNotFound(measurement.target.clone()))?;
+        let after_start = measurement.from.is_none_or(|from| x >= from);
+        let before_stop = measurement.to.is_none_or(|to| x <= to);
+        if after_start && before_stop {
+            samples.push((x, y));
+        }
+    }
+
+    let failed =
+        |reason: &str| Error::MeasurementFailed(format!("'{}' {reason}", measurement.name));
+    let (&(first_x, first_y), &(last_x, last_y)) = samples
+        .first()
+        .zip(samples.last())
+        .ok_or_else(|| failed("has no samples in its window"))?;
+
+    let max = || samples.iter().map(|&(_, y)| y).fold(f64::MIN, f64::max);
+    let min = || samples.iter().map(|&(_, y)| y).fold(f64::MAX, f64::min);
+
+    match measurement.function {
+        MeasureFunction::Max => Ok(max()),
+        MeasureFunction::Min => Ok(min()),
+        MeasureFunction::Pp => Ok(max() - min()),
+        MeasureFunction::Avg => {
+            if last_x == first_x {
+                return Ok(samples.iter().map(|&(_, y)| y).sum::<f64>() / samples.len() as f64);
+            }
+            // Trapezoidal integration, since transient time steps are not uniform.
+            let area: f64 = samples
+                .windows(2)
+                .map(|pair| 0.5 * (pair[0].1 + pair[1].1) * (pair[1].0 - pair[0].0))
+                .sum();
+            Ok(area / (last_x - first_x))
+        }
+        MeasureFunction::Rise { low, high } => {
+            let low_level = first_y + low * (last_y - first_y);
+            let high_level = first_y + high * (last_y - first_y);
+            let (low_index, low_x) = crossing(&samples, low_level, 0)
+                .ok_or_else(|| failed("never crosses its low level"))?;
+            let (_, high_x) = crossing(&samples, high_level, low_index)
+                .ok_or_else(|| failed("never crosses its high level"))?;
+            Ok(high_x - low_x)
+        }
+    }
+}
+
+/// Finds the first crossing of `level` at or after sample `start`, returning the index of the
+/// sample before the crossing and the linearly interpolated sweep value.
+fn crossing(samples: &[(f64, f64)], level: f64, start: usize) -> Option<(usize, f64)> {
+    samples
+        .windows(2)
+        .enumerate()
+        .skip(start)
+        .find_map(|(i, pair)| {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if y0 == level {
+                Some((i, x0))
+            } else if (y0 - level) * (y1 - level) < 0.0 || y1 == level {
+                Some((i, x0 + (level - y0) * (x1 - x0) / (y1 - y0)))
+            } else {
+                None
+            }
+        })
+}
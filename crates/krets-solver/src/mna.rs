@@ -0,0 +1,291 @@
+use crate::{prelude::*, stampable::Stampable};
+use faer::{
+    Mat,
+    prelude::Solve,
+    sparse::{SparseColMat, linalg::solvers::Lu},
+};
+use krets_parser::elements::Element;
+
+/// An assembled real MNA system `G x = e`.
+///
+/// Row `i` of the system belongs to the unknown `unknowns[i]`, a node voltage such as
+/// `V(out)` or a branch current such as `I(V1)`, following the index map of the circuit.
+#[derive(Debug, Clone)]
+pub struct MnaMatrix {
+    /// The conductance matrix `G`.
+    pub conductance: SparseColMat<usize, f64>,
+    /// The excitation vector `e`, a single column.
+    pub excitation: Mat<f64>,
+    /// The names of the unknowns, in row order.
+    pub unknowns: Vec<String>,
+}
+
+impl MnaMatrix {
+    /// Builds the system from conductance and excitation stamps. Stamps on the same entry
+    /// are summed.
+    pub fn new(
+        index_map: &HashMap<String, usize>,
+        g_stamps: &[Triplet<usize, usize, f64>],
+        e_stamps: &[Triplet<usize, usize, f64>],
+    ) -> Result<Self> {
+        let size = index_map.len();
+        let conductance = SparseColMat::try_new_from_triplets(size, size, &sum_triplets(g_stamps))
+            .map_err(|_| Error::MatrixBuild)?;
+
+        let mut excitation = Mat::zeros(size, 1);
+        for &Triplet { row, col, val } in e_stamps {
+            excitation[(row, col)] += val;
+        }
+
+        let mut unknowns = vec![String::new(); size];
+        for (name, &index) in index_map {
+            unknowns[index] = name.clone();
+        }
+
+        Ok(MnaMatrix {
+            conductance,
+            excitation,
+            unknowns,
+        })
+    }
+
+    /// Assembles the DC system of `elements`, linearized around `solution_map`.
+    pub fn assemble_dc(
+        elements: &[&Element],
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+    ) -> Result<Self> {
+        let mut g_stamps = Vec::new();
+        let mut e_stamps = Vec::new();
+        for element in elements {
+            g_stamps.extend(element.stamp_conductance_matrix_dc(index_map, solution_map));
+            e_stamps.extend(element.stamp_excitation_vector_dc(index_map, solution_map));
+        }
+        Self::new(index_map, &g_stamps, &e_stamps)
+    }
+
+    /// Returns the number of unknowns.
+    pub fn size(&self) -> usize {
+        self.unknowns.len()
+    }
+
+    /// Returns the conductance matrix as a dense matrix.
+    pub fn to_dense(&self) -> Mat<f64> {
+        self.conductance.to_dense()
+    }
+
+    /// Returns whether the conductance matrix is symmetric, comparing every entry with its
+    /// mirror image up to a relative `tolerance`.
+    ///
+    /// Circuits of resistors and current sources give symmetric matrices. Voltage sources,
+    /// controlled sources and linearized transistors generally break the symmetry.
+    pub fn is_symmetric(&self, tolerance: f64) -> bool {
+        let triplets: Vec<_> = self
+            .conductance
+            .as_ref()
+            .triplet_iter()
+            .map(|triplet| Triplet::new(triplet.row, triplet.col, *triplet.val))
+            .collect();
+        is_symmetric(&triplets, tolerance)
+    }
+
+    /// Computes the LU factorization of the conductance matrix.
+    pub fn factorize(&self) -> Result<Lu<usize, f64>> {
+        self.conductance
+            .sp_lu()
+            .map_err(|_| Error::MatrixDecomposition)
+    }
+
+    /// Solves the system and returns the value of every unknown keyed by its name.
+    pub fn solve(&self) -> Result<HashMap<String, f64>> {
+        let x = self.factorize()?.solve(&self.excitation);
+        Ok(self
+            .unknowns
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.clone(), x[(index, 0)]))
+            .collect())
+    }
+}
+
+/// Returns whether the matrix of the summed `triplets` is symmetric, comparing every entry with
+/// its mirror image up to a relative `tolerance`.
+pub fn is_symmetric(triplets: &[Triplet<usize, usize, f64>], tolerance: f64) -> bool {
+    let entries: HashMap<(usize, usize), f64> = triplets
+        .iter()
+        .map(|triplet| ((triplet.row, triplet.col), triplet.val))
+        .collect();
+    entries.iter().all(|(&(row, col), &value)| {
+        let mirror = entries.get(&(col, row)).copied().unwrap_or(0.0);
+        (value - mirror).abs() <= tolerance * value.abs().max(mirror.abs())
+    })
+}
+
+/// A row or column of the matrix that [`Reduction`] may eliminate.
+enum Singleton {
+    Row(usize),
+    Col(usize),
+}
+
+/// The system left after eliminating singleton rows and columns of an MNA system, the way
+/// sparse direct solvers do before factorizing.
+///
+/// A row with a single entry fixes its unknown directly, like the branch-current row of a
+/// current source or the row of a voltage source from a node to ground. The unknown is then
+/// moved to the right-hand side of the other rows. A column with a single entry, like the
+/// branch current of such a voltage source, only appears in one equation, which is set aside
+/// and solved for that unknown once all others are known. Both can expose further singletons.
+///
+/// For a network of resistors and independent sources, the reduced system is the symmetric
+/// positive definite nodal matrix of the nodes that are not fixed by a grounded voltage source.
+pub(crate) struct Reduction {
+    /// Unknowns fixed by a singleton row, with their values.
+    fixed: Vec<(usize, f64)>,
+    /// `(row, column)` pairs of the singleton columns, in the order they were eliminated.
+    deferred: Vec<(usize, usize)>,
+    /// The original index of every unknown of the reduced system.
+    unknowns: Vec<usize>,
+    /// The matrix of the reduced system.
+    pub(crate) triplets: Vec<Triplet<usize, usize, f64>>,
+    /// The right-hand side of the reduced system.
+    pub(crate) b: Vec<f64>,
+}
+
+impl Reduction {
+    /// Eliminates the singletons of the matrix of the summed `triplets`, which has `size`
+    /// unknowns. Returns `None` if the remaining system is not square, so it is structurally
+    /// singular.
+    pub(crate) fn new(
+        triplets: &[Triplet<usize, usize, f64>],
+        b: &[f64],
+        size: usize,
+    ) -> Option<Self> {
+        let mut rows = vec![Vec::new(); size];
+        let mut cols = vec![Vec::new(); size];
+        for &Triplet { row, col, val } in triplets.iter().filter(|t| t.val != 0.0) {
+            rows[row].push((col, val));
+            cols[col].push((row, val));
+        }
+
+        let mut row_active = vec![true; size];
+        let mut col_active = vec![true; size];
+        let mut row_count: Vec<usize> = rows.iter().map(Vec::len).collect();
+        let mut col_count: Vec<usize> = cols.iter().map(Vec::len).collect();
+        let mut b = b.to_vec();
+        let mut fixed = Vec::new();
+        let mut deferred = Vec::new();
+
+        let mut candidates: Vec<Singleton> = (0..size)
+            .filter(|&col| col_count[col] == 1)
+            .map(Singleton::Col)
+            .chain(
+                (0..size)
+                    .filter(|&row| row_count[row] == 1)
+                    .map(Singleton::Row),
+            )
+            .collect();
+        while let Some(candidate) = candidates.pop() {
+            match candidate {
+                Singleton::Row(row) if row_active[row] && row_count[row] == 1 => {
+                    let &(col, val) = rows[row].iter().find(|(col, _)| col_active[*col])?;
+                    let value = b[row] / val;
+                    fixed.push((col, value));
+                    row_active[row] = false;
+                    col_active[col] = false;
+                    for &(other, a) in &cols[col] {
+                        if row_active[other] {
+                            b[other] -= a * value;
+                            row_count[other] -= 1;
+                            if row_count[other] == 1 {
+                                candidates.push(Singleton::Row(other));
+                            }
+                        }
+                    }
+                }
+                Singleton::Col(col) if col_active[col] && col_count[col] == 1 => {
+                    let &(row, _) = cols[col].iter().find(|(row, _)| row_active[*row])?;
+                    deferred.push((row, col));
+                    row_active[row] = false;
+                    col_active[col] = false;
+                    for &(other, _) in &rows[row] {
+                        if col_active[other] {
+                            col_count[other] -= 1;
+                            if col_count[other] == 1 {
+                                candidates.push(Singleton::Col(other));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let active_rows: Vec<usize> = (0..size).filter(|&i| row_active[i]).collect();
+        let unknowns: Vec<usize> = (0..size).filter(|&i| col_active[i]).collect();
+        if active_rows.len() != unknowns.len() {
+            return None;
+        }
+        let mut reduced_row = vec![usize::MAX; size];
+        let mut reduced_col = vec![usize::MAX; size];
+        for (i, &row) in active_rows.iter().enumerate() {
+            reduced_row[row] = i;
+        }
+        for (i, &col) in unknowns.iter().enumerate() {
+            reduced_col[col] = i;
+        }
+        let triplets = triplets
+            .iter()
+            .filter(|t| row_active[t.row] && col_active[t.col])
+            .map(|t| Triplet::new(reduced_row[t.row], reduced_col[t.col], t.val))
+            .collect();
+        let b = active_rows.iter().map(|&row| b[row]).collect();
+
+        Some(Reduction {
+            fixed,
+            deferred,
+            unknowns,
+            triplets,
+            b,
+        })
+    }
+
+    /// Returns the number of unknowns of the reduced system.
+    pub(crate) fn size(&self) -> usize {
+        self.unknowns.len()
+    }
+
+    /// Assembles the solution of the full system of `triplets` and `b` from the solution `y`
+    /// of the reduced system.
+    pub(crate) fn expand(
+        &self,
+        triplets: &[Triplet<usize, usize, f64>],
+        b: &[f64],
+        y: &[f64],
+    ) -> Vec<f64> {
+        let mut x = vec![0.0; b.len()];
+        for &(col, value) in &self.fixed {
+            x[col] = value;
+        }
+        for (&col, &value) in self.unknowns.iter().zip(y) {
+            x[col] = value;
+        }
+
+        let mut rows = vec![Vec::new(); b.len()];
+        for triplet in triplets {
+            rows[triplet.row].push((triplet.col, triplet.val));
+        }
+        for &(row, col) in self.deferred.iter().rev() {
+            let mut pivot = 0.0;
+            let mut rest = b[row];
+            for &(other, val) in &rows[row] {
+                if other == col {
+                    pivot += val;
+                } else {
+                    rest -= val * x[other];
+                }
+            }
+            x[col] = rest / pivot;
+        }
+        x
+    }
+}
@@ -0,0 +1,200 @@
+use crate::AnalysisResult;
+use faer::c64;
+use std::collections::{BTreeSet, HashMap};
+
+/// The width of a value formatted as `{:.6e}`, e.g. `-1.234560e-3`, with room for a
+/// two-digit exponent.
+const VALUE_WIDTH: usize = 13;
+
+/// Selects what [`format_result`] shows.
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    /// The signals to show, e.g. `V(out)` or `I(V1)`, or all of them when empty. The sweep
+    /// variable of a table, like `time` or `frequency`, is always shown.
+    pub signals: Vec<String>,
+    /// The largest number of points of a sweep to show, or `None` to show all of them. The
+    /// points left out are counted in a final line.
+    pub max_rows: Option<usize>,
+}
+
+impl ReportOptions {
+    fn shows(&self, signal: &str) -> bool {
+        self.signals.is_empty() || self.signals.iter().any(|s| s == signal)
+    }
+
+    /// Returns the number of the `total` points to show.
+    fn row_count(&self, total: usize) -> usize {
+        self.max_rows.map_or(total, |max_rows| max_rows.min(total))
+    }
+}
+
+/// Formats an analysis result as aligned plain-text tables, e.g. to print it to the console.
+///
+/// Operating points, transfer functions and sensitivities list one signal per line. DC sweeps,
+/// noise analyses and transients have one line per point with a column per signal, the sweep
+/// variable first. AC results list the magnitude and phase in degrees of every signal at every
+/// frequency. Values missing from a point are shown as `-`. Every line ends with a newline.
+pub fn format_result(result: &AnalysisResult, options: &ReportOptions) -> String {
+    let lines = match result {
+        AnalysisResult::Op(row) | AnalysisResult::Tf(row) | AnalysisResult::Sensitivity(row) => {
+            format_values(row, options)
+        }
+        AnalysisResult::Dc(rows, _) => format_table(rows, "step", options),
+        AnalysisResult::Noise(rows) => format_table(rows, "frequency", options),
+        AnalysisResult::Transient(rows, _) => format_table(rows, "time", options),
+        AnalysisResult::Ac(rows) => format_ac(rows, options),
+        AnalysisResult::PoleZero { poles, zeros } => poles
+            .iter()
+            .map(|pole| ("pole", pole))
+            .chain(zeros.iter().map(|zero| ("zero", zero)))
+            .map(|(kind, value)| {
+                format!(
+                    "{kind} {:>width$.6e} {:+.6e}j",
+                    value.re,
+                    value.im,
+                    width = VALUE_WIDTH
+                )
+            })
+            .collect(),
+    };
+
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Returns the unit of a node voltage `V(..)` or branch current `I(..)`.
+fn unit(signal: &str) -> &'static str {
+    if signal.starts_with("V(") {
+        "V"
+    } else if signal.starts_with("I(") {
+        "A"
+    } else {
+        ""
+    }
+}
+
+/// Lists the signals of a single solution with their values and units.
+fn format_values(row: &HashMap<String, f64>, options: &ReportOptions) -> Vec<String> {
+    let mut values: Vec<(&String, &f64)> = row
+        .iter()
+        .filter(|(signal, _)| options.shows(signal))
+        .collect();
+    values.sort_by_key(|(signal, _)| *signal);
+
+    let width = values
+        .iter()
+        .map(|(signal, _)| signal.len())
+        .chain(["Signal".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        format!("{:<width$} | {:>VALUE_WIDTH$}", "Signal", "Value"),
+        format!("{:-<width$}-+-{:-<VALUE_WIDTH$}", "", ""),
+    ];
+    for (signal, value) in values {
+        let line = format!(
+            "{signal:<width$} | {value:>VALUE_WIDTH$.6e} {}",
+            unit(signal)
+        );
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Lays out a sweep as one line per point with a column per signal, `axis` first.
+fn format_table(rows: &[HashMap<String, f64>], axis: &str, options: &ReportOptions) -> Vec<String> {
+    if rows.is_empty() {
+        return vec!["No results.".to_string()];
+    }
+
+    let signals: BTreeSet<&String> = rows
+        .iter()
+        .flat_map(|row| row.keys())
+        .filter(|signal| *signal != axis && options.shows(signal))
+        .collect();
+    let columns: Vec<&str> = rows
+        .iter()
+        .any(|row| row.contains_key(axis))
+        .then_some(axis)
+        .into_iter()
+        .chain(signals.into_iter().map(String::as_str))
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .map(|column| column.len().max(VALUE_WIDTH))
+        .collect();
+
+    let cells = |row: &dyn Fn(&str, usize) -> String| -> String {
+        columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, &width)| row(column, width))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    let mut lines = vec![
+        cells(&|column, width| format!("{column:>width$}")),
+        cells(&|_, width| "-".repeat(width)),
+    ];
+    let shown = options.row_count(rows.len());
+    for row in &rows[..shown] {
+        lines.push(cells(&|column, width| match row.get(column) {
+            Some(value) => format!("{value:>width$.6e}"),
+            None => format!("{:>width$}", "-"),
+        }));
+    }
+    lines.extend(truncation_note(rows.len() - shown));
+    lines
+}
+
+/// Lists the magnitude and phase in degrees of every signal at every frequency.
+fn format_ac(rows: &[HashMap<String, c64>], options: &ReportOptions) -> Vec<String> {
+    if rows.is_empty() {
+        return vec!["No results.".to_string()];
+    }
+
+    let signals: BTreeSet<&String> = rows
+        .iter()
+        .flat_map(|row| row.keys())
+        .filter(|signal| *signal != "frequency" && options.shows(signal))
+        .collect();
+    let width = signals
+        .iter()
+        .map(|signal| signal.len())
+        .chain(["Signal".len()])
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        format!(
+            "{:>VALUE_WIDTH$} | {:<width$} | {:>VALUE_WIDTH$} | {:>VALUE_WIDTH$}",
+            "Frequency", "Signal", "Magnitude", "Phase (deg)"
+        ),
+        format!(
+            "{:-<VALUE_WIDTH$}-+-{:-<width$}-+-{:-<VALUE_WIDTH$}-+-{:-<VALUE_WIDTH$}",
+            "", "", "", ""
+        ),
+    ];
+    let shown = options.row_count(rows.len());
+    for row in &rows[..shown] {
+        let frequency = row.get("frequency").map_or(f64::NAN, |f| f.re);
+        for signal in &signals {
+            let Some(value) = row.get(*signal) else {
+                continue;
+            };
+            lines.push(format!(
+                "{frequency:>VALUE_WIDTH$.6e} | {signal:<width$} | {:>VALUE_WIDTH$.6e} | {:>VALUE_WIDTH$.6e}",
+                value.norm(),
+                value.arg().to_degrees()
+            ));
+        }
+    }
+    lines.extend(truncation_note(rows.len() - shown));
+    lines
+}
+
+/// Returns a line counting the points left out of a table, if any.
+fn truncation_note(hidden: usize) -> Option<String> {
+    (hidden > 0).then(|| format!("... {hidden} more points"))
+}
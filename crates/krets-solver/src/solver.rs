@@ -1,27 +1,155 @@
 pub mod ac;
 pub mod dc;
+pub mod monte_carlo;
+pub mod noise;
 pub mod op;
+pub mod param_sweep;
+pub mod pole_zero;
+pub mod sensitivity;
+pub mod tf;
 pub mod transient;
 
 use crate::config::SolverConfig;
+use crate::events::CrossingEvent;
+use crate::measure;
 use crate::prelude::*;
 use faer::sparse::Triplet;
-use krets_parser::analyses::Analysis;
+use krets_parser::analyses::{Analysis, MonteCarlo, TransientAnalysis};
 use krets_parser::circuit::Circuit;
-use std::collections::HashMap;
+use krets_parser::elements::Element;
+use krets_parser::measure::MeasureAnalysis;
+use log::warn;
+use std::collections::{HashMap, HashSet};
 use std::ops::AddAssign;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
 
 // Declare the sub-modules for each analysis type.
 
+/// Progress reporting and cancellation for long-running analyses.
+///
+/// The AC and transient analyses report the fraction of the analysis completed after every
+/// frequency or time point and stop early, returning the rows solved so far, once `cancel` is
+/// set. The other analyses ignore it.
+#[derive(Default)]
+pub struct Progress<'a> {
+    /// Called with the fraction of the analysis completed, between 0 and 1.
+    pub report: Option<&'a mut dyn FnMut(f64)>,
+    /// Stops the analysis before its next step once set to `true`.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Progress<'_> {
+    /// Reports that `fraction` of the analysis has been completed.
+    pub fn report(&mut self, fraction: f64) {
+        if let Some(report) = self.report.as_mut() {
+            report(fraction.clamp(0.0, 1.0));
+        }
+    }
+
+    /// Returns whether the analysis should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
 /// The main Solver struct, which acts as a dispatcher for different analysis types.
+///
+/// The circuit is held in an [`Arc`], so solvers created from clones of the same
+/// `Arc<Circuit>` share the parsed elements and models. Analyses that change element values
+/// while they run, like DC sweeps, parameter sweeps and Monte Carlo analyses, first make the
+/// circuit of their solver unique, copying it only if it is still shared.
 pub struct Solver {
-    circuit: Circuit,
+    circuit: Arc<Circuit>,
     config: SolverConfig,
+    /// The operating point found by the last [`Solver::solve_op_warm`].
+    previous_op_result: Option<HashMap<String, f64>>,
+    /// The Newton-Raphson iterations spent by the last [`Solver::solve_op_warm`].
+    last_op_iterations: Option<usize>,
 }
 
 impl Solver {
-    pub const fn new(circuit: Circuit, config: SolverConfig) -> Self {
-        Self { circuit, config }
+    /// Creates a solver for the circuit, bringing its elements to the circuit temperature:
+    /// the `.temp` card of the netlist if present, otherwise `config.temperature`.
+    ///
+    /// Pass an `Arc<Circuit>` to share the circuit with other solvers. It is only copied if
+    /// its elements are not at the circuit temperature yet or it has switches.
+    pub fn new(circuit: impl Into<Arc<Circuit>>, config: SolverConfig) -> Self {
+        let mut circuit = circuit.into();
+        let temperature = circuit.temperature.unwrap_or(config.temperature);
+        if !circuit.is_at_temperature(temperature) {
+            Arc::make_mut(&mut circuit).set_temperature(temperature);
+        }
+        // Switches keep their state in the circuit, which must stay local to this solver.
+        if circuit
+            .elements
+            .iter()
+            .any(|element| matches!(element, Element::Switch(_)))
+        {
+            Arc::make_mut(&mut circuit);
+        }
+        Self {
+            circuit,
+            config,
+            previous_op_result: None,
+            last_op_iterations: None,
+        }
+    }
+
+    /// Solves the DC operating point of the circuit.
+    ///
+    /// Returns the node voltages and branch currents keyed as `V(node)` and `I(element)`,
+    /// without going through `AnalysisResult`, including the current of every element with
+    /// `config.probe_currents`. Convergence or decomposition failures are returned as an `Error`.
+    pub fn solve_op(&self) -> Result<HashMap<String, f64>> {
+        let mut solution = op::solve(&self.circuit, &self.config)?;
+        if self.config.probe_currents {
+            op::insert_element_currents(&self.circuit, &mut solution);
+        }
+        Ok(solution)
+    }
+
+    /// Solves the DC operating point like [`Solver::solve_op`], warm-starting Newton-Raphson
+    /// from `guess` instead of the `.nodeset` voltages alone.
+    ///
+    /// `guess` is typically the solution of a previous run on the same or a slightly modified
+    /// circuit, such as [`Solver::previous_op_result`]. Keys that are not part of this circuit
+    /// are ignored, and `.nodeset` voltages fill in any node the guess does not cover. The
+    /// solution and the iterations spent are kept for [`Solver::previous_op_result`] and
+    /// [`Solver::last_op_iterations`].
+    pub fn solve_op_warm(&mut self, guess: &HashMap<String, f64>) -> Result<HashMap<String, f64>> {
+        let mut initial_guess = op::nodeset_guess(&self.circuit);
+        initial_guess.extend(
+            guess
+                .iter()
+                .filter(|(key, _)| self.circuit.index_map.contains_key(*key))
+                .map(|(key, &value)| (key.clone(), value)),
+        );
+
+        let (mut solution, iterations) =
+            op::solve_from_counted(&self.circuit, &self.config, &initial_guess)?;
+        self.previous_op_result = Some(solution.clone());
+        self.last_op_iterations = Some(iterations);
+
+        if self.config.probe_currents {
+            op::insert_element_currents(&self.circuit, &mut solution);
+        }
+        Ok(solution)
+    }
+
+    /// Returns the operating point found by the last [`Solver::solve_op_warm`], if any.
+    pub fn previous_op_result(&self) -> Option<&HashMap<String, f64>> {
+        self.previous_op_result.as_ref()
+    }
+
+    /// Returns the Newton-Raphson iterations spent by the last [`Solver::solve_op_warm`],
+    /// including those of any homotopy stages.
+    pub fn last_op_iterations(&self) -> Option<usize> {
+        self.last_op_iterations
     }
 
     /// Main entry point for running a circuit analysis.
@@ -29,27 +157,260 @@ impl Solver {
     /// This function dispatches to the appropriate internal solver based on the
     /// `Analysis` enum variant provided.
     pub fn solve(&mut self, analysis: Analysis) -> Result<AnalysisResult> {
-        match analysis {
-            Analysis::Op => {
-                let result = op::solve(&self.circuit, &self.config)?;
-                Ok(AnalysisResult::Op(result))
+        solve_analysis(&mut self.circuit, &self.config, analysis)
+    }
+
+    /// Runs a circuit analysis like [`Solver::solve`], reporting the fraction completed to
+    /// `progress` and stopping early once `cancel` is set (see [`Progress`]).
+    ///
+    /// A cancelled AC or transient analysis returns the rows solved before the cancellation.
+    pub fn solve_with_progress(
+        &mut self,
+        analysis: Analysis,
+        progress: Option<&mut dyn FnMut(f64)>,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<AnalysisResult> {
+        let mut progress = Progress {
+            report: progress,
+            cancel,
+        };
+        solve_analysis_with_progress(&mut self.circuit, &self.config, analysis, &mut progress)
+    }
+
+    /// Runs a transient analysis, handing every time point to `on_step` instead of collecting
+    /// them (see [`transient::solve_with`]). Use this to stream or downsample long simulations.
+    /// With `.save` cards, every time point only holds the saved signals (see [`retain_saved`]).
+    pub fn solve_transient_with<F>(
+        &self,
+        analysis: &TransientAnalysis,
+        mut on_step: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&HashMap<String, f64>),
+    {
+        match saved_signals(&self.circuit) {
+            Some(saved) => {
+                transient::solve_with(&self.circuit, &self.config, analysis, |solution| {
+                    let mut solution = solution.clone();
+                    retain_saved(&mut solution, Some(&saved));
+                    on_step(&solution);
+                })
             }
-            Analysis::Dc(dc_params) => {
-                // Pass the circuit mutably to allow the sweep to temporarily change element values.
-                let result = dc::solve(&mut self.circuit, &self.config, &dc_params)?;
-                Ok(AnalysisResult::Dc(result))
+            None => transient::solve_with(&self.circuit, &self.config, analysis, on_step),
+        }
+    }
+
+    /// Runs a transient analysis and returns the crossing times of every event (see
+    /// [`transient::find_crossings`]).
+    pub fn find_crossings(
+        &self,
+        analysis: &TransientAnalysis,
+        events: &[CrossingEvent],
+    ) -> Result<Vec<Vec<f64>>> {
+        transient::find_crossings(&self.circuit, &self.config, analysis, events)
+    }
+
+    /// Runs a Monte Carlo analysis, returning the rows of every run together with the mean and
+    /// standard deviation of every quantity over the runs (see [`monte_carlo::solve`]).
+    pub fn monte_carlo(&mut self, analysis: &MonteCarlo) -> Result<monte_carlo::MonteCarloResult> {
+        monte_carlo::solve(&mut self.circuit, &self.config, analysis)
+    }
+
+    /// Returns the branch current and absorbed power of every element, keyed by identifier,
+    /// at the operating point `op_solution` (see [`op::element_power`]).
+    pub fn element_power(&self, op_solution: &HashMap<String, f64>) -> HashMap<String, (f64, f64)> {
+        op::element_power(&self.circuit, op_solution)
+    }
+
+    /// Returns the node with the largest violation of Kirchhoff's current law at the operating
+    /// point `op_solution` and that violation in amperes (see [`op::kcl_residual`]).
+    pub fn kcl_residual(&self, op_solution: &HashMap<String, f64>) -> Option<(String, f64)> {
+        op::kcl_residual(&self.circuit, op_solution)
+    }
+
+    /// Returns the Thevenin equivalent `(voltage, resistance)` of the circuit looking into the
+    /// port between `node_a` and `node_b` (see [`op::thevenin`]). The Norton current is
+    /// `voltage / resistance`.
+    pub fn thevenin(&mut self, node_a: &str, node_b: &str) -> Result<(f64, f64)> {
+        op::thevenin(
+            Arc::make_mut(&mut self.circuit),
+            &self.config,
+            node_a,
+            node_b,
+        )
+    }
+}
+
+/// Runs a single analysis on the circuit, see `Solver::solve`.
+fn solve_analysis(
+    circuit: &mut Arc<Circuit>,
+    config: &SolverConfig,
+    analysis: Analysis,
+) -> Result<AnalysisResult> {
+    solve_analysis_with_progress(circuit, config, analysis, &mut Progress::default())
+}
+
+/// Runs a single analysis on the circuit, see `Solver::solve_with_progress`.
+///
+/// With `.save` cards, the rows only hold the saved signals (see [`retain_saved`]). The full
+/// solution is still computed, and transient time points are filtered before being collected.
+/// DC sweeps and transients carry the values of their `.measure` cards (see [`measure_all`]).
+///
+/// Only the analyses that change element values while they run make `circuit` unique.
+fn solve_analysis_with_progress(
+    circuit: &mut Arc<Circuit>,
+    config: &SolverConfig,
+    analysis: Analysis,
+    progress: &mut Progress,
+) -> Result<AnalysisResult> {
+    let saved = saved_signals(circuit);
+
+    match analysis {
+        Analysis::Op => {
+            let mut result = op::solve(circuit, config)?;
+            if config.probe_currents {
+                op::insert_element_currents(circuit, &mut result);
             }
-            Analysis::Ac(ac_params) => {
-                let result = ac::solve(&self.circuit, &self.config, &ac_params)?;
-                Ok(AnalysisResult::Ac(result))
+            retain_saved(&mut result, saved.as_ref());
+            Ok(AnalysisResult::Op(result))
+        }
+        Analysis::Dc(dc_params) => {
+            // Pass the circuit mutably to allow the sweep to temporarily change element values.
+            let mut result = dc::solve(Arc::make_mut(circuit), config, &dc_params)?;
+            for row in &mut result {
+                retain_saved(row, saved.as_ref());
+            }
+            let measurements = measure_all(circuit, MeasureAnalysis::Dc, &result, "step");
+            Ok(AnalysisResult::Dc(result, measurements))
+        }
+        Analysis::Ac(ac_params) => {
+            let mut result = ac::solve_with_progress(circuit, config, &ac_params, progress)?;
+            for row in &mut result {
+                retain_saved(row, saved.as_ref());
             }
-            Analysis::Transient(transient_params) => {
-                // Pass the circuit mutably to allow time-dependent elements to update their state.
-                let result = transient::solve(&self.circuit, &self.config, &transient_params)?;
-                Ok(AnalysisResult::Transient(result))
+            Ok(AnalysisResult::Ac(result))
+        }
+        Analysis::Transient(transient_params) => {
+            let mut result = Vec::new();
+            transient::solve_with_progress(
+                circuit,
+                config,
+                &transient_params,
+                |solution| {
+                    let mut solution = solution.clone();
+                    retain_saved(&mut solution, saved.as_ref());
+                    result.push(solution);
+                },
+                progress,
+            )?;
+            let measurements = measure_all(circuit, MeasureAnalysis::Tran, &result, "time");
+            Ok(AnalysisResult::Transient(result, measurements))
+        }
+        Analysis::Noise(noise_params) => {
+            let mut result = noise::solve(circuit, config, &noise_params)?;
+            for row in &mut result {
+                retain_saved(row, saved.as_ref());
             }
+            Ok(AnalysisResult::Noise(result))
+        }
+        Analysis::Tf(tf_params) => {
+            let result = tf::solve(circuit, config, &tf_params)?;
+            Ok(AnalysisResult::Tf(result))
+        }
+        Analysis::Sensitivity(sensitivity_params) => {
+            let result = sensitivity::solve(circuit, config, &sensitivity_params)?;
+            Ok(AnalysisResult::Sensitivity(result))
+        }
+        Analysis::PoleZero(pole_zero_params) => {
+            let (poles, zeros) = pole_zero::solve(circuit, config, &pole_zero_params)?;
+            Ok(AnalysisResult::PoleZero { poles, zeros })
+        }
+        Analysis::Sweep(sweep_params) => {
+            // The sweep changes the swept value before every run of the inner analysis.
+            param_sweep::solve(circuit, config, &sweep_params)
+        }
+        Analysis::MonteCarlo(monte_carlo_params) => {
+            let result = monte_carlo::solve(circuit, config, &monte_carlo_params)?;
+            Ok(result.runs)
+        }
+    }
+}
+
+/// Returns the signals to report, or `None` to report all of them when the circuit has no
+/// `.save` cards.
+///
+/// These are the saved signals together with the targets of the `.measure` cards, which
+/// [`measure_all`] still needs. A saved signal that is neither the voltage of a node nor
+/// the current of an element is reported with a warning.
+fn saved_signals(circuit: &Circuit) -> Option<HashSet<String>> {
+    if circuit.saves.is_empty() {
+        return None;
+    }
+
+    let identifiers: HashSet<String> = circuit.elements.iter().map(Element::identifier).collect();
+    for signal in &circuit.saves {
+        let inner = |prefix: &str| {
+            signal
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(')'))
+        };
+        let known = match (inner("V("), inner("I(")) {
+            (Some(node), _) => circuit.nodes.iter().any(|n| n == node),
+            (_, Some(identifier)) => identifiers.contains(identifier),
+            _ => false,
+        };
+        if !known {
+            warn!("Ignoring .save for unknown signal {signal}");
         }
     }
+
+    Some(
+        circuit
+            .saves
+            .iter()
+            .chain(circuit.measurements.iter().map(|m| &m.target))
+            .cloned()
+            .collect(),
+    )
+}
+
+/// Evaluates the `.measure` cards of `circuit` for `analysis` over the result `rows`, whose
+/// sweep variable is `axis` (see [`measure::measure`]).
+///
+/// Returns the measured values keyed by measurement name. A measurement that cannot be
+/// evaluated, e.g. because its target never crosses the level, is reported with a warning and
+/// left out, so it does not throw away the analysis.
+fn measure_all(
+    circuit: &Circuit,
+    analysis: MeasureAnalysis,
+    rows: &[HashMap<String, f64>],
+    axis: &str,
+) -> HashMap<String, f64> {
+    circuit
+        .measurements
+        .iter()
+        .filter(|measurement| measurement.analysis == analysis)
+        .filter_map(
+            |measurement| match measure::measure(measurement, rows, axis) {
+                Ok(value) => Some((measurement.name.clone(), value)),
+                Err(e) => {
+                    warn!("Ignoring .measure {}: {e}", measurement.name);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// Drops the node voltages `V(...)` and branch currents `I(...)` of `row` that are not in
+/// `saved`, keeping everything when `saved` is `None`. Everything else, like `time`,
+/// `frequency` or the DC sweep `step`, is always kept.
+pub fn retain_saved<V>(row: &mut HashMap<String, V>, saved: Option<&HashSet<String>>) {
+    if let Some(saved) = saved {
+        row.retain(|key, _| {
+            !(key.starts_with("V(") || key.starts_with("I(")) || saved.contains(key)
+        });
+    }
 }
 
 /// Generic function to sum triplets for both DC (f64) and AC (c64) analysis.
@@ -69,24 +430,58 @@ where
         .collect()
 }
 
+/// The outcome of a Newton-Raphson convergence check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Convergence {
+    /// Whether every unknown changed by less than its tolerance.
+    pub converged: bool,
+    /// The unknown whose change exceeded its tolerance the most, e.g. `V(out)`.
+    pub worst_node: String,
+    /// The absolute change of `worst_node` over the last iteration.
+    pub delta: f64,
+}
+
+impl Convergence {
+    /// Reports a check that failed on the last of `iterations` Newton-Raphson iterations.
+    pub fn into_error(self, iterations: usize) -> Error {
+        Error::ConvergenceFailed {
+            iterations,
+            worst_node: self.worst_node,
+            delta: self.delta,
+        }
+    }
+}
+
 /// Checks if the Newton-Raphson iteration has converged.
 ///
 /// Convergence is determined by comparing the change between the previous and current
-/// solution vectors against a set of relative and absolute tolerances.
+/// solution vectors against a set of relative and absolute tolerances. The unknown with the
+/// largest change relative to its tolerance is reported as the worst node.
 pub fn convergence_check(
     previous_result: &HashMap<String, f64>,
     result: &HashMap<String, f64>,
     config: &SolverConfig,
-) -> bool {
+) -> Convergence {
     let reltol = config.relative_tolerance;
     let current_tol = config.current_absolute_tolerance;
     let voltage_tol = config.voltage_absolute_tolerance;
 
     if previous_result.is_empty() {
-        return false;
+        return Convergence {
+            converged: false,
+            worst_node: String::new(),
+            delta: f64::INFINITY,
+        };
     }
 
-    result.iter().all(|(name, &value)| {
+    let mut worst = Convergence {
+        converged: true,
+        worst_node: String::new(),
+        delta: 0.0,
+    };
+    let mut worst_ratio = 0.0;
+
+    for (name, &value) in result {
         let prev_value = previous_result.get(name).copied().unwrap_or(0.0);
 
         let diff = (value - prev_value).abs();
@@ -99,6 +494,24 @@ pub fn convergence_check(
             voltage_tol
         };
 
-        diff <= reltol * scale + atol
-    })
+        // A diverged (NaN) unknown never counts as converged and is always the worst.
+        let tolerance = reltol * scale + atol;
+        if diff.is_nan() || diff > tolerance {
+            worst.converged = false;
+        }
+
+        // Ties are broken by name, so the reported node does not depend on the map order.
+        let ratio = if diff.is_nan() {
+            f64::INFINITY
+        } else {
+            diff / tolerance
+        };
+        if ratio > worst_ratio || (ratio == worst_ratio && *name < worst.worst_node) {
+            worst_ratio = ratio;
+            worst.worst_node.clone_from(name);
+            worst.delta = diff;
+        }
+    }
+
+    worst
 }
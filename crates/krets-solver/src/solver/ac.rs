@@ -1,25 +1,57 @@
 use log::info;
 use std::collections::HashMap;
 
-use crate::{config::SolverConfig, prelude::*, solver::op, stampable::Stampable};
+use crate::{
+    config::SolverConfig,
+    prelude::*,
+    solver::{Progress, op},
+    stampable::Stampable,
+};
 use faer::{
     Mat, c64,
     prelude::Solve,
     sparse::{SparseColMat, Triplet},
 };
-use krets_parser::{analyses::AcAnalysis, circuit::Circuit};
+use krets_parser::{analyses::AcAnalysis, circuit::Circuit, elements::Element};
 
-/// Solves for the small-signal AC response of the circuit at a given frequency.
+/// Solves for the small-signal AC response of the circuit over the frequencies of the sweep.
+///
+/// The analysis runs in a fixed order:
+/// 1. The full nonlinear DC operating point is solved, including any homotopy fallback.
+/// 2. Every nonlinear element is linearized around that bias: the operating point is the
+///    solution map handed to all AC stamps. The stamps of the elements that do not depend on
+///    the frequency (see [`is_frequency_dependent`]) are built once here.
+/// 3. For every frequency, only the frequency-dependent stamps are rebuilt on top of the
+///    fixed linearization, and the complex-valued MNA system is solved.
 ///
-/// This function first calculates the DC operating point to determine the linearized models
-/// for non-linear components. It then constructs and solves the complex-valued MNA
-/// system for the specified frequency.
+/// A linear sweep may include 0Hz, where capacitors are open and inductors are shorts. A
+/// frequency range that cannot be swept, such as a decade sweep from 0Hz, is rejected with
+/// [`Error::InvalidFrequencyRange`] before the operating point is solved.
 pub fn solve(
     circuit: &Circuit,
     config: &SolverConfig,
     parameters: &AcAnalysis,
 ) -> Result<Vec<HashMap<String, c64>>> {
-    // Changed return type
+    solve_with_progress(circuit, config, parameters, &mut Progress::default())
+}
+
+/// Solves for the small-signal AC response like [`solve`], reporting the fraction of the
+/// frequencies solved to `progress`. Once `progress` is cancelled, the frequencies solved so
+/// far are returned.
+pub fn solve_with_progress(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    parameters: &AcAnalysis,
+    progress: &mut Progress,
+) -> Result<Vec<HashMap<String, c64>>> {
+    if let Some(reason) = parameters.invalid_range() {
+        return Err(Error::InvalidFrequencyRange {
+            fstart: parameters.fstart,
+            fstop: parameters.fstop,
+            reason: reason.to_string(),
+        });
+    }
+
     // First, find the DC operating point. This is crucial for linearizing non-linear components.
     info!("Calculating DC operating point for AC analysis...");
     let dc_solution = op::solve(circuit, config)?;
@@ -29,6 +61,18 @@ pub fn solve(
     let size = index_map.len();
     let mut all_results = Vec::new(); // Store results for each frequency
 
+    // Linearize around the bias once. The frequency argument is unused by these stamps.
+    let (reactive_elements, static_elements): (Vec<&Element>, Vec<&Element>) = circuit
+        .elements
+        .iter()
+        .partition(|element| is_frequency_dependent(element));
+    let mut static_g_stamps = Vec::new();
+    let mut static_e_stamps = Vec::new();
+    for element in &static_elements {
+        static_g_stamps.extend(element.stamp_conductance_matrix_ac(index_map, &dc_solution, 0.0));
+        static_e_stamps.extend(element.stamp_excitation_vector_ac(index_map, &dc_solution, 0.0));
+    }
+
     // --- Frequency Sweep Logic ---
     let frequencies = parameters.clone().generate_frequencies();
     info!(
@@ -36,18 +80,18 @@ pub fn solve(
         frequencies.len()
     );
 
-    for frequency in frequencies {
-        if frequency <= 0.0 {
-            // Skip non-positive frequencies as they are physically meaningless
-            // and can cause issues (e.g., divide by zero in impedance calculations).
-            info!("Skipping non-positive frequency: {frequency}");
-            continue;
+    let count = frequencies.len();
+    for (i, frequency) in frequencies.into_iter().enumerate() {
+        if progress.is_cancelled() {
+            info!("AC sweep cancelled after {i} of {count} frequencies.");
+            break;
         }
-        // Recalculate stamps for the current frequency
-        let mut g_stamps = Vec::new();
-        let mut e_stamps = Vec::new();
+        progress.report(i as f64 / count as f64);
+        // Add the stamps of the frequency-dependent elements to the fixed linearization.
+        let mut g_stamps = static_g_stamps.clone();
+        let mut e_stamps = static_e_stamps.clone();
 
-        for element in &circuit.elements {
+        for element in &reactive_elements {
             g_stamps.extend(element.stamp_conductance_matrix_ac(
                 index_map,
                 &dc_solution,
@@ -105,5 +149,27 @@ pub fn solve(
         all_results.push(solution_map); // Add results for this frequency
         // info!("Solved for f = {} Hz", frequency);
     }
+    if !progress.is_cancelled() {
+        progress.report(1.0);
+    }
     Ok(all_results) // Return the collected results
 }
+
+/// Returns whether the AC stamps of `element` depend on the frequency: the energy storing
+/// elements, the transmission lines, the diodes and the transistors, whose small-signal models
+/// include junction or gate capacitances. The stamps of all other elements only depend on the
+/// operating point.
+fn is_frequency_dependent(element: &Element) -> bool {
+    matches!(
+        element,
+        Element::Capacitor(_)
+            | Element::Inductor(_)
+            | Element::Diode(_)
+            | Element::MutualInductance(_)
+            | Element::BJT(_)
+            | Element::NMOSFET(_)
+            | Element::PMOSFET(_)
+            | Element::SubcktInstance(_)
+            | Element::TransmissionLine(_)
+    )
+}
@@ -1,124 +1,261 @@
-use crate::{prelude::*, stampable::Stampable};
+use crate::{prelude::*, stampable::Stampable, topology::check_floating_nodes};
 use faer::{
     Mat,
     prelude::Solve,
-    sparse::{SparseColMat, Triplet},
+    sparse::{
+        SparseColMat, Triplet,
+        linalg::solvers::{Lu, SymbolicLu},
+    },
 };
 use krets_parser::{analyses::DcAnalysis, circuit::Circuit, elements::Element};
 use std::collections::HashMap;
 
-/// Solves for the DC response of a circuit while sweeping a source.
+/// A symbolic LU factorization together with the sparsity pattern it was computed for.
+struct SymbolicCache {
+    col_ptr: Vec<usize>,
+    row_idx: Vec<usize>,
+    symbolic: SymbolicLu<usize>,
+}
+
+/// Solves for the DC response of a circuit while sweeping the value of an element.
 ///
 /// This function performs a DC sweep analysis by repeatedly solving for the circuit's
-/// operating point at each step of the sweep.
+/// operating point at each step of the sweep. Any element with a
+/// [primary value](Element::primary_value) can be swept, usually a source. With a secondary
+/// element, the primary sweep is repeated for every secondary value and each row is tagged
+/// with that value under the identifier of the secondary element. The swept elements are
+/// restored afterward, also when the sweep fails.
+///
+/// A swept source only enters the excitation vector, so for a linear circuit the
+/// conductance matrix is built and factorized once and every sweep point is a single
+/// re-solve. Sweeping any other element refactorizes the matrix at every point. Nonlinear
+/// circuits are refactorized every Newton-Raphson iteration, but reuse the symbolic analysis
+/// as long as the sparsity pattern does not change. The `dc_voltage_divider_100_steps`
+/// benchmark covers the source sweep.
+///
+/// A resistor can only be swept through 0Ω in group 2 form (`G2` on its card), as an ordinary
+/// resistor is stamped as the conductance `1/R`.
 pub fn solve(
     circuit: &mut Circuit,
     config: &SolverConfig,
     dc_analysis: &DcAnalysis,
 ) -> Result<Vec<HashMap<String, f64>>> {
-    let index_map = &circuit.index_map;
-    let size = index_map.len();
+    let primary = swept_element(circuit, &dc_analysis.element, &dc_analysis.values())?;
+    let secondary = match (&dc_analysis.element2, dc_analysis.secondary_values()) {
+        (Some(element2), Some(values)) => Some(swept_element(circuit, element2, &values)?),
+        _ => None,
+    };
+
+    check_floating_nodes(circuit)?;
+
+    let result = sweep(
+        circuit,
+        config,
+        dc_analysis,
+        primary.0,
+        secondary.map(|(i, _)| i),
+    );
+
+    // Restore the original values of the swept elements.
+    for (index, original_value) in [Some(primary), secondary].into_iter().flatten() {
+        circuit.elements[index].set_primary_value(original_value);
+    }
+
+    result
+}
 
+/// Finds the element to be swept through `values`, returning its index in `circuit.elements`
+/// and its value.
+fn swept_element(circuit: &Circuit, identifier: &str, values: &[f64]) -> Result<(usize, f64)> {
     // Find the index of the element to be swept. This is faster than finding the element by name in every loop.
-    let sweep_element_index = circuit
+    let index = circuit
         .elements
         .iter()
-        .position(|x| x.identifier() == dc_analysis.element)
-        .ok_or_else(|| Error::ElementNotFound(dc_analysis.element.clone()))?;
+        .position(|x| x.identifier() == identifier)
+        .ok_or_else(|| Error::ElementNotFound(identifier.to_string()))?;
 
     // Store the original value of the swept element to restore it after the analysis.
-    let original_value = match &circuit.elements[sweep_element_index] {
-        Element::VoltageSource(vs) => vs.dc_value,
-        Element::CurrentSource(is) => is.value,
-        _ => {
-            return Err(Error::InvalidElementFormat(
-                "DC sweep element must be a voltage or current source".to_string(),
-            ));
-        }
-    };
+    let value = circuit.elements[index].primary_value().ok_or_else(|| {
+        Error::InvalidElementFormat(format!(
+            "DC sweep element '{identifier}' must be a resistor, capacitor, inductor or independent source"
+        ))
+    })?;
+    check_swept_values(&circuit.elements[index], values)?;
 
-    let mut all_results = Vec::new();
-    let mut last_op_solution = HashMap::new(); // Use last solution as a "warm start" for the next step
+    Ok((index, value))
+}
 
-    // Use an integer-based loop to avoid floating-point precision issues.
-    let num_steps =
-        ((dc_analysis.stop - dc_analysis.start) / dc_analysis.step_size).abs() as usize + 1;
+/// Returns an error if `element` cannot take one of the swept `values`, e.g. an ordinary
+/// resistor swept through 0Ω.
+pub(super) fn check_swept_values(element: &Element, values: &[f64]) -> Result<()> {
+    match values
+        .iter()
+        .find(|&&value| !element.accepts_primary_value(value))
+    {
+        Some(value) => Err(Error::InvalidElementFormat(format!(
+            "Element '{}' cannot be swept to {value}; a resistor needs the G2 flag to reach 0 ohm",
+            element.identifier()
+        ))),
+        None => Ok(()),
+    }
+}
 
-    for i in 0..num_steps {
-        let current_sweep_val = dc_analysis.start + (i as f64 * dc_analysis.step_size);
+/// Runs the primary sweep, once for every value of the secondary source if there is one.
+fn sweep(
+    circuit: &mut Circuit,
+    config: &SolverConfig,
+    dc_analysis: &DcAnalysis,
+    primary_index: usize,
+    secondary_index: Option<usize>,
+) -> Result<Vec<HashMap<String, f64>>> {
+    let index_map = &circuit.index_map;
+    let size = index_map.len();
 
-        // Update the value of the sweep element for the current step.
-        match &mut circuit.elements[sweep_element_index] {
-            Element::VoltageSource(vs) => vs.dc_value = current_sweep_val,
-            Element::CurrentSource(is) => is.value = current_sweep_val,
-            _ => unreachable!(),
-        }
+    let has_nonlinear_elements = circuit.elements.iter().any(Element::is_nonlinear);
+    // Only swept sources leave the conductance matrix unchanged between sweep points.
+    let matrix_is_fixed = [Some(primary_index), secondary_index]
+        .into_iter()
+        .flatten()
+        .all(|index| {
+            matches!(
+                circuit.elements[index],
+                Element::VoltageSource(_) | Element::CurrentSource(_)
+            )
+        });
+
+    let mut symbolic_cache: Option<SymbolicCache> = None;
+    let mut linear_lu: Option<Lu<usize, f64>> = None;
 
-        let mut op_result = HashMap::new();
-        let mut previous_op_result = last_op_solution.clone(); // Warm start from previous sweep point
+    let mut all_results = Vec::new();
+    // Use last solution as a "warm start" for the next step, starting from the `.nodeset` guesses.
+    let mut last_op_solution = super::op::nodeset_guess(circuit);
 
-        let elements: Vec<&Element> = circuit
-            .elements
-            .iter()
-            .filter(|e| !matches!(e, Element::Capacitor(_)))
-            .collect();
-        let has_nonlinear_elements = elements.iter().any(|e| e.is_nonlinear());
+    let primary_values = dc_analysis.values();
+    let secondary_values = match dc_analysis.secondary_values() {
+        Some(values) => values.into_iter().map(Some).collect(),
+        None => vec![None],
+    };
 
-        for iter in 0..config.maximum_iterations {
-            let mut g_stamps = Vec::new();
-            let mut e_stamps = Vec::new();
+    for secondary_value in secondary_values {
+        if let (Some(index), Some(value)) = (secondary_index, secondary_value) {
+            circuit.elements[index].set_primary_value(value);
+        }
 
-            for element in &elements {
-                g_stamps
-                    .extend(element.stamp_conductance_matrix_dc(index_map, &previous_op_result));
-                e_stamps.extend(element.stamp_excitation_vector_dc(index_map, &previous_op_result));
+        for (i, &current_sweep_val) in primary_values.iter().enumerate() {
+            // Update the value of the sweep element for the current step.
+            circuit.elements[primary_index].set_primary_value(current_sweep_val);
+            if !matrix_is_fixed {
+                linear_lu = None;
             }
+            let elements: Vec<&Element> = circuit
+                .elements
+                .iter()
+                .filter(|e| !matches!(e, Element::Capacitor(_)))
+                .collect();
 
-            let g_stamps_summed = sum_triplets(&g_stamps);
-            let e_stamps_summed = sum_triplets(&e_stamps);
+            let mut op_result = HashMap::new();
+            let mut previous_op_result = last_op_solution.clone(); // Warm start from previous sweep point
 
-            // FIX: Use `.map_err()` to convert the LU decomposition error.
-            let lu = SparseColMat::try_new_from_triplets(size, size, &g_stamps_summed)
-                .map_err(|e| Error::Unexpected(e.to_string()))?
-                .sp_lu()
-                .map_err(|_| Error::DecompositionFailed)?;
+            for iter in 0..config.maximum_iterations {
+                let mut e_stamps = Vec::new();
+                for element in &elements {
+                    e_stamps
+                        .extend(element.stamp_excitation_vector_dc(index_map, &previous_op_result));
+                }
+                let e_stamps_summed = sum_triplets(&e_stamps);
 
-            let mut b = Mat::zeros(size, 1);
-            for &Triplet { row, col, val } in &e_stamps_summed {
-                b[(row, col)] = val;
-            }
-            let x = lu.solve(&b);
+                let nonlinear_lu;
+                let lu = if has_nonlinear_elements {
+                    let g_mat = conductance_matrix(&elements, index_map, &previous_op_result)?;
+                    nonlinear_lu = factorize(&g_mat, &mut symbolic_cache)?;
+                    &nonlinear_lu
+                } else {
+                    match &mut linear_lu {
+                        Some(lu) => lu,
+                        linear_lu @ None => {
+                            let g_mat =
+                                conductance_matrix(&elements, index_map, &previous_op_result)?;
+                            linear_lu.insert(factorize(&g_mat, &mut symbolic_cache)?)
+                        }
+                    }
+                };
 
-            op_result = index_map
-                .iter()
-                .map(|(node, &idx)| (node.clone(), x[(idx, 0)]))
-                .collect();
+                let mut b = Mat::zeros(size, 1);
+                for &Triplet { row, col, val } in &e_stamps_summed {
+                    b[(row, col)] = val;
+                }
+                let x = lu.solve(&b);
 
-            op_result.insert("step".to_string(), i as f64);
+                op_result = index_map
+                    .iter()
+                    .map(|(node, &idx)| (node.clone(), x[(idx, 0)]))
+                    .collect();
 
-            if !has_nonlinear_elements {
-                break; // Circuit is linear, one iteration is enough.
-            }
-            if convergence_check(&previous_op_result, &op_result, config) {
-                break; // Converged for this sweep point.
+                op_result.insert("step".to_string(), i as f64);
+
+                if !has_nonlinear_elements {
+                    break; // Circuit is linear, one iteration is enough.
+                }
+                let convergence = convergence_check(&previous_op_result, &op_result, config);
+                if convergence.converged {
+                    break; // Converged for this sweep point.
+                }
+                previous_op_result.clone_from(&op_result);
+
+                if iter == config.maximum_iterations - 1 {
+                    return Err(convergence.into_error(config.maximum_iterations));
+                }
             }
-            previous_op_result.clone_from(&op_result);
 
-            if iter == config.maximum_iterations - 1 {
-                return Err(Error::MaximumIterationsExceeded(config.maximum_iterations));
+            last_op_solution.clone_from(&op_result);
+            if config.probe_currents {
+                super::op::insert_element_currents(circuit, &mut op_result);
+            }
+            if let (Some(element2), Some(value)) = (&dc_analysis.element2, secondary_value) {
+                op_result.insert(element2.clone(), value);
             }
+            all_results.push(op_result);
         }
+    }
+
+    Ok(all_results)
+}
 
-        last_op_solution.clone_from(&op_result);
-        all_results.push(op_result);
+/// Builds the DC conductance matrix of the elements, linearized around `solution_map`.
+fn conductance_matrix(
+    elements: &[&Element],
+    index_map: &HashMap<String, usize>,
+    solution_map: &HashMap<String, f64>,
+) -> Result<SparseColMat<usize, f64>> {
+    let size = index_map.len();
+    let mut g_stamps = Vec::new();
+    for element in elements {
+        g_stamps.extend(element.stamp_conductance_matrix_dc(index_map, solution_map));
     }
 
-    // Restore the original value of the swept element.
-    match &mut circuit.elements[sweep_element_index] {
-        Element::VoltageSource(vs) => vs.dc_value = original_value,
-        Element::CurrentSource(is) => is.value = original_value,
-        _ => unreachable!(),
+    SparseColMat::try_new_from_triplets(size, size, &sum_triplets(&g_stamps))
+        .map_err(|e| Error::Unexpected(e.to_string()))
+}
+
+/// Computes the LU factorization of `g_mat`, reusing the cached symbolic analysis when
+/// the sparsity pattern is unchanged.
+fn factorize(
+    g_mat: &SparseColMat<usize, f64>,
+    cache: &mut Option<SymbolicCache>,
+) -> Result<Lu<usize, f64>> {
+    let pattern = g_mat.symbolic();
+    let reusable = cache.as_ref().is_some_and(|cache| {
+        cache.col_ptr == pattern.col_ptr() && cache.row_idx == pattern.row_idx()
+    });
+
+    if !reusable {
+        *cache = Some(SymbolicCache {
+            col_ptr: pattern.col_ptr().to_vec(),
+            row_idx: pattern.row_idx().to_vec(),
+            symbolic: SymbolicLu::try_new(pattern).map_err(|_| Error::DecompositionFailed)?,
+        });
     }
+    let symbolic = cache.as_ref().unwrap().symbolic.clone();
 
-    Ok(all_results)
+    Lu::try_new_with_symbolic(symbolic, g_mat.as_ref()).map_err(|_| Error::DecompositionFailed)
 }
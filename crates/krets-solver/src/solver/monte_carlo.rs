@@ -0,0 +1,182 @@
+use crate::prelude::*;
+use krets_parser::{
+    analyses::{Analysis, Distribution, MonteCarlo},
+    circuit::Circuit,
+    elements::Element,
+};
+use std::sync::Arc;
+
+use super::param_sweep::{merge, with_sweep_column};
+
+/// The mean and standard deviation of one quantity over the runs of a Monte Carlo analysis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// The outcome of a Monte Carlo analysis.
+#[derive(Debug, Clone)]
+pub struct MonteCarloResult {
+    /// The rows of every run concatenated, each with a `run` column holding the run index.
+    pub runs: AnalysisResult,
+    /// The statistics of every quantity over the runs, one map per point of the inner
+    /// analysis (a single map for an operating point). AC quantities use their magnitude.
+    pub statistics: Vec<HashMap<String, Statistics>>,
+}
+
+/// Runs the inner analysis of the Monte Carlo analysis `monte_carlo.runs` times.
+///
+/// Before every run, each resistor, capacitor and inductor with a tolerance gets a value drawn
+/// around its nominal value from the configured distribution. The nominal values are restored
+/// afterward, also when a run fails. Points of the runs are matched by index, so points beyond
+/// the shortest run (e.g. with adaptive time steps) have no statistics.
+pub fn solve(
+    circuit: &mut Arc<Circuit>,
+    config: &SolverConfig,
+    monte_carlo: &MonteCarlo,
+) -> Result<MonteCarloResult> {
+    if matches!(*monte_carlo.inner, Analysis::PoleZero(_)) {
+        return Err(Error::UnsupportedAnalysis(
+            "pole-zero analyses cannot be run in a Monte Carlo analysis".to_string(),
+        ));
+    }
+
+    let nominal: Vec<(usize, f64, f64)> = circuit
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, element)| {
+            let (value, tolerance) = match element {
+                Element::Resistor(r) => (r.value, r.tolerance?),
+                Element::Capacitor(c) => (c.value, c.tolerance?),
+                Element::Inductor(l) => (l.value, l.tolerance?),
+                _ => return None,
+            };
+            Some((index, value, tolerance))
+        })
+        .collect();
+
+    let mut rng = SplitMix64::new(monte_carlo.seed);
+    let mut merged = None;
+    let mut samples: Vec<Vec<HashMap<String, f64>>> = Vec::new();
+    let mut outcome = Ok(());
+    for run in 0..monte_carlo.runs {
+        for &(index, value, tolerance) in &nominal {
+            let deviation = match monte_carlo.distribution {
+                // The tolerance is the 3-sigma deviation of the normal distribution.
+                Distribution::Gaussian => rng.next_gaussian() * tolerance / 3.0,
+                Distribution::Uniform => (2.0 * rng.next_f64() - 1.0) * tolerance,
+            };
+            Arc::make_mut(circuit).elements[index].set_primary_value(value * (1.0 + deviation));
+        }
+        match super::solve_analysis(circuit, config, (*monte_carlo.inner).clone()) {
+            Ok(result) => {
+                samples.push(magnitudes(&result));
+                merged = Some(merge(merged, with_sweep_column(result, "run", run as f64)));
+            }
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        }
+    }
+
+    // Restore the nominal element values.
+    for &(index, value, _) in &nominal {
+        Arc::make_mut(circuit).elements[index].set_primary_value(value);
+    }
+    outcome?;
+
+    Ok(MonteCarloResult {
+        runs: merged.unwrap_or(AnalysisResult::Dc(Vec::new(), HashMap::new())),
+        statistics: statistics(&samples),
+    })
+}
+
+/// Returns the rows of a result as real values, taking the magnitude of AC quantities.
+fn magnitudes(result: &AnalysisResult) -> Vec<HashMap<String, f64>> {
+    match result {
+        AnalysisResult::Op(row) | AnalysisResult::Tf(row) | AnalysisResult::Sensitivity(row) => {
+            vec![row.clone()]
+        }
+        AnalysisResult::Dc(rows, _)
+        | AnalysisResult::Transient(rows, _)
+        | AnalysisResult::Noise(rows) => rows.clone(),
+        AnalysisResult::Ac(rows) => rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(key, value)| (key.clone(), value.norm()))
+                    .collect()
+            })
+            .collect(),
+        AnalysisResult::PoleZero { .. } => {
+            unreachable!("pole-zero analyses are rejected before the runs")
+        }
+    }
+}
+
+/// Computes the mean and sample standard deviation of every quantity at every point.
+fn statistics(samples: &[Vec<HashMap<String, f64>>]) -> Vec<HashMap<String, Statistics>> {
+    let points = samples.iter().map(Vec::len).min().unwrap_or(0);
+    (0..points)
+        .map(|point| {
+            samples[0][point]
+                .keys()
+                .map(|key| {
+                    let values: Vec<f64> = samples
+                        .iter()
+                        .filter_map(|run| run[point].get(key).copied())
+                        .collect();
+                    let n = values.len() as f64;
+                    let mean = values.iter().sum::<f64>() / n;
+                    let variance = if values.len() > 1 {
+                        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+                    } else {
+                        0.0
+                    };
+                    (
+                        key.clone(),
+                        Statistics {
+                            mean,
+                            std_dev: variance.sqrt(),
+                        },
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A small, seedable pseudo-random number generator (SplitMix64), so that Monte Carlo runs
+/// are reproducible without an external dependency.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed value in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a standard normally distributed value (Box-Muller transform).
+    fn next_gaussian(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
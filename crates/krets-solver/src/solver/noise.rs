@@ -0,0 +1,134 @@
+use log::info;
+use std::collections::HashMap;
+
+use crate::{config::SolverConfig, prelude::*, solver::op, stampable::Stampable};
+use faer::{
+    Mat, c64,
+    prelude::Solve,
+    sparse::{SparseColMat, Triplet},
+};
+use krets_parser::{analyses::NoiseAnalysis, circuit::Circuit, constants::Q, elements::Element};
+
+/// A noise current source connected between two nodes, with its power spectral density in A²/Hz.
+struct NoiseSource {
+    plus: Option<usize>,
+    minus: Option<usize>,
+    psd: f64,
+}
+
+/// Solves for the noise spectral densities of the circuit over a frequency sweep.
+///
+/// Every resistor contributes a thermal noise current `4kT/R` and every diode a shot
+/// noise current `2qI`, evaluated at the DC operating point. Instead of solving the
+/// circuit once per noise source, the adjoint system `Gᵀ y = e_out` is solved once per
+/// frequency: `y` then holds the transfer from a unit injection at any row to the
+/// output voltage. Each result row holds the `frequency`, the output noise PSD `onoise`
+/// in V²/Hz and the noise PSD referred to the input source, `inoise`.
+pub fn solve(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    parameters: &NoiseAnalysis,
+) -> Result<Vec<HashMap<String, f64>>> {
+    if let Some(reason) = parameters.invalid_range() {
+        return Err(Error::InvalidFrequencyRange {
+            fstart: parameters.fstart,
+            fstop: parameters.fstop,
+            reason: reason.to_string(),
+        });
+    }
+
+    info!("Calculating DC operating point for noise analysis...");
+    let dc_solution = op::solve(circuit, config)?;
+
+    let index_map = &circuit.index_map;
+    let size = index_map.len();
+
+    let output_key = format!("V({})", parameters.output_node);
+    let output_index = *index_map
+        .get(&output_key)
+        .ok_or_else(|| Error::NodeNotFound(output_key.clone()))?;
+
+    // Independent sources carry their value on the row of their branch current.
+    let input_key = format!("I({})", parameters.input_source);
+    let input_index = *index_map
+        .get(&input_key)
+        .ok_or_else(|| Error::ElementNotFound(parameters.input_source.clone()))?;
+
+    let noise_sources: Vec<NoiseSource> = circuit
+        .elements
+        .iter()
+        .filter_map(|element| noise_source(element, index_map, &dc_solution))
+        .collect();
+
+    let frequencies = parameters.clone().generate_frequencies();
+    info!(
+        "Starting noise sweep over {} frequencies...",
+        frequencies.len()
+    );
+
+    let mut all_results = Vec::with_capacity(frequencies.len());
+    for frequency in frequencies {
+        let mut g_stamps = Vec::new();
+        for element in &circuit.elements {
+            g_stamps.extend(element.stamp_conductance_matrix_ac(
+                index_map,
+                &dc_solution,
+                frequency,
+            ));
+        }
+
+        // Transposing the stamps gives the adjoint system.
+        let g_stamps_transposed: Vec<Triplet<usize, usize, c64>> = sum_triplets(&g_stamps)
+            .into_iter()
+            .map(|Triplet { row, col, val }| Triplet::new(col, row, val))
+            .collect();
+
+        let lu = SparseColMat::try_new_from_triplets(size, size, &g_stamps_transposed)
+            .map_err(|e| Error::Unexpected(format!("Matrix build failed at f={frequency}: {e}")))?
+            .sp_lu()
+            .map_err(|_| Error::DecompositionFailed)?;
+
+        let mut b = Mat::<c64>::zeros(size, 1);
+        b[(output_index, 0)] = c64::new(1.0, 0.0);
+        let y = lu.solve(&b);
+
+        let transfer = |index: Option<usize>| index.map_or(c64::new(0.0, 0.0), |i| y[(i, 0)]);
+
+        let output_noise: f64 = noise_sources
+            .iter()
+            .map(|source| (transfer(source.plus) - transfer(source.minus)).norm_sqr() * source.psd)
+            .sum();
+        let gain = transfer(Some(input_index)).norm_sqr();
+
+        let mut solution_map = HashMap::new();
+        solution_map.insert("frequency".to_string(), frequency);
+        solution_map.insert("onoise".to_string(), output_noise);
+        solution_map.insert("inoise".to_string(), output_noise / gain);
+        all_results.push(solution_map);
+    }
+
+    Ok(all_results)
+}
+
+/// Returns the equivalent noise current source of an element, if it is noisy.
+fn noise_source(
+    element: &Element,
+    index_map: &HashMap<String, usize>,
+    dc_solution: &HashMap<String, f64>,
+) -> Option<NoiseSource> {
+    let index = |node: &str| index_map.get(&format!("V({node})")).copied();
+
+    match element {
+        Element::Resistor(r) => Some(NoiseSource {
+            plus: index(&r.plus),
+            minus: index(&r.minus),
+            psd: r.thermal_noise_psd(r.temperature),
+        }),
+        Element::Diode(d) => Some(NoiseSource {
+            plus: index(&d.plus),
+            minus: index(&d.minus),
+            psd: 2.0 * Q * d.current(dc_solution).abs(),
+        }),
+        _ => None,
+    }
+}
@@ -1,18 +1,152 @@
-use crate::{prelude::*, stampable::Stampable};
-use faer::{
-    Mat,
-    prelude::Solve,
-    sparse::{SparseColMat, Triplet},
+use crate::{
+    conditioning::solve_linear_system,
+    config::Homotopy,
+    prelude::*,
+    stampable::{MOSFET_GMIN, Stampable},
+    topology::check_floating_nodes,
 };
-use krets_parser::{circuit::Circuit, elements::Element};
-use log::info;
+use faer::sparse::Triplet;
+use krets_parser::{
+    circuit::Circuit,
+    elements::{Element, current_source::CurrentSource},
+};
+use log::{info, warn};
 use std::collections::HashMap;
 
 /// Solves for the DC operating point of the circuit.
 ///
 /// This function implements the Newton-Raphson iterative method to find the DC steady-state
-/// solution for a potentially non-linear circuit.
+/// solution for a potentially non-linear circuit, starting from the `.nodeset` guesses.
 pub fn solve(circuit: &Circuit, config: &SolverConfig) -> Result<HashMap<String, f64>> {
+    solve_from(circuit, config, &nodeset_guess(circuit))
+}
+
+/// Returns the `.nodeset` voltages of the circuit as a starting guess for Newton-Raphson.
+///
+/// Nodes that are not part of the circuit are skipped with a warning.
+pub fn nodeset_guess(circuit: &Circuit) -> HashMap<String, f64> {
+    circuit
+        .nodesets
+        .iter()
+        .filter(|(node, _)| {
+            let known = circuit.index_map.contains_key(*node);
+            if !known {
+                warn!("Ignoring .nodeset for unknown node {node}");
+            }
+            known
+        })
+        .map(|(node, &value)| (node.clone(), value))
+        .collect()
+}
+
+/// Solves for the DC operating point, starting Newton-Raphson from `initial_guess`.
+///
+/// Floating nodes are reported before solving (see [`check_floating_nodes`]). If the plain
+/// Newton-Raphson iteration does not converge, the solver falls back to the homotopy selected
+/// in `config.homotopy`, gmin stepping (see [`gmin_stepping`]) or source stepping (see
+/// [`source_stepping`]).
+pub fn solve_from(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    initial_guess: &HashMap<String, f64>,
+) -> Result<HashMap<String, f64>> {
+    solve_from_counted(circuit, config, initial_guess).map(|(solution, _)| solution)
+}
+
+/// Solves for the DC operating point like [`solve_from`], also returning the total number of
+/// Newton-Raphson iterations spent, including those of any homotopy stages.
+pub fn solve_from_counted(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    initial_guess: &HashMap<String, f64>,
+) -> Result<(HashMap<String, f64>, usize)> {
+    check_floating_nodes(circuit)?;
+
+    let mut iterations = 0;
+    let result = newton_raphson(
+        circuit,
+        config,
+        initial_guess.clone(),
+        0.0,
+        1.0,
+        &mut iterations,
+    );
+    let result = match (result, config.homotopy) {
+        (Err(Error::ConvergenceFailed { .. }), Homotopy::GminStepping) if config.gmin_steps > 0 => {
+            info!("Newton-Raphson did not converge, falling back to gmin stepping.");
+            gmin_stepping(circuit, config, &mut iterations)
+        }
+        (Err(Error::ConvergenceFailed { .. }), Homotopy::SourceStepping { steps }) if steps > 0 => {
+            info!("Newton-Raphson did not converge, falling back to source stepping.");
+            source_stepping(circuit, config, steps, &mut iterations)
+        }
+        (result, _) => result,
+    };
+    result.map(|solution| (solution, iterations))
+}
+
+/// Finds the operating point with a gmin-stepping homotopy.
+///
+/// A conductance `gmin` is connected from every node to ground, which keeps the
+/// linearized system well conditioned and pulls the node voltages towards zero. The
+/// conductance is reduced geometrically from `config.gmin` to `config.minimum_conductance`
+/// over `config.gmin_steps` stages, each warm-started from the previous solution. A final
+/// stage without any added conductance yields the operating point of the original circuit.
+fn gmin_stepping(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    iterations: &mut usize,
+) -> Result<HashMap<String, f64>> {
+    let steps = config.gmin_steps;
+    let ratio = if steps > 1 {
+        (config.minimum_conductance / config.gmin).powf(1.0 / (steps - 1) as f64)
+    } else {
+        1.0
+    };
+
+    let mut guess = HashMap::new();
+    for step in 0..steps {
+        let gmin = config.gmin * ratio.powi(step as i32);
+        info!("gmin stepping: stage {} with gmin = {gmin:e}", step + 1);
+        guess = newton_raphson(circuit, config, guess, gmin, 1.0, iterations)?;
+    }
+
+    newton_raphson(circuit, config, guess, 0.0, 1.0, iterations)
+}
+
+/// Finds the operating point with a source-stepping homotopy.
+///
+/// All independent sources are scaled by a factor that is ramped from `1 / steps` up to 1
+/// in `steps` equal stages, each warm-started from the previous solution. With all sources
+/// near zero the circuit sits close to the trivial all-zero solution, and every stage only
+/// moves it a little. The last stage, at full source values, yields the operating point.
+fn source_stepping(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    steps: usize,
+    iterations: &mut usize,
+) -> Result<HashMap<String, f64>> {
+    let mut guess = HashMap::new();
+    for step in 1..=steps {
+        let source_factor = step as f64 / steps as f64;
+        info!("source stepping: stage {step} with sources at {source_factor:.3} of their value");
+        guess = newton_raphson(circuit, config, guess, 0.0, source_factor, iterations)?;
+    }
+    Ok(guess)
+}
+
+/// Runs the Newton-Raphson iteration from `initial_guess`, with an extra conductance
+/// `gmin` stamped from every node to ground and the excitation of every independent source
+/// scaled by `source_factor`. The elements themselves are left untouched. Every iteration
+/// run is added to `iterations`.
+fn newton_raphson(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    initial_guess: HashMap<String, f64>,
+    gmin: f64,
+    source_factor: f64,
+    iterations: &mut usize,
+) -> Result<HashMap<String, f64>> {
     let index_map = &circuit.index_map;
     let size = index_map.len();
 
@@ -27,35 +161,47 @@ pub fn solve(circuit: &Circuit, config: &SolverConfig) -> Result<HashMap<String,
     // only needs to run for one iteration.
     let has_nonlinear_elements = elements.iter().any(|e| e.is_nonlinear());
 
+    // The gmin conductances only touch the node rows, never the branch-current rows.
+    let gmin_stamps: Vec<Triplet<usize, usize, f64>> = if gmin > 0.0 {
+        index_map
+            .iter()
+            .filter(|(key, _)| key.starts_with("V("))
+            .map(|(_, &idx)| Triplet::new(idx, idx, gmin))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
     let mut result = HashMap::new();
-    let mut previous_result = HashMap::new();
+    let mut previous_result = initial_guess;
 
     for iter in 0..config.maximum_iterations {
+        *iterations += 1;
+
         // This is the core of the Newton-Raphson method. The Jacobian (g_stamps)
         // and the RHS vector (e_stamps) are recalculated based on the solution from
         // the previous iteration (`previous_result`).
-        let mut g_stamps = Vec::new();
+        let mut g_stamps = gmin_stamps.clone();
         let mut e_stamps = Vec::new();
 
         for element in &elements {
             g_stamps.extend(element.stamp_conductance_matrix_dc(index_map, &previous_result));
-            e_stamps.extend(element.stamp_excitation_vector_dc(index_map, &previous_result));
+            let stamps = element.stamp_excitation_vector_dc(index_map, &previous_result);
+            if is_independent_source(element) {
+                e_stamps.extend(
+                    stamps
+                        .into_iter()
+                        .map(|t| Triplet::new(t.row, t.col, t.val * source_factor)),
+                );
+            } else {
+                e_stamps.extend(stamps);
+            }
         }
 
         let g_stamps_summed = sum_triplets(&g_stamps);
         let e_stamps_summed = sum_triplets(&e_stamps);
 
-        let lu = SparseColMat::try_new_from_triplets(size, size, &g_stamps_summed)
-            .map_err(|_| Error::MatrixBuild)?
-            .sp_lu()
-            .map_err(|_| Error::MatrixDecomposition)?;
-
-        let mut b = Mat::zeros(size, 1);
-        for &Triplet { row, col, val } in &e_stamps_summed {
-            b[(row, col)] = val;
-        }
-
-        let x = lu.solve(&b);
+        let x = solve_linear_system(&g_stamps_summed, &e_stamps_summed, size, config)?;
 
         result = index_map
             .iter()
@@ -67,7 +213,8 @@ pub fn solve(circuit: &Circuit, config: &SolverConfig) -> Result<HashMap<String,
             break;
         }
 
-        if convergence_check(&previous_result, &result, config) {
+        let convergence = convergence_check(&previous_result, &result, config);
+        if convergence.converged {
             info!("Converged after {} iterations", iter + 1);
             break;
         }
@@ -76,11 +223,249 @@ pub fn solve(circuit: &Circuit, config: &SolverConfig) -> Result<HashMap<String,
         previous_result.clone_from(&result);
 
         if iter == config.maximum_iterations - 1 {
-            info!("Warning: Maximum iterations reached without convergence.");
-            return Err(Error::MaximumIterationsExceeded(config.maximum_iterations));
+            info!(
+                "Warning: Maximum iterations reached without convergence, worst node {}.",
+                convergence.worst_node
+            );
+            return Err(convergence.into_error(config.maximum_iterations));
         }
     }
 
     // Return the final converged operating point solution.
     Ok(result)
 }
+
+/// Returns whether `element` is an independent voltage or current source.
+fn is_independent_source(element: &Element) -> bool {
+    matches!(
+        element,
+        Element::VoltageSource(_) | Element::CurrentSource(_)
+    )
+}
+
+/// The current injected into a port to measure its Thevenin resistance.
+const THEVENIN_TEST_CURRENT: f64 = 1e-6;
+
+/// Computes the Thevenin equivalent `(voltage, resistance)` looking into the port between
+/// `node_a` and `node_b`.
+///
+/// The voltage is the open-circuit voltage `V(node_a) - V(node_b)` at the operating point.
+/// A second operating point is then solved with a small test current injected into `node_a`
+/// and drawn from `node_b`, and the resistance is the resulting change of the port voltage
+/// divided by the test current. For nonlinear circuits this is the small-signal resistance at
+/// the operating point. Either node may be ground; a port between a node and itself is a short
+/// with a zero voltage and resistance.
+pub fn thevenin(
+    circuit: &mut Circuit,
+    config: &SolverConfig,
+    node_a: &str,
+    node_b: &str,
+) -> Result<(f64, f64)> {
+    for node in [node_a, node_b] {
+        if node != "0" && !circuit.index_map.contains_key(&format!("V({node})")) {
+            return Err(Error::NodeNotFound(node.to_string()));
+        }
+    }
+    if node_a == node_b {
+        return Ok((0.0, 0.0));
+    }
+
+    let port_voltage = |solution: &HashMap<String, f64>| {
+        let voltage = |node: &str| solution.get(&format!("V({node})")).copied().unwrap_or(0.0);
+        voltage(node_a) - voltage(node_b)
+    };
+
+    let open_circuit = solve(circuit, config)?;
+
+    // A current source drives its current from `plus` through itself into `minus`.
+    let test_source = CurrentSource {
+        name: "thevenin_test".to_string(),
+        value: THEVENIN_TEST_CURRENT,
+        plus: node_b.to_string(),
+        minus: node_a.to_string(),
+        ac_amplitude: 0.0,
+        ac_phase: 0.0,
+        pwl: None,
+    };
+    let test_current_key = format!("I({})", test_source.identifier());
+    let test_current_index = circuit.index_map.len();
+    circuit
+        .index_map
+        .insert(test_current_key.clone(), test_current_index);
+    circuit.elements.push(Element::CurrentSource(test_source));
+
+    let loaded = solve_from(circuit, config, &open_circuit);
+
+    // Remove the test source again, also when the loaded solve failed.
+    circuit.elements.pop();
+    circuit.index_map.remove(&test_current_key);
+    let loaded = loaded?;
+
+    let voltage = port_voltage(&open_circuit);
+    let resistance = (port_voltage(&loaded) - voltage) / THEVENIN_TEST_CURRENT;
+    Ok((voltage, resistance))
+}
+
+/// Adds the current of every element at the DC operating point `solution` as `I(element)`,
+/// see [`element_power`] for the sign convention. The branch currents of group-2 elements
+/// are already part of the solution and are kept as solved.
+pub fn insert_element_currents(circuit: &Circuit, solution: &mut HashMap<String, f64>) {
+    for (identifier, (current, _)) in element_power(circuit, solution) {
+        solution
+            .entry(format!("I({identifier})"))
+            .or_insert(current);
+    }
+}
+
+/// Computes the branch current and the absorbed power of every element at a DC operating point.
+///
+/// Currents are those flowing into the first terminal of the element (`plus`, the drain or the
+/// collector), so the power is positive for elements dissipating power and negative for
+/// elements delivering it, such as sources. Nodes missing from `solution`, like ground, are
+/// at 0V. Elements without a current of their own, like mutual inductances, are left out.
+pub fn element_power(
+    circuit: &Circuit,
+    solution: &HashMap<String, f64>,
+) -> HashMap<String, (f64, f64)> {
+    let voltage = |node: &str| solution.get(&format!("V({node})")).copied().unwrap_or(0.0);
+    let branch_current = |identifier: &str| {
+        solution
+            .get(&format!("I({identifier})"))
+            .copied()
+            .unwrap_or(0.0)
+    };
+    let two_terminal = |plus: &str, minus: &str, current: f64| {
+        (current, (voltage(plus) - voltage(minus)) * current)
+    };
+
+    circuit
+        .elements
+        .iter()
+        .filter_map(|element| {
+            let current_and_power = match element {
+                Element::Resistor(r) if r.g2 => {
+                    two_terminal(&r.plus, &r.minus, branch_current(&r.identifier()))
+                }
+                Element::Resistor(r) => two_terminal(
+                    &r.plus,
+                    &r.minus,
+                    (voltage(&r.plus) - voltage(&r.minus)) * r.conductance(r.temperature),
+                ),
+                // No DC current flows through a capacitor.
+                Element::Capacitor(_) => (0.0, 0.0),
+                Element::Inductor(l) => {
+                    two_terminal(&l.plus, &l.minus, branch_current(&l.identifier()))
+                }
+                Element::VoltageSource(vs) => {
+                    two_terminal(&vs.plus, &vs.minus, branch_current(&vs.identifier()))
+                }
+                Element::CurrentSource(is) => two_terminal(&is.plus, &is.minus, is.value),
+                Element::Diode(d) => two_terminal(&d.plus, &d.minus, d.current(solution)),
+                Element::VCVS(e) => {
+                    two_terminal(&e.plus, &e.minus, branch_current(&e.identifier()))
+                }
+                Element::CCVS(h) => {
+                    two_terminal(&h.plus, &h.minus, branch_current(&h.identifier()))
+                }
+                Element::VCCS(g) => two_terminal(
+                    &g.plus,
+                    &g.minus,
+                    g.transconductance * (voltage(&g.control_plus) - voltage(&g.control_minus)),
+                ),
+                Element::CCCS(f) => two_terminal(
+                    &f.plus,
+                    &f.minus,
+                    f.gain * branch_current(&f.control_source),
+                ),
+                Element::Switch(s) => two_terminal(
+                    &s.plus,
+                    &s.minus,
+                    (voltage(&s.plus) - voltage(&s.minus)) * s.conductance(),
+                ),
+                Element::NMOSFET(m) => {
+                    let v_gs = voltage(&m.gate) - voltage(&m.source);
+                    let v_ds = voltage(&m.drain) - voltage(&m.source);
+                    // Include the leakage of the conductance stamped in parallel with the channel.
+                    let i_d = m.i_d(v_gs, v_ds) + MOSFET_GMIN * v_ds;
+                    (i_d, v_ds * i_d)
+                }
+                Element::PMOSFET(m) => {
+                    let v_gs = voltage(&m.gate) - voltage(&m.source);
+                    let v_ds = voltage(&m.drain) - voltage(&m.source);
+                    // Include the leakage of the conductance stamped in parallel with the channel.
+                    let i_d = m.i_d(v_gs, v_ds) + MOSFET_GMIN * v_ds;
+                    (i_d, v_ds * i_d)
+                }
+                Element::BJT(q) => {
+                    // The operating point is normalized to NPN polarity, so the power needs
+                    // no polarity correction.
+                    let op = q.operating_point(solution);
+                    let power = op.i_c * (op.v_be - op.v_bc) + op.i_b * op.v_be;
+                    (q.polarity() * op.i_c, power)
+                }
+                Element::TransmissionLine(t) => {
+                    // The current into the first port leaves through the second one, so the
+                    // line absorbs the difference of the port voltages, zero at DC.
+                    let current = branch_current(&t.identifier());
+                    let v1 = voltage(&t.plus1) - voltage(&t.minus1);
+                    let v2 = voltage(&t.plus2) - voltage(&t.minus2);
+                    (current, (v1 - v2) * current)
+                }
+                Element::MutualInductance(_) | Element::SubcktInstance(_) => return None,
+            };
+            Some((element.identifier(), current_and_power))
+        })
+        .collect()
+}
+
+/// Checks Kirchhoff's current law at the DC operating point `solution`.
+///
+/// The currents of [`element_power`] flow into the first terminal of every element and out of
+/// the others, so they must sum to zero at every node. Control terminals of controlled sources
+/// and the gates of MOSFETs draw no current. Ground is left out, as it closes the circuit.
+///
+/// Returns the node with the largest current imbalance and that imbalance in amperes, or `None`
+/// for a circuit without nodes.
+pub fn kcl_residual(circuit: &Circuit, solution: &HashMap<String, f64>) -> Option<(String, f64)> {
+    let currents = element_power(circuit, solution);
+    let mut residuals: HashMap<&str, f64> = HashMap::new();
+
+    for element in &circuit.elements {
+        let Some(&(current, _)) = currents.get(&element.identifier()) else {
+            continue;
+        };
+        // The current leaving each node into a terminal of the element.
+        let terminals = match element {
+            Element::BJT(q) => {
+                let i_b = q.polarity() * q.operating_point(solution).i_b;
+                vec![
+                    (q.collector.as_str(), current),
+                    (q.base.as_str(), i_b),
+                    (q.emitter.as_str(), -current - i_b),
+                ]
+            }
+            Element::NMOSFET(m) => vec![(m.drain.as_str(), current), (m.source.as_str(), -current)],
+            Element::PMOSFET(m) => vec![(m.drain.as_str(), current), (m.source.as_str(), -current)],
+            Element::TransmissionLine(t) => vec![
+                (t.plus1.as_str(), current),
+                (t.minus1.as_str(), -current),
+                (t.plus2.as_str(), -current),
+                (t.minus2.as_str(), current),
+            ],
+            _ => {
+                let nodes = element.nodes();
+                vec![(nodes[0], current), (nodes[1], -current)]
+            }
+        };
+        for (node, current) in terminals {
+            if circuit.index_map.contains_key(&format!("V({node})")) {
+                *residuals.entry(node).or_default() += current;
+            }
+        }
+    }
+
+    residuals
+        .into_iter()
+        .map(|(node, residual)| (node.to_string(), residual))
+        .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+}
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use krets_parser::{
+    analyses::{Analysis, ParamSweep},
+    circuit::Circuit,
+};
+use std::sync::Arc;
+
+/// The quantity changed by a parameter sweep.
+enum SweepTarget {
+    /// The circuit temperature in degrees Celsius.
+    Temperature,
+    /// The value of the element at this index of `circuit.elements`.
+    Element(usize),
+}
+
+/// Runs the inner analysis of the sweep once for every swept value.
+///
+/// The rows of all runs are concatenated into a single result of the inner analysis type,
+/// with a column named after the swept element (or `temp`) holding the swept value, so that
+/// the rows can be grouped into a family of curves. An inner operating point analysis yields
+/// one row per swept value, returned as an `AnalysisResult::Dc`, and so does an inner transfer
+/// function analysis. The measurements of the single runs are
+/// not kept. The swept value is restored afterward, also when an inner analysis fails.
+pub fn solve(
+    circuit: &mut Arc<Circuit>,
+    config: &SolverConfig,
+    sweep: &ParamSweep,
+) -> Result<AnalysisResult> {
+    if matches!(*sweep.inner, Analysis::PoleZero(_)) {
+        return Err(Error::UnsupportedAnalysis(
+            "pole-zero analyses cannot be swept".to_string(),
+        ));
+    }
+
+    let key = sweep.element_or_param.as_str();
+    let (target, original_value) = if key.eq_ignore_ascii_case("temp") {
+        let temperature = circuit.temperature.unwrap_or(config.temperature);
+        (SweepTarget::Temperature, temperature)
+    } else {
+        let index = circuit
+            .elements
+            .iter()
+            .position(|element| element.identifier() == key)
+            .ok_or_else(|| Error::ElementNotFound(key.to_string()))?;
+        let value = circuit.elements[index].primary_value().ok_or_else(|| {
+            Error::InvalidElementFormat(format!(
+                "Swept element '{key}' must be a resistor, capacitor, inductor or independent source"
+            ))
+        })?;
+        super::dc::check_swept_values(&circuit.elements[index], &sweep.values())?;
+        (SweepTarget::Element(index), value)
+    };
+
+    let mut merged = None;
+    let mut outcome = Ok(());
+    for value in sweep.values() {
+        set_value(Arc::make_mut(circuit), &target, value);
+        match super::solve_analysis(circuit, config, (*sweep.inner).clone()) {
+            Ok(result) => merged = Some(merge(merged, with_sweep_column(result, key, value))),
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        }
+    }
+
+    // Restore the original value of the swept element or temperature.
+    set_value(Arc::make_mut(circuit), &target, original_value);
+    outcome?;
+
+    Ok(merged.unwrap_or(AnalysisResult::Dc(Vec::new(), HashMap::new())))
+}
+
+fn set_value(circuit: &mut Circuit, target: &SweepTarget, value: f64) {
+    match *target {
+        SweepTarget::Temperature => circuit.set_temperature(value),
+        SweepTarget::Element(index) => circuit.elements[index].set_primary_value(value),
+    }
+}
+
+/// Adds the swept value as a column to every row of `result`.
+pub(super) fn with_sweep_column(result: AnalysisResult, key: &str, value: f64) -> AnalysisResult {
+    fn tag<T: From<f64>>(
+        mut rows: Vec<HashMap<String, T>>,
+        key: &str,
+        value: f64,
+    ) -> Vec<HashMap<String, T>> {
+        for row in &mut rows {
+            row.insert(key.to_string(), T::from(value));
+        }
+        rows
+    }
+
+    match result {
+        AnalysisResult::Op(row) | AnalysisResult::Tf(row) | AnalysisResult::Sensitivity(row) => {
+            AnalysisResult::Dc(tag(vec![row], key, value), HashMap::new())
+        }
+        AnalysisResult::Dc(rows, _) => AnalysisResult::Dc(tag(rows, key, value), HashMap::new()),
+        AnalysisResult::Ac(rows) => AnalysisResult::Ac(tag(rows, key, value)),
+        AnalysisResult::Transient(rows, _) => {
+            AnalysisResult::Transient(tag(rows, key, value), HashMap::new())
+        }
+        AnalysisResult::Noise(rows) => AnalysisResult::Noise(tag(rows, key, value)),
+        AnalysisResult::PoleZero { .. } => {
+            unreachable!("pole-zero analyses are rejected before sweeping")
+        }
+    }
+}
+
+/// Appends the rows of `next` to the results collected so far.
+pub(super) fn merge(merged: Option<AnalysisResult>, next: AnalysisResult) -> AnalysisResult {
+    match (merged, next) {
+        (None, next) => next,
+        (Some(AnalysisResult::Dc(mut rows, measurements)), AnalysisResult::Dc(next, _)) => {
+            rows.extend(next);
+            AnalysisResult::Dc(rows, measurements)
+        }
+        (Some(AnalysisResult::Ac(mut rows)), AnalysisResult::Ac(next)) => {
+            rows.extend(next);
+            AnalysisResult::Ac(rows)
+        }
+        (
+            Some(AnalysisResult::Transient(mut rows, measurements)),
+            AnalysisResult::Transient(next, _),
+        ) => {
+            rows.extend(next);
+            AnalysisResult::Transient(rows, measurements)
+        }
+        (Some(AnalysisResult::Noise(mut rows)), AnalysisResult::Noise(next)) => {
+            rows.extend(next);
+            AnalysisResult::Noise(rows)
+        }
+        _ => unreachable!("every sweep point runs the same inner analysis"),
+    }
+}
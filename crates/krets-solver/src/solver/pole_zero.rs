@@ -0,0 +1,134 @@
+use log::info;
+use std::collections::HashMap;
+
+use crate::{config::SolverConfig, prelude::*, solver::op, stampable::Stampable};
+use faer::{Mat, c64};
+use krets_parser::{analyses::PoleZeroAnalysis, circuit::Circuit, elements::Element};
+
+/// Generalized eigenvalues whose `S_b` is below this fraction of the largest reactive entry
+/// are infinite, i.e. they stem from the algebraic (non-reactive) part of the circuit.
+const INFINITE_EIGENVALUE_TOLERANCE: f64 = 1e-9;
+
+/// Finds the poles and zeros, in rad/s, of the transfer function from an input source to the
+/// voltage of an output node.
+///
+/// The circuit is linearized at its DC operating point into the MNA system `(G + sC) x = b`.
+/// The AC stamps of all elements are affine in `jω`, so stamping them at `ω = 1` yields `G`
+/// as the real part and the reactive matrix `C` of the capacitors and inductors as the
+/// imaginary part. The poles are the finite generalized eigenvalues `s` of `G + sC`. By
+/// Cramer's rule the zeros are those of the same system with the column of the output
+/// replaced by the input excitation `b`.
+pub fn solve(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    parameters: &PoleZeroAnalysis,
+) -> Result<(Vec<c64>, Vec<c64>)> {
+    // The delay of a transmission line is not a rational function of `s`.
+    if circuit
+        .elements
+        .iter()
+        .any(|element| matches!(element, Element::TransmissionLine(_)))
+    {
+        return Err(Error::UnsupportedAnalysis(
+            "pole-zero analyses do not support transmission lines".to_string(),
+        ));
+    }
+
+    info!("Calculating DC operating point for pole-zero analysis...");
+    let dc_solution = op::solve(circuit, config)?;
+
+    let index_map = &circuit.index_map;
+    let size = index_map.len();
+
+    let output_key = format!("V({})", parameters.output);
+    let output_index = *index_map
+        .get(&output_key)
+        .ok_or_else(|| Error::NodeNotFound(output_key.clone()))?;
+    let excitation = input_excitation(circuit, &parameters.input)?;
+
+    let mut g_stamps = Vec::new();
+    for element in &circuit.elements {
+        g_stamps.extend(element.stamp_conductance_matrix_ac(
+            index_map,
+            &dc_solution,
+            1.0 / (2.0 * PI),
+        ));
+    }
+
+    let mut conductance = Mat::<f64>::zeros(size, size);
+    let mut reactance = Mat::<f64>::zeros(size, size);
+    for Triplet { row, col, val } in sum_triplets(&g_stamps) {
+        conductance[(row, col)] = val.re;
+        reactance[(row, col)] = val.im;
+    }
+
+    let poles = finite_eigenvalues(&conductance, &reactance)?;
+
+    for row in 0..size {
+        conductance[(row, output_index)] = excitation.get(&row).copied().unwrap_or(0.0);
+        reactance[(row, output_index)] = 0.0;
+    }
+    let zeros = finite_eigenvalues(&conductance, &reactance)?;
+
+    info!("Found {} poles and {} zeros.", poles.len(), zeros.len());
+    Ok((poles, zeros))
+}
+
+/// Returns the right-hand side of a unit input on the source `input`, keyed by row.
+///
+/// A voltage source is excited on the row of its branch current. A current source drives its
+/// current from `plus` through itself into `minus`.
+fn input_excitation(circuit: &Circuit, input: &str) -> Result<HashMap<usize, f64>> {
+    let index_map = &circuit.index_map;
+    let node = |name: &str| index_map.get(&format!("V({name})")).copied();
+
+    let element = circuit
+        .elements
+        .iter()
+        .find(|element| element.identifier() == input)
+        .ok_or_else(|| Error::ElementNotFound(input.to_string()))?;
+
+    let mut excitation = HashMap::new();
+    match element {
+        Element::VoltageSource(_) => {
+            excitation.insert(index_map[&format!("I({input})")], 1.0);
+        }
+        Element::CurrentSource(source) => {
+            if let Some(plus) = node(&source.plus) {
+                excitation.insert(plus, -1.0);
+            }
+            if let Some(minus) = node(&source.minus) {
+                excitation.insert(minus, 1.0);
+            }
+        }
+        _ => {
+            return Err(Error::InvalidElementFormat(format!(
+                "Pole-zero input '{input}' must be an independent voltage or current source"
+            )));
+        }
+    }
+    Ok(excitation)
+}
+
+/// Returns the finite values `s` for which `conductance + s * reactance` is singular.
+fn finite_eigenvalues(conductance: &Mat<f64>, reactance: &Mat<f64>) -> Result<Vec<c64>> {
+    if conductance.nrows() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let scale = (0..reactance.nrows())
+        .flat_map(|row| (0..reactance.ncols()).map(move |col| (row, col)))
+        .map(|(row, col)| reactance[(row, col)].abs())
+        .fold(0.0, f64::max);
+
+    // `G v = λ C v` holds for `λ = -s`.
+    let eigen = conductance
+        .generalized_eigen(reactance)
+        .map_err(|_| Error::DecompositionFailed)?;
+    let (s_a, s_b) = (eigen.S_a(), eigen.S_b());
+
+    Ok((0..conductance.nrows())
+        .filter(|&i| s_b[i].norm() > INFINITE_EIGENVALUE_TOLERANCE * scale)
+        .map(|i| -s_a[i] / s_b[i])
+        .collect())
+}
@@ -0,0 +1,76 @@
+use log::info;
+use std::collections::HashMap;
+
+use crate::{config::SolverConfig, mna::MnaMatrix, prelude::*, solver::op};
+use faer::{Mat, prelude::Solve};
+use krets_parser::{analyses::SensitivityAnalysis, circuit::Circuit, elements::Element};
+
+/// Solves for the DC sensitivity of an output node voltage to the value of every resistor and
+/// independent source, keyed by the element identifier.
+///
+/// The circuit is linearized at its DC operating point and the Jacobian `J` is factorized
+/// once. A single adjoint solve `Jᵀ λ = e_out` then gives every sensitivity as
+/// `-λᵀ ∂F/∂p`, where `∂F/∂p` is the derivative of the element stamps with respect to its
+/// value `p`. Sources carry their value on the row of their branch current, so their
+/// sensitivity is the adjoint of that row. A resistor scales its stamp by `1/p`, or by `p`
+/// for a group 2 resistor. Zero-valued resistors have no finite sensitivity and are left out.
+pub fn solve(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    parameters: &SensitivityAnalysis,
+) -> Result<HashMap<String, f64>> {
+    info!("Calculating DC operating point for sensitivity analysis...");
+    let dc_solution = op::solve(circuit, config)?;
+
+    let index_map = &circuit.index_map;
+    let size = index_map.len();
+
+    let output_key = format!("V({})", parameters.output);
+    let output_index = *index_map
+        .get(&output_key)
+        .ok_or_else(|| Error::NodeNotFound(output_key.clone()))?;
+
+    // Capacitors are open circuits at DC, as in the operating point.
+    let elements: Vec<&Element> = circuit
+        .elements
+        .iter()
+        .filter(|e| !matches!(e, Element::Capacitor(_)))
+        .collect();
+    let lu = MnaMatrix::assemble_dc(&elements, index_map, &dc_solution)?.factorize()?;
+
+    let mut e_out = Mat::<f64>::zeros(size, 1);
+    e_out[(output_index, 0)] = 1.0;
+    let adjoint = lu.solve_transpose(&e_out);
+    let adjoint_of = |key: &str| index_map.get(key).map(|&index| adjoint[(index, 0)]);
+    let voltage = |node: &str| dc_solution.get(&format!("V({node})")).copied();
+
+    let mut result = HashMap::new();
+    for element in &elements {
+        let sensitivity = match element {
+            Element::VoltageSource(_) | Element::CurrentSource(_) => {
+                adjoint_of(&format!("I({})", element.identifier()))
+            }
+            Element::Resistor(r) if r.value == 0.0 => None,
+            Element::Resistor(r) if r.g2 => {
+                // The branch row holds `V(plus) - V(minus) - R i = 0`.
+                let key = format!("I({})", r.identifier());
+                let current = dc_solution.get(&key).copied().unwrap_or(0.0);
+                adjoint_of(&key).map(|lambda| lambda * r.resistance() * current / r.value)
+            }
+            Element::Resistor(r) => {
+                // The conductance adds `g (V(plus) - V(minus))` to the plus row and
+                // subtracts it from the minus row.
+                let lambda_plus = adjoint_of(&format!("V({})", r.plus)).unwrap_or(0.0);
+                let lambda_minus = adjoint_of(&format!("V({})", r.minus)).unwrap_or(0.0);
+                let vd = voltage(&r.plus).unwrap_or(0.0) - voltage(&r.minus).unwrap_or(0.0);
+                let g = r.conductance(r.temperature);
+                Some((lambda_plus - lambda_minus) * g * vd / r.value)
+            }
+            _ => None,
+        };
+        if let Some(sensitivity) = sensitivity {
+            result.insert(element.identifier(), sensitivity);
+        }
+    }
+    Ok(result)
+}
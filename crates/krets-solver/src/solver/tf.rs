@@ -0,0 +1,67 @@
+use log::info;
+use std::collections::HashMap;
+
+use crate::{config::SolverConfig, mna::MnaMatrix, prelude::*, solver::op};
+use faer::{Mat, prelude::Solve};
+use krets_parser::{analyses::TfAnalysis, circuit::Circuit, elements::Element};
+
+/// Solves for the small-signal DC transfer function from an input voltage source to an
+/// output node.
+///
+/// The circuit is linearized at its DC operating point and the Jacobian is factorized once.
+/// A unit excitation on the branch row of the input source gives the `gain` at the output
+/// and the current drawn from the source, whose inverse is the `input_resistance`. One extra
+/// solve with a unit current injected into the output node, with all independent sources
+/// zeroed, gives the `output_resistance`.
+pub fn solve(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    parameters: &TfAnalysis,
+) -> Result<HashMap<String, f64>> {
+    info!("Calculating DC operating point for transfer function analysis...");
+    let dc_solution = op::solve(circuit, config)?;
+
+    let index_map = &circuit.index_map;
+    let size = index_map.len();
+
+    let output_key = format!("V({})", parameters.output);
+    let output_index = *index_map
+        .get(&output_key)
+        .ok_or_else(|| Error::NodeNotFound(output_key.clone()))?;
+
+    // Voltage sources carry their value on the row of their branch current.
+    let input_key = format!("I({})", parameters.input_source);
+    let input_index = *index_map
+        .get(&input_key)
+        .ok_or_else(|| Error::ElementNotFound(parameters.input_source.clone()))?;
+
+    // Capacitors are open circuits at DC, as in the operating point.
+    let elements: Vec<&Element> = circuit
+        .elements
+        .iter()
+        .filter(|e| !matches!(e, Element::Capacitor(_)))
+        .collect();
+    let lu = MnaMatrix::assemble_dc(&elements, index_map, &dc_solution)?.factorize()?;
+
+    let unit_solve = |index: usize| {
+        let mut b = Mat::<f64>::zeros(size, 1);
+        b[(index, 0)] = 1.0;
+        lu.solve(&b)
+    };
+
+    let input_response = unit_solve(input_index);
+    let output_response = unit_solve(output_index);
+
+    // The branch current flows into the `plus` terminal of the source, so a source driving
+    // the circuit carries a negative current.
+    let input_current = -input_response[(input_index, 0)];
+
+    let mut result = HashMap::new();
+    result.insert("gain".to_string(), input_response[(output_index, 0)]);
+    result.insert("input_resistance".to_string(), 1.0 / input_current);
+    result.insert(
+        "output_resistance".to_string(),
+        output_response[(output_index, 0)],
+    );
+    Ok(result)
+}
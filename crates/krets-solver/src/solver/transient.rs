@@ -1,114 +1,586 @@
-use log::info;
+use log::{info, warn};
 use std::collections::HashMap;
 
 use super::{convergence_check, sum_triplets};
-use crate::{config::SolverConfig, prelude::*, solver::op, stampable::Stampable};
+use crate::{
+    config::{IntegrationMethod, SolverConfig},
+    delay::DelayLine,
+    events::{CrossingDetector, CrossingEvent},
+    prelude::*,
+    solver::{Progress, op},
+    stampable::Stampable,
+};
 use faer::{
     Mat,
     prelude::Solve,
-    sparse::{SparseColMat, Triplet},
+    sparse::{SparseColMat, Triplet, linalg::solvers::Lu},
 };
-use krets_parser::{analyses::TransientAnalysis, circuit::Circuit};
+use krets_parser::{analyses::TransientAnalysis, circuit::Circuit, elements::Element};
+
+/// Upper bound on how much the adaptive step may grow after an accepted step.
+const MAX_STEP_GROWTH: f64 = 2.0;
+
+/// Lower bound on how much the adaptive step may shrink after a rejected step.
+const MIN_STEP_SHRINK: f64 = 0.25;
+
+/// Safety factor applied to the step size predicted from the truncation error.
+const STEP_SAFETY: f64 = 0.9;
 
-/// Solves for the transient (time-domain) response of a circuit using a fixed time step.
+/// Solves for the transient (time-domain) response of a circuit.
+///
+/// By default the circuit is integrated with the fixed `time_step` of the analysis.
+/// When `config.adaptive_time_step` is set, the step size is instead chosen from an
+/// estimate of the local truncation error, and the returned rows are spaced non-uniformly.
+///
+/// Every time point is collected into the returned vector; see [`solve_with`] to process
+/// long simulations one time point at a time instead.
 pub fn solve(
     circuit: &Circuit,
     config: &SolverConfig,
     tran_analysis: &TransientAnalysis,
 ) -> Result<Vec<HashMap<String, f64>>> {
-    // 1. Solve for the initial DC operating point (t=0).
-    info!("Calculating initial operating point...");
-    let mut initial_op = op::solve(circuit, config)?;
+    let mut all_results = Vec::new();
+    solve_with(circuit, config, tran_analysis, |solution| {
+        all_results.push(solution.clone());
+    })?;
+    Ok(all_results)
+}
+
+/// Solves for the transient response of a circuit like [`solve`], but hands every accepted
+/// time point to `on_step` in order of time, starting with the solution at t=0.
+///
+/// Only the last accepted time points are kept in memory, so the memory use does not grow
+/// with the number of time steps.
+pub fn solve_with<F>(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    tran_analysis: &TransientAnalysis,
+    on_step: F,
+) -> Result<()>
+where
+    F: FnMut(&HashMap<String, f64>),
+{
+    solve_with_progress(
+        circuit,
+        config,
+        tran_analysis,
+        on_step,
+        &mut Progress::default(),
+    )
+}
+
+/// Solves for the transient response like [`solve_with`], reporting the fraction of the
+/// simulated time to `progress`. Once `progress` is cancelled, no further time points are
+/// solved and `Ok` is returned.
+pub fn solve_with_progress<F>(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    tran_analysis: &TransientAnalysis,
+    mut on_step: F,
+    progress: &mut Progress,
+) -> Result<()>
+where
+    F: FnMut(&HashMap<String, f64>),
+{
+    // 1. Find the initial solution (t=0), either from the DC operating point or,
+    // with UIC, directly from the `.ic` node voltages.
+    let mut initial_op = if tran_analysis.use_initial_conditions {
+        info!("Using initial conditions instead of the operating point...");
+        initial_conditions_solution(circuit)?
+    } else {
+        info!("Calculating initial operating point...");
+        op::solve(circuit, config)?
+    };
     initial_op.insert("time".to_string(), 0.0);
-    let index_map = &circuit.index_map;
-    let size = index_map.len();
 
-    // The first result is the DC solution at t=0.
-    let mut all_results = vec![initial_op];
-    let time_step = tran_analysis.time_step;
-    let num_steps = (tran_analysis.stop_time / time_step).round() as usize;
+    on_step(&initial_op);
+    if config.adaptive_time_step {
+        solve_adaptive(
+            circuit,
+            config,
+            tran_analysis,
+            initial_op,
+            &mut on_step,
+            progress,
+        )
+    } else {
+        solve_fixed(
+            circuit,
+            config,
+            tran_analysis,
+            initial_op,
+            &mut on_step,
+            progress,
+        )
+    }
+}
 
-    // Check if the circuit contains any non-linear elements. If not, the solver
-    // only needs to run for one iteration.
-    let has_nonlinear_elements = &circuit
-        .elements
+/// Runs a transient analysis and returns the times at which the signals of `events` cross
+/// their levels, one list per event in the order of `events`.
+///
+/// The crossings are found with a [`CrossingDetector`] as the time points are solved, so the
+/// time points themselves are not kept.
+pub fn find_crossings(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    tran_analysis: &TransientAnalysis,
+    events: &[CrossingEvent],
+) -> Result<Vec<Vec<f64>>> {
+    if let Some(event) = events
         .iter()
-        .any(krets_parser::elements::Element::is_nonlinear);
+        .find(|event| !circuit.index_map.contains_key(&event.signal))
+    {
+        return Err(Error::NodeNotFound(event.signal.clone()));
+    }
+
+    let mut detectors: Vec<CrossingDetector> =
+        events.iter().cloned().map(CrossingDetector::new).collect();
+    solve_with(circuit, config, tran_analysis, |solution| {
+        for detector in &mut detectors {
+            detector.observe(solution);
+        }
+    })?;
+    Ok(detectors
+        .into_iter()
+        .map(CrossingDetector::into_crossings)
+        .collect())
+}
+
+/// Seeds the t=0 solution from the `.ic` node voltages of the circuit and the `ic=` values of
+/// its capacitors and inductors, which take precedence. A capacitor sets its `plus` node
+/// relative to its `minus` node (or `minus` relative to ground), and an inductor sets its
+/// branch current. Nodes without an initial condition and all other branch currents start
+/// at zero.
+fn initial_conditions_solution(circuit: &Circuit) -> Result<HashMap<String, f64>> {
+    let mut solution: HashMap<String, f64> = circuit
+        .index_map
+        .keys()
+        .map(|key| (key.clone(), 0.0))
+        .collect();
+
+    for (node, &value) in &circuit.initial_conditions {
+        let entry = solution
+            .get_mut(node)
+            .ok_or_else(|| Error::NodeNotFound(node.clone()))?;
+        *entry = value;
+    }
+
+    for element in &circuit.elements {
+        match element {
+            Element::Capacitor(c) => {
+                let Some(voltage) = c.initial_condition else {
+                    continue;
+                };
+                let plus = format!("V({})", c.plus);
+                let minus = format!("V({})", c.minus);
+                if solution.contains_key(&plus) {
+                    let v_minus = solution.get(&minus).copied().unwrap_or(0.0);
+                    solution.insert(plus, v_minus + voltage);
+                } else if solution.contains_key(&minus) {
+                    solution.insert(minus, -voltage);
+                }
+            }
+            Element::Inductor(l) => {
+                if let Some(current) = l.initial_condition {
+                    solution.insert(format!("I({})", l.identifier()), current);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(solution)
+}
+
+/// Walks from t=0 to `stop_time` with a fixed time step.
+fn solve_fixed(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    tran_analysis: &TransientAnalysis,
+    initial_op: HashMap<String, f64>,
+    on_step: &mut impl FnMut(&HashMap<String, f64>),
+    progress: &mut Progress,
+) -> Result<()> {
+    let mut prev_solution = initial_op;
+    let mut older_solution: Option<HashMap<String, f64>> = None;
+    let time_step = tran_analysis.time_step;
+    let num_steps = (tran_analysis.stop_time / time_step).round() as usize;
 
     info!(
         "Starting transient analysis from t=0 to t={}s with a {}s time step.",
         tran_analysis.stop_time, time_step
     );
 
+    let mut system = StaticSystem::new(circuit, time_step)?;
+    let mut delay_lines = DelayLine::for_circuit(circuit, &prev_solution);
+    if delay_lines.iter().any(|line| time_step > line.delay()) {
+        warn!(
+            "The time step is longer than the delay of a transmission line, which blurs its response."
+        );
+    }
+
     for step in 1..=num_steps {
+        if progress.is_cancelled() {
+            info!(
+                "Transient analysis cancelled at t={}s.",
+                prev_solution["time"]
+            );
+            break;
+        }
         let current_time = step as f64 * time_step;
-        let prev_solution = all_results.last().unwrap();
-
-        let mut op_result_at_t = HashMap::new();
+        let (companion_step, history) = companion_history(
+            config.integration_method,
+            &prev_solution,
+            older_solution.as_ref().map(|older| (older, time_step)),
+            time_step,
+        );
+        if companion_step != system.time_step {
+            system = StaticSystem::new(circuit, companion_step)?;
+        }
         // Use the solution from the previous time step as the initial guess (a "warm start").
-        let mut previous_nr_guess = prev_solution.clone();
-
-        for iter in 0..config.maximum_iterations {
-            let mut g_stamps = Vec::new();
-            let mut e_stamps = Vec::new();
-
-            // Build the MNA matrices using the discretized, linearized stamps (companion models).
-            for element in &circuit.elements {
-                g_stamps.extend(element.stamp_conductance_matrix_transient(
-                    index_map,
-                    &previous_nr_guess,
-                    prev_solution,
-                    time_step,
-                ));
-                e_stamps.extend(element.stamp_excitation_vector_transient(
-                    index_map,
-                    &previous_nr_guess,
-                    prev_solution,
-                    time_step,
-                ));
-            }
+        let op_result_at_t = solve_time_point(
+            circuit,
+            config,
+            &system,
+            &delay_lines,
+            &history,
+            prev_solution.clone(),
+            current_time,
+        )?;
+        for line in &mut delay_lines {
+            line.accept(&op_result_at_t);
+        }
+        on_step(&op_result_at_t);
+        progress.report(step as f64 / num_steps as f64);
+        older_solution = Some(std::mem::replace(&mut prev_solution, op_result_at_t));
+    }
+    Ok(())
+}
 
-            let g_stamps_summed = sum_triplets(&g_stamps);
-            let e_stamps_summed = sum_triplets(&e_stamps);
+/// Returns the step and previous solution to hand to the backward Euler companion models of
+/// capacitors and inductors so that they integrate a step of size `h` with `method`.
+///
+/// Gear2 (BDF2) discretizes `dx/dt` at the new point as
+/// `(a0 * x_n - (1 + w) * x_n-1 + w² / (1 + w) * x_n-2) / h`, with `a0 = (1 + 2w) / (1 + w)`
+/// and `w = h / h_prev`. This is backward Euler with the step `h / a0` from a history point
+/// blended from the previous two points, so the companion models need no knowledge of the
+/// method. Without an `older` point, backward Euler is used to bootstrap the first step.
+fn companion_history(
+    method: IntegrationMethod,
+    prev_solution: &HashMap<String, f64>,
+    older: Option<(&HashMap<String, f64>, f64)>,
+    h: f64,
+) -> (f64, HashMap<String, f64>) {
+    match (method, older) {
+        (IntegrationMethod::Gear2, Some((older_solution, h_prev))) => {
+            let w = h / h_prev;
+            let a0 = (1.0 + 2.0 * w) / (1.0 + w);
+            let history = prev_solution
+                .iter()
+                .map(|(name, &x_n)| {
+                    if name == "time" {
+                        return (name.clone(), x_n);
+                    }
+                    let x_nm1 = older_solution.get(name).copied().unwrap_or(0.0);
+                    let blended = ((1.0 + w) * x_n - w * w / (1.0 + w) * x_nm1) / a0;
+                    (name.clone(), blended)
+                })
+                .collect();
+            (h / a0, history)
+        }
+        _ => (h, prev_solution.clone()),
+    }
+}
 
-            let lu = SparseColMat::try_new_from_triplets(size, size, &g_stamps_summed)
-                .map_err(|e| Error::Unexpected(e.to_string()))?
-                .sp_lu()
-                .map_err(|_| Error::DecompositionFailed)?;
+/// Walks from t=0 to `stop_time`, adapting the time step to the local truncation error.
+///
+/// After each step the backward Euler solution (the corrector) is compared against a
+/// linear extrapolation of the previous two points (the predictor). Their difference
+/// estimates the local truncation error, which decides whether the step is accepted and
+/// how large the next step should be.
+fn solve_adaptive(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    tran_analysis: &TransientAnalysis,
+    initial_op: HashMap<String, f64>,
+    on_step: &mut impl FnMut(&HashMap<String, f64>),
+    progress: &mut Progress,
+) -> Result<()> {
+    let stop_time = tran_analysis.stop_time;
+    // Without an explicit limit, keep at least 50 points across the simulation like SPICE does.
+    // A step may not skip over the delay of a transmission line, whose far port only sees
+    // the waves of accepted time points.
+    let mut delay_lines = DelayLine::for_circuit(circuit, &initial_op);
+    let max_step = delay_lines
+        .iter()
+        .map(DelayLine::delay)
+        .fold(config.max_step.min(stop_time / 50.0), f64::min);
+    let min_step = config.min_step;
 
-            let mut b = Mat::zeros(size, 1);
-            for &Triplet { row, col, val } in &e_stamps_summed {
-                b[(row, col)] = val;
-            }
-            let x = lu.solve(&b);
+    // The last two accepted points, which the truncation error predictor is built from.
+    let mut prev_solution = initial_op;
+    let mut older_solution: Option<HashMap<String, f64>> = None;
+    let mut time = 0.0;
+    let mut h = tran_analysis.time_step.clamp(min_step, max_step);
+    let mut h_prev: Option<f64> = None;
+
+    info!(
+        "Starting adaptive transient analysis from t=0 to t={stop_time}s with steps in [{min_step}, {max_step}]s."
+    );
 
-            // #[cfg(debug_assertions)]
-            // {
-            //     print_system(&g_stamps_summed, &b, &x, index_map);
-            // }
+    let mut system = StaticSystem::new(circuit, h)?;
 
-            op_result_at_t = index_map
-                .iter()
-                .map(|(node, &idx)| (node.clone(), x[(idx, 0)]))
-                .collect();
+    while time < stop_time {
+        if progress.is_cancelled() {
+            info!("Transient analysis cancelled at t={time}s.");
+            break;
+        }
+        // Land exactly on the stop time instead of overshooting it.
+        let step = h.min(stop_time - time);
+        let next_time = time + step;
+        let (companion_step, history) = companion_history(
+            config.integration_method,
+            &prev_solution,
+            older_solution.as_ref().zip(h_prev),
+            step,
+        );
+        if companion_step != system.time_step {
+            system = StaticSystem::new(circuit, companion_step)?;
+        }
 
-            op_result_at_t.insert("time".to_string(), current_time);
+        // Warm start from the previous point, but evaluate time-dependent sources at the
+        // new time point from the first iteration on, so the truncation error estimate
+        // is not polluted by a source that lags one step behind.
+        let mut initial_guess = prev_solution.clone();
+        initial_guess.insert("time".to_string(), next_time);
 
-            // For purely linear circuits, we only need one iteration.
-            if !has_nonlinear_elements {
-                break;
+        let corrected = match solve_time_point(
+            circuit,
+            config,
+            &system,
+            &delay_lines,
+            &history,
+            initial_guess,
+            next_time,
+        ) {
+            Ok(solution) => solution,
+            Err(Error::ConvergenceFailed { .. } | Error::DecompositionFailed)
+                if step > min_step =>
+            {
+                // Newton-Raphson did not converge; retry with a much smaller step.
+                h = (step * MIN_STEP_SHRINK).max(min_step);
+                continue;
             }
+            Err(e) => return Err(e),
+        };
 
-            if convergence_check(&previous_nr_guess, &op_result_at_t, config) {
-                break; // Newton-Raphson converged for this time step.
-            }
-            previous_nr_guess.clone_from(&op_result_at_t);
-            if iter == config.maximum_iterations - 1 {
-                return Err(Error::MaximumIterationsExceeded(config.maximum_iterations));
+        let error_ratio = truncation_error_ratio(
+            config,
+            &prev_solution,
+            older_solution.as_ref(),
+            &corrected,
+            step,
+            h_prev,
+        );
+
+        // Scale the step so the next error lands just inside the tolerance. Backward Euler
+        // is first order, so the local error grows with the square of the step.
+        let scale = if error_ratio > 0.0 {
+            (STEP_SAFETY / error_ratio.sqrt()).clamp(MIN_STEP_SHRINK, MAX_STEP_GROWTH)
+        } else {
+            MAX_STEP_GROWTH
+        };
+
+        if error_ratio > 1.0 && step > min_step {
+            // Reject the step and retry from the same point.
+            h = (step * scale).max(min_step);
+            continue;
+        }
+        if error_ratio > 1.0 {
+            return Err(Error::TimeStepTooSmall(time));
+        }
+
+        time = next_time;
+        h_prev = Some(step);
+        h = (step * scale).clamp(min_step, max_step);
+        for line in &mut delay_lines {
+            line.accept(&corrected);
+        }
+        on_step(&corrected);
+        progress.report(time / stop_time);
+        older_solution = Some(std::mem::replace(&mut prev_solution, corrected));
+    }
+
+    Ok(())
+}
+
+/// Returns the largest local truncation error across the node voltages, relative to the
+/// tolerance `reltol * |V| + abstol`. Values above 1 mean the step should be rejected.
+///
+/// `current` is the last accepted point and `previous` the one before it, if any.
+fn truncation_error_ratio(
+    config: &SolverConfig,
+    current: &HashMap<String, f64>,
+    previous: Option<&HashMap<String, f64>>,
+    corrected: &HashMap<String, f64>,
+    h: f64,
+    h_prev: Option<f64>,
+) -> f64 {
+    corrected
+        .iter()
+        .filter(|(name, _)| name.starts_with("V("))
+        .map(|(name, &value)| {
+            let x_n = current.get(name).copied().unwrap_or(0.0);
+            let lte = match (previous, h_prev) {
+                (Some(previous), Some(h_prev)) => {
+                    // Linear predictor through the last two accepted points.
+                    let x_nm1 = previous.get(name).copied().unwrap_or(0.0);
+                    let predicted = x_n + (h / h_prev) * (x_n - x_nm1);
+                    (h / (h + h_prev)) * (value - predicted)
+                }
+                // Only a single point is known, so fall back to a constant predictor.
+                _ => 0.5 * (value - x_n),
+            };
+            let tolerance = config.reltol * value.abs().max(x_n.abs()) + config.abstol;
+            lte.abs() / tolerance
+        })
+        .fold(0.0, f64::max)
+}
+
+/// The part of the companion-model system that does not change between time points of
+/// equal companion step size.
+///
+/// The conductance stamps of linear elements only depend on the time step, so they are
+/// assembled once. For a linear circuit this is the whole conductance matrix, which is
+/// then factorized once and every time point only rebuilds the excitation vector.
+struct StaticSystem {
+    /// The time step the companion models were discretized with, see [`companion_history`].
+    time_step: f64,
+    /// Summed conductance stamps of the linear elements.
+    conductance: Vec<Triplet<usize, usize, f64>>,
+    /// Factorization of the conductance matrix, if the circuit has no nonlinear elements.
+    lu: Option<Lu<usize, f64>>,
+}
+
+impl StaticSystem {
+    fn new(circuit: &Circuit, time_step: f64) -> Result<Self> {
+        let index_map = &circuit.index_map;
+        let size = index_map.len();
+        let empty = HashMap::new();
+
+        let mut g_stamps = Vec::new();
+        for element in circuit.elements.iter().filter(|e| !e.is_nonlinear()) {
+            g_stamps.extend(
+                element.stamp_conductance_matrix_transient(index_map, &empty, &empty, time_step),
+            );
+        }
+        let conductance = sum_triplets(&g_stamps);
+
+        let lu = if circuit.elements.iter().any(Element::is_nonlinear) {
+            None
+        } else {
+            Some(
+                SparseColMat::try_new_from_triplets(size, size, &conductance)
+                    .map_err(|e| Error::Unexpected(e.to_string()))?
+                    .sp_lu()
+                    .map_err(|_| Error::DecompositionFailed)?,
+            )
+        };
+
+        Ok(Self {
+            time_step,
+            conductance,
+            lu,
+        })
+    }
+}
+
+/// Solves the companion-model MNA system at `time`, given the history point of the companion
+/// models (the accepted solution of the previous time point for backward Euler), the static
+/// part of the system for the companion step, the histories of the transmission lines and the
+/// first Newton-Raphson guess.
+fn solve_time_point(
+    circuit: &Circuit,
+    config: &SolverConfig,
+    system: &StaticSystem,
+    delay_lines: &[DelayLine],
+    prev_solution: &HashMap<String, f64>,
+    initial_guess: HashMap<String, f64>,
+    time: f64,
+) -> Result<HashMap<String, f64>> {
+    let index_map = &circuit.index_map;
+    let size = index_map.len();
+    let h = system.time_step;
+
+    let mut op_result_at_t = HashMap::new();
+    let mut previous_nr_guess = initial_guess;
+    // The waves arriving at transmission lines were launched at earlier time points.
+    let incident_waves: Vec<_> = delay_lines
+        .iter()
+        .flat_map(|line| line.stamp(index_map, time))
+        .collect();
+
+    for iter in 0..config.maximum_iterations {
+        let mut e_stamps = incident_waves.clone();
+        for element in &circuit.elements {
+            e_stamps.extend(element.stamp_excitation_vector_transient(
+                index_map,
+                &previous_nr_guess,
+                prev_solution,
+                h,
+            ));
+        }
+        let e_stamps_summed = sum_triplets(&e_stamps);
+
+        let mut b = Mat::zeros(size, 1);
+        for &Triplet { row, col, val } in &e_stamps_summed {
+            b[(row, col)] = val;
+        }
+
+        let x = match &system.lu {
+            Some(lu) => lu.solve(&b),
+            None => {
+                // Only the nonlinear elements need to be relinearized every iteration.
+                let mut g_stamps = system.conductance.clone();
+                for element in circuit.nonlinear_elements() {
+                    g_stamps.extend(element.stamp_conductance_matrix_transient(
+                        index_map,
+                        &previous_nr_guess,
+                        prev_solution,
+                        h,
+                    ));
+                }
+                let g_stamps_summed = sum_triplets(&g_stamps);
+
+                SparseColMat::try_new_from_triplets(size, size, &g_stamps_summed)
+                    .map_err(|e| Error::Unexpected(e.to_string()))?
+                    .sp_lu()
+                    .map_err(|_| Error::DecompositionFailed)?
+                    .solve(&b)
             }
+        };
+
+        op_result_at_t = index_map
+            .iter()
+            .map(|(node, &idx)| (node.clone(), x[(idx, 0)]))
+            .collect();
+
+        op_result_at_t.insert("time".to_string(), time);
+
+        // For purely linear circuits, we only need one iteration.
+        if system.lu.is_some() {
+            break;
         }
 
-        all_results.push(op_result_at_t);
+        let convergence = convergence_check(&previous_nr_guess, &op_result_at_t, config);
+        if convergence.converged {
+            break; // Newton-Raphson converged for this time step.
+        }
+        previous_nr_guess.clone_from(&op_result_at_t);
+        if iter == config.maximum_iterations - 1 {
+            return Err(convergence.into_error(config.maximum_iterations));
+        }
     }
-    Ok(all_results)
+
+    Ok(op_result_at_t)
 }
@@ -1,8 +1,9 @@
 use crate::prelude::*;
 use krets_parser::elements::{
-    Element, bjt::BJT, capacitor::Capacitor, current_source::CurrentSource, diode::Diode,
-    inductor::Inductor, nmosfet::NMOSFET, resistor::Resistor, subcircuit::SubcircuitInstance,
-    voltage_source::VoltageSource,
+    Element, bjt::BJT, capacitor::Capacitor, cccs::CCCS, ccvs::CCVS, current_source::CurrentSource,
+    diode::Diode, inductor::Inductor, mutual_inductance::MutualInductance, nmosfet::NMOSFET,
+    pmosfet::PMOSFET, resistor::Resistor, subcircuit::SubcircuitInstance, switch::Switch,
+    transmission_line::TransmissionLine, vccs::VCCS, vcvs::VCVS, voltage_source::VoltageSource,
 };
 
 /// A macro to forward a method call to the correct inner element struct.
@@ -15,9 +16,17 @@ macro_rules! dispatch {
             Element::Resistor(e) => e.$method($($args),*),
             Element::Capacitor(e) => e.$method($($args),*),
             Element::Inductor(e) => e.$method($($args),*),
+            Element::MutualInductance(e) => e.$method($($args),*),
             Element::Diode(e) => e.$method($($args),*),
             Element::BJT(e) => e.$method($($args),*),
             Element::NMOSFET(e) => e.$method($($args),*),
+            Element::PMOSFET(e) => e.$method($($args),*),
+            Element::VCVS(e) => e.$method($($args),*),
+            Element::VCCS(e) => e.$method($($args),*),
+            Element::CCVS(e) => e.$method($($args),*),
+            Element::CCCS(e) => e.$method($($args),*),
+            Element::Switch(e) => e.$method($($args),*),
+            Element::TransmissionLine(e) => e.$method($($args),*),
             Element::SubcktInstance(e) => e.$method($($args),*),
         }
     };
@@ -30,6 +39,11 @@ macro_rules! dispatch {
 /// for DC, AC, and transient analyses. These methods are called during circuit simulation to assemble the system equations.
 ///
 /// The default implementations for transient stamps assume resistive behavior, using the DC stamp.
+///
+/// A positive branch current `I(element)` of a group 2 element flows from `plus` through the
+/// element to `minus`. It leaves the `plus` node and enters the `minus` node, so its column holds
+/// +1 in the KCL row of `plus` and -1 in that of `minus`. A voltage source delivering power thus
+/// has a negative branch current. Debug builds check this for every DC and transient stamp.
 pub trait Stampable {
     /// Adds the DC conductance matrix stamp for this element.
     ///
@@ -134,6 +148,28 @@ pub trait Stampable {
     ) -> Vec<Triplet<usize, usize, f64>> {
         self.stamp_excitation_vector_dc(index_map, solution_map)
     }
+
+    /// Adds the noise current stamp for this element.
+    ///
+    /// The equivalent noise current source of the element is stamped like a conductance
+    /// between its nodes, with its power spectral density in A²/Hz in place of the
+    /// conductance. Summed over all elements, this gives the spectral density matrix of the
+    /// noise currents injected into the nodes. It is kept apart from the deterministic stamps,
+    /// so by default an element is noiseless.
+    ///
+    /// # Arguments
+    /// * `index_map` - Maps node/branch identifiers to matrix indices.
+    /// * `temperature` - The temperature of the noise sources in Kelvin.
+    ///
+    /// # Returns
+    /// A vector of triplets representing non-zero entries in the noise spectral density matrix.
+    fn stamp_noise_current(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _temperature: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        vec![]
+    }
 }
 
 impl Stampable for Element {
@@ -142,7 +178,11 @@ impl Stampable for Element {
         index_map: &HashMap<String, usize>,
         solution_map: &HashMap<String, f64>,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        dispatch!(self, stamp_conductance_matrix_dc(index_map, solution_map))
+        let triplets = dispatch!(self, stamp_conductance_matrix_dc(index_map, solution_map));
+        if cfg!(debug_assertions) {
+            check_branch_current_convention(self, index_map, &triplets);
+        }
+        triplets
     }
     fn stamp_excitation_vector_dc(
         &self,
@@ -180,10 +220,14 @@ impl Stampable for Element {
         prev_solution: &HashMap<String, f64>,
         time_step: f64,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        dispatch!(
+        let triplets = dispatch!(
             self,
             stamp_conductance_matrix_transient(index_map, solution_map, prev_solution, time_step)
-        )
+        );
+        if cfg!(debug_assertions) {
+            check_branch_current_convention(self, index_map, &triplets);
+        }
+        triplets
     }
     fn stamp_excitation_vector_transient(
         &self,
@@ -197,6 +241,58 @@ impl Stampable for Element {
             stamp_excitation_vector_transient(index_map, solution_map, prev_solution, time_step)
         )
     }
+
+    fn stamp_noise_current(
+        &self,
+        index_map: &HashMap<String, usize>,
+        temperature: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        dispatch!(self, stamp_noise_current(index_map, temperature))
+    }
+}
+
+/// Asserts that the stamp of a group 2 element couples its branch current into the KCL rows of
+/// its terminals following the sign convention of [`Stampable`].
+///
+/// Capacitors are skipped, as their stamps never use the branch current.
+fn check_branch_current_convention(
+    element: &Element,
+    index_map: &HashMap<String, usize>,
+    triplets: &[Triplet<usize, usize, f64>],
+) {
+    if !element.is_g2() || matches!(element, Element::Capacitor(_)) {
+        return;
+    }
+    let Some(&index_current) = index_map.get(&format!("I({})", element.identifier())) else {
+        return;
+    };
+    let nodes = element.nodes();
+    if nodes[0] == nodes[1] {
+        return;
+    }
+    // The far port of a transmission line also couples the branch current into its nodes.
+    if matches!(element, Element::TransmissionLine(_))
+        && (nodes[2..].contains(&nodes[0]) || nodes[2..].contains(&nodes[1]))
+    {
+        return;
+    }
+
+    for (node, expected) in [(nodes[0], 1.0), (nodes[1], -1.0)] {
+        let Some(&index_node) = index_map.get(&format!("V({node})")) else {
+            continue;
+        };
+        let coupling: f64 = triplets
+            .iter()
+            .filter(|t| t.row == index_node && t.col == index_current)
+            .map(|t| t.val)
+            .sum();
+        debug_assert_eq!(
+            coupling,
+            expected,
+            "branch current of {} enters the KCL row of {node} with the wrong sign",
+            element.identifier()
+        );
+    }
 }
 
 impl Stampable for Resistor {
@@ -224,12 +320,16 @@ impl Stampable for Resistor {
             }
 
             if let Some(&index_current) = index_current {
-                triplets.push(Triplet::new(index_current, index_current, -self.value));
+                triplets.push(Triplet::new(
+                    index_current,
+                    index_current,
+                    -self.resistance(),
+                ));
             }
         } else {
             triplets = Vec::with_capacity(4);
 
-            let g = 1.0 / self.value;
+            let g = self.conductance(self.temperature);
             if let Some(&ip) = index_plus {
                 triplets.push(Triplet::new(ip, ip, g));
             }
@@ -274,12 +374,12 @@ impl Stampable for Resistor {
                 triplets.push(Triplet::new(
                     index_current,
                     index_current,
-                    -c64::new(self.value, 0.0),
+                    -c64::new(self.resistance(), 0.0),
                 ));
             }
         } else {
             triplets = Vec::with_capacity(4);
-            let g = c64::new(1.0 / self.value, 0.0);
+            let g = c64::new(self.conductance(self.temperature), 0.0);
             if let Some(&ip) = index_plus {
                 triplets.push(Triplet::new(ip, ip, g));
             }
@@ -314,26 +414,109 @@ impl Stampable for Resistor {
         // A resistor is a passive component and does not add to the excitation vector.
         Vec::new()
     }
+
+    fn stamp_noise_current(
+        &self,
+        index_map: &HashMap<String, usize>,
+        temperature: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let index_plus = index_map.get(&format!("V({})", self.plus));
+        let index_minus = index_map.get(&format!("V({})", self.minus));
+        let psd = self.thermal_noise_psd(temperature);
+
+        let mut triplets = Vec::with_capacity(4);
+        if let Some(&index_plus) = index_plus {
+            triplets.push(Triplet::new(index_plus, index_plus, psd));
+        }
+        if let Some(&index_minus) = index_minus {
+            triplets.push(Triplet::new(index_minus, index_minus, psd));
+        }
+        if let (Some(&index_plus), Some(&index_minus)) = (index_plus, index_minus) {
+            triplets.push(Triplet::new(index_plus, index_minus, -psd));
+            triplets.push(Triplet::new(index_minus, index_plus, -psd));
+        }
+        triplets
+    }
 }
 
 impl Stampable for BJT {
-    // --- Stamping methods remain unchanged ---
     fn stamp_conductance_matrix_dc(
         &self,
-        _index_map: &HashMap<String, usize>,
-        _solution_map: &HashMap<String, f64>,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        // TODO: Implement BJT DC conductance stamp
-        todo!()
+        let op = self.operating_point(solution_map);
+
+        let index_c = index_map.get(&format!("V({})", self.collector));
+        let index_b = index_map.get(&format!("V({})", self.base));
+        let index_e = index_map.get(&format!("V({})", self.emitter));
+
+        let mut triplets = Vec::with_capacity(16);
+
+        // The hybrid-pi companion model: g_pi (b-e), g_mu (b-c), g_o (c-e) and a
+        // transconductance g_m * v_be driving current from collector to emitter.
+        // The conductances are polarity independent, since both the junction voltages
+        // and the currents flip sign for a PNP device.
+        for (a, b, g) in [
+            (index_b, index_e, op.g_pi),
+            (index_b, index_c, op.g_mu),
+            (index_c, index_e, op.g_o),
+        ] {
+            if let Some(&ia) = a {
+                triplets.push(Triplet::new(ia, ia, g));
+            }
+            if let Some(&ib) = b {
+                triplets.push(Triplet::new(ib, ib, g));
+            }
+            if let (Some(&ia), Some(&ib)) = (a, b) {
+                triplets.push(Triplet::new(ia, ib, -g));
+                triplets.push(Triplet::new(ib, ia, -g));
+            }
+        }
+
+        if let (Some(&ic), Some(&ib)) = (index_c, index_b) {
+            triplets.push(Triplet::new(ic, ib, op.g_m));
+        }
+        if let (Some(&ic), Some(&ie)) = (index_c, index_e) {
+            triplets.push(Triplet::new(ic, ie, -op.g_m));
+        }
+        if let (Some(&ie), Some(&ib)) = (index_e, index_b) {
+            triplets.push(Triplet::new(ie, ib, -op.g_m));
+        }
+        if let Some(&ie) = index_e {
+            triplets.push(Triplet::new(ie, ie, op.g_m));
+        }
+
+        triplets
     }
 
     fn stamp_excitation_vector_dc(
         &self,
-        _index_map: &HashMap<String, usize>,
-        _solution_map: &HashMap<String, f64>,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        // TODO: Implement BJT DC excitation stamp
-        todo!()
+        let op = self.operating_point(solution_map);
+        let p = self.polarity();
+        let v_ce = op.v_be - op.v_bc;
+
+        // Equivalent currents: the part of the terminal currents not captured by the
+        // linearized conductances at the current bias point.
+        let i_c_eq = p * (op.i_c - (op.g_m * op.v_be + op.g_o * v_ce - op.g_mu * op.v_bc));
+        let i_b_eq = p * (op.i_b - (op.g_pi * op.v_be + op.g_mu * op.v_bc));
+
+        let mut triplets = Vec::with_capacity(3);
+
+        if let Some(&ic) = index_map.get(&format!("V({})", self.collector)) {
+            triplets.push(Triplet::new(ic, 0, -i_c_eq));
+        }
+        if let Some(&ib) = index_map.get(&format!("V({})", self.base)) {
+            triplets.push(Triplet::new(ib, 0, -i_b_eq));
+        }
+        if let Some(&ie) = index_map.get(&format!("V({})", self.emitter)) {
+            triplets.push(Triplet::new(ie, 0, i_c_eq + i_b_eq));
+        }
+
+        triplets
     }
 
     fn stamp_excitation_vector_ac(
@@ -348,12 +531,54 @@ impl Stampable for BJT {
 
     fn stamp_conductance_matrix_ac(
         &self,
-        _index_map: &HashMap<String, usize>,
-        _solution_map: &HashMap<String, f64>,
-        _frequency: f64,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+        frequency: f64,
     ) -> Vec<Triplet<usize, usize, faer::c64>> {
-        // TODO: Implement BJT AC conductance stamp (small-signal model)
-        todo!()
+        // The hybrid-pi model linearized at the DC operating point in `solution_map`, with
+        // the junction capacitances in parallel to g_pi and g_mu.
+        let op = self.operating_point(solution_map);
+        let (c_pi, c_mu) = self.capacitances(&op);
+        let omega = 2.0 * PI * frequency;
+
+        let index_c = index_map.get(&format!("V({})", self.collector));
+        let index_b = index_map.get(&format!("V({})", self.base));
+        let index_e = index_map.get(&format!("V({})", self.emitter));
+
+        let mut triplets = Vec::with_capacity(16);
+
+        for (a, b, y) in [
+            (index_b, index_e, c64::new(op.g_pi, omega * c_pi)),
+            (index_b, index_c, c64::new(op.g_mu, omega * c_mu)),
+            (index_c, index_e, c64::new(op.g_o, 0.0)),
+        ] {
+            if let Some(&ia) = a {
+                triplets.push(Triplet::new(ia, ia, y));
+            }
+            if let Some(&ib) = b {
+                triplets.push(Triplet::new(ib, ib, y));
+            }
+            if let (Some(&ia), Some(&ib)) = (a, b) {
+                triplets.push(Triplet::new(ia, ib, -y));
+                triplets.push(Triplet::new(ib, ia, -y));
+            }
+        }
+
+        let g_m = c64::new(op.g_m, 0.0);
+        if let (Some(&ic), Some(&ib)) = (index_c, index_b) {
+            triplets.push(Triplet::new(ic, ib, g_m));
+        }
+        if let (Some(&ic), Some(&ie)) = (index_c, index_e) {
+            triplets.push(Triplet::new(ic, ie, -g_m));
+        }
+        if let (Some(&ie), Some(&ib)) = (index_e, index_b) {
+            triplets.push(Triplet::new(ie, ib, -g_m));
+        }
+        if let Some(&ie) = index_e {
+            triplets.push(Triplet::new(ie, ie, g_m));
+        }
+
+        triplets
     }
 }
 
@@ -378,7 +603,7 @@ impl Stampable for Capacitor {
 
         let admittance = c64 {
             re: 0.0,
-            im: 2.0 * PI * frequency * self.value,
+            im: 2.0 * PI * frequency * self.capacitance(),
         };
 
         let mut triplets = Vec::with_capacity(4);
@@ -446,7 +671,7 @@ impl Stampable for Capacitor {
         _prev_solution: &HashMap<String, f64>, // Not needed for a linear capacitor's conductance
         h: f64,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        let g = self.value / h;
+        let g = self.capacitance() / h;
 
         let index_plus = index_map.get(&format!("V({})", self.plus));
         let index_minus = index_map.get(&format!("V({})", self.minus));
@@ -490,7 +715,7 @@ impl Stampable for Capacitor {
         let v_prev = v_plus_prev - v_minus_prev;
 
         // Calculate the equivalent current source value: I_eq = (C/h) * v_prev
-        let i_eq = -(self.value / h) * v_prev;
+        let i_eq = -(self.capacitance() / h) * v_prev;
 
         let mut triplets = Vec::with_capacity(2);
 
@@ -535,13 +760,19 @@ impl Stampable for CurrentSource {
         _solution_map: &HashMap<String, f64>,
         _frequency: f64,
     ) -> Vec<Triplet<usize, usize, faer::c64>> {
-        // FIX: Implemented the AC stamp, which is identical to the DC stamp for a
-        // frequency-independent current source.
+        // The AC stamp is identical to the DC stamp for a frequency-independent current source.
         let index_plus = index_map.get(&format!("V({})", self.plus));
         let index_minus = index_map.get(&format!("V({})", self.minus));
         let index_current = index_map.get(&format!("I({})", self.identifier()));
 
         let mut triplets = Vec::with_capacity(3);
+        if let Some(&index_current) = index_current {
+            triplets.push(Triplet::new(
+                index_current,
+                index_current,
+                c64::new(1.0, 0.0),
+            ));
+        }
 
         if let (Some(&index_plus), Some(&index_current)) = (index_plus, index_current) {
             triplets.push(Triplet::new(index_plus, index_current, c64::new(1.0, 0.0)));
@@ -575,10 +806,27 @@ impl Stampable for CurrentSource {
         _solution_map: &HashMap<String, f64>,
         _frequency: f64,
     ) -> Vec<Triplet<usize, usize, faer::c64>> {
-        // FIX: Implemented the AC excitation stamp. For a simple source, this is a
-        // real value, but it's represented as a complex number.
+        // Only the AC specification excites the branch current; the DC value sets the bias.
+        match index_map.get(&format!("I({})", self.identifier())) {
+            Some(&i) => vec![Triplet::new(
+                i,
+                0,
+                c64::from_polar(self.ac_amplitude, self.ac_phase.to_radians()),
+            )],
+            None => Vec::new(),
+        }
+    }
+
+    fn stamp_excitation_vector_transient(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+        _prev_solution: &HashMap<String, f64>,
+        _time_step: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let current_time = solution_map.get("time").cloned().unwrap_or(0.0);
         match index_map.get(&format!("I({})", self.identifier())) {
-            Some(i) => vec![Triplet::new(*i, 0, c64::new(self.value, 0.0))],
+            Some(i) => vec![Triplet::new(*i, 0, self.transient_value_at(current_time))],
             None => Vec::new(),
         }
     }
@@ -615,12 +863,13 @@ impl Stampable for Diode {
         &self,
         index_map: &HashMap<String, usize>,
         solution_map: &HashMap<String, f64>,
-        _frequency: f64,
+        frequency: f64,
     ) -> Vec<Triplet<usize, usize, faer::c64>> {
-        // FIX: Implemented AC stamp. For a diode, the small-signal AC conductance
-        // at a given DC bias point is the same as its linearized DC conductance.
+        // The small-signal admittance at the DC bias point is the linearized DC
+        // conductance in parallel with the junction and diffusion capacitance.
         let conductance = self.conductance(solution_map);
-        let conductance_complex = c64::new(conductance, 0.0);
+        let susceptance = 2.0 * PI * frequency * self.capacitance(solution_map);
+        let conductance_complex = c64::new(conductance, susceptance);
 
         let index_plus = index_map.get(&format!("V({})", self.plus));
         let index_minus = index_map.get(&format!("V({})", self.minus));
@@ -671,6 +920,67 @@ impl Stampable for Diode {
         // contribute to the excitation vector in small-signal AC analysis.
         vec![]
     }
+
+    fn stamp_conductance_matrix_transient(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+        _prev_solution: &HashMap<String, f64>,
+        h: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // The linearized junction conductance in parallel with the Backward Euler
+        // companion conductance of the stored charge, C(Vd) / h.
+        let g = self.conductance(solution_map) + self.capacitance(solution_map) / h;
+
+        let index_plus = index_map.get(&format!("V({})", self.plus));
+        let index_minus = index_map.get(&format!("V({})", self.minus));
+
+        let mut triplets = Vec::with_capacity(4);
+
+        if let Some(&ip) = index_plus {
+            triplets.push(Triplet::new(ip, ip, g));
+        }
+        if let Some(&im) = index_minus {
+            triplets.push(Triplet::new(im, im, g));
+        }
+        if let (Some(&ip), Some(&im)) = (index_plus, index_minus) {
+            triplets.push(Triplet::new(ip, im, -g));
+            triplets.push(Triplet::new(im, ip, -g));
+        }
+
+        triplets
+    }
+
+    fn stamp_excitation_vector_transient(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+        prev_solution: &HashMap<String, f64>,
+        h: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let index_plus = index_map.get(&format!("V({})", self.plus));
+        let index_minus = index_map.get(&format!("V({})", self.minus));
+
+        // The charging current (Q(Vd) - Q_prev) / h, linearized around the current
+        // iterate Vd_k, leaves (Q(Vd_k) - Q_prev - C(Vd_k) * Vd_k) / h as a source.
+        let v_d = self.v_d(solution_map);
+        let charge_current = (self.charge(solution_map)
+            - self.charge(prev_solution)
+            - self.capacitance(solution_map) * v_d)
+            / h;
+        let i_eq = self.equivalent_current(solution_map) + charge_current;
+
+        let mut triplets = Vec::with_capacity(2);
+
+        if let Some(&ip) = index_plus {
+            triplets.push(Triplet::new(ip, 0, -i_eq));
+        }
+        if let Some(&im) = index_minus {
+            triplets.push(Triplet::new(im, 0, i_eq));
+        }
+
+        triplets
+    }
 }
 
 impl Stampable for Inductor {
@@ -700,6 +1010,13 @@ impl Stampable for Inductor {
             triplets.push(Triplet::new(index_current, index_minus, -1.0));
         }
 
+        // The branch equation reads V(plus) - V(minus) - esr * I = 0.
+        if let Some(&index_current) = index_current
+            && self.esr != 0.0
+        {
+            triplets.push(Triplet::new(index_current, index_current, -self.esr));
+        }
+
         triplets
     }
 
@@ -712,7 +1029,7 @@ impl Stampable for Inductor {
         let index_plus = index_map.get(&format!("V({})", self.plus));
         let index_minus = index_map.get(&format!("V({})", self.minus));
         let index_current = index_map.get(&format!("I({})", self.identifier()));
-        let impedance = c64::new(0.0, 2.0 * PI * frequency * self.value);
+        let impedance = c64::new(self.esr, 2.0 * PI * frequency * self.inductance());
         let mut triplets = Vec::with_capacity(5);
 
         if let (Some(&index_plus), Some(&index_current)) = (index_plus, index_current) {
@@ -778,7 +1095,7 @@ impl Stampable for Inductor {
         let mut triplets = Vec::with_capacity(5);
 
         if let Some(&ic) = index_current {
-            triplets.push(Triplet::new(ic, ic, -self.value / h));
+            triplets.push(Triplet::new(ic, ic, -self.inductance() / h - self.esr));
         }
 
         if let (Some(&ip), Some(&ic)) = (index_plus, index_current) {
@@ -809,125 +1126,31 @@ impl Stampable for Inductor {
             .unwrap();
 
         if let Some(&ic) = index_current {
-            vec![Triplet::new(ic, 0, -(self.value / h) * i_prev)]
+            vec![Triplet::new(ic, 0, -(self.inductance() / h) * i_prev)]
         } else {
             vec![]
         }
     }
 }
 
-impl Stampable for NMOSFET {
-    fn stamp_conductance_matrix_dc(
-        &self,
-        index_map: &HashMap<String, usize>,
-        solution_map: &HashMap<String, f64>,
-    ) -> Vec<Triplet<usize, usize, f64>> {
-        let v_g = solution_map
-            .get(&format!("V({})", self.gate))
-            .unwrap_or(&0.0);
-        let v_s = solution_map
-            .get(&format!("V({})", self.source))
-            .unwrap_or(&0.0);
-        let v_d = solution_map
-            .get(&format!("V({})", self.drain))
-            .unwrap_or(&0.0);
-
-        let v_gs = v_g - v_s;
-        let v_ds = v_d - v_s;
-
-        let mut triplets = Vec::new();
-        let g_m = self.g_m(v_gs, v_ds);
-        let g_ds = self.g_ds(v_gs, v_ds);
-
-        let index_d = index_map.get(&self.drain);
-        let index_s = index_map.get(&self.source);
-        let index_g = index_map.get(&self.gate);
-
-        if let Some(&id) = index_d {
-            triplets.push(Triplet::new(id, id, g_ds));
-        }
-
-        if let Some(&is) = index_s {
-            triplets.push(Triplet::new(is, is, g_ds + g_m));
-        }
-
-        if let (Some(&id), Some(&is)) = (index_d, index_s) {
-            triplets.push(Triplet::new(id, is, -(g_ds + g_m)));
-            triplets.push(Triplet::new(is, id, g_ds + g_m));
-        }
-
-        if let (Some(&is), Some(&ig)) = (index_s, index_g) {
-            triplets.push(Triplet::new(is, ig, g_m));
-        }
-
-        if let (Some(&id), Some(&ig)) = (index_d, index_g) {
-            triplets.push(Triplet::new(id, ig, g_m));
-        }
-
-        triplets
-    }
-
-    fn stamp_excitation_vector_dc(
-        &self,
-        index_map: &HashMap<String, usize>,
-        solution_map: &HashMap<String, f64>,
-    ) -> Vec<Triplet<usize, usize, f64>> {
-        let v_g = solution_map
-            .get(&format!("V({})", self.gate))
-            .unwrap_or(&0.0);
-        let v_s = solution_map
-            .get(&format!("V({})", self.source))
-            .unwrap_or(&0.0);
-        let v_d = solution_map
-            .get(&format!("V({})", self.drain))
-            .unwrap_or(&0.0);
-
-        let v_gs = v_g - v_s;
-        let v_ds = v_d - v_s;
-        let g_ds = self.g_ds(v_gs, v_ds);
-        let g_m = self.g_m(v_gs, v_ds);
-        let i_d = self.i_d(v_gs, v_ds);
-
-        let i_eq = i_d - g_ds * v_ds - g_m * v_gs;
-
-        let mut triplets = Vec::new();
-
-        if let Some(&is) = index_map.get(&self.source) {
-            triplets.push(Triplet::new(is, 0, i_eq));
-        }
-
-        if let Some(&id) = index_map.get(&self.drain) {
-            triplets.push(Triplet::new(id, 0, -i_eq));
-        }
-        triplets
-    }
-
-    fn stamp_excitation_vector_ac(
-        &self,
-        _index_map: &HashMap<String, usize>,
-        _solution_map: &HashMap<String, f64>,
-        _frequency: f64,
-    ) -> Vec<Triplet<usize, usize, faer::c64>> {
-        vec![]
-    }
-
-    fn stamp_conductance_matrix_ac(
-        &self,
-        _index_map: &HashMap<String, usize>,
-        _solution_map: &HashMap<String, f64>,
-        _frequency: f64,
-    ) -> Vec<Triplet<usize, usize, faer::c64>> {
-        todo!()
-    }
+/// Returns the matrix indices of the branch currents of both coupled inductors.
+fn coupled_branch_indices(
+    coupling: &MutualInductance,
+    index_map: &HashMap<String, usize>,
+) -> Option<(usize, usize)> {
+    let index_a = index_map.get(&format!("I({})", coupling.inductor_a))?;
+    let index_b = index_map.get(&format!("I({})", coupling.inductor_b))?;
+    Some((*index_a, *index_b))
 }
 
-impl Stampable for SubcircuitInstance {
+impl Stampable for MutualInductance {
     fn stamp_conductance_matrix_dc(
         &self,
         _index_map: &HashMap<String, usize>,
         _solution_map: &HashMap<String, f64>,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        unreachable!("Subcircuit instances should be expanded before stamping")
+        // Coupled inductors are plain shorts at DC.
+        vec![]
     }
 
     fn stamp_excitation_vector_dc(
@@ -935,16 +1158,25 @@ impl Stampable for SubcircuitInstance {
         _index_map: &HashMap<String, usize>,
         _solution_map: &HashMap<String, f64>,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        unreachable!("Subcircuit instances should be expanded before stamping")
+        vec![]
     }
 
     fn stamp_conductance_matrix_ac(
         &self,
-        _index_map: &HashMap<String, usize>,
+        index_map: &HashMap<String, usize>,
         _solution_map: &HashMap<String, f64>,
-        _frequency: f64,
+        frequency: f64,
     ) -> Vec<Triplet<usize, usize, c64>> {
-        unreachable!("Subcircuit instances should be expanded before stamping")
+        // Each branch equation gains the voltage induced by the other inductor's current.
+        let impedance = c64::new(0.0, 2.0 * PI * frequency * self.inductance);
+
+        match coupled_branch_indices(self, index_map) {
+            Some((ia, ib)) => vec![
+                Triplet::new(ia, ib, -impedance),
+                Triplet::new(ib, ia, -impedance),
+            ],
+            None => vec![],
+        }
     }
 
     fn stamp_excitation_vector_ac(
@@ -953,28 +1185,354 @@ impl Stampable for SubcircuitInstance {
         _solution_map: &HashMap<String, f64>,
         _frequency: f64,
     ) -> Vec<Triplet<usize, usize, c64>> {
-        unreachable!("Subcircuit instances should be expanded before stamping")
+        vec![]
     }
-}
 
-impl Stampable for VoltageSource {
-    fn stamp_conductance_matrix_dc(
+    fn stamp_conductance_matrix_transient(
         &self,
         index_map: &HashMap<String, usize>,
         _solution_map: &HashMap<String, f64>,
+        _prev_solution: &HashMap<String, f64>,
+        h: f64,
     ) -> Vec<Triplet<usize, usize, f64>> {
-        let index_plus = index_map.get(&format!("V({})", self.plus));
-        let index_minus = index_map.get(&format!("V({})", self.minus));
-        let index_current = index_map.get(&format!("I({})", self.identifier()));
-
-        let mut triplets = Vec::with_capacity(4);
-
-        if let (Some(&ip), Some(&ic)) = (index_plus, index_current) {
-            triplets.push(Triplet::new(ip, ic, 1.0));
-            triplets.push(Triplet::new(ic, ip, 1.0));
+        match coupled_branch_indices(self, index_map) {
+            Some((ia, ib)) => vec![
+                Triplet::new(ia, ib, -self.inductance / h),
+                Triplet::new(ib, ia, -self.inductance / h),
+            ],
+            None => vec![],
         }
+    }
 
-        if let (Some(&im), Some(&ic)) = (index_minus, index_current) {
+    fn stamp_excitation_vector_transient(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        prev_solution: &HashMap<String, f64>,
+        h: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let Some((ia, ib)) = coupled_branch_indices(self, index_map) else {
+            return vec![];
+        };
+
+        let i_a_prev = prev_solution
+            .get(&format!("I({})", self.inductor_a))
+            .copied()
+            .unwrap_or(0.0);
+        let i_b_prev = prev_solution
+            .get(&format!("I({})", self.inductor_b))
+            .copied()
+            .unwrap_or(0.0);
+
+        vec![
+            Triplet::new(ia, 0, -(self.inductance / h) * i_b_prev),
+            Triplet::new(ib, 0, -(self.inductance / h) * i_a_prev),
+        ]
+    }
+}
+
+/// Small conductance placed in parallel with every MOSFET channel so that a node
+/// connected only to devices in cut-off does not leave the matrix singular.
+pub(crate) const MOSFET_GMIN: f64 = 1e-12;
+
+/// Returns `(v_gs, v_ds)` for a MOSFET from the current solution.
+fn mosfet_voltages(
+    drain: &str,
+    gate: &str,
+    source: &str,
+    solution_map: &HashMap<String, f64>,
+) -> (f64, f64) {
+    let v_g = solution_map
+        .get(&format!("V({gate})"))
+        .copied()
+        .unwrap_or(0.0);
+    let v_s = solution_map
+        .get(&format!("V({source})"))
+        .copied()
+        .unwrap_or(0.0);
+    let v_d = solution_map
+        .get(&format!("V({drain})"))
+        .copied()
+        .unwrap_or(0.0);
+
+    (v_g - v_s, v_d - v_s)
+}
+
+/// Builds the linearized MOSFET conductance stamp.
+///
+/// The drain current `i_d` flows from drain to source, so the drain row sees
+/// `g_ds * v_ds + g_m * v_gs` and the source row sees its negative. N- and
+/// P-channel devices share this stamp as both report `g_m` and `g_ds` as the
+/// partial derivatives of their own drain current. The same topology is used for
+/// the DC Newton iterations and the small-signal AC model.
+fn stamp_mosfet_conductance<N>(
+    index_map: &HashMap<String, usize>,
+    drain: &str,
+    gate: &str,
+    source: &str,
+    g_m: N,
+    g_ds: N,
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Add<Output = N> + std::ops::Neg<Output = N>,
+{
+    let index_d = index_map.get(&format!("V({drain})"));
+    let index_g = index_map.get(&format!("V({gate})"));
+    let index_s = index_map.get(&format!("V({source})"));
+
+    let mut triplets = Vec::with_capacity(6);
+
+    if let Some(&id) = index_d {
+        triplets.push(Triplet::new(id, id, g_ds));
+    }
+    if let Some(&is) = index_s {
+        triplets.push(Triplet::new(is, is, g_ds + g_m));
+    }
+    if let (Some(&id), Some(&is)) = (index_d, index_s) {
+        triplets.push(Triplet::new(id, is, -(g_ds + g_m)));
+        triplets.push(Triplet::new(is, id, -g_ds));
+    }
+    if let (Some(&id), Some(&ig)) = (index_d, index_g) {
+        triplets.push(Triplet::new(id, ig, g_m));
+    }
+    if let (Some(&is), Some(&ig)) = (index_s, index_g) {
+        triplets.push(Triplet::new(is, ig, -g_m));
+    }
+
+    triplets
+}
+
+/// Builds the small-signal AC stamp of a MOSFET biased at the operating point in
+/// `solution_map`, including the Meyer gate capacitances when they are known.
+fn stamp_mosfet_ac(
+    index_map: &HashMap<String, usize>,
+    (drain, gate, source): (&str, &str, &str),
+    (g_m, g_ds): (f64, f64),
+    gate_capacitances: Option<(f64, f64)>,
+    frequency: f64,
+) -> Vec<Triplet<usize, usize, c64>> {
+    let mut triplets = stamp_mosfet_conductance(
+        index_map,
+        drain,
+        gate,
+        source,
+        c64::new(g_m, 0.0),
+        c64::new(g_ds + MOSFET_GMIN, 0.0),
+    );
+
+    if let Some((c_gs, c_gd)) = gate_capacitances {
+        let omega = 2.0 * PI * frequency;
+        for (node, capacitance) in [(source, c_gs), (drain, c_gd)] {
+            let admittance = c64::new(0.0, omega * capacitance);
+            let index_gate = index_map.get(&format!("V({gate})"));
+            let index_node = index_map.get(&format!("V({node})"));
+
+            if let Some(&ig) = index_gate {
+                triplets.push(Triplet::new(ig, ig, admittance));
+            }
+            if let Some(&inode) = index_node {
+                triplets.push(Triplet::new(inode, inode, admittance));
+            }
+            if let (Some(&ig), Some(&inode)) = (index_gate, index_node) {
+                triplets.push(Triplet::new(ig, inode, -admittance));
+                triplets.push(Triplet::new(inode, ig, -admittance));
+            }
+        }
+    }
+
+    triplets
+}
+
+/// Builds the MOSFET companion current source stamp for the equivalent current
+/// `i_eq = i_d - g_ds * v_ds - g_m * v_gs`.
+fn stamp_mosfet_excitation(
+    index_map: &HashMap<String, usize>,
+    drain: &str,
+    source: &str,
+    i_eq: f64,
+) -> Vec<Triplet<usize, usize, f64>> {
+    let mut triplets = Vec::with_capacity(2);
+
+    if let Some(&id) = index_map.get(&format!("V({drain})")) {
+        triplets.push(Triplet::new(id, 0, -i_eq));
+    }
+    if let Some(&is) = index_map.get(&format!("V({source})")) {
+        triplets.push(Triplet::new(is, 0, i_eq));
+    }
+
+    triplets
+}
+
+impl Stampable for NMOSFET {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let (v_gs, v_ds) = mosfet_voltages(&self.drain, &self.gate, &self.source, solution_map);
+
+        stamp_mosfet_conductance(
+            index_map,
+            &self.drain,
+            &self.gate,
+            &self.source,
+            self.g_m(v_gs, v_ds),
+            self.g_ds(v_gs, v_ds) + MOSFET_GMIN,
+        )
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let (v_gs, v_ds) = mosfet_voltages(&self.drain, &self.gate, &self.source, solution_map);
+        let i_eq =
+            self.i_d(v_gs, v_ds) - self.g_ds(v_gs, v_ds) * v_ds - self.g_m(v_gs, v_ds) * v_gs;
+
+        stamp_mosfet_excitation(index_map, &self.drain, &self.source, i_eq)
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, faer::c64>> {
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+        frequency: f64,
+    ) -> Vec<Triplet<usize, usize, faer::c64>> {
+        // The small-signal model is linearized around the DC operating point.
+        let (v_gs, v_ds) = mosfet_voltages(&self.drain, &self.gate, &self.source, solution_map);
+
+        stamp_mosfet_ac(
+            index_map,
+            (&self.drain, &self.gate, &self.source),
+            (self.g_m(v_gs, v_ds), self.g_ds(v_gs, v_ds)),
+            self.gate_capacitances(v_gs, v_ds),
+            frequency,
+        )
+    }
+}
+
+impl Stampable for PMOSFET {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let (v_gs, v_ds) = mosfet_voltages(&self.drain, &self.gate, &self.source, solution_map);
+
+        stamp_mosfet_conductance(
+            index_map,
+            &self.drain,
+            &self.gate,
+            &self.source,
+            self.g_m(v_gs, v_ds),
+            self.g_ds(v_gs, v_ds) + MOSFET_GMIN,
+        )
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let (v_gs, v_ds) = mosfet_voltages(&self.drain, &self.gate, &self.source, solution_map);
+        let i_eq =
+            self.i_d(v_gs, v_ds) - self.g_ds(v_gs, v_ds) * v_ds - self.g_m(v_gs, v_ds) * v_gs;
+
+        stamp_mosfet_excitation(index_map, &self.drain, &self.source, i_eq)
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, faer::c64>> {
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+        frequency: f64,
+    ) -> Vec<Triplet<usize, usize, faer::c64>> {
+        // The small-signal model is linearized around the DC operating point.
+        let (v_gs, v_ds) = mosfet_voltages(&self.drain, &self.gate, &self.source, solution_map);
+
+        stamp_mosfet_ac(
+            index_map,
+            (&self.drain, &self.gate, &self.source),
+            (self.g_m(v_gs, v_ds), self.g_ds(v_gs, v_ds)),
+            self.gate_capacitances(v_gs, v_ds),
+            frequency,
+        )
+    }
+}
+
+impl Stampable for SubcircuitInstance {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        unreachable!("Subcircuit instances should be expanded before stamping")
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        unreachable!("Subcircuit instances should be expanded before stamping")
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        unreachable!("Subcircuit instances should be expanded before stamping")
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        unreachable!("Subcircuit instances should be expanded before stamping")
+    }
+}
+
+impl Stampable for VoltageSource {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        let index_plus = index_map.get(&format!("V({})", self.plus));
+        let index_minus = index_map.get(&format!("V({})", self.minus));
+        let index_current = index_map.get(&format!("I({})", self.identifier()));
+
+        let mut triplets = Vec::with_capacity(4);
+
+        if let (Some(&ip), Some(&ic)) = (index_plus, index_current) {
+            triplets.push(Triplet::new(ip, ic, 1.0));
+            triplets.push(Triplet::new(ic, ip, 1.0));
+        }
+
+        if let (Some(&im), Some(&ic)) = (index_minus, index_current) {
             triplets.push(Triplet::new(im, ic, -1.0));
             triplets.push(Triplet::new(ic, im, -1.0));
         }
@@ -1028,7 +1586,11 @@ impl Stampable for VoltageSource {
         let mut triplets = Vec::with_capacity(1);
 
         if let Some(&ic) = index_map.get(&format!("I({})", self.identifier())) {
-            triplets.push(Triplet::new(ic, 0, c64::new(self.ac_amplitude, 0.0)));
+            triplets.push(Triplet::new(
+                ic,
+                0,
+                c64::from_polar(self.ac_amplitude, self.ac_phase.to_radians()),
+            ));
         }
         triplets
     }
@@ -1048,3 +1610,548 @@ impl Stampable for VoltageSource {
         }
     }
 }
+
+/// Builds the VCVS stamp for any numeric type used by the solvers.
+///
+/// The branch current `I(E)` enters the KCL rows of the output nodes, and the
+/// branch equation enforces `V(n+) - V(n-) - gain * (V(nc+) - V(nc-)) = 0`.
+fn stamp_vcvs<N>(
+    source: &VCVS,
+    index_map: &HashMap<String, usize>,
+    one: N,
+    gain: N,
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Neg<Output = N>,
+{
+    let index_plus = index_map.get(&format!("V({})", source.plus));
+    let index_minus = index_map.get(&format!("V({})", source.minus));
+    let index_control_plus = index_map.get(&format!("V({})", source.control_plus));
+    let index_control_minus = index_map.get(&format!("V({})", source.control_minus));
+    let index_current = index_map.get(&format!("I({})", source.identifier()));
+
+    let mut triplets = Vec::with_capacity(6);
+
+    if let (Some(&ip), Some(&ic)) = (index_plus, index_current) {
+        triplets.push(Triplet::new(ip, ic, one));
+        triplets.push(Triplet::new(ic, ip, one));
+    }
+    if let (Some(&im), Some(&ic)) = (index_minus, index_current) {
+        triplets.push(Triplet::new(im, ic, -one));
+        triplets.push(Triplet::new(ic, im, -one));
+    }
+    if let (Some(&icp), Some(&ic)) = (index_control_plus, index_current) {
+        triplets.push(Triplet::new(ic, icp, -gain));
+    }
+    if let (Some(&icm), Some(&ic)) = (index_control_minus, index_current) {
+        triplets.push(Triplet::new(ic, icm, gain));
+    }
+
+    triplets
+}
+
+impl Stampable for VCVS {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        stamp_vcvs(self, index_map, 1.0, self.gain)
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        stamp_vcvs(
+            self,
+            index_map,
+            c64::new(1.0, 0.0),
+            c64::new(self.gain, 0.0),
+        )
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+}
+
+/// Builds the VCCS stamp for any numeric type used by the solvers.
+///
+/// A current `gm * (V(nc+) - V(nc-))` flows from `n+` through the source to `n-`.
+fn stamp_vccs<N>(
+    source: &VCCS,
+    index_map: &HashMap<String, usize>,
+    gm: N,
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Neg<Output = N>,
+{
+    let index_plus = index_map.get(&format!("V({})", source.plus));
+    let index_minus = index_map.get(&format!("V({})", source.minus));
+    let index_control_plus = index_map.get(&format!("V({})", source.control_plus));
+    let index_control_minus = index_map.get(&format!("V({})", source.control_minus));
+
+    let mut triplets = Vec::with_capacity(4);
+
+    if let (Some(&ip), Some(&icp)) = (index_plus, index_control_plus) {
+        triplets.push(Triplet::new(ip, icp, gm));
+    }
+    if let (Some(&ip), Some(&icm)) = (index_plus, index_control_minus) {
+        triplets.push(Triplet::new(ip, icm, -gm));
+    }
+    if let (Some(&im), Some(&icp)) = (index_minus, index_control_plus) {
+        triplets.push(Triplet::new(im, icp, -gm));
+    }
+    if let (Some(&im), Some(&icm)) = (index_minus, index_control_minus) {
+        triplets.push(Triplet::new(im, icm, gm));
+    }
+
+    triplets
+}
+
+impl Stampable for VCCS {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        stamp_vccs(self, index_map, self.transconductance)
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        stamp_vccs(self, index_map, c64::new(self.transconductance, 0.0))
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+}
+
+/// Builds the CCVS stamp for any numeric type used by the solvers.
+///
+/// The branch equation enforces `V(n+) - V(n-) - r * I(vname) = 0`.
+fn stamp_ccvs<N>(
+    source: &CCVS,
+    index_map: &HashMap<String, usize>,
+    one: N,
+    transresistance: N,
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Neg<Output = N>,
+{
+    let index_plus = index_map.get(&format!("V({})", source.plus));
+    let index_minus = index_map.get(&format!("V({})", source.minus));
+    let index_current = index_map.get(&format!("I({})", source.identifier()));
+    let index_control = index_map.get(&format!("I({})", source.control_source));
+
+    let mut triplets = Vec::with_capacity(5);
+
+    if let (Some(&ip), Some(&ic)) = (index_plus, index_current) {
+        triplets.push(Triplet::new(ip, ic, one));
+        triplets.push(Triplet::new(ic, ip, one));
+    }
+    if let (Some(&im), Some(&ic)) = (index_minus, index_current) {
+        triplets.push(Triplet::new(im, ic, -one));
+        triplets.push(Triplet::new(ic, im, -one));
+    }
+    if let (Some(&ictrl), Some(&ic)) = (index_control, index_current) {
+        triplets.push(Triplet::new(ic, ictrl, -transresistance));
+    }
+
+    triplets
+}
+
+impl Stampable for CCVS {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        stamp_ccvs(self, index_map, 1.0, self.transresistance)
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        stamp_ccvs(
+            self,
+            index_map,
+            c64::new(1.0, 0.0),
+            c64::new(self.transresistance, 0.0),
+        )
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+}
+
+/// Builds the CCCS stamp for any numeric type used by the solvers.
+///
+/// A current `gain * I(vname)` flows from `n+` through the source to `n-`.
+fn stamp_cccs<N>(
+    source: &CCCS,
+    index_map: &HashMap<String, usize>,
+    gain: N,
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Neg<Output = N>,
+{
+    let index_plus = index_map.get(&format!("V({})", source.plus));
+    let index_minus = index_map.get(&format!("V({})", source.minus));
+    let index_control = index_map.get(&format!("I({})", source.control_source));
+
+    let mut triplets = Vec::with_capacity(2);
+
+    if let (Some(&ip), Some(&ictrl)) = (index_plus, index_control) {
+        triplets.push(Triplet::new(ip, ictrl, gain));
+    }
+    if let (Some(&im), Some(&ictrl)) = (index_minus, index_control) {
+        triplets.push(Triplet::new(im, ictrl, -gain));
+    }
+
+    triplets
+}
+
+impl Stampable for CCCS {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        stamp_cccs(self, index_map, self.gain)
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        stamp_cccs(self, index_map, c64::new(self.gain, 0.0))
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // A controlled source has no independent excitation.
+        vec![]
+    }
+}
+
+/// Stamps the conductance `g` of a switch between its two nodes.
+fn stamp_switch<N>(
+    switch: &Switch,
+    index_map: &HashMap<String, usize>,
+    g: N,
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Neg<Output = N>,
+{
+    let index_plus = index_map.get(&format!("V({})", switch.plus));
+    let index_minus = index_map.get(&format!("V({})", switch.minus));
+
+    let mut triplets = Vec::with_capacity(4);
+    if let Some(&ip) = index_plus {
+        triplets.push(Triplet::new(ip, ip, g));
+    }
+    if let Some(&im) = index_minus {
+        triplets.push(Triplet::new(im, im, g));
+    }
+    if let (Some(&ip), Some(&im)) = (index_plus, index_minus) {
+        triplets.push(Triplet::new(ip, im, -g));
+        triplets.push(Triplet::new(im, ip, -g));
+    }
+
+    triplets
+}
+
+impl Stampable for Switch {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // The state is re-evaluated on every Newton-Raphson iteration, so the iteration only
+        // converges once the switch settles in a state consistent with its control.
+        self.update_state(solution_map);
+        stamp_switch(self, index_map, self.conductance())
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // A switch is a resistor in either state and does not add to the excitation vector.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // The switch keeps the state it settled in at the operating point.
+        stamp_switch(self, index_map, c64::new(self.conductance(), 0.0))
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // A switch is a resistor in either state and does not add to the excitation vector.
+        vec![]
+    }
+}
+
+/// Builds the two-port stamp of a transmission line from its chain (ABCD) parameters.
+///
+/// The branch current `I(T)` flows into `plus1` and out of `minus1`. The far port draws
+/// `i2 = c * V1 - a * I(T)` into `plus2`, and the branch equation enforces
+/// `d * V1 - V2 - b * I(T) = 0`, where `V1` and `V2` are the port voltages. At DC the chain
+/// parameters are those of a pair of wires, `a = d = 1` and `b = c = 0`.
+fn stamp_transmission_line<N>(
+    line: &TransmissionLine,
+    index_map: &HashMap<String, usize>,
+    one: N,
+    [a, b, c, d]: [N; 4],
+) -> Vec<Triplet<usize, usize, N>>
+where
+    N: Copy + std::ops::Neg<Output = N>,
+{
+    let index_plus1 = index_map.get(&format!("V({})", line.plus1));
+    let index_minus1 = index_map.get(&format!("V({})", line.minus1));
+    let index_plus2 = index_map.get(&format!("V({})", line.plus2));
+    let index_minus2 = index_map.get(&format!("V({})", line.minus2));
+    let Some(&ic) = index_map.get(&format!("I({})", line.identifier())) else {
+        return vec![];
+    };
+
+    let mut triplets = Vec::with_capacity(15);
+    triplets.push(Triplet::new(ic, ic, -b));
+    if let Some(&ip1) = index_plus1 {
+        triplets.push(Triplet::new(ip1, ic, one));
+        triplets.push(Triplet::new(ic, ip1, d));
+    }
+    if let Some(&im1) = index_minus1 {
+        triplets.push(Triplet::new(im1, ic, -one));
+        triplets.push(Triplet::new(ic, im1, -d));
+    }
+    if let Some(&ip2) = index_plus2 {
+        triplets.push(Triplet::new(ic, ip2, -one));
+        triplets.push(Triplet::new(ip2, ic, -a));
+        if let Some(&ip1) = index_plus1 {
+            triplets.push(Triplet::new(ip2, ip1, c));
+        }
+        if let Some(&im1) = index_minus1 {
+            triplets.push(Triplet::new(ip2, im1, -c));
+        }
+    }
+    if let Some(&im2) = index_minus2 {
+        triplets.push(Triplet::new(ic, im2, one));
+        triplets.push(Triplet::new(im2, ic, a));
+        if let Some(&ip1) = index_plus1 {
+            triplets.push(Triplet::new(im2, ip1, -c));
+        }
+        if let Some(&im1) = index_minus1 {
+            triplets.push(Triplet::new(im2, im1, c));
+        }
+    }
+
+    triplets
+}
+
+impl Stampable for TransmissionLine {
+    fn stamp_conductance_matrix_dc(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        stamp_transmission_line(self, index_map, 1.0, [1.0, 0.0, 0.0, 1.0])
+    }
+
+    fn stamp_excitation_vector_dc(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // A transmission line has no independent excitation.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_ac(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // The lossless line has the electrical length `θ = ωTD`, so `cosh(jθ) = cos θ` and
+        // `sinh(jθ) = j sin θ`.
+        let theta = 2.0 * PI * frequency * self.td;
+        let (sin, cos) = theta.sin_cos();
+        let chain = [
+            c64::new(cos, 0.0),
+            c64::new(0.0, self.z0 * sin),
+            c64::new(0.0, sin / self.z0),
+            c64::new(cos, 0.0),
+        ];
+        stamp_transmission_line(self, index_map, c64::new(1.0, 0.0), chain)
+    }
+
+    fn stamp_excitation_vector_ac(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _frequency: f64,
+    ) -> Vec<Triplet<usize, usize, c64>> {
+        // A transmission line has no independent excitation.
+        vec![]
+    }
+
+    fn stamp_conductance_matrix_transient(
+        &self,
+        index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _prev_solution: &HashMap<String, f64>,
+        _time_step: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // Each port is the characteristic impedance in series with the wave arriving from the
+        // other port. The first port is written with its branch current,
+        // `V1 - Z0 * I(T) = E1`, and the second as its Norton equivalent.
+        let index_plus1 = index_map.get(&format!("V({})", self.plus1));
+        let index_minus1 = index_map.get(&format!("V({})", self.minus1));
+        let Some(&ic) = index_map.get(&format!("I({})", self.identifier())) else {
+            return vec![];
+        };
+
+        let mut triplets = vec![Triplet::new(ic, ic, -self.z0)];
+        if let Some(&ip1) = index_plus1 {
+            triplets.push(Triplet::new(ip1, ic, 1.0));
+            triplets.push(Triplet::new(ic, ip1, 1.0));
+        }
+        if let Some(&im1) = index_minus1 {
+            triplets.push(Triplet::new(im1, ic, -1.0));
+            triplets.push(Triplet::new(ic, im1, -1.0));
+        }
+
+        let g = 1.0 / self.z0;
+        let index_plus2 = index_map.get(&format!("V({})", self.plus2));
+        let index_minus2 = index_map.get(&format!("V({})", self.minus2));
+        if let Some(&ip2) = index_plus2 {
+            triplets.push(Triplet::new(ip2, ip2, g));
+        }
+        if let Some(&im2) = index_minus2 {
+            triplets.push(Triplet::new(im2, im2, g));
+        }
+        if let (Some(&ip2), Some(&im2)) = (index_plus2, index_minus2) {
+            triplets.push(Triplet::new(ip2, im2, -g));
+            triplets.push(Triplet::new(im2, ip2, -g));
+        }
+        triplets
+    }
+
+    fn stamp_excitation_vector_transient(
+        &self,
+        _index_map: &HashMap<String, usize>,
+        _solution_map: &HashMap<String, f64>,
+        _prev_solution: &HashMap<String, f64>,
+        _time_step: f64,
+    ) -> Vec<Triplet<usize, usize, f64>> {
+        // The waves arriving at the ports depend on the solution one delay ago, which only the
+        // transient solver keeps, see [`stamp_incident_waves`].
+        vec![]
+    }
+}
+
+/// Builds the transient excitation stamp of the waves `e1` and `e2` arriving at the ports of a
+/// transmission line, the sources in series with the characteristic impedance of each port.
+pub(crate) fn stamp_incident_waves(
+    line: &TransmissionLine,
+    index_map: &HashMap<String, usize>,
+    e1: f64,
+    e2: f64,
+) -> Vec<Triplet<usize, usize, f64>> {
+    let mut triplets = Vec::with_capacity(3);
+    if let Some(&ic) = index_map.get(&format!("I({})", line.identifier())) {
+        triplets.push(Triplet::new(ic, 0, e1));
+    }
+    if let Some(&ip2) = index_map.get(&format!("V({})", line.plus2)) {
+        triplets.push(Triplet::new(ip2, 0, e2 / line.z0));
+    }
+    if let Some(&im2) = index_map.get(&format!("V({})", line.minus2)) {
+        triplets.push(Triplet::new(im2, 0, -e2 / line.z0));
+    }
+    triplets
+}
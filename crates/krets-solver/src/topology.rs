@@ -0,0 +1,98 @@
+use crate::prelude::*;
+use krets_parser::{circuit::Circuit, elements::Element};
+
+/// Checks that every node of the circuit has a DC path to ground (node `0`).
+///
+/// Resistors, inductors, switches, voltage sources (including controlled ones), the
+/// conductors of transmission lines and the junctions of semiconductors conduct at DC;
+/// capacitors, current sources and the inputs of controlled sources do not. A node that is
+/// not connected to ground through conducting elements leaves the MNA matrix singular, so it
+/// is reported as `Error::FloatingNode` before any factorization is attempted. Nodes are
+/// checked in netlist order.
+pub fn check_floating_nodes(circuit: &Circuit) -> Result<()> {
+    let mut nodes = DisjointSet::default();
+    nodes.index("0");
+
+    for element in &circuit.elements {
+        for node in element.nodes() {
+            nodes.index(node);
+        }
+        for (a, b) in dc_connections(element) {
+            nodes.union(a, b);
+        }
+    }
+
+    let ground = nodes.index("0");
+    for element in &circuit.elements {
+        for node in element.nodes() {
+            let index = nodes.index(node);
+            if nodes.find(index) != nodes.find(ground) {
+                return Err(Error::FloatingNode(node.to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the pairs of nodes an element connects with a DC conductive path.
+fn dc_connections(element: &Element) -> Vec<(&str, &str)> {
+    match element {
+        Element::Resistor(r) => vec![(&r.plus, &r.minus)],
+        Element::Inductor(l) => vec![(&l.plus, &l.minus)],
+        Element::VoltageSource(vs) => vec![(&vs.plus, &vs.minus)],
+        Element::VCVS(e) => vec![(&e.plus, &e.minus)],
+        Element::CCVS(h) => vec![(&h.plus, &h.minus)],
+        Element::Switch(s) => vec![(&s.plus, &s.minus)],
+        // At DC a transmission line is a pair of wires from one port to the other.
+        Element::TransmissionLine(t) => vec![(&t.plus1, &t.plus2), (&t.minus1, &t.minus2)],
+        Element::Diode(d) => vec![(&d.plus, &d.minus)],
+        Element::BJT(q) => vec![(&q.base, &q.emitter), (&q.base, &q.collector)],
+        // The gate is insulated; only the channel conducts.
+        Element::NMOSFET(m) => vec![(&m.drain, &m.source)],
+        Element::PMOSFET(m) => vec![(&m.drain, &m.source)],
+        Element::Capacitor(_)
+        | Element::CurrentSource(_)
+        | Element::VCCS(_)
+        | Element::CCCS(_)
+        | Element::MutualInductance(_)
+        | Element::SubcktInstance(_) => vec![],
+    }
+    .into_iter()
+    .map(|(a, b): (&String, &String)| (a.as_str(), b.as_str()))
+    .collect()
+}
+
+/// A union-find over node names.
+#[derive(Default)]
+struct DisjointSet<'a> {
+    indices: HashMap<&'a str, usize>,
+    parents: Vec<usize>,
+}
+
+impl<'a> DisjointSet<'a> {
+    /// Returns the index of `node`, adding it as its own set if it is new.
+    fn index(&mut self, node: &'a str) -> usize {
+        let next = self.parents.len();
+        let index = *self.indices.entry(node).or_insert(next);
+        if index == next {
+            self.parents.push(next);
+        }
+        index
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            // Path halving keeps the trees shallow.
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: &'a str, b: &'a str) {
+        let (a, b) = (self.index(a), self.index(b));
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        self.parents[root_a] = root_b;
+    }
+}
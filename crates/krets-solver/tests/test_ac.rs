@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use krets_parser::analyses::Analysis;
+    use faer::c64;
+    use krets_parser::{analyses::Analysis, elements::Element};
     use krets_solver::{config::SolverConfig, solver::Solver};
     use std::{env, path::Path};
 
@@ -45,6 +46,32 @@ mod tests {
         assert!((solution.get("V(out)").unwrap().im - (-1.55223e-01)).abs() < 1e-3);
     }
 
+    #[test]
+    fn test_matched_transmission_line_ac() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 AC 1\nR1 in a 50\nT1 a 0 b 0 Z0=50 TD=10n\nR2 b 0 50\n",
+        )
+        .unwrap();
+        // A quarter period of the delay at 25MHz.
+        let ac_analysis = krets_parser::analyses::AcAnalysis {
+            fstart: 25e6,
+            sweep: krets_parser::analyses::AcSweep::Linear { total_points: 1 },
+            fstop: 25e6,
+        };
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let solution = solver
+            .solve(Analysis::Ac(ac_analysis))
+            .unwrap()
+            .into_ac()
+            .first()
+            .unwrap()
+            .clone();
+
+        // The matched line looks like its impedance and only delays the wave.
+        assert!((solution["V(a)"] - c64::new(0.5, 0.0)).norm() < 1e-9);
+        assert!((solution["V(b)"] - c64::new(0.0, -0.5)).norm() < 1e-9);
+    }
+
     #[test]
     fn test_high_pass_filter_ac() {
         let path = Path::new(&circuits_dir()).join("high_pass_filter/high_pass_filter.cir");
@@ -103,4 +130,285 @@ mod tests {
             assert!((solution.get("V(out)").unwrap().im - vout(frequency).1).abs() < 1e-3);
         }
     }
+
+    #[test]
+    fn test_ac_sweep_from_zero_hz() {
+        use krets_parser::analyses::{AcAnalysis, AcSweep};
+        use krets_solver::prelude::Error;
+
+        // At 0Hz the inductor shorts `a` to `out` and the capacitors are open, so R1 and R2
+        // divide the input and no current reaches R3.
+        let netlist = "V1 in 0 AC 1\nR1 in a 1k\nL1 a out 1m\nR2 out 0 1k\nC1 out 0 1u\nC2 in b 1u\nR3 b 0 1k\n";
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let rows = solver
+            .solve(Analysis::Ac(AcAnalysis {
+                sweep: AcSweep::Linear { total_points: 3 },
+                fstart: 0.0,
+                fstop: 1000.0,
+            }))
+            .unwrap()
+            .into_ac();
+        assert_eq!(rows.len(), 3);
+        let dc = &rows[0];
+        assert_eq!(dc["frequency"], c64::new(0.0, 0.0));
+        assert!((dc["V(out)"] - c64::new(0.5, 0.0)).norm() < 1e-12);
+        assert!((dc["V(a)"] - dc["V(out)"]).norm() < 1e-12);
+        assert!((dc["I(L1)"] - c64::new(0.5e-3, 0.0)).norm() < 1e-12);
+        assert!(dc["V(b)"].norm() < 1e-12);
+        assert!(rows[1]["V(b)"].norm() > 0.0);
+
+        // A logarithmic sweep cannot reach 0Hz and is rejected rather than left empty.
+        let result = solver.solve(Analysis::Ac(AcAnalysis {
+            sweep: AcSweep::Decade {
+                points_per_decade: 10,
+            },
+            fstart: 0.0,
+            fstop: 1000.0,
+        }));
+        assert!(matches!(result, Err(Error::InvalidFrequencyRange { .. })));
+    }
+
+    #[test]
+    fn test_transformer_ac() {
+        let path = Path::new(&circuits_dir()).join("transformer/transformer.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig::default();
+        let ac_analysis = krets_parser::analyses::AcAnalysis {
+            fstart: 1000.0,
+            sweep: krets_parser::analyses::AcSweep::Linear { total_points: 1 },
+            fstop: 1000.0,
+        };
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver
+            .solve(Analysis::Ac(ac_analysis))
+            .unwrap()
+            .into_ac()
+            .first()
+            .unwrap()
+            .clone();
+
+        // Solve the two coupled loop equations by hand:
+        //   (R1 + jwL1) I1 + jwM I2 = 1
+        //   jwM I1 + (jwL2 + RL) I2 = 0
+        let (r1, rl, l, k) = (1.0, 10.0, 1e-3, 0.98);
+        let w = 2.0 * std::f64::consts::PI * 1000.0;
+        let z_self = c64::new(0.0, w * l);
+        let z_mutual = c64::new(0.0, w * k * l);
+        let z_secondary = z_self + c64::new(rl, 0.0);
+        let i1 =
+            c64::new(1.0, 0.0) / (c64::new(r1, 0.0) + z_self - z_mutual * z_mutual / z_secondary);
+        let v_secondary = z_mutual * i1 / z_secondary * rl;
+
+        let v_out = solution.get("V(s)").unwrap();
+        assert!((v_out.norm() - v_secondary.norm()).abs() < 1e-6);
+        assert!((v_out - v_secondary).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_common_source_gain_roll_off() {
+        let path = Path::new(&circuits_dir()).join("common_source/common_source.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig::default();
+        let ac_analysis = krets_parser::analyses::AcAnalysis {
+            fstart: 100.0,
+            sweep: krets_parser::analyses::AcSweep::Decade {
+                points_per_decade: 10,
+            },
+            fstop: 10e6,
+        };
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver.solve(Analysis::Ac(ac_analysis)).unwrap().into_ac();
+
+        // Square-law device in saturation: V(out) = VDD - RD * beta / 2 * (VGS - VTO)^2 * (1 + lambda * V(out)).
+        let (vdd, rd, cl, beta, lambda, v_ov) = (5.0, 10e3, 1e-9, 200e-6, 0.01, 1.0);
+        let v_out = (vdd - rd * beta / 2.0 * v_ov * v_ov) / (1.0 + rd * beta / 2.0 * lambda);
+        let g_m = beta * v_ov * (1.0 + lambda * v_out);
+        let g_out = 1.0 / rd + beta / 2.0 * lambda * v_ov * v_ov;
+        let a0 = g_m / g_out;
+        let f_pole = g_out / (2.0 * std::f64::consts::PI * cl);
+
+        for point in &solution {
+            let f = point.get("frequency").unwrap().re;
+            let gain = point.get("V(out)").unwrap().norm();
+            let expected = a0 / (1.0 + (f / f_pole).powi(2)).sqrt();
+            assert!(
+                (gain - expected).abs() < 1e-3 * a0,
+                "gain at {f} Hz was {gain}, expected {expected}"
+            );
+        }
+
+        // The amplifier inverts at low frequency and rolls off at 20 dB/decade past the pole.
+        let first = solution.first().unwrap().get("V(out)").unwrap();
+        assert!(first.re < 0.0);
+        let last = solution.last().unwrap().get("V(out)").unwrap().norm();
+        assert!((last - a0 * f_pole / 10e6).abs() < 1e-2 * last);
+    }
+
+    #[test]
+    fn test_common_emitter_gain_and_pole() {
+        let netlist = "
+VCC vcc 0 10
+VIN in 0 DC 0 AC 1
+RS in x 1k
+CIN x b 1
+RB vcc b 1meg
+RC vcc c 5k
+QN1 c b 0
+";
+        let mut circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let (c_je, c_jc) = (10e-12, 2e-12);
+        for element in &mut circuit.elements {
+            if let Element::BJT(q) = element {
+                q.model.base_emitter_capacitance = c_je;
+                q.model.base_collector_capacitance = c_jc;
+            }
+        }
+        let bjt = circuit
+            .elements
+            .iter()
+            .find_map(|element| match element {
+                Element::BJT(q) => Some(q.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let op = solver.solve_op().unwrap();
+        let bias = bjt.operating_point(&op);
+
+        let ac_analysis = krets_parser::analyses::AcAnalysis {
+            fstart: 100.0,
+            sweep: krets_parser::analyses::AcSweep::Decade {
+                points_per_decade: 10,
+            },
+            fstop: 1e9,
+        };
+        let solution = solver.solve(Analysis::Ac(ac_analysis)).unwrap().into_ac();
+
+        // Midband: the coupling capacitor is a short and the junction capacitances are open.
+        let (rs, rb, rc) = (1e3, 1e6, 5e3);
+        let r_in = 1.0 / (bias.g_pi + 1.0 / rb);
+        let midband = -bias.g_m * rc * r_in / (rs + r_in);
+        let first = solution.first().unwrap()["V(c)"];
+        assert!(midband < -50.0);
+        assert!((first.re - midband).abs() < 1e-2 * midband.abs());
+        assert!(first.im.abs() < 1e-2 * midband.abs());
+
+        // Solve the two-node hybrid-pi model by hand at every frequency, up to the
+        // convergence tolerance of the operating point.
+        for point in &solution {
+            let f = point["frequency"].re;
+            let omega = 2.0 * std::f64::consts::PI * f;
+            let y_s = 1.0 / (c64::new(rs, 0.0) + 1.0 / c64::new(0.0, omega));
+            let y_pi = c64::new(bias.g_pi + 1.0 / rb, omega * c_je);
+            let y_mu = c64::new(bias.g_mu, omega * c_jc);
+            let g_c = c64::new(1.0 / rc + bias.g_o, 0.0);
+            let g_m = c64::new(bias.g_m, 0.0);
+
+            // [y_s + y_pi + y_mu, -y_mu; g_m - y_mu, g_c + y_mu] [v_b; v_c] = [y_s; 0]
+            let (a11, a12, a21, a22) = (y_s + y_pi + y_mu, -y_mu, g_m - y_mu, g_c + y_mu);
+            let v_c = -a21 * y_s / (a11 * a22 - a12 * a21);
+            assert!(
+                (point["V(c)"] - v_c).norm() < 1e-4 * v_c.norm().max(1.0),
+                "V(c) at {f} Hz was {}, expected {v_c}",
+                point["V(c)"]
+            );
+        }
+
+        // The Miller-multiplied base-collector capacitance sets the dominant pole.
+        let r_source = 1.0 / (1.0 / rs + 1.0 / r_in);
+        let c_miller = c_je + c_jc * (1.0 + bias.g_m * rc);
+        let f_pole = 1.0 / (2.0 * std::f64::consts::PI * r_source * c_miller);
+        let distance = |f: f64| (f / f_pole).ln().abs();
+        let nearest = solution
+            .iter()
+            .min_by(|a, b| distance(a["frequency"].re).total_cmp(&distance(b["frequency"].re)))
+            .unwrap();
+        let (f_nearest, gain_at_pole) = (nearest["frequency"].re, nearest["V(c)"].norm());
+        let expected = midband.abs() / (1.0 + (f_nearest / f_pole).powi(2)).sqrt();
+        assert!(
+            (gain_at_pole - expected).abs() < 0.1 * expected,
+            "gain near the {f_pole} Hz pole was {gain_at_pole}, expected {expected}"
+        );
+        assert!(solution.last().unwrap()["V(c)"].norm() < 1e-2 * midband.abs());
+    }
+
+    #[test]
+    fn test_diode_biased_attenuator() {
+        // The AC signal is coupled through C1 and R1 into a diode, whose small-signal
+        // resistance nVt/Id is set by the DC bias current of I1.
+        let gain_at = |bias: &str| {
+            let netlist = format!(
+                "V1 in 0 DC 0 AC 1\nC1 in a 1\nR1 a out 1k\nD1 out 0 DMOD\nI1 0 out {bias}\n\
+                 .model DMOD D (is=1e-14)\n"
+            );
+            let circuit = krets_parser::parser::parse_circuit_description(&netlist).unwrap();
+            let diode = circuit
+                .elements
+                .iter()
+                .find_map(|element| match element {
+                    Element::Diode(diode) => Some(diode.clone()),
+                    _ => None,
+                })
+                .unwrap();
+
+            let mut solver = Solver::new(circuit, SolverConfig::default());
+            let op = solver.solve_op().unwrap();
+            let ac_analysis = krets_parser::analyses::AcAnalysis {
+                fstart: 1e3,
+                sweep: krets_parser::analyses::AcSweep::Linear { total_points: 1 },
+                fstop: 1e3,
+            };
+            let solution = solver.solve(Analysis::Ac(ac_analysis)).unwrap().into_ac();
+
+            let r_d = 1.0 / diode.conductance(&op);
+            let expected = r_d / (1e3 + r_d);
+            (solution[0]["V(out)"], expected)
+        };
+
+        let (high_bias, expected_high) = gain_at("1m");
+        let (low_bias, expected_low) = gain_at("10u");
+        assert!((high_bias - c64::new(expected_high, 0.0)).norm() < 1e-6 * expected_high.max(1e-3));
+        assert!((low_bias - c64::new(expected_low, 0.0)).norm() < 1e-6);
+
+        // A hundred times less bias current raises the diode resistance about a hundredfold.
+        assert!(expected_high < 0.03 && expected_low > 0.7);
+        assert!(low_bias.norm() > 20.0 * high_bias.norm());
+    }
+
+    #[test]
+    fn test_ac_source_phase() {
+        use krets_solver::stampable::Stampable;
+
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "I1 0 out ac 1 90\nR1 out 0 1k\nV1 in 0 ac 2 -90\nR2 in 0 1k\n",
+        )
+        .unwrap();
+        let Some(source @ Element::CurrentSource(_)) = circuit.elements.first() else {
+            panic!("expected a current source");
+        };
+        let excitation =
+            source.stamp_excitation_vector_ac(&circuit.index_map, &Default::default(), 1e3);
+        assert_eq!(excitation.len(), 1);
+        assert!(excitation[0].val.re.abs() < 1e-15);
+        assert!((excitation[0].val.im - 1.0).abs() < 1e-15);
+
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let solution = solver
+            .solve(Analysis::Ac(krets_parser::analyses::AcAnalysis {
+                fstart: 1e3,
+                sweep: krets_parser::analyses::AcSweep::Linear { total_points: 1 },
+                fstop: 1e3,
+            }))
+            .unwrap()
+            .into_ac();
+
+        // The source drives 1 mA at 90 degrees into `out`, and V1 sets `in` to 2 V at -90.
+        let out = solution[0]["V(out)"];
+        assert!((out - c64::new(0.0, 1e3)).norm() < 1e-9, "V(out) = {out}");
+        let vin = solution[0]["V(in)"];
+        assert!((vin - c64::new(0.0, -2.0)).norm() < 1e-12, "V(in) = {vin}");
+    }
 }
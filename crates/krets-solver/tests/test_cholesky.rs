@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use faer::Mat;
+    use krets_parser::parser::{parse_circuit_description, parse_circuit_description_file};
+    use krets_solver::{
+        conditioning::solve_cholesky, config::SolverConfig, mna::MnaMatrix, prelude::*,
+    };
+    use std::{env, path::Path};
+
+    fn circuits_dir() -> String {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+        Path::new(&manifest_dir)
+            .parent()
+            .and_then(Path::parent)
+            .unwrap()
+            .join("circuits/")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn assemble(
+        circuit: &krets_parser::circuit::Circuit,
+    ) -> (MnaMatrix, Vec<Triplet<usize, usize, f64>>) {
+        let elements: Vec<_> = circuit.elements.iter().collect();
+        let mna = MnaMatrix::assemble_dc(&elements, &circuit.index_map, &HashMap::new()).unwrap();
+        let triplets = mna
+            .conductance
+            .as_ref()
+            .triplet_iter()
+            .map(|t| Triplet::new(t.row, t.col, *t.val))
+            .collect();
+        (mna, triplets)
+    }
+
+    fn cholesky(mna: &MnaMatrix, triplets: &[Triplet<usize, usize, f64>]) -> Option<Mat<f64>> {
+        solve_cholesky(
+            triplets,
+            &mna.excitation,
+            mna.size(),
+            &SolverConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_cholesky_matches_lu_on_resistor_ladder() {
+        let path = Path::new(&circuits_dir()).join("resistor_ladder_500/resistor_ladder_500.cir");
+        let circuit = parse_circuit_description_file(&path).unwrap();
+        let (mna, triplets) = assemble(&circuit);
+
+        let x = cholesky(&mna, &triplets).expect("the ladder should use the Cholesky path");
+        let direct = mna.solve().unwrap();
+        for (i, name) in mna.unknowns.iter().enumerate() {
+            assert!(
+                (x[(i, 0)] - direct[name]).abs() <= 1e-12 * direct[name].abs().max(1e-12),
+                "{name}: {} vs {}",
+                x[(i, 0)],
+                direct[name]
+            );
+        }
+    }
+
+    #[test]
+    fn test_cholesky_with_current_source() {
+        let circuit =
+            parse_circuit_description("V1 in 0 5\nR1 in a 1k\nR2 a b 2k\nI1 0 b 1m\nR3 b 0 3k\n")
+                .unwrap();
+        let (mna, triplets) = assemble(&circuit);
+        let x = cholesky(&mna, &triplets).unwrap();
+        let direct = mna.solve().unwrap();
+        for (i, name) in mna.unknowns.iter().enumerate() {
+            assert!((x[(i, 0)] - direct[name]).abs() < 1e-12, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_floating_voltage_source_falls_back_to_lu() {
+        // V2 sits between two nodes, so its rows are not singletons and leave a zero on the
+        // diagonal: the matrix is symmetric but indefinite.
+        let circuit =
+            parse_circuit_description("V1 in 0 5\nR1 in a 1k\nV2 a b 1\nR2 b 0 2k\nR3 a 0 3k\n")
+                .unwrap();
+        let (mna, triplets) = assemble(&circuit);
+        assert!(mna.is_symmetric(1e-12));
+        assert!(cholesky(&mna, &triplets).is_none());
+    }
+
+    #[test]
+    fn test_controlled_source_falls_back_to_lu() {
+        // The transconductance only stamps the entry of `out` in the column of `in`, and R1
+        // couples both nodes so the asymmetry stays in the reduced system.
+        let circuit =
+            parse_circuit_description("I1 0 in 1m\nR1 in out 1k\nG1 out 0 in 0 2m\nR2 out 0 1k\n")
+                .unwrap();
+        let (mna, triplets) = assemble(&circuit);
+        assert!(cholesky(&mna, &triplets).is_none());
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use faer::sparse::{SparseColMat, Triplet};
+    use krets_parser::{analyses::Analysis, parser::parse_circuit_description};
+    use krets_solver::{
+        conditioning::{Equilibration, estimate_condition_number},
+        config::SolverConfig,
+        prelude::sum_triplets,
+        solver::Solver,
+        stampable::Stampable,
+    };
+    use std::collections::HashMap;
+
+    // A 1V source across a divider of two 1T resistors: the source rows hold entries of one,
+    // the node rows conductances of 1e-12.
+    const ILL_SCALED_DIVIDER: &str = "V1 in 0 1\nR1 in out 1T\nR2 out 0 1T\n";
+
+    fn condition_number(triplets: &[Triplet<usize, usize, f64>], size: usize) -> f64 {
+        let lu = SparseColMat::try_new_from_triplets(size, size, triplets)
+            .unwrap()
+            .sp_lu()
+            .unwrap();
+        estimate_condition_number(triplets, size, &lu)
+    }
+
+    fn divider_output(config: SolverConfig) -> f64 {
+        let circuit = parse_circuit_description(ILL_SCALED_DIVIDER).unwrap();
+        let mut solver = Solver::new(circuit, config);
+        solver.solve(Analysis::Op).unwrap().into_op()["V(out)"]
+    }
+
+    #[test]
+    fn test_equilibration_improves_conditioning() {
+        let circuit = parse_circuit_description(ILL_SCALED_DIVIDER).unwrap();
+        let size = circuit.index_map.len();
+        let stamps: Vec<_> = circuit
+            .elements
+            .iter()
+            .flat_map(|element| {
+                element.stamp_conductance_matrix_dc(&circuit.index_map, &HashMap::new())
+            })
+            .collect();
+        let triplets = sum_triplets(&stamps);
+
+        let unscaled = condition_number(&triplets, size);
+        let equilibration = Equilibration::new(&triplets, size);
+        let scaled = condition_number(&equilibration.scale_matrix(&triplets), size);
+        assert!(unscaled > 1e11, "unscaled condition number {unscaled}");
+        assert!(scaled < 10.0, "equilibrated condition number {scaled}");
+
+        // Every scale factor is a power of two.
+        for scale in equilibration
+            .row_scale
+            .iter()
+            .chain(&equilibration.col_scale)
+        {
+            assert_eq!(scale.log2().fract(), 0.0, "scale {scale}");
+        }
+    }
+
+    #[test]
+    fn test_equilibrated_divider_accuracy() {
+        let error = |equilibrate| {
+            let config = SolverConfig {
+                equilibrate,
+                check_conditioning: true,
+                ..SolverConfig::default()
+            };
+            (divider_output(config) - 0.5).abs()
+        };
+
+        let unscaled = error(false);
+        let equilibrated = error(true);
+        assert!(equilibrated < 1e-12, "equilibrated error {equilibrated}");
+        assert!(equilibrated <= unscaled, "{equilibrated} > {unscaled}");
+    }
+}
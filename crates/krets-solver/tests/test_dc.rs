@@ -1,8 +1,11 @@
 #[cfg(test)]
 mod tests {
-    use krets_parser::analyses::{Analysis, DcAnalysis};
-    use krets_solver::{config::SolverConfig, solver::Solver};
-    use std::{env, path::Path};
+    use krets_parser::{
+        analyses::{Analysis, DcAnalysis, DcSweepType},
+        elements::Element,
+    };
+    use krets_solver::{config::SolverConfig, error::Error, solver::Solver};
+    use std::{collections::HashMap, env, path::Path};
 
     // Function to get the project root path at runtime
     fn manifest_dir() -> String {
@@ -33,7 +36,8 @@ mod tests {
             element: "V1".to_string(),
             start: 0.0,
             stop: 1.0,
-            step_size: 1.0,
+            sweep: DcSweepType::Linear { step_size: 1.0 },
+            ..Default::default()
         };
 
         let solution = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
@@ -53,7 +57,320 @@ mod tests {
         assert!((second_result.get("I(V1)").unwrap() - (-1.0 / 3000.0)).abs() < 1e-4);
     }
 
+    #[test]
+    fn test_cmos_inverter_switching_threshold() {
+        let path = Path::new(&circuits_dir()).join("cmos_inverter/cmos_inverter.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig::default();
+        let mut solver = Solver::new(circuit, config);
+
+        let dc_analysis = DcAnalysis {
+            element: "VIN".to_string(),
+            start: 0.0,
+            stop: 5.0,
+            sweep: DcSweepType::Linear { step_size: 0.05 },
+            ..Default::default()
+        };
+
+        let solution = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
+        assert_eq!(solution.len(), 101);
+
+        // The output swings rail to rail.
+        let first = &solution[0];
+        let last = &solution[solution.len() - 1];
+        assert!((first.get("V(out)").unwrap() - 5.0).abs() < 1e-3);
+        assert!(last.get("V(out)").unwrap().abs() < 1e-3);
+
+        // The transfer curve is monotonically decreasing.
+        for pair in solution.windows(2) {
+            assert!(pair[1].get("V(out)").unwrap() <= &(pair[0].get("V(out)").unwrap() + 1e-6));
+        }
+
+        // With matched devices the switching threshold, where V(out) = V(in), sits at VDD / 2.
+        let margin = |point: &HashMap<String, f64>| point["V(out)"] - point["V(in)"];
+        let crossing = solution
+            .windows(2)
+            .find(|pair| margin(&pair[0]) >= 0.0 && margin(&pair[1]) < 0.0)
+            .unwrap();
+        let (m0, m1) = (margin(&crossing[0]), margin(&crossing[1]));
+        let v_switch = crossing[0]["V(in)"] + 0.05 * m0 / (m0 - m1);
+        assert!(
+            (v_switch - 2.5).abs() < 1e-2,
+            "switching threshold was {v_switch}"
+        );
+    }
+
     // #[test]
+    // Solves every sweep point as an independent operating point, refactorizing from scratch.
+    fn naive_sweep(circuit_name: &str, source: &str, values: &[f64]) -> Vec<HashMap<String, f64>> {
+        let path = Path::new(&circuits_dir()).join(format!("{circuit_name}/{circuit_name}.cir"));
+        values
+            .iter()
+            .map(|&value| {
+                let mut circuit =
+                    krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+                for element in &mut circuit.elements {
+                    if let Element::VoltageSource(vs) = element
+                        && vs.identifier() == source
+                    {
+                        vs.dc_value = value;
+                    }
+                }
+                let mut solver = Solver::new(circuit, SolverConfig::default());
+                solver.solve(Analysis::Op).unwrap().into_op()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reused_factorization_matches_naive_sweep() {
+        for (circuit_name, source, tolerance) in [
+            ("voltage_divider", "V1", 1e-12),
+            ("cmos_inverter", "VIN", 1e-4),
+        ] {
+            let path =
+                Path::new(&circuits_dir()).join(format!("{circuit_name}/{circuit_name}.cir"));
+            let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+            let mut solver = Solver::new(circuit, SolverConfig::default());
+            let dc_analysis = DcAnalysis {
+                element: source.to_string(),
+                start: 0.0,
+                stop: 5.0,
+                sweep: DcSweepType::Linear { step_size: 0.5 },
+                ..Default::default()
+            };
+            let sweep = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
+
+            let values: Vec<f64> = (0..sweep.len()).map(|i| i as f64 * 0.5).collect();
+            let naive = naive_sweep(circuit_name, source, &values);
+
+            for (swept, reference) in sweep.iter().zip(&naive) {
+                for (key, value) in reference {
+                    let difference = (swept[key] - value).abs();
+                    assert!(
+                        difference <= tolerance * value.abs().max(1.0),
+                        "{circuit_name}: {key} differs by {difference}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_switch_comparator_hysteresis() {
+        let path = Path::new(&circuits_dir()).join("switch_comparator/switch_comparator.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        // The switch state carries over from one sweep into the next.
+        let mut sweep = |start: f64, stop: f64, step_size: f64| {
+            let dc_analysis = DcAnalysis {
+                element: "VIN".to_string(),
+                start,
+                stop,
+                sweep: DcSweepType::Linear { step_size },
+                ..Default::default()
+            };
+            let rows = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
+            move |v_in: f64| {
+                rows.iter()
+                    .find(|row| (row["V(in)"] - v_in).abs() < 1e-9)
+                    .unwrap()["V(out)"]
+            }
+        };
+        let load_on = 5.0 * 1e3 / (1e3 + 1.0);
+        let load_off = 5.0 * 1e3 / (1e3 + 1e6);
+
+        // Rising input: the load only connects once V(in) exceeds V(ref) + VH = 3V.
+        let rising = sweep(0.0, 5.0, 0.1);
+        assert!((rising(2.9) - load_off).abs() < 1e-6);
+        assert!((rising(3.1) - load_on).abs() < 1e-6);
+
+        // Falling input: the load stays connected until V(in) drops below V(ref) - VH = 2V.
+        let falling = sweep(5.0, 0.0, -0.1);
+        assert!((falling(2.9) - load_on).abs() < 1e-6);
+        assert!((falling(2.1) - load_on).abs() < 1e-6);
+        assert!((falling(1.9) - load_off).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_oscillating_switch_does_not_converge() {
+        // The switch opens as soon as it pulls its own control node high, so it never settles.
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "VDD vdd 0 DC 5
+VREF ref 0 DC 2.5
+S1 vdd out ref out SMOD
+RL out 0 1k
+.model SMOD SW (vt=0 ron=1 roff=1meg)
+",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        assert!(solver.solve(Analysis::Op).is_err());
+    }
+
+    #[test]
+    fn test_nested_sweep_grid() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "VDS d 0 DC 0
+VGS g 0 DC 0
+MN1 d g 0 0 NMOD
+.model NMOD NMOS (kp=100u vto=1.0 lambda=0.01)
+",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let dc_analysis = DcAnalysis {
+            element: "VDS".to_string(),
+            start: 0.0,
+            stop: 4.0,
+            sweep: DcSweepType::Linear { step_size: 2.0 },
+            element2: Some("VGS".to_string()),
+            start2: 2.0,
+            stop2: 4.0,
+            step2: 1.0,
+        };
+        let rows = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
+
+        assert_eq!(rows.len(), 9);
+        for (row, (v_gs, v_ds)) in rows.iter().zip(
+            [2.0, 3.0, 4.0]
+                .into_iter()
+                .flat_map(|v_gs| [0.0, 2.0, 4.0].map(|v_ds| (v_gs, v_ds))),
+        ) {
+            assert_eq!(row["VGS"], v_gs);
+            assert!((row["V(g)"] - v_gs).abs() < 1e-9);
+            assert!((row["V(d)"] - v_ds).abs() < 1e-9);
+        }
+
+        // More gate drive gives more drain current at the same drain voltage.
+        let i_d = |row: &HashMap<String, f64>| -row["I(VDS)"];
+        assert!(i_d(&rows[2]) < i_d(&rows[5]) && i_d(&rows[5]) < i_d(&rows[8]));
+
+        // Both sources are restored after the sweep.
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+        assert!(solution["V(d)"].abs() < 1e-9);
+        assert!(solution["V(g)"].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_diode_decade_current_sweep() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "I1 0 a 1n
+D1 a 0 DMOD
+.model DMOD D (is=1e-14)
+",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let dc_analysis = DcAnalysis {
+            element: "I1".to_string(),
+            start: 1e-9,
+            stop: 1e-6,
+            sweep: DcSweepType::Decade {
+                points_per_decade: 5,
+            },
+            ..Default::default()
+        };
+        let values = dc_analysis.values();
+        let rows = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
+
+        // Three decades of five points each, plus the stop value.
+        assert_eq!(rows.len(), 16);
+        assert_eq!(values.len(), 16);
+        assert_eq!(values.first(), Some(&1e-9));
+        assert_eq!(values.last(), Some(&1e-6));
+
+        // The diode voltage rises by ln(10) thermal voltages per decade of current.
+        let v_a: Vec<f64> = rows.iter().map(|row| row["V(a)"]).collect();
+        for decade in v_a.chunks(5).collect::<Vec<_>>().windows(2) {
+            let step = decade[1][0] - decade[0][0];
+            assert!((step - 0.0595).abs() < 2e-3, "step {step}");
+        }
+    }
+
+    #[test]
+    fn test_resistor_sweep() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 10\nR1 in out 1k\nR2 out 0 1k\n",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let dc_analysis = DcAnalysis {
+            element: "R2".to_string(),
+            start: 1e3,
+            stop: 4e3,
+            sweep: DcSweepType::Linear { step_size: 1e3 },
+            ..Default::default()
+        };
+        let rows = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
+
+        // The matrix changes with the resistance, so every point needs its own factorization.
+        assert_eq!(rows.len(), 4);
+        for (row, r2) in rows.iter().zip([1e3, 2e3, 3e3, 4e3]) {
+            assert!((row["V(out)"] - 10.0 * r2 / (1e3 + r2)).abs() < 1e-9);
+        }
+
+        // The resistor is restored after the sweep.
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+        assert!((solution["V(out)"] - 5.0).abs() < 1e-9);
+
+        // Elements without a value cannot be swept.
+        let mut solver = Solver::new(
+            krets_parser::parser::parse_circuit_description(
+                "V1 in 0 10\nR1 in out 1k\nE1 x 0 out 0 2\nR2 x 0 1k\n",
+            )
+            .unwrap(),
+            SolverConfig::default(),
+        );
+        let result = solver.solve(Analysis::Dc(DcAnalysis {
+            element: "E1".to_string(),
+            ..Default::default()
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_resistor_sweep_through_zero() {
+        let sweep = DcAnalysis {
+            element: "R2".to_string(),
+            start: 0.0,
+            stop: 2e3,
+            sweep: DcSweepType::Linear { step_size: 1e3 },
+            ..Default::default()
+        };
+
+        // An ordinary resistor is stamped as 1/R and cannot become a short.
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 10\nR1 in out 1k\nR2 out 0 1k\n",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let result = solver.solve(Analysis::Dc(sweep.clone()));
+        assert!(
+            matches!(&result, Err(Error::InvalidElementFormat(message)) if message.contains("G2")),
+            "{result:?}"
+        );
+
+        // In group 2 form the resistor passes through 0 ohm as an ideal wire.
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 10\nR1 in out 1k\nR2 out 0 1k G2\n",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let rows = solver.solve(Analysis::Dc(sweep)).unwrap().into_dc();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0]["V(out)"].abs() < 1e-12);
+        assert!((rows[0]["I(R2)"] - 10e-3).abs() < 1e-12);
+        for (row, r2) in rows.iter().zip([0.0, 1e3, 2e3]) {
+            assert!((row["V(out)"] - 10.0 * r2 / (1e3 + r2)).abs() < 1e-9);
+        }
+    }
+
     // fn test_inverter() {
     //     let path = Path::new(&circuits_dir()).join("inverter/dc/inverter.cir");
     //     let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
@@ -64,7 +381,7 @@ mod tests {
     //         element: "V1".to_string(),
     //         start: 0.0,
     //         stop: 1.0,
-    //         step_size: 1.0,
+    //         sweep: DcSweepType::Linear { step_size: 1.0 },
     //     };
 
     //     let solution = solver.solve(Analysis::Dc(dc_analysis)).unwrap().into_dc();
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use faer::Mat;
+    use krets_parser::{
+        analyses::Analysis,
+        parser::{parse_circuit_description, parse_circuit_description_file},
+    };
+    use krets_solver::{
+        config::{LinearSolver, SolverConfig},
+        iterative::solve_iterative,
+        mna::MnaMatrix,
+        prelude::*,
+        solver::Solver,
+    };
+    use std::{env, path::Path};
+
+    fn circuits_dir() -> String {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+        Path::new(&manifest_dir)
+            .parent()
+            .and_then(Path::parent)
+            .unwrap()
+            .join("circuits/")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn ladder_500() -> krets_parser::circuit::Circuit {
+        let path = Path::new(&circuits_dir()).join("resistor_ladder_500/resistor_ladder_500.cir");
+        parse_circuit_description_file(&path).unwrap()
+    }
+
+    fn assert_close(direct: &HashMap<String, f64>, iterative: &HashMap<String, f64>) {
+        assert_eq!(direct.len(), iterative.len());
+        for (key, value) in direct {
+            let difference = (iterative[key] - value).abs();
+            assert!(difference < 1e-8, "{key}: {} vs {value}", iterative[key]);
+        }
+    }
+
+    #[test]
+    fn test_iterative_solve_matches_lu_on_resistor_ladder() {
+        let circuit = ladder_500();
+        let elements: Vec<_> = circuit.elements.iter().collect();
+        let mna = MnaMatrix::assemble_dc(&elements, &circuit.index_map, &HashMap::new()).unwrap();
+        let triplets: Vec<_> = mna
+            .conductance
+            .as_ref()
+            .triplet_iter()
+            .map(|t| Triplet::new(t.row, t.col, *t.val))
+            .collect();
+
+        let direct = mna.solve().unwrap();
+        for method in [LinearSolver::IterativeCg, LinearSolver::IterativeBicgstab] {
+            // The iteration converges on its own rather than falling back to LU.
+            let x = solve_iterative(&triplets, &mna.excitation, mna.size(), method, 1e-12, 1_000)
+                .unwrap_or_else(|| panic!("{method:?} did not converge"));
+            let iterative: HashMap<String, f64> = mna
+                .unknowns
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.clone(), x[(i, 0)]))
+                .collect();
+            assert_close(&direct, &iterative);
+        }
+    }
+
+    #[test]
+    fn test_iterative_operating_point_matches_lu() {
+        let direct = Solver::new(ladder_500(), SolverConfig::default())
+            .solve(Analysis::Op)
+            .unwrap()
+            .into_op();
+        for linear_solver in [LinearSolver::IterativeCg, LinearSolver::IterativeBicgstab] {
+            let config = SolverConfig {
+                linear_solver,
+                ..SolverConfig::default()
+            };
+            let iterative = Solver::new(ladder_500(), config)
+                .solve(Analysis::Op)
+                .unwrap()
+                .into_op();
+            assert_close(&direct, &iterative);
+        }
+    }
+
+    #[test]
+    fn test_cg_solves_current_driven_resistor_network() {
+        // Without voltage sources the matrix of a resistor network is symmetric positive
+        // definite, which conjugate gradients require.
+        let circuit = parse_circuit_description(
+            "I1 0 a 1m\nR1 a b 1k\nR2 b 0 2k\nR3 a 0 3k\nR4 b c 500\nR5 c 0 1k\n",
+        )
+        .unwrap();
+        let elements: Vec<_> = circuit.elements.iter().collect();
+        let mna = MnaMatrix::assemble_dc(&elements, &circuit.index_map, &HashMap::new()).unwrap();
+        let triplets: Vec<_> = mna
+            .conductance
+            .as_ref()
+            .triplet_iter()
+            .map(|t| Triplet::new(t.row, t.col, *t.val))
+            .collect();
+
+        let x = solve_iterative(
+            &triplets,
+            &mna.excitation,
+            mna.size(),
+            LinearSolver::IterativeCg,
+            1e-12,
+            100,
+        )
+        .expect("CG did not converge");
+        let direct = mna.solve().unwrap();
+        for (i, name) in mna.unknowns.iter().enumerate() {
+            assert!((x[(i, 0)] - direct[name]).abs() < 1e-9, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_iterative_solve_gives_up_after_iteration_cap() {
+        // A 2x2 system needs two iterations; one is not enough.
+        let triplets = vec![
+            Triplet::new(0, 0, 2.0),
+            Triplet::new(0, 1, 1.0),
+            Triplet::new(1, 0, 1.0),
+            Triplet::new(1, 1, 3.0),
+        ];
+        let b = Mat::from_fn(2, 1, |i, _| [1.0, 2.0][i]);
+        assert!(solve_iterative(&triplets, &b, 2, LinearSolver::IterativeCg, 1e-12, 1).is_none());
+        let x = solve_iterative(&triplets, &b, 2, LinearSolver::IterativeCg, 1e-12, 2).unwrap();
+        assert!((x[(0, 0)] - 0.2).abs() < 1e-12 && (x[(1, 0)] - 0.6).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use faer::sparse::Triplet;
+    use krets_parser::{elements::Element, parser::parse_circuit_description};
+    use krets_solver::mna::MnaMatrix;
+    use std::collections::HashMap;
+
+    fn index_map() -> HashMap<String, usize> {
+        HashMap::from([("V(a)".to_string(), 0), ("V(b)".to_string(), 1)])
+    }
+
+    #[test]
+    fn test_solve_two_by_two_system() {
+        // [3 -1; -1 2] x = [1; 0], with the stamp of the first diagonal split in two.
+        let g_stamps = [
+            Triplet::new(0, 0, 2.0),
+            Triplet::new(0, 0, 1.0),
+            Triplet::new(0, 1, -1.0),
+            Triplet::new(1, 0, -1.0),
+            Triplet::new(1, 1, 2.0),
+        ];
+        let mna = MnaMatrix::new(&index_map(), &g_stamps, &[Triplet::new(0, 0, 1.0)]).unwrap();
+
+        assert_eq!(mna.unknowns, vec!["V(a)", "V(b)"]);
+        assert_eq!(mna.to_dense()[(0, 0)], 3.0);
+        assert!(mna.is_symmetric(1e-12));
+
+        let solution = mna.solve().unwrap();
+        assert!((solution["V(a)"] - 0.4).abs() < 1e-12);
+        assert!((solution["V(b)"] - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_asymmetric_system() {
+        let g_stamps = [
+            Triplet::new(0, 0, 1.0),
+            Triplet::new(1, 0, 2.0),
+            Triplet::new(1, 1, 1.0),
+        ];
+        let mna = MnaMatrix::new(&index_map(), &g_stamps, &[]).unwrap();
+        assert!(!mna.is_symmetric(1e-12));
+    }
+
+    #[test]
+    fn test_assemble_dc_divider() {
+        let circuit = parse_circuit_description("I1 0 a 1m\nR1 a b 1k\nR2 b 0 1k\n").unwrap();
+        let elements: Vec<&Element> = circuit.elements.iter().collect();
+        let mna = MnaMatrix::assemble_dc(&elements, &circuit.index_map, &HashMap::new()).unwrap();
+
+        // The two nodes and the branch current of the source.
+        assert_eq!(mna.size(), 3);
+        let solution = mna.solve().unwrap();
+        assert!((solution["V(a)"] - 2.0).abs() < 1e-12);
+        assert!((solution["V(b)"] - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, Distribution, MonteCarlo};
+    use krets_parser::parser::parse_circuit_description;
+    use krets_solver::{config::SolverConfig, solver::Solver};
+
+    fn toleranced_divider_solver() -> Solver {
+        let netlist = "V1 in 0 10\nR1 in out 1k tol=5%\nR2 out 0 1k tol=5%\n";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        Solver::new(circuit, SolverConfig::default())
+    }
+
+    fn divider_monte_carlo(seed: u64) -> MonteCarlo {
+        MonteCarlo {
+            runs: 2000,
+            seed,
+            distribution: Distribution::Gaussian,
+            inner: Box::new(Analysis::Op),
+        }
+    }
+
+    #[test]
+    fn test_divider_monte_carlo_statistics() {
+        let mut solver = toleranced_divider_solver();
+        let result = solver.monte_carlo(&divider_monte_carlo(1)).unwrap();
+
+        let rows = result.runs.into_dc();
+        assert_eq!(rows.len(), 2000);
+        assert_eq!(rows[1999]["run"], 1999.0);
+
+        // V(out) = 10 * R2 / (R1 + R2), so a relative deviation of sigma = 5% / 3 in each
+        // resistor gives sigma(V(out)) = 10 * sigma * sqrt(2) / 4.
+        let stats = result.statistics[0]["V(out)"];
+        let expected_std_dev = 10.0 * (0.05 / 3.0) * 2f64.sqrt() / 4.0;
+        assert!((stats.mean - 5.0).abs() < 0.01, "mean {}", stats.mean);
+        assert!(
+            (stats.std_dev - expected_std_dev).abs() < 0.1 * expected_std_dev,
+            "std_dev {} expected {expected_std_dev}",
+            stats.std_dev
+        );
+
+        // The nominal values are restored after the analysis.
+        let op = solver.solve_op().unwrap();
+        assert!((op["V(out)"] - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_monte_carlo_is_reproducible() {
+        let first = toleranced_divider_solver()
+            .solve(Analysis::MonteCarlo(divider_monte_carlo(7)))
+            .unwrap()
+            .into_dc();
+        let second = toleranced_divider_solver()
+            .solve(Analysis::MonteCarlo(divider_monte_carlo(7)))
+            .unwrap()
+            .into_dc();
+        let other = toleranced_divider_solver()
+            .solve(Analysis::MonteCarlo(divider_monte_carlo(8)))
+            .unwrap()
+            .into_dc();
+
+        assert_eq!(first, second);
+        assert_ne!(first[0]["V(out)"], other[0]["V(out)"]);
+    }
+}
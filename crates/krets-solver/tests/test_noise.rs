@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::{
+        analyses::{AcSweep, Analysis, NoiseAnalysis},
+        constants::{KB, TEMPERATURE},
+    };
+    use krets_solver::{config::SolverConfig, solver::Solver, stampable::Stampable};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_resistor_thermal_noise() {
+        // R1 is the only noisy element; C1 shapes its noise like the signal path.
+        let circuit_description = "
+V1 in 0 DC 0 AC 1
+R1 in out 1k
+C1 out 0 1u
+    ";
+        let circuit = krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+        let config = SolverConfig::default();
+        let mut solver = Solver::new(circuit, config);
+        let analysis = Analysis::Noise(NoiseAnalysis {
+            output_node: "out".to_string(),
+            input_source: "V1".to_string(),
+            sweep: AcSweep::Decade {
+                points_per_decade: 5,
+            },
+            fstart: 1.0,
+            fstop: 1e5,
+        });
+        let solution = solver.solve(analysis).unwrap().into_noise();
+        assert_eq!(solution.len(), 26);
+
+        let thermal = 4.0 * KB * TEMPERATURE * 1e3;
+        for row in &solution {
+            let frequency = row["frequency"];
+            let omega_rc = 2.0 * PI * frequency * 1e3 * 1e-6;
+            let gain_squared = 1.0 / (1.0 + omega_rc * omega_rc);
+
+            assert!((row["onoise"] / (thermal * gain_squared) - 1.0).abs() < 1e-9);
+            assert!((row["inoise"] / thermal - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_resistor_noise_current_stamp() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 DC 1\nR1 in out 1k\nR2 out 0 2k",
+        )
+        .unwrap();
+        let index = |node: &str| circuit.index_map[&format!("V({node})")];
+        let psd = |r: f64| 4.0 * KB * TEMPERATURE / r;
+
+        let mut stamps: Vec<(usize, usize, f64)> = circuit
+            .elements
+            .iter()
+            .flat_map(|element| element.stamp_noise_current(&circuit.index_map, TEMPERATURE))
+            .map(|triplet| (triplet.row, triplet.col, triplet.val))
+            .collect();
+        stamps.sort_by_key(|&(row, col, _)| (row, col));
+
+        // R1 is stamped between `in` and `out`, R2 from `out` to ground; V1 is noiseless.
+        let (i_in, i_out) = (index("in"), index("out"));
+        let mut expected = vec![
+            (i_in, i_in, psd(1e3)),
+            (i_out, i_out, psd(1e3)),
+            (i_in, i_out, -psd(1e3)),
+            (i_out, i_in, -psd(1e3)),
+            (i_out, i_out, psd(2e3)),
+        ];
+        expected.sort_by_key(|&(row, col, _)| (row, col));
+        assert_eq!(stamps.len(), expected.len());
+        for ((row, col, val), (e_row, e_col, e_val)) in stamps.iter().zip(&expected) {
+            assert_eq!((row, col), (e_row, e_col));
+            assert!((val - e_val).abs() < 1e-12 * e_val.abs());
+        }
+    }
+
+    #[test]
+    fn test_noise_unknown_input_source() {
+        let circuit_description = "
+V1 in 0 DC 0 AC 1
+R1 in out 1k
+R2 out 0 1k
+    ";
+        let circuit = krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let analysis = Analysis::Noise(NoiseAnalysis {
+            output_node: "out".to_string(),
+            input_source: "V2".to_string(),
+            sweep: AcSweep::Linear { total_points: 1 },
+            fstart: 1e3,
+            fstop: 1e3,
+        });
+        assert!(matches!(
+            solver.solve(analysis),
+            Err(krets_solver::prelude::Error::ElementNotFound(_))
+        ));
+    }
+}
@@ -1,8 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use krets_parser::analyses::Analysis;
-    use krets_solver::{config::SolverConfig, solver::Solver};
-    use std::{env, path::Path};
+    use krets_parser::{
+        analyses::{AcAnalysis, AcSweep, Analysis, DcAnalysis, DcSweepType, TransientAnalysis},
+        builder::CircuitBuilder,
+        elements::Element,
+    };
+    use krets_solver::{
+        config::{Homotopy, SolverConfig},
+        solver::Solver,
+    };
+    use std::{collections::HashMap, env, path::Path, sync::Arc};
 
     // Function to get the project root path at runtime
     fn manifest_dir() -> String {
@@ -67,6 +74,48 @@ V2 3 0 20
         assert!((solution.get("I(V2)").unwrap() - 1.0).abs() < 1e-3);
     }
 
+    #[test]
+    fn test_vcvs_amplifier() {
+        let circuit_description = "
+V1 in 0 0.5
+R1 in 0 1k
+E1 out 0 in 0 10
+R2 out 0 1k
+    ";
+        let circuit = krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+        let config = SolverConfig::default();
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+
+        assert!((solution.get("V(out)").unwrap() - 5.0).abs() < 1e-6);
+        // The amplifier sources the load current, so it flows out of the plus terminal.
+        assert!((solution.get("I(E1)").unwrap() - (-5e-3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_controlled_sources() {
+        let circuit_description = "
+V1 in 0 1
+Vsense in mid 0
+R1 mid 0 1k
+G1 0 out_g in 0 1m
+R2 out_g 0 1k
+H1 out_h 0 Vsense 1k
+R3 out_h 0 1k
+F1 0 out_f vsense 2
+R4 out_f 0 1k
+    ";
+        let circuit = krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+        let config = SolverConfig::default();
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+
+        assert!((solution.get("I(Vsense)").unwrap() - 1e-3).abs() < 1e-9);
+        assert!((solution.get("V(out_g)").unwrap() - 1.0).abs() < 1e-6);
+        assert!((solution.get("V(out_h)").unwrap() - 1.0).abs() < 1e-6);
+        assert!((solution.get("V(out_f)").unwrap() - 2.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_voltage_divider() {
         let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
@@ -80,6 +129,324 @@ V2 3 0 20
         assert!((solution.get("I(V1)").unwrap() - 1. / 3000.).abs() < 1e-3);
     }
 
+    #[test]
+    fn test_voltage_divider_builder() {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let parsed = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let built = CircuitBuilder::new()
+            .voltage_source("V1", "in", "0", 1.0)
+            .resistor("R1", "in", "out", 1000.0)
+            .resistor("R2", "out", "0", 2000.0)
+            .build()
+            .unwrap();
+        assert_eq!(built.index_map, parsed.index_map);
+
+        let parsed_solution = Solver::new(parsed, SolverConfig::default())
+            .solve_op()
+            .unwrap();
+        let built_solution = Solver::new(built, SolverConfig::default())
+            .solve_op()
+            .unwrap();
+        assert_eq!(built_solution, parsed_solution);
+    }
+
+    #[test]
+    fn test_ground_alias_solves_like_zero() {
+        let with_zero = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 1\nR1 in out 1k\nR2 out 0 2k\nX1 out half div\n.subckt div a b\nR1 a b 1k\nR2 b 0 1k\n.ends\n",
+        )
+        .unwrap();
+        let with_gnd = krets_parser::parser::parse_circuit_description(
+            "V1 in gnd 1\nR1 in out 1k\nR2 out GND 2k\nX1 out half div\n.subckt div a b\nR1 a b 1k\nR2 b gnd 1k\n.ends\n",
+        )
+        .unwrap();
+        assert_eq!(with_gnd.index_map, with_zero.index_map);
+
+        let zero_solution = Solver::new(with_zero, SolverConfig::default())
+            .solve_op()
+            .unwrap();
+        let gnd_solution = Solver::new(with_gnd, SolverConfig::default())
+            .solve_op()
+            .unwrap();
+        assert_eq!(gnd_solution, zero_solution);
+        assert!(!gnd_solution.contains_key("V(gnd)"));
+        assert!(!gnd_solution.contains_key("V(1_gnd)"));
+    }
+
+    #[test]
+    fn test_g2_resistor_reports_current() {
+        let netlist = "V1 in 0 10\nR1 in out 50 G2 % note\nR2 out 0 50\n";
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        assert!(circuit.index_map.contains_key("I(R1)"));
+        assert!(!circuit.index_map.contains_key("I(R2)"));
+
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+        assert!((solution["V(out)"] - 5.0).abs() < 1e-9);
+        assert!((solution["I(R1)"] - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_voltage_divider_probe_currents() {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig {
+            probe_currents: true,
+            ..SolverConfig::default()
+        };
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+
+        assert!((solution["I(R2)"] - solution["V(out)"] / 2000.0).abs() < 1e-12);
+        assert!(
+            (solution["I(R1)"] - (solution["V(in)"] - solution["V(out)"]) / 1000.0).abs() < 1e-12
+        );
+        // The solved branch current of the source is kept.
+        assert!((solution["I(V1)"] + 1.0 / 3000.0).abs() < 1e-9);
+
+        let rows = solver
+            .solve(Analysis::Dc(DcAnalysis {
+                element: "V1".to_string(),
+                start: 0.0,
+                stop: 3.0,
+                sweep: DcSweepType::Linear { step_size: 1.0 },
+                ..DcAnalysis::default()
+            }))
+            .unwrap()
+            .into_dc();
+        for row in &rows {
+            assert!((row["I(R2)"] - row["V(out)"] / 2000.0).abs() < 1e-12);
+        }
+        assert!((rows[3]["I(R2)"] - 1e-3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_voltage_divider_solve_op() {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let solver = Solver::new(circuit, SolverConfig::default());
+        let solution = solver.solve_op().unwrap();
+
+        assert_eq!(solution.len(), 3);
+        assert!((solution["V(in)"] - 1.0).abs() < 1e-9);
+        assert!((solution["V(out)"] - 2.0 / 3.0).abs() < 1e-9);
+        assert!((solution["I(V1)"].abs() - 1.0 / 3000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_voltage_divider_thevenin() {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        // Looking into `out`, the source is shorted and R1 (1k) is in parallel with R2 (2k).
+        let (voltage, resistance) = solver.thevenin("out", "0").unwrap();
+        assert!((voltage - 2.0 / 3.0).abs() < 1e-9);
+        assert!((resistance - 2000.0 / 3.0).abs() < 1e-6);
+
+        let (voltage, resistance) = solver.thevenin("0", "out").unwrap();
+        assert!((voltage + 2.0 / 3.0).abs() < 1e-9);
+        assert!((resistance - 2000.0 / 3.0).abs() < 1e-6);
+
+        // The source is an ideal voltage source, so the port across it is a short.
+        let (voltage, resistance) = solver.thevenin("in", "0").unwrap();
+        assert!((voltage - 1.0).abs() < 1e-9);
+        assert!(resistance.abs() < 1e-6);
+
+        assert_eq!(solver.thevenin("out", "out").unwrap(), (0.0, 0.0));
+        assert!(solver.thevenin("out", "missing").is_err());
+
+        // The test source is removed again.
+        assert_eq!(solver.solve_op().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_voltage_divider_element_power() {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let solver = Solver::new(circuit, SolverConfig::default());
+        let solution = solver.solve_op().unwrap();
+        let power = solver.element_power(&solution);
+
+        assert_eq!(power.len(), 3);
+        let (i_r1, p_r1) = power["R1"];
+        let (i_r2, p_r2) = power["R2"];
+        assert!((i_r1 - 1.0 / 3000.0).abs() < 1e-12);
+        assert!((i_r2 - 1.0 / 3000.0).abs() < 1e-12);
+        assert!((p_r2 - 2.0 * p_r1).abs() < 1e-12);
+
+        // The source delivers exactly the power the resistors dissipate.
+        let (_, p_v1) = power["V1"];
+        assert!(p_v1 < 0.0);
+        assert!((p_v1 + p_r1 + p_r2).abs() < 1e-12);
+        assert!((p_r1 + p_r2 - 1.0 / 3000.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_nonlinear_element_power_balance() {
+        for name in [
+            "common_emitter",
+            "cmos_inverter",
+            "diode_iv_curve",
+            "common_source",
+        ] {
+            let path = Path::new(&circuits_dir()).join(format!("{name}/{name}.cir"));
+            let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+            let solver = Solver::new(circuit, SolverConfig::default());
+            let solution = solver.solve_op().unwrap();
+            let power = solver.element_power(&solution);
+
+            let total: f64 = power.values().map(|&(_, p)| p).sum();
+            let scale: f64 = power.values().map(|&(_, p)| p.abs()).sum();
+            // Nonlinear devices are evaluated at the converged solution, so the balance only
+            // holds to within the Newton-Raphson tolerance.
+            assert!(total.abs() <= 1e-4 * scale, "{name}: {power:?}");
+        }
+    }
+
+    #[test]
+    fn test_zero_ohm_resistor_senses_current() {
+        let netlist = "V1 in 0 5\nR1 in a 0\nR2 a 0 1k\nR3 a 0 4k\n";
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let solution = Solver::new(circuit, SolverConfig::default())
+            .solve_op()
+            .unwrap();
+
+        // The ideal wire carries the current of both loads from `in` into `a`.
+        assert!((solution["V(a)"] - 5.0).abs() < 1e-12);
+        assert!((solution["I(R1)"] - 6.25e-3).abs() < 1e-12);
+        assert!(solution.values().all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn test_options_card_overrides_config() {
+        let netlist = "V1 in 0 1\nR1 in out 1k\nR2 out 0 2k\n.options reltol=1e-4 itl1=200\n";
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let mut config = SolverConfig::default();
+        config.apply_options(&circuit.options);
+
+        assert_eq!(config.relative_tolerance, 1e-4);
+        assert_eq!(config.reltol, 1e-4);
+        assert_eq!(config.maximum_iterations, 200);
+        let default = SolverConfig::default();
+        assert_eq!(
+            config.voltage_absolute_tolerance,
+            default.voltage_absolute_tolerance
+        );
+        assert_eq!(
+            config.current_absolute_tolerance,
+            default.current_absolute_tolerance
+        );
+        assert_eq!(config.minimum_conductance, default.minimum_conductance);
+
+        let solution = Solver::new(circuit, config).solve_op().unwrap();
+        assert!((solution["V(out)"] - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_kcl_holds_at_operating_point() {
+        for name in [
+            "voltage_divider",
+            "diode_bridge",
+            "common_emitter",
+            "cmos_inverter",
+            "common_source",
+            "transformer",
+        ] {
+            let path = Path::new(&circuits_dir()).join(format!("{name}/{name}.cir"));
+            let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+            let solver = Solver::new(circuit, SolverConfig::default());
+            let solution = solver.solve_op().unwrap();
+
+            let scale = solver
+                .element_power(&solution)
+                .values()
+                .map(|&(i, _)| i.abs())
+                .fold(0.0, f64::max);
+
+            // Nonlinear devices only satisfy KCL to within the Newton-Raphson tolerance.
+            let (node, residual) = solver.kcl_residual(&solution).unwrap();
+            assert!(
+                residual.abs() <= 1e-4 * scale + 1e-12,
+                "{name}: {residual} A at {node}"
+            );
+        }
+
+        // Controlled sources and a group 2 resistor carry their current from plus to minus.
+        let netlist = "V1 in 0 2\nR1 in a 1k g2\nE1 b 0 a 0 3\nR2 b 0 2k\nG1 0 c b 0 1m\nR3 c 0 1k\nF1 d 0 V1 2\nR4 d 0 500\nH1 e 0 V1 1k\nR5 e 0 1k\nR6 a 0 1k\n";
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let solver = Solver::new(circuit, SolverConfig::default());
+        let solution = solver.solve_op().unwrap();
+        let (node, residual) = solver.kcl_residual(&solution).unwrap();
+        assert!(residual.abs() < 1e-12, "{residual} A at {node}");
+
+        // Sourcing power, the voltage source carries a negative branch current.
+        assert!(solution["I(V1)"] < 0.0);
+    }
+
+    #[test]
+    fn test_reduce_series_resistor_chain() {
+        // A source drives a chain of ten 1k resistors into two 5k resistors in parallel. The
+        // capacitor, open at DC, keeps `out` from being merged away.
+        let mut netlist = String::from("V1 in 0 10\n");
+        for i in 0..10 {
+            let plus = if i == 0 {
+                "in".to_string()
+            } else {
+                format!("n{i}")
+            };
+            let minus = if i == 9 {
+                "out".to_string()
+            } else {
+                format!("n{}", i + 1)
+            };
+            netlist.push_str(&format!("R{i} {plus} {minus} 1k\n"));
+        }
+        netlist.push_str("RA out 0 5k\nRB 0 out 5k\nC1 out 0 1u\n");
+        let circuit = krets_parser::parser::parse_circuit_description(&netlist).unwrap();
+
+        let full = Solver::new(circuit.clone(), SolverConfig::default())
+            .solve_op()
+            .unwrap();
+
+        let mut reduced = circuit;
+        let merged = reduced.reduce_passives();
+        assert_eq!(reduced.elements.len(), 4);
+        assert_eq!(reduced.nodes.len(), 3);
+        assert_eq!(merged["R0"].len(), 10);
+        assert_eq!(merged["RA"].len(), 2);
+
+        let solution = Solver::new(reduced, SolverConfig::default())
+            .solve_op()
+            .unwrap();
+        assert!((solution["V(out)"] - full["V(out)"]).abs() < 1e-9);
+        assert!((solution["V(out)"] - 10.0 * 2.5 / 12.5).abs() < 1e-9);
+        assert!((solution["I(V1)"] - full["I(V1)"]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_reduce_passives_keeps_saved_signals() {
+        let mut circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 10\nR1 in a 1k\nR2 a b 1k\nR3 b out 1k\nR4 out 0 1k\nR5 out 0 1k\n\
+             C1 out 0 1u\n.save V(a) I(R4)\n",
+        )
+        .unwrap();
+        let merged = circuit.reduce_passives();
+
+        // `a` stays between R1 and R2, which leaves only R2 and R3 to merge in series. R4 is
+        // saved, so R5 stays next to it.
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged["R2"], ["R2", "R3"]);
+        assert!(circuit.nodes.iter().any(|node| node == "a"));
+        assert!(circuit.elements.iter().any(|e| e.identifier() == "R4"));
+
+        let solution = Solver::new(circuit, SolverConfig::default())
+            .solve(Analysis::Op)
+            .unwrap()
+            .into_op();
+        assert!((solution["V(a)"] - 10.0 * 2.5 / 3.5).abs() < 1e-9);
+    }
+
     #[test]
     fn test_low_pass_filter_op() {
         let path = Path::new(&circuits_dir()).join("low_pass_filter/low_pass_filter.cir");
@@ -138,4 +505,330 @@ V2 3 0 20
         assert!((solution.get("V(out)").unwrap() - 0.517).abs() < 1e-3);
         assert!((solution.get("I(V1)").unwrap() - 4.82e-04).abs() < 1e-3);
     }
+
+    #[test]
+    fn test_common_emitter_op() {
+        let path = Path::new(&circuits_dir()).join("common_emitter/common_emitter.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig::default();
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+
+        // With IS=1e-16 and BF=100: V(b) = 0.7718 V, IC = 0.9228 mA, V(c) = 10 - 5k * IC.
+        assert!((solution.get("V(b)").unwrap() - 0.7718).abs() < 1e-3);
+        assert!((solution.get("V(c)").unwrap() - 5.3859).abs() < 1e-3);
+
+        // The supply delivers both the collector and the base current.
+        let i_b = (10.0 - solution.get("V(b)").unwrap()) / 1e6;
+        let i_c = (10.0 - solution.get("V(c)").unwrap()) / 5e3;
+        assert!((i_c / i_b - 100.0).abs() < 1e-2);
+        assert!((solution.get("I(VCC)").unwrap() + i_b + i_c).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_common_emitter_warm_start() {
+        let path = Path::new(&circuits_dir()).join("common_emitter/common_emitter.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit.clone(), SolverConfig::default());
+
+        let cold = solver.solve_op_warm(&HashMap::new()).unwrap();
+        let cold_iterations = solver.last_op_iterations().unwrap();
+        let previous = solver.previous_op_result().unwrap().clone();
+
+        // Re-solving a slightly modified circuit from the previous operating point.
+        let mut tweaked = circuit;
+        for element in &mut tweaked.elements {
+            if let Element::Resistor(resistor) = element
+                && resistor.identifier() == "RC"
+            {
+                resistor.value *= 1.05;
+            }
+        }
+        let mut tweaked_solver = Solver::new(tweaked, SolverConfig::default());
+        let warm = tweaked_solver.solve_op_warm(&previous).unwrap();
+        let warm_iterations = tweaked_solver.last_op_iterations().unwrap();
+
+        assert!(
+            warm_iterations < cold_iterations,
+            "warm {warm_iterations} vs cold {cold_iterations} iterations"
+        );
+        // The base bias does not depend on the collector resistor.
+        assert!((warm["V(b)"] - cold["V(b)"]).abs() < 1e-6);
+        assert!(warm["V(c)"] < cold["V(c)"]);
+    }
+
+    #[test]
+    fn test_diode_bridge_gmin_stepping() {
+        let path = Path::new(&circuits_dir()).join("diode_bridge/diode_bridge.cir");
+
+        // Plain Newton-Raphson does not find the operating point of the floating bridge.
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig {
+            gmin_steps: 0,
+            ..SolverConfig::default()
+        };
+        let mut solver = Solver::new(circuit, config);
+        assert!(matches!(
+            solver.solve(Analysis::Op),
+            Err(krets_solver::prelude::Error::ConvergenceFailed { .. })
+        ));
+
+        // Gmin stepping walks it there.
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig::default();
+        let mut solver = Solver::new(circuit, config);
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+
+        let v = |node: &str| *solution.get(node).unwrap();
+        assert!((v("V(p)") - 4.2763).abs() < 1e-3);
+        assert!((v("V(n)") - 0.6878).abs() < 1e-3);
+
+        // Two forward biased diodes sit between the source and the load.
+        let v_load = v("V(p)") - v("V(n)");
+        assert!((5.0 - 10.0 * v_load / 1e3 - v_load - 1.4).abs() < 0.2);
+        assert!((v("I(V1)") + v_load / 1e3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_bistable_multivibrator_source_stepping() {
+        // Two cross-coupled common-emitter stages, each holding the other one off or on.
+        let multivibrator = "
+VCC vcc 0 DC 12
+RC1 vcc c1 1k
+RC2 vcc c2 1k
+RB1 c1 b2 10k
+RB2 c2 b1 10k
+QN1 c1 b1 0
+QN2 c2 b2 0
+";
+        let solve = |homotopy: Homotopy| {
+            let circuit = krets_parser::parser::parse_circuit_description(multivibrator).unwrap();
+            let config = SolverConfig {
+                homotopy,
+                ..SolverConfig::default()
+            };
+            Solver::new(circuit, config).solve(Analysis::Op)
+        };
+
+        // Neither plain Newton-Raphson nor gmin stepping converge from all nodes at zero.
+        assert!(matches!(
+            solve(Homotopy::GminStepping),
+            Err(krets_solver::prelude::Error::ConvergenceFailed { .. })
+        ));
+
+        // Ramping the supply up from zero does.
+        let solution = solve(Homotopy::SourceStepping { steps: 10 })
+            .unwrap()
+            .into_op();
+        let v = |node: &str| solution[node];
+        assert!(v("V(b1)") > 0.6 && v("V(b2)") > 0.6);
+
+        // The supply delivers the current of both collector resistors.
+        let i_rc = (12.0 - v("V(c1)")) / 1e3 + (12.0 - v("V(c2)")) / 1e3;
+        assert!((v("I(VCC)") + i_rc).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_diode_forward_drop_temperature() {
+        let circuit_description = "
+V1 in 0 5
+R1 in a 1k
+D1 a 0 DMOD
+.model DMOD D (is=1e-14)
+    ";
+        let forward_drop = |temperature: f64| {
+            let circuit =
+                krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+            let config = SolverConfig {
+                temperature,
+                ..SolverConfig::default()
+            };
+            let mut solver = Solver::new(circuit, config);
+            let solution = solver.solve(Analysis::Op).unwrap().into_op();
+            *solution.get("V(a)").unwrap()
+        };
+
+        let cold = forward_drop(-40.0);
+        let room = forward_drop(27.0);
+        let hot = forward_drop(125.0);
+        assert!(cold > room && room > hot);
+
+        // Silicon junctions lose roughly 2 mV per degree at a fixed current.
+        let slope = (hot - cold) / 165.0;
+        assert!(slope < -1.5e-3 && slope > -2.5e-3);
+    }
+
+    #[test]
+    fn test_convergence_failure_reports_worst_node() {
+        // The switch shorts `out` as soon as the source drives it above the reference and opens
+        // again once shorted, so the voltage of `out` never settles. The source current is
+        // fixed, so `out` is the only unknown that keeps changing.
+        let circuit_description = "
+VREF ref 0 DC 2.5
+I1 0 out 5m
+RL out 0 1k
+S1 out 0 out ref SMOD
+.model SMOD SW (vt=0 ron=1 roff=1meg)
+    ";
+        let circuit = krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        match solver.solve(Analysis::Op) {
+            Err(krets_solver::prelude::Error::ConvergenceFailed {
+                iterations,
+                worst_node,
+                delta,
+            }) => {
+                assert_eq!(iterations, SolverConfig::default().maximum_iterations);
+                assert_eq!(worst_node, "V(out)");
+                assert!(delta > 1.0, "delta was {delta}");
+            }
+            other => panic!("Expected a convergence failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_floating_rc_island() {
+        // Nodes `a` and `b` only connect to the rest of the circuit through capacitors.
+        let circuit_description = "
+V1 in 0 5
+R1 in 0 1k
+C1 in a 1u
+R2 a b 1k
+C2 b 0 1u
+    ";
+        let circuit = krets_parser::parser::parse_circuit_description(circuit_description).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        assert!(matches!(
+            solver.solve(Analysis::Op),
+            Err(krets_solver::prelude::Error::FloatingNode(node)) if node == "a"
+        ));
+    }
+
+    #[test]
+    fn test_nodeset_selects_latch_state() {
+        // Two cross-coupled NMOS inverters with resistive loads hold either `q` or `qb` high.
+        let latch = "
+VDD vdd 0 DC 5
+R1 vdd q 100k
+R2 vdd qb 100k
+MN1 q qb 0 0 NMOD
+MN2 qb q 0 0 NMOD
+.model NMOD NMOS (kp=100u vto=1.0 lambda=0.01)
+";
+        let solve = |nodeset: &str| {
+            let netlist = format!("{latch}{nodeset}\n");
+            let circuit = krets_parser::parser::parse_circuit_description(&netlist).unwrap();
+            let mut solver = Solver::new(circuit, SolverConfig::default());
+            let solution = solver.solve(Analysis::Op).unwrap().into_op();
+            (solution["V(q)"], solution["V(qb)"])
+        };
+
+        let (q, qb) = solve(".nodeset V(q)=5 V(qb)=0");
+        assert!(q > 4.9 && qb < 0.2, "q = {q}, qb = {qb}");
+
+        let (q, qb) = solve(".nodeset V(q)=0 V(qb)=5");
+        assert!(qb > 4.9 && q < 0.2, "q = {q}, qb = {qb}");
+
+        // A nodeset on an unknown node is ignored rather than rejected.
+        let (q, qb) = solve(".nodeset V(q)=5 V(qb)=0 V(missing)=1");
+        assert!(q > 4.9 && qb < 0.2, "q = {q}, qb = {qb}");
+    }
+
+    #[test]
+    fn test_query_signals_without_consuming_result() {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let op = solver.solve(Analysis::Op).unwrap();
+        assert!((op.op_value("V(out)").unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((op.op_value("V(in)").unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(op.op_value("V(missing)"), None);
+        // Accessors of other analyses do not apply to an operating point.
+        assert_eq!(op.dc_series("V(out)"), None);
+        assert_eq!(op.ac_series("V(out)"), None);
+        assert_eq!(op.tran_series("V(out)"), None);
+        // The result is still there to be consumed.
+        assert_eq!(op.into_op().len(), 3);
+
+        let dc = solver
+            .solve(Analysis::Dc(DcAnalysis {
+                element: "V1".to_string(),
+                start: 0.0,
+                stop: 3.0,
+                sweep: DcSweepType::Linear { step_size: 1.0 },
+                ..Default::default()
+            }))
+            .unwrap();
+        let out = dc.dc_series("V(out)").unwrap();
+        assert_eq!(out.len(), 4);
+        for (vin, vout) in dc.dc_series("V(in)").unwrap().iter().zip(&out) {
+            assert!((vout - vin * 2.0 / 3.0).abs() < 1e-9);
+        }
+        assert_eq!(dc.dc_series("V(missing)"), None);
+        assert_eq!(dc.op_value("V(out)"), None);
+
+        let ac = solver
+            .solve(Analysis::Ac(AcAnalysis {
+                sweep: AcSweep::Linear { total_points: 3 },
+                fstart: 1.0,
+                fstop: 3.0,
+            }))
+            .unwrap();
+        assert_eq!(ac.ac_series("frequency").unwrap().len(), 3);
+
+        let tran = solver
+            .solve(Analysis::Transient(TransientAnalysis {
+                time_step: 1e-3,
+                stop_time: 1e-2,
+                use_initial_conditions: false,
+            }))
+            .unwrap();
+        let time = tran.tran_series("time").unwrap();
+        let out = tran.tran_series("V(out)").unwrap();
+        assert_eq!(time.len(), out.len());
+        assert!(out.iter().all(|v| (v - 2.0 / 3.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_solvers_share_circuit() {
+        let circuit = Arc::new(
+            krets_parser::parser::parse_circuit_description(
+                "V1 in 0 10\nR1 in out 1k\nR2 out 0 1k\n",
+            )
+            .unwrap(),
+        );
+        let mut first = Solver::new(Arc::clone(&circuit), SolverConfig::default());
+        let mut second = Solver::new(Arc::clone(&circuit), SolverConfig::default());
+        assert_eq!(Arc::strong_count(&circuit), 3);
+
+        // Analyses that leave the circuit unchanged run on the shared circuit.
+        let op = first.solve(Analysis::Op).unwrap().into_op();
+        assert_eq!(second.solve(Analysis::Op).unwrap().into_op(), op);
+        assert_eq!(Arc::strong_count(&circuit), 3);
+
+        // A DC sweep changes the swept source, so its solver takes its own copy first.
+        let sweep = Analysis::Dc(DcAnalysis {
+            element: "V1".to_string(),
+            start: 0.0,
+            stop: 2.0,
+            sweep: DcSweepType::Linear { step_size: 1.0 },
+            ..Default::default()
+        });
+        first.solve(sweep).unwrap();
+        assert_eq!(Arc::strong_count(&circuit), 2);
+        assert_eq!(second.solve(Analysis::Op).unwrap().into_op(), op);
+
+        // The state of a switch belongs to the solver running it, so it is never shared.
+        let circuit = Arc::new(
+            krets_parser::parser::parse_circuit_description(
+                "V1 in 0 5\nS1 in out in 0 SMOD\nR1 out 0 1k\n.model SMOD SW (vt=2.5)\n",
+            )
+            .unwrap(),
+        );
+        let _solver = Solver::new(Arc::clone(&circuit), SolverConfig::default());
+        assert_eq!(Arc::strong_count(&circuit), 1);
+    }
 }
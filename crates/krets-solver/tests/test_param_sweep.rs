@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, DcAnalysis, DcSweepType, ParamSweep};
+    use krets_solver::{config::SolverConfig, error::Error, solver::Solver};
+    use std::{env, path::Path};
+
+    // Function to get the project root path at runtime
+    fn manifest_dir() -> String {
+        env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string())
+    }
+
+    // Function to get the circuits directory path
+    fn circuits_dir() -> String {
+        // Adjust the path to navigate from the crate's root to the workspace root's circuits dir
+        Path::new(&manifest_dir())
+            .parent() // Go up from crates/krets-solver
+            .and_then(Path::parent) // Go up from crates
+            .unwrap()
+            .join("circuits/")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn voltage_divider_solver() -> Solver {
+        let path = Path::new(&circuits_dir()).join("voltage_divider/voltage_divider.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        Solver::new(circuit, SolverConfig::default())
+    }
+
+    #[test]
+    fn test_load_resistor_sweep() {
+        let mut solver = voltage_divider_solver();
+        let sweep = ParamSweep {
+            element_or_param: "R2".to_string(),
+            start: 500.0,
+            stop: 5000.0,
+            step_size: 500.0,
+            inner: Box::new(Analysis::Op),
+        };
+
+        let rows = solver.solve(Analysis::Sweep(sweep)).unwrap().into_dc();
+        assert_eq!(rows.len(), 10);
+
+        for row in &rows {
+            let r2 = row["R2"];
+            assert!((row["V(out)"] - r2 / (1000.0 + r2)).abs() < 1e-9);
+        }
+        // A larger load resistor always takes a larger share of the supply.
+        assert!(
+            rows.windows(2)
+                .all(|pair| pair[1]["V(out)"] > pair[0]["V(out)"])
+        );
+
+        // The load resistor is restored after the sweep.
+        let solution = solver.solve(Analysis::Op).unwrap().into_op();
+        assert!((solution["V(out)"] - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_nested_dc_sweep_family() {
+        let mut solver = voltage_divider_solver();
+        let sweep = ParamSweep {
+            element_or_param: "R2".to_string(),
+            start: 1000.0,
+            stop: 3000.0,
+            step_size: 1000.0,
+            inner: Box::new(Analysis::Dc(DcAnalysis {
+                element: "V1".to_string(),
+                start: 0.0,
+                stop: 1.0,
+                sweep: DcSweepType::Linear { step_size: 0.5 },
+                ..Default::default()
+            })),
+        };
+
+        let rows = solver.solve(Analysis::Sweep(sweep)).unwrap().into_dc();
+        assert_eq!(rows.len(), 9);
+        for row in &rows {
+            let ratio = row["R2"] / (1000.0 + row["R2"]);
+            assert!((row["V(out)"] - ratio * row["V(in)"]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_sweep_unknown_element() {
+        let mut solver = voltage_divider_solver();
+        let sweep = ParamSweep {
+            element_or_param: "R9".to_string(),
+            start: 1.0,
+            stop: 2.0,
+            step_size: 1.0,
+            inner: Box::new(Analysis::Op),
+        };
+
+        let result = solver.solve(Analysis::Sweep(sweep));
+        assert!(matches!(result, Err(Error::ElementNotFound(name)) if name == "R9"));
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, PoleZeroAnalysis};
+    use krets_parser::parser::parse_circuit_description;
+    use krets_solver::{config::SolverConfig, prelude::c64, solver::Solver};
+    use std::f64::consts::PI;
+
+    fn pole_zero(netlist: &str, output: &str, input: &str) -> (Vec<c64>, Vec<c64>) {
+        let circuit = parse_circuit_description(netlist).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        solver
+            .solve(Analysis::PoleZero(PoleZeroAnalysis {
+                output: output.to_string(),
+                input: input.to_string(),
+            }))
+            .unwrap()
+            .into_pole_zero()
+    }
+
+    #[test]
+    fn test_rc_low_pass_pole() {
+        let (poles, zeros) = pole_zero("V1 in 0 0\nR1 in out 1k\nC1 out 0 1u\n", "out", "V1");
+
+        // A single real pole at -1/RC rad/s, i.e. 1/(2 pi RC) Hz, and no finite zeros.
+        assert_eq!(poles.len(), 1);
+        let (re, im) = (poles[0].re, poles[0].im);
+        assert!((re + 1e3).abs() < 1e-6, "pole at {re}");
+        assert!(im.abs() < 1e-9);
+        assert!((-re / (2.0 * PI) - 159.154943).abs() < 1e-3);
+        assert!(zeros.is_empty(), "zeros {zeros:?}");
+    }
+
+    #[test]
+    fn test_rc_high_pass_zero_at_origin() {
+        let (poles, zeros) = pole_zero("V1 in 0 0\nC1 in out 1u\nR1 out 0 1k\n", "out", "V1");
+
+        assert_eq!(poles.len(), 1);
+        assert!((poles[0].re + 1e3).abs() < 1e-6);
+        assert_eq!(zeros.len(), 1);
+        assert!(zeros[0].norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_rlc_complex_poles() {
+        // Series RLC with w0 = 1/sqrt(LC) = 1e4 rad/s and Q = 5.
+        let (poles, _) = pole_zero(
+            "V1 in 0 0\nR1 in a 20\nL1 a out 10m\nC1 out 0 1u\n",
+            "out",
+            "V1",
+        );
+
+        assert_eq!(poles.len(), 2);
+        for c64 { re, im } in poles {
+            assert!((re + 1e3).abs() < 1e-3, "pole at {re}{im:+}j");
+            assert!((im.abs() - (1e8f64 - 1e6).sqrt()).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, DcAnalysis, DcSweepType};
+    use krets_solver::{
+        config::SolverConfig,
+        report::{ReportOptions, format_result},
+        solver::Solver,
+    };
+
+    fn solver() -> Solver {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 10\nR1 in out 1k\nR2 out 0 1k\n",
+        )
+        .unwrap();
+        Solver::new(circuit, SolverConfig::default())
+    }
+
+    #[test]
+    fn test_op_columns_are_aligned() {
+        let result = solver().solve(Analysis::Op).unwrap();
+        let report = format_result(&result, &ReportOptions::default());
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert!(lines[0].starts_with("Signal"));
+        assert_eq!(lines.len(), 2 + 3);
+        let separator = lines[0].find(" | ").unwrap();
+        assert_eq!(lines[1].find("-+-"), Some(separator));
+        for line in &lines[2..] {
+            assert_eq!(line.find(" | "), Some(separator), "{line}");
+            // The values end in the same column, followed by their unit.
+            assert_eq!(line.len(), lines[2].len(), "{line}");
+        }
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("V(out)") && line.ends_with("5.000000e0 V"))
+        );
+        assert!(lines.iter().any(|line| line.ends_with(" A")));
+    }
+
+    #[test]
+    fn test_sweep_signal_filter_and_row_limit() {
+        let result = solver()
+            .solve(Analysis::Dc(DcAnalysis {
+                element: "V1".to_string(),
+                start: 0.0,
+                stop: 4.0,
+                sweep: DcSweepType::Linear { step_size: 1.0 },
+                ..DcAnalysis::default()
+            }))
+            .unwrap();
+        let options = ReportOptions {
+            signals: vec!["V(out)".to_string()],
+            max_rows: Some(2),
+        };
+        let report = format_result(&result, &options);
+        let lines: Vec<&str> = report.lines().collect();
+
+        let header: Vec<&str> = lines[0].split_whitespace().collect();
+        assert_eq!(header, ["step", "V(out)"]);
+        assert_eq!(lines.len(), 2 + 2 + 1);
+        assert_eq!(lines[3].split_whitespace().nth(1), Some("5.000000e-1"));
+        assert_eq!(lines[4], "... 3 more points");
+        for line in &lines[..4] {
+            assert_eq!(line.len(), lines[0].len(), "{line}");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, SensitivityAnalysis};
+    use krets_parser::parser::parse_circuit_description;
+    use krets_solver::{config::SolverConfig, error::Error, solver::Solver};
+    use std::collections::HashMap;
+
+    fn sensitivities(netlist: &str, output: &str) -> Result<HashMap<String, f64>, Error> {
+        let circuit = parse_circuit_description(netlist).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        solver
+            .solve(Analysis::Sensitivity(SensitivityAnalysis {
+                output: output.to_string(),
+            }))
+            .map(|result| result.into_sensitivity())
+    }
+
+    #[test]
+    fn test_divider_sensitivity() {
+        let result = sensitivities("V1 in 0 10\nR1 in out 1k\nR2 out 0 3k\n", "out").unwrap();
+
+        // V(out) = V1 R2 / (R1 + R2)
+        let (v1, r1, r2) = (10.0, 1e3, 3e3);
+        let sum_squared = (r1 + r2) * (r1 + r2);
+        assert!((result["R2"] - v1 * r1 / sum_squared).abs() < 1e-12);
+        assert!((result["R1"] + v1 * r2 / sum_squared).abs() < 1e-12);
+        assert!((result["V1"] - r2 / (r1 + r2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sensitivity_matches_finite_differences() {
+        let netlist = |i1: f64, r3: f64| {
+            format!(
+                "V1 in 0 5\nR1 in mid 2k\nR2 mid 0 1k\nR3 mid out {r3}\nR4 out 0 4k\nI1 0 out {i1}\n"
+            )
+        };
+        let solve_out = |i1: f64, r3: f64| {
+            let circuit = parse_circuit_description(&netlist(i1, r3)).unwrap();
+            Solver::new(circuit, SolverConfig::default())
+                .solve(Analysis::Op)
+                .unwrap()
+                .into_op()["V(out)"]
+        };
+        let result = sensitivities(&netlist(1e-3, 500.0), "out").unwrap();
+
+        let h = 1e-3;
+        let d_r3 = (solve_out(1e-3, 500.0 + h) - solve_out(1e-3, 500.0 - h)) / (2.0 * h);
+        assert!((result["R3"] - d_r3).abs() < 1e-9);
+        let d_i1 = (solve_out(1e-3 + 1e-6, 500.0) - solve_out(1e-3 - 1e-6, 500.0)) / 2e-6;
+        assert!((result["I1"] - d_i1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sensitivity_unknown_output() {
+        let result = sensitivities("V1 in 0 10\nR1 in 0 1k\n", "out");
+        assert!(matches!(result, Err(Error::NodeNotFound(node)) if node == "V(out)"));
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use krets_parser::analyses::{Analysis, TfAnalysis};
+    use krets_parser::parser::parse_circuit_description;
+    use krets_solver::{config::SolverConfig, error::Error, solver::Solver};
+
+    fn divider_solver() -> Solver {
+        let netlist = "V1 in 0 10\nR1 in out 1k\nR2 out 0 3k\nRL out 0 1meg\n";
+        let circuit = parse_circuit_description(netlist).unwrap();
+        Solver::new(circuit, SolverConfig::default())
+    }
+
+    #[test]
+    fn test_divider_transfer_function() {
+        let mut solver = divider_solver();
+        let result = solver
+            .solve(Analysis::Tf(TfAnalysis {
+                output: "out".to_string(),
+                input_source: "V1".to_string(),
+            }))
+            .unwrap()
+            .into_tf();
+
+        let r1 = 1e3;
+        let r2 = 3e3 * 1e6 / (3e3 + 1e6); // R2 in parallel with the load
+        assert!((result["gain"] - r2 / (r1 + r2)).abs() < 1e-9);
+        assert!((result["input_resistance"] - (r1 + r2)).abs() < 1e-6);
+        assert!((result["output_resistance"] - r1 * r2 / (r1 + r2)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_transfer_function_unknown_source() {
+        let mut solver = divider_solver();
+        let result = solver.solve(Analysis::Tf(TfAnalysis {
+            output: "out".to_string(),
+            input_source: "V9".to_string(),
+        }));
+        assert!(matches!(result, Err(Error::ElementNotFound(source)) if source == "V9"));
+    }
+}
@@ -1,128 +1,26 @@
 #[cfg(test)]
 mod tests {
-    use krets_parser::analyses::{Analysis, TransientAnalysis};
-    use krets_solver::{AnalysisResult, config::SolverConfig, solver::Solver};
-    use std::{env, path::Path};
+    use krets_parser::analyses::{Analysis, DcAnalysis, DcSweepType, TransientAnalysis};
+    use krets_solver::{
+        config::{IntegrationMethod, SolverConfig},
+        error::Error,
+        events::{CrossingEvent, Edge},
+        solver::Solver,
+    };
+    use std::{
+        collections::HashMap,
+        env,
+        path::Path,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+    };
     // Function to get the project root path at runtime
     fn manifest_dir() -> String {
         env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string())
     }
 
-    #[allow(dead_code)]
-    fn print_results_to_console(result: &AnalysisResult) {
-        match result {
-            AnalysisResult::Op(op_solution) => {
-                let mut sorted_results: Vec<_> = op_solution.iter().collect();
-                sorted_results.sort_by_key(|(k, _)| *k);
-
-                println!("{:<15} | {:<15}", "Node/Branch", "Value");
-                println!("{:-<15}-+-{:-<15}", "", "");
-
-                for (node_or_branch, value) in sorted_results {
-                    let unit = if node_or_branch.starts_with('V') {
-                        "V"
-                    } else {
-                        "A"
-                    };
-                    println!("{:<15} | {:>14.6e} {}", node_or_branch, value, unit);
-                }
-            }
-            AnalysisResult::Dc(dc_solution) => {
-                if dc_solution.is_empty() {
-                    println!("DC sweep produced no results.");
-                    return;
-                }
-                // Get headers from the first result, sorted for consistent order
-                let mut headers: Vec<_> = dc_solution[0].keys().collect();
-                headers.sort();
-
-                // Print header
-                for header in &headers {
-                    print!("{:<18}", header);
-                }
-                println!();
-                println!("{:-<width$}", "", width = headers.len() * 18);
-
-                // Print rows
-                for step_result in dc_solution {
-                    for header in &headers {
-                        if let Some(value) = step_result.get(*header) {
-                            print!("{:<18.6e}", value);
-                        } else {
-                            print!("{:<18}", "N/A");
-                        }
-                    }
-                    println!();
-                }
-            }
-            AnalysisResult::Ac(ac_sweep_solution) => {
-                // ac_sweep_solution is now Vec<HashMap<String, c64>>
-                if ac_sweep_solution.is_empty() {
-                    println!("AC sweep produced no results.");
-                    return;
-                }
-
-                // Print header once
-                println!(
-                    "{:<18} | {:<15} | {:<20} | {:<20}",
-                    "Frequency (Hz)", "Node/Branch", "Magnitude", "Phase (deg)"
-                );
-                println!("{:-<18}-+-{:-<15}-+-{:-<20}-+-{:-<20}", "", "", "", "");
-
-                // Iterate through each frequency step's results
-                for ac_solution_step in ac_sweep_solution {
-                    // Extract frequency (assuming it's stored as "frequency" key)
-                    let frequency = ac_solution_step.get("frequency").map_or(f64::NAN, |c| c.re); // Get real part for frequency
-
-                    // Sort results for the current frequency step
-                    let mut sorted_results: Vec<_> = ac_solution_step
-                        .iter()
-                        .filter(|(k, _)| **k != "frequency") // Exclude the frequency entry itself
-                        .collect();
-                    sorted_results.sort_by_key(|(k, _)| *k);
-
-                    // Print results for the current frequency
-                    for (node, value) in sorted_results {
-                        let (mag, phase_deg) = (value.norm(), value.arg().to_degrees());
-                        println!(
-                            "{:<18.6e} | {:<15} | {:>19.6e} | {:>19.6e}",
-                            frequency, node, mag, phase_deg
-                        );
-                    }
-                    println!("{:-<18}-+-{:-<15}-+-{:-<20}-+-{:-<20}", "", "", "", ""); // Separator between frequencies
-                }
-            }
-            AnalysisResult::Transient(tran_solution) => {
-                if tran_solution.is_empty() {
-                    println!("Transient analysis produced no results.");
-                    return;
-                }
-                // Get headers from the first result, sorted for consistent order
-                let mut headers: Vec<_> = tran_solution[0].keys().collect();
-                headers.sort();
-
-                // Print header
-                for header in &headers {
-                    print!("{:<18}", header);
-                }
-                println!();
-                println!("{:-<width$}", "", width = headers.len() * 18);
-
-                // Print rows
-                for step_result in tran_solution {
-                    for header in &headers {
-                        if let Some(value) = step_result.get(*header) {
-                            print!("{:<18.6e}", value);
-                        } else {
-                            print!("{:<18}", "N/A");
-                        }
-                    }
-                    println!();
-                }
-            }
-        }
-    }
-
     // Function to get the circuits directory path
     fn circuits_dir() -> String {
         // Adjust the path to navigate from the crate's root to the workspace root's circuits dir
@@ -146,10 +44,10 @@ mod tests {
         let tran_analysis = TransientAnalysis {
             time_step: 50e-6, // 50us
             stop_time: 50e-3, // 50ms
+            use_initial_conditions: false,
         };
 
         let solution = solver.solve(Analysis::Transient(tran_analysis)).unwrap();
-        // print_results_to_console(&solution);
         let transient_solution = solution.clone().into_transient();
 
         let result_t0 = &transient_solution[0];
@@ -160,6 +58,90 @@ mod tests {
         assert!((result_last.get("V(out)").unwrap() - 0.989).abs() < 1e-3);
     }
 
+    // Integrates a linear circuit with backward Euler, assembling and factorizing the full
+    // MNA system from scratch at every time step.
+    fn naive_linear_transient(
+        circuit: &krets_parser::circuit::Circuit,
+        initial_op: HashMap<String, f64>,
+        time_step: f64,
+        num_steps: usize,
+    ) -> Vec<HashMap<String, f64>> {
+        use faer::{
+            Mat,
+            prelude::Solve,
+            sparse::{SparseColMat, Triplet},
+        };
+        use krets_solver::{prelude::sum_triplets, stampable::Stampable};
+
+        let index_map = &circuit.index_map;
+        let size = index_map.len();
+        let mut results = vec![initial_op];
+
+        for step in 1..=num_steps {
+            let prev = results.last().unwrap();
+            let mut g_stamps = Vec::new();
+            let mut e_stamps = Vec::new();
+            for element in &circuit.elements {
+                g_stamps.extend(
+                    element.stamp_conductance_matrix_transient(index_map, prev, prev, time_step),
+                );
+                e_stamps.extend(
+                    element.stamp_excitation_vector_transient(index_map, prev, prev, time_step),
+                );
+            }
+
+            let lu = SparseColMat::try_new_from_triplets(size, size, &sum_triplets(&g_stamps))
+                .unwrap()
+                .sp_lu()
+                .unwrap();
+            let mut b = Mat::zeros(size, 1);
+            for Triplet { row, col, val } in sum_triplets(&e_stamps) {
+                b[(row, col)] = val;
+            }
+            let x = lu.solve(&b);
+
+            let mut solution: HashMap<String, f64> = index_map
+                .iter()
+                .map(|(node, &idx)| (node.clone(), x[(idx, 0)]))
+                .collect();
+            solution.insert("time".to_string(), step as f64 * time_step);
+            results.push(solution);
+        }
+        results
+    }
+
+    #[test]
+    fn test_cached_factorization_matches_naive_assembly() {
+        let path = Path::new(&circuits_dir()).join("dual_rc_ladder/dual_rc_ladder.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+
+        let mut solver = Solver::new(circuit.clone(), SolverConfig::default());
+        let mut initial_op = solver.solve(Analysis::Op).unwrap().into_op();
+        initial_op.insert("time".to_string(), 0.0);
+
+        let tran_analysis = TransientAnalysis {
+            time_step: 50e-6,
+            stop_time: 10e-3,
+            use_initial_conditions: false,
+        };
+        let solution = solver
+            .solve(Analysis::Transient(tran_analysis))
+            .unwrap()
+            .into_transient();
+
+        let reference = naive_linear_transient(&circuit, initial_op, 50e-6, 200);
+        assert_eq!(solution.len(), reference.len());
+        for (cached, naive) in solution.iter().zip(&reference) {
+            for (key, value) in naive {
+                assert!(
+                    (cached[key] - value).abs() <= 1e-12 * value.abs().max(1.0),
+                    "{key} differs at t = {}",
+                    naive["time"]
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_rectifier() {
         let path = Path::new(&circuits_dir()).join("rectifier/rectifier.cir");
@@ -170,11 +152,290 @@ mod tests {
         let tran_analysis = TransientAnalysis {
             time_step: 50e-6, // 50us
             stop_time: 50e-3, // 20ms
+            use_initial_conditions: false,
         };
 
-        let solution = solver.solve(Analysis::Transient(tran_analysis)).unwrap();
-        print_results_to_console(&solution);
-        // let transient_solution = solution.clone().into_transient();
+        solver.solve(Analysis::Transient(tran_analysis)).unwrap();
+    }
+
+    #[test]
+    fn test_rectifier_adaptive_time_step() {
+        let path = Path::new(&circuits_dir()).join("rectifier/rectifier.cir");
+        let peak = |solution: &[HashMap<String, f64>]| {
+            solution
+                .iter()
+                .map(|row| row["V(out_dc)"])
+                .fold(f64::MIN, f64::max)
+        };
+
+        // Reference: a fine fixed step that resolves the 60 Hz peaks well.
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let reference = solver
+            .solve(Analysis::Transient(TransientAnalysis {
+                time_step: 10e-6,
+                stop_time: 50e-3,
+                use_initial_conditions: false,
+            }))
+            .unwrap()
+            .into_transient();
+
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let config = SolverConfig {
+            adaptive_time_step: true,
+            ..SolverConfig::default()
+        };
+        let mut solver = Solver::new(circuit, config);
+        let adaptive = solver
+            .solve(Analysis::Transient(TransientAnalysis {
+                time_step: 50e-6,
+                stop_time: 50e-3,
+                use_initial_conditions: false,
+            }))
+            .unwrap()
+            .into_transient();
+
+        let (peak_reference, peak_adaptive) = (peak(&reference), peak(&adaptive));
+        assert!(
+            (peak_adaptive - peak_reference).abs() < 0.01 * peak_reference,
+            "adaptive peak {peak_adaptive} V, reference peak {peak_reference} V"
+        );
+
+        // Every row carries its own time stamp, strictly increasing up to the stop time.
+        let times: Vec<f64> = adaptive.iter().map(|row| row["time"]).collect();
+        assert!(times.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!((times.last().unwrap() - 50e-3).abs() < 1e-12);
+
+        // The step size actually varies.
+        let steps: Vec<f64> = times.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let min = steps.iter().copied().fold(f64::MAX, f64::min);
+        let max = steps.iter().copied().fold(f64::MIN, f64::max);
+        assert!(max > 2.0 * min);
+    }
+
+    #[test]
+    fn test_capacitor_charging_from_initial_condition() {
+        let path = Path::new(&circuits_dir()).join("rc_initial_condition/rc_initial_condition.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let tran_analysis = TransientAnalysis {
+            time_step: 10e-6, // 10us
+            stop_time: 5e-3,  // 5ms
+            use_initial_conditions: true,
+        };
+        let solution = solver
+            .solve(Analysis::Transient(tran_analysis))
+            .unwrap()
+            .into_transient();
+
+        // The operating point is skipped, so the capacitor starts at its preset voltage.
+        let result_t0 = &solution[0];
+        assert!((result_t0["V(out)"] - 0.5).abs() < 1e-12);
+        assert!(result_t0["V(in)"].abs() < 1e-12);
+
+        // V(out) = 1 - 0.5 * exp(-t/RC), with RC = 1ms.
+        let result_1ms = &solution[100];
+        assert!((result_1ms["time"] - 1e-3).abs() < 1e-9);
+        assert!((result_1ms["V(out)"] - (1.0 - 0.5 * (-1.0f64).exp())).abs() < 2e-3);
+
+        let result_last = solution.last().unwrap();
+        assert!((result_last["V(out)"] - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_inductor_series_resistance() {
+        let solve = |esr: &str| {
+            let netlist = format!("V1 in 0 1\nR1 in a 10\nL1 a 0 1m ic=0 {esr}\n");
+            let circuit = krets_parser::parser::parse_circuit_description(&netlist).unwrap();
+            let mut solver = Solver::new(circuit, SolverConfig::default());
+            let tran_analysis = TransientAnalysis {
+                time_step: 1e-6,
+                stop_time: 2e-3,
+                use_initial_conditions: true,
+            };
+            solver
+                .solve(Analysis::Transient(tran_analysis))
+                .unwrap()
+                .into_transient()
+        };
+
+        // An ideal inductor settles at V1 / R1.
+        let ideal = solve("");
+        assert!((ideal.last().unwrap()["I(L1)"] - 0.1).abs() < 1e-6);
+
+        // The series resistance limits the current to V1 / (R1 + esr), reached with the time
+        // constant L / (R1 + esr) = 50us.
+        let lossy = solve("esr=10");
+        assert!((lossy.last().unwrap()["I(L1)"] - 0.05).abs() < 1e-6);
+        let result_50us = &lossy[50];
+        assert!((result_50us["I(L1)"] - 0.05 * (1.0 - (-1.0f64).exp())).abs() < 5e-4);
+    }
+
+    #[test]
+    fn test_capacitor_element_initial_condition() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "R1 out 0 1k\nC1 out 0 1u ic=3\nL1 out2 0 1m ic=2m\nR2 out2 0 1",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let tran_analysis = TransientAnalysis {
+            time_step: 10e-6,
+            stop_time: 5e-3,
+            use_initial_conditions: true,
+        };
+        let solution = solver
+            .solve(Analysis::Transient(tran_analysis))
+            .unwrap()
+            .into_transient();
+
+        let result_t0 = &solution[0];
+        assert!((result_t0["V(out)"] - 3.0).abs() < 1e-12);
+        assert!((result_t0["I(L1)"] - 2e-3).abs() < 1e-12);
+
+        // V(out) = 3 * exp(-t/RC), with RC = 1ms.
+        let result_1ms = &solution[100];
+        assert!((result_1ms["V(out)"] - 3.0 * (-1.0f64).exp()).abs() < 1e-2);
+        assert!(solution.last().unwrap()["V(out)"] < 0.1);
+    }
+
+    #[test]
+    fn test_transmission_line_delays_and_reflects_a_step() {
+        let solve = |load: &str| {
+            let netlist = format!(
+                "V1 in 0 dc 0 PULSE(0 1 1n 1n 1n 1 2)\nR1 in a 50\nT1 a 0 b 0 Z0=50 TD=10n\n{load}"
+            );
+            let circuit = krets_parser::parser::parse_circuit_description(&netlist).unwrap();
+            let mut solver = Solver::new(circuit, SolverConfig::default());
+            let tran_analysis = TransientAnalysis {
+                time_step: 0.1e-9,
+                stop_time: 30e-9,
+                use_initial_conditions: false,
+            };
+            solver
+                .solve(Analysis::Transient(tran_analysis))
+                .unwrap()
+                .into_transient()
+        };
+        let at = |solution: &[HashMap<String, f64>], time: f64, signal: &str| {
+            solution
+                .iter()
+                .find(|point| point["time"] >= time - 1e-15)
+                .unwrap()[signal]
+        };
+
+        // A matched line launches half of the step, which arrives at the load one delay later
+        // without any reflection.
+        let matched = solve("R2 b 0 50");
+        assert!((at(&matched, 5e-9, "V(a)") - 0.5).abs() < 1e-6);
+        assert!(at(&matched, 10e-9, "V(b)").abs() < 1e-6);
+        assert!((at(&matched, 15e-9, "V(b)") - 0.5).abs() < 1e-6);
+        assert!((at(&matched, 25e-9, "V(a)") - 0.5).abs() < 1e-6);
+        assert!((at(&matched, 15e-9, "I(T1)") - 0.01).abs() < 1e-8);
+
+        // An open line doubles the step at its end, and the reflection settles the source end
+        // at the full step after a round trip.
+        let open = solve("R2 b 0 1G");
+        assert!((at(&open, 15e-9, "V(a)") - 0.5).abs() < 1e-6);
+        assert!((at(&open, 15e-9, "V(b)") - 1.0).abs() < 1e-6);
+        assert!((at(&open, 25e-9, "V(a)") - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_streaming_transient_matches_collected() {
+        let circuit =
+            krets_parser::parser::parse_circuit_description("R1 out 0 1k\nC1 out 0 1u ic=3")
+                .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let tran_analysis = TransientAnalysis {
+            time_step: 10e-6,
+            stop_time: 5e-3,
+            use_initial_conditions: true,
+        };
+
+        // Integrate V(out) over time without keeping the time points around.
+        let mut steps = 0;
+        let mut area = 0.0;
+        let mut last_time = 0.0;
+        solver
+            .solve_transient_with(&tran_analysis, |solution| {
+                steps += 1;
+                area += solution["V(out)"] * (solution["time"] - last_time);
+                last_time = solution["time"];
+            })
+            .unwrap();
+
+        // The integral of 3 * exp(-t/RC) over 5 RC.
+        assert_eq!(steps, 501);
+        assert!((area - 3e-3 * (1.0 - (-5.0f64).exp())).abs() < 5e-5);
+
+        let collected = solver
+            .solve(Analysis::Transient(tran_analysis))
+            .unwrap()
+            .into_transient();
+        let collected_area: f64 = collected
+            .windows(2)
+            .map(|pair| pair[1]["V(out)"] * (pair[1]["time"] - pair[0]["time"]))
+            .sum();
+        assert_eq!(collected.len(), steps);
+        assert!((area - collected_area).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_cancelled_transient_returns_partial_result() {
+        let circuit =
+            krets_parser::parser::parse_circuit_description("R1 out 0 1k\nC1 out 0 1u ic=3")
+                .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let tran_analysis = TransientAnalysis {
+            time_step: 10e-6,
+            stop_time: 5e-3,
+            use_initial_conditions: true,
+        };
+
+        // Cancel once ten time steps have been reported.
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut fractions = Vec::new();
+        let mut report = |fraction: f64| {
+            fractions.push(fraction);
+            if fractions.len() == 10 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        };
+        let solution = solver
+            .solve_with_progress(
+                Analysis::Transient(tran_analysis),
+                Some(&mut report),
+                Some(cancel.clone()),
+            )
+            .unwrap()
+            .into_transient();
+
+        // The t=0 point and the ten steps solved before the cancellation.
+        assert_eq!(solution.len(), 11);
+        assert!((solution.last().unwrap()["time"] - 100e-6).abs() < 1e-12);
+        assert_eq!(fractions.len(), 10);
+        assert!((fractions[9] - 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_initial_condition_unknown_node() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 DC 1\nR1 in out 1k\nC1 out 0 1u\n.ic V(missing)=1",
+        )
+        .unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+
+        let tran_analysis = TransientAnalysis {
+            time_step: 10e-6,
+            stop_time: 1e-3,
+            use_initial_conditions: true,
+        };
+        let result = solver.solve(Analysis::Transient(tran_analysis));
+        assert!(matches!(result, Err(Error::NodeNotFound(node)) if node == "V(missing)"));
     }
 
     #[test]
@@ -187,10 +448,10 @@ mod tests {
         let tran_analysis = TransientAnalysis {
             time_step: 50e-6, // 50us
             stop_time: 20e-3, // 20ms
+            use_initial_conditions: false,
         };
 
         let solution = solver.solve(Analysis::Transient(tran_analysis)).unwrap();
-        // print_results_to_console(&solution);
         let transient_solution = solution.clone().into_transient();
 
         let result_t0 = &transient_solution[0];
@@ -207,8 +468,40 @@ mod tests {
 
         let result_last = transient_solution.last().unwrap();
         assert!((result_last.get("V(out)").unwrap() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_low_pass_filter_measurements() {
+        let path = Path::new(&circuits_dir()).join("low_pass_filter/transient.cir");
+        let circuit = krets_parser::parser::parse_circuit_description_file(&path).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
 
-        // print_results_to_console(&solution);
+        let tran_analysis = TransientAnalysis {
+            time_step: 50e-6,
+            stop_time: 20e-3,
+            use_initial_conditions: false,
+        };
+        let solution = solver.solve(Analysis::Transient(tran_analysis)).unwrap();
+        let measurements = solution.measurements().unwrap();
+
+        assert_eq!(measurements.len(), 3);
+        // After 15 time constants the output has settled on the 1V step.
+        assert!((measurements["vfinal"] - 1.0).abs() < 1e-3);
+        assert!((measurements["vpp"] - 1.0).abs() < 1e-3);
+        // The 10%-90% rise time of an RC low-pass is RC * ln(9).
+        let expected_rise = 1e-3 * 9f64.ln();
+        assert!((measurements["trise"] - expected_rise).abs() / expected_rise < 0.05);
+
+        // DC sweeps do not pick up transient measurements.
+        let dc_analysis = DcAnalysis {
+            element: "V1".to_string(),
+            start: 0.0,
+            stop: 1.0,
+            sweep: DcSweepType::Linear { step_size: 1.0 },
+            ..Default::default()
+        };
+        let solution = solver.solve(Analysis::Dc(dc_analysis)).unwrap();
+        assert!(solution.measurements().unwrap().is_empty());
     }
 
     #[test]
@@ -221,10 +514,10 @@ mod tests {
         let tran_analysis = TransientAnalysis {
             time_step: 10e-6, // 10us
             stop_time: 2e-3,  // 2ms
+            use_initial_conditions: false,
         };
 
         let solution = solver.solve(Analysis::Transient(tran_analysis)).unwrap();
-        print_results_to_console(&solution);
         let transient_solution = solution.clone().into_transient();
 
         let result_t0 = &transient_solution[0];
@@ -233,7 +526,160 @@ mod tests {
 
         let result_last = transient_solution.last().unwrap();
         assert!((result_last.get("V(out)").unwrap() - 1.0).abs() < 1e-3);
+    }
+
+    fn transient_with_method(
+        netlist: &str,
+        integration_method: IntegrationMethod,
+        time_step: f64,
+        stop_time: f64,
+    ) -> Vec<HashMap<String, f64>> {
+        let circuit = krets_parser::parser::parse_circuit_description(netlist).unwrap();
+        let config = SolverConfig {
+            integration_method,
+            ..SolverConfig::default()
+        };
+        let mut solver = Solver::new(circuit, config);
+        solver
+            .solve(Analysis::Transient(TransientAnalysis {
+                time_step,
+                stop_time,
+                use_initial_conditions: true,
+            }))
+            .unwrap()
+            .into_transient()
+    }
 
-        // print_results_to_console(&solution);
+    #[test]
+    fn test_gear2_is_more_accurate_than_backward_euler() {
+        // RC charging from 0V with tau = 1ms, sampled at tau / 10.
+        let netlist = "V1 in 0 1\nR1 in out 1k\nC1 out 0 1u ic=0\n";
+        let max_error = |method| {
+            transient_with_method(netlist, method, 100e-6, 5e-3)
+                .iter()
+                .skip(1)
+                .map(|row| (row["V(out)"] - (1.0 - (-row["time"] / 1e-3).exp())).abs())
+                .fold(0.0, f64::max)
+        };
+
+        let euler = max_error(IntegrationMethod::BackwardEuler);
+        let gear2 = max_error(IntegrationMethod::Gear2);
+        assert!(gear2 < euler / 3.0, "gear2 {gear2}, backward Euler {euler}");
+    }
+
+    #[test]
+    fn test_gear2_stable_on_stiff_diode_clamp() {
+        // tau = RC = 1us, integrated with steps of 10 tau: the capacitor charges until the
+        // diode clamps it. An A-stable but not L-stable method would ring around the clamp.
+        let netlist = "
+V1 in 0 5
+R1 in a 1k
+C1 a 0 1n ic=0
+D1 a 0 DMOD
+.model DMOD D (is=1e-14)
+";
+        let rows = transient_with_method(netlist, IntegrationMethod::Gear2, 10e-6, 200e-6);
+        let clamp = rows.last().unwrap()["V(a)"];
+        assert!(clamp > 0.5 && clamp < 0.8, "clamp {clamp}");
+
+        // Any deviation from the clamp left by the first step dies out instead of ringing.
+        let deviations: Vec<f64> = rows[1..].iter().map(|row| row["V(a)"] - clamp).collect();
+        for pair in deviations.windows(2) {
+            assert!(pair[0].abs() < 1e-3, "deviation {}", pair[0]);
+            assert!(
+                pair[1].abs() <= 0.5 * pair[0].abs() + 1e-12,
+                "{} -> {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    /// Returns how long after the falling edge at 50ns the anode takes to drop below -1V,
+    /// with the diode carrying 4.3mA before the edge and driven with 5V reverse after it.
+    fn diode_turn_off_time(transit_time: &str) -> f64 {
+        let netlist = format!(
+            "V1 in 0 DC 5 PULSE(5 -5 50n 1n 1n 1u 2u)\nR1 in a 1k\nD1 a 0 DMOD\n.model DMOD D (is=1e-14 tt={transit_time})\n"
+        );
+        let circuit = krets_parser::parser::parse_circuit_description(&netlist).unwrap();
+        let mut solver = Solver::new(circuit, SolverConfig::default());
+        let rows = solver
+            .solve(Analysis::Transient(TransientAnalysis {
+                time_step: 0.5e-9,
+                stop_time: 250e-9,
+                use_initial_conditions: false,
+            }))
+            .unwrap()
+            .into_transient();
+
+        rows.iter()
+            .find(|row| row["time"] > 50e-9 && row["V(a)"] < -1.0)
+            .map(|row| row["time"] - 50e-9)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_diode_stored_charge_delays_turn_off() {
+        // Without charge storage the diode blocks as soon as the source reverses.
+        let resistive = diode_turn_off_time("0");
+        assert!(resistive < 2e-9, "resistive turn-off {resistive}");
+
+        // The stored charge TT * IF keeps the diode conducting in reverse for the storage
+        // time TT * ln(1 + IF / IR) = 100n * ln(1 + 4.3m / 5.7m), about 56ns.
+        let stored = diode_turn_off_time("100n");
+        assert!(stored > 45e-9 && stored < 80e-9, "stored turn-off {stored}");
+    }
+
+    #[test]
+    fn test_sine_zero_crossings() {
+        let circuit = krets_parser::parser::parse_circuit_description(
+            "V1 in 0 SIN(0 1 1k 0 0 0)\nR1 in 0 1k\n",
+        )
+        .unwrap();
+        // Adaptive steps are spaced unevenly, so every crossing is interpolated over its own
+        // step.
+        let config = SolverConfig {
+            adaptive_time_step: true,
+            max_step: 7e-6,
+            ..SolverConfig::default()
+        };
+        let solver = Solver::new(circuit, config);
+        let analysis = TransientAnalysis {
+            time_step: 1e-6,
+            stop_time: 2.2e-3,
+            use_initial_conditions: false,
+        };
+        let crossings = solver
+            .find_crossings(
+                &analysis,
+                &[
+                    CrossingEvent::new("V(in)", 0.0, Edge::Rising),
+                    CrossingEvent::new("V(in)", 0.0, Edge::Falling),
+                    CrossingEvent::new("V(in)", 0.5, Edge::Rising),
+                ],
+            )
+            .unwrap();
+
+        let assert_times = |found: &[f64], expected: &[f64], tolerance: f64| {
+            assert_eq!(found.len(), expected.len(), "crossings {found:?}");
+            for (found, expected) in found.iter().zip(expected) {
+                assert!(
+                    (found - expected).abs() < tolerance,
+                    "{found} vs {expected}"
+                );
+            }
+        };
+        // The sine starts at zero, which is not a crossing; it first rises through zero after
+        // a full period.
+        assert_times(&crossings[0], &[1e-3, 2e-3], 1e-9);
+        assert_times(&crossings[1], &[0.5e-3, 1.5e-3], 1e-9);
+        // sin(2π f t) = 0.5 at t = 1 / (12 f). Away from zero the sine is curved, so the
+        // linear interpolation is less exact there.
+        let first = 1e-3 / 12.0;
+        assert_times(&crossings[2], &[first, 1e-3 + first, 2e-3 + first], 1e-7);
+
+        let missing =
+            solver.find_crossings(&analysis, &[CrossingEvent::new("V(x)", 0.0, Edge::Either)]);
+        assert!(matches!(missing, Err(Error::NodeNotFound(node)) if node == "V(x)"));
     }
 }